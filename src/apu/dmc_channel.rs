@@ -1,7 +1,5 @@
 use memory::Memory;
-
-use std::cell::RefCell;
-use std::rc::Rc;
+use rom::TvSystem;
 
 // how many cpu cycles per single dmc output change
 static NTSC_RATE : [u16; 16] = [
@@ -14,7 +12,7 @@ static PAL_RATE : [u16; 16] = [
     176, 148, 132, 118,  98,  78,  66,  50
 ];
 
-struct Reader<'a> {
+struct Reader {
     sample_address: u16,
     sample_length: u16,
     current_address: u16,
@@ -22,14 +20,12 @@ struct Reader<'a> {
     loop_flag: bool,
     interrupt_enabled: bool,
     interrupt_flag: bool,
-    memory: Option<Rc<RefCell<Box<Memory + 'a>>>>,
     buffer: Option<u8>,
-    delay_cpu: u8,
-    borrow_read_workaround: Option<u16>,
+    dma_request: Option<u16>, // sample address the memory bus has not fetched yet
 }
 
-impl<'a> Reader<'a> {
-    fn new() -> Reader<'a> {
+impl Reader {
+    fn new() -> Reader {
         Reader {
             sample_address: 0,
             sample_length: 0,
@@ -38,10 +34,8 @@ impl<'a> Reader<'a> {
             loop_flag: false,
             interrupt_enabled: false,
             interrupt_flag: false,
-            memory: None,
             buffer: None,
-            delay_cpu: 0,
-            borrow_read_workaround: None,
+            dma_request: None,
         }
     }
 
@@ -65,22 +59,9 @@ impl<'a> Reader<'a> {
         self.buffer = None;
 
         if self.current_length > 0 {
-            // FIXME: CPU stall duration varies, not always 4
-            self.delay_cpu = 4;
-
-            let new_buf = if let Some(ref memory) = self.memory {
-                // borrow checker workaround. This may get invoked when
-                // memory is being written into --> memory already borrowed
-                // --> attempt to reborrow panics.
-                // set a flag to read the value after memory is available again
-                if let Some(_) = self.borrow_read_workaround {
-                    panic!("Borrow workaround broke");
-                }
-                self.borrow_read_workaround = Some(self.current_address);
-            } else {
-                panic!("Memory bus unexpectedly None");
-            };
-
+            // the memory bus stalls the cpu and fetches the byte; the buffer is filled
+            // when the fetch completes
+            self.dma_request = Some(self.current_address);
 
             if self.current_address == 0xFFFF {
                 self.current_address = 0x8000;
@@ -97,8 +78,6 @@ impl<'a> Reader<'a> {
                     self.interrupt_flag = true;
                 }
             }
-
-            self.buffer = Some(0);
         } else {
             assert!(self.interrupt_flag || !self.loop_flag);
         }
@@ -106,19 +85,6 @@ impl<'a> Reader<'a> {
         return out;
     }
 
-    fn borrow_workaround(&mut self) {
-        if let Some(addr) = self.borrow_read_workaround {
-
-            if let Some(ref memory) = self.memory {
-                let val = memory.borrow_mut().read(addr);
-                self.buffer = Some(val);
-            } else {
-                panic!("Invariant violation in apu dmc mem read");
-            };
-
-            self.borrow_read_workaround = None;
-        }
-    }
 }
 
 struct Output {
@@ -158,15 +124,16 @@ impl Output {
     }
 }
 
-pub struct DmcChannel<'a> {
+pub struct DmcChannel {
     enabled: bool,
     rate: u16,
     counter: u16,
-    reader: Reader<'a>,
+    reader: Reader,
     output: Output,
+    rate_table: &'static [u16; 16],
 }
 
-impl<'a> Memory for DmcChannel<'a> {
+impl Memory for DmcChannel {
 
     fn read(&mut self, address: u16) ->  u8 {
         panic!("Invalid read attempt of dmc channel register {:0x}",
@@ -180,8 +147,7 @@ impl<'a> Memory for DmcChannel<'a> {
                 self.reader.interrupt_flag = false;
             }
             self.reader.loop_flag = (0b0100_0000 & value) != 0;
-            // FIXME: Properly select NTSC/PAL rates
-            self.rate = NTSC_RATE[(0b0000_1111 & value) as usize];
+            self.rate = self.rate_table[(0b0000_1111 & value) as usize];
         } else if address == 0x4011 {
             self.output.output_level = (0b0111_1111 & value);
         } else if address == 0x4012 {
@@ -195,25 +161,31 @@ impl<'a> Memory for DmcChannel<'a> {
     }
 }
 
-impl<'a> DmcChannel<'a> {
-    pub fn new() -> DmcChannel<'a> {
+impl DmcChannel {
+    pub fn new() -> DmcChannel {
         DmcChannel {
             enabled: false,
             rate: 0,
             counter: 0,
             reader: Reader::new(),
             output: Output::new(),
+            rate_table: &NTSC_RATE,
         }
     }
 
+    pub fn set_tv_system(&mut self, tv_system: &TvSystem) {
+        self.rate_table = match *tv_system {
+            TvSystem::PAL => &PAL_RATE,
+            _ => &NTSC_RATE,
+        };
+    }
+
     pub fn enable_channel(&mut self, enable: bool) {
         self.enabled = enable;
         self.reader.enable(enable);
     }
 
     pub fn cycle_timer(&mut self) {
-        self.reader.borrow_workaround();
-
         if !self.enabled {
             return;
         }
@@ -242,8 +214,12 @@ impl<'a> DmcChannel<'a> {
         self.reader.interrupt_flag = false;
     }
 
-    pub fn set_memory(&mut self, mem: Rc<RefCell<Box<Memory + 'a>>>) {
-        self.reader.memory = Some(mem);
+    pub fn dma_request(&mut self) -> Option<u16> {
+        self.reader.dma_request.take()
+    }
+
+    pub fn dma_complete(&mut self, value: u8) {
+        self.reader.buffer = Some(value);
     }
 
     pub fn active(&self) -> bool {
@@ -254,12 +230,6 @@ impl<'a> DmcChannel<'a> {
     pub fn dmc_rate(&self) -> u16 {
         self.rate
     }
-
-    pub fn delay_cpu(&mut self) -> u8 {
-        let out = self.reader.delay_cpu;
-        self.reader.delay_cpu = 0;
-        out
-    }
 }
 
 
@@ -267,50 +237,36 @@ impl<'a> DmcChannel<'a> {
 mod tests {
     use super::*;
     use memory::Memory;
-    use std::rc::Rc;
-    use std::cell::RefCell;
-
-    struct MockMemory {
 
-    }
-
-    impl MockMemory {
-        fn new() -> MockMemory {
-            MockMemory {
-
-            }
-        }
-    }
-
-    impl Memory for MockMemory {
-        fn read(&mut self, address: u16) -> u8 {
-            0
-        }
-
-        fn write(&mut self, address: u16, value: u8) {
-
-        }
-    }
-
-    fn create_test_dmc<'a>() -> DmcChannel<'a> {
-        let mut channel = DmcChannel::new();
-        let mem = Rc::new(
-            RefCell::new(
-                Box::new(MockMemory::new()) as Box<Memory>));
-        channel.set_memory(mem);
-        channel
+    fn create_test_dmc() -> DmcChannel {
+        DmcChannel::new()
     }
 
 
 
     // implements tests present in the various nes APU test roms
 
+    // completes sample fetches like the memory bus would
     fn delay_dmc(dmc: &mut DmcChannel, count: u16) {
         for _ in 0..dmc.rate*8*count {
             dmc.cycle_timer();
+            if dmc.dma_request().is_some() {
+                dmc.dma_complete(0);
+            }
         }
     }
 
+    #[test]
+    fn enabling_channel_requests_byte_from_sample_address() {
+        let mut dmc = create_test_dmc();
+        dmc.write(0x4012, 0x02);
+        dmc.write(0x4013, 1);
+        dmc.enable_channel(true);
+
+        assert_eq!(Some(0xc080), dmc.dma_request());
+        assert_eq!(None, dmc.dma_request());
+    }
+
     // tests from dmc basics test rom by blargg
     #[test]
     fn channel_is_active_and_then_disabled_after_sample_ends() {
@@ -537,6 +493,9 @@ mod tests {
         dmc.write(0x4010, 0x8F);
         dmc.write(0x4013, 0);
         dmc.enable_channel(true);
+        // the memory bus fetches the byte before the next register write
+        assert!(dmc.dma_request().is_some());
+        dmc.dma_complete(0);
 
         assert!(dmc.pending_interrupt());
         assert!(!dmc.active());
@@ -12,6 +12,7 @@ mod linear_counter;
 mod timer;
 
 use memory::Memory;
+use rom::TvSystem;
 
 use self::pulse_channel::{PulseChannel};
 use self::sweep::Complement;
@@ -22,8 +23,6 @@ use self::envelope::Envelope;
 use self::sweep::Sweep;
 
 use std::collections::VecDeque;
-use std::cell::RefCell;
-use std::rc::Rc;
 
 const APU_STATUS_REGISTER : u16 = 0x4015;
 const FRAME_COUNTER_REGISTER : u16 = 0x4017;
@@ -40,7 +39,33 @@ enum CycleState {
     NormalCycle,
 }
 
+// frame counter step timings. These are the documented apu cycles multiplied by two, see FrameCounter::cycle
+struct FrameSteps {
+    first_quarter: u32,
+    half: u32,
+    third_quarter: u32,
+    four_step_end: u32, // interrupt is raised on this and the two following cycles
+    five_step_end: u32,
+}
+
+static NTSC_FRAME_STEPS: FrameSteps = FrameSteps {
+    first_quarter: 7457,
+    half: 14913,
+    third_quarter: 22371,
+    four_step_end: 29828,
+    five_step_end: 37281,
+};
+
+static PAL_FRAME_STEPS: FrameSteps = FrameSteps {
+    first_quarter: 8313,
+    half: 16627,
+    third_quarter: 24939,
+    four_step_end: 33252,
+    five_step_end: 41565,
+};
+
 struct FrameCounter {
+    steps: &'static FrameSteps,
     mode: FrameMode,
     cycle: u32,
     interrupt_disabled: bool,
@@ -52,6 +77,7 @@ struct FrameCounter {
 impl FrameCounter {
     fn new() -> FrameCounter {
          FrameCounter {
+            steps: &NTSC_FRAME_STEPS,
             mode: FrameMode::Mode0,
             cycle: 0,
             interrupt_disabled: true,
@@ -79,30 +105,31 @@ impl FrameCounter {
         // apu cycle. This fixes the half-cycle issue with timings
         // where some actions occur on half cycle (at apu cycle 3728.5
         // for example)
+        let steps = self.steps;
         match self.mode {
             FrameMode::Mode0 => {
-                if self.cycle == 7457 ||
-                    self.cycle == 22371 {
+                if self.cycle == steps.first_quarter ||
+                    self.cycle == steps.third_quarter {
                     retval = CycleState::QuarterFrameCycle;
-                } else if self.cycle == 14913 {
+                } else if self.cycle == steps.half {
                     retval = CycleState::HalfFrameCycle;
-                } else if self.cycle == 29828 {
+                } else if self.cycle == steps.four_step_end {
                     self.interrupt();
-                } else if self.cycle == 29829 {
+                } else if self.cycle == steps.four_step_end + 1 {
                     self.interrupt();
                     retval = CycleState::HalfFrameCycle;
-                } else if self.cycle == 29830 {
+                } else if self.cycle == steps.four_step_end + 2 {
                     self.interrupt();
                     self.cycle = 0;
                 }
             },
             FrameMode::Mode1 => {
-                if self.cycle == 7457 ||
-                    self.cycle == 22371 {
+                if self.cycle == steps.first_quarter ||
+                    self.cycle == steps.third_quarter {
                     retval = CycleState::QuarterFrameCycle;
-                } else if self.cycle == 14913 {
+                } else if self.cycle == steps.half {
                     retval = CycleState::HalfFrameCycle;
-                } else if self.cycle == 37281 {
+                } else if self.cycle == steps.five_step_end {
                     self.cycle = 0;
                     retval = CycleState::HalfFrameCycle;
                 }
@@ -148,12 +175,12 @@ impl<T : AudioFormatNum> Audio<T> for SDLAudio<T> {
 }
 
 
-pub struct Apu<'a> {
+pub struct Apu {
     pulse_channel_1: PulseChannel,
     pulse_channel_2: PulseChannel,
     triangle_channel: TriangleChannel,
     noise_channel: NoiseChannel,
-    dmc_channel: DmcChannel<'a>,
+    dmc_channel: DmcChannel,
     frame_counter: FrameCounter,
     buffer: Vec<f32>,
     sample_cycle: f64,
@@ -163,7 +190,7 @@ pub struct Apu<'a> {
     is_even_cycle: bool,
 }
 
-impl<'a> Memory for Apu<'a> {
+impl Memory for Apu {
     fn read(&mut self,  address: u16) -> u8 {
         if address == APU_STATUS_REGISTER {
 
@@ -257,8 +284,8 @@ impl<'a> Memory for Apu<'a> {
     }
 }
 
-impl<'a> Apu<'a> {
-    pub fn new(audio_queue: Box<Audio<f32>>) -> Apu<'a> {
+impl Apu {
+    pub fn new(audio_queue: Box<Audio<f32>>) -> Apu {
         Apu {
             pulse_channel_1: PulseChannel::new(Complement::One),
             pulse_channel_2: PulseChannel::new(Complement::Two),
@@ -280,6 +307,17 @@ impl<'a> Apu<'a> {
         self.max_samples_before_clearing_buffer = samples as usize;
     }
 
+    // PAL apu runs at the slower PAL cpu clock and uses different frame counter timings and
+    // noise\dmc rate tables
+    pub fn set_tv_system(&mut self, tv_system: &TvSystem) {
+        self.frame_counter.steps = match *tv_system {
+            TvSystem::PAL => &PAL_FRAME_STEPS,
+            _ => &NTSC_FRAME_STEPS,
+        };
+        self.noise_channel.set_tv_system(tv_system);
+        self.dmc_channel.set_tv_system(tv_system);
+    }
+
     pub fn set_sampling_rate(&mut self, cpu_frequency: f64, sample_rate: i32) {
         self.cycles_per_sample =
             ((cpu_frequency*1000_000.0) / sample_rate as f64);
@@ -367,8 +405,13 @@ impl<'a> Apu<'a> {
         self.dmc_channel.pending_interrupt()
     }
 
-    pub fn set_memory(&mut self, mem: Rc<RefCell<Box<Memory + 'a>>>) {
-        self.dmc_channel.set_memory(mem);
+    // address of a dmc sample byte waiting to be fetched by the memory bus
+    pub fn dmc_dma_request(&mut self) -> Option<u16> {
+        self.dmc_channel.dma_request()
+    }
+
+    pub fn dmc_dma_complete(&mut self, value: u8) {
+        self.dmc_channel.dma_complete(value);
     }
 
     fn output(&self) -> f64 {
@@ -383,10 +426,6 @@ impl<'a> Apu<'a> {
             + 0.00335*self.dmc_channel.output();
         pulse_output + tnd_output
     }
-
-    pub fn delay_cpu(&mut self) -> u8 {
-        self.dmc_channel.delay_cpu()
-    }
 }
 
 
@@ -395,8 +434,6 @@ impl<'a> Apu<'a> {
 mod tests {
     use super::*;
     use memory::Memory;
-    use std::rc::Rc;
-    use std::cell::RefCell;
 
     struct MockAudio {
     }
@@ -415,43 +452,18 @@ mod tests {
         }
     }
 
-    struct MockMemory {
-
-    }
-
-    impl MockMemory {
-        fn new() -> MockMemory {
-            MockMemory {
-
-            }
-        }
-    }
-
-    impl Memory for MockMemory {
-        fn read(&mut self, address: u16) -> u8 {
-            0
-        }
-
-        fn write(&mut self, address: u16, value: u8) {
-
-        }
-    }
-
-    fn create_test_apu<'a>() -> Apu<'a> {
-
+    fn create_test_apu() -> Apu {
         let audio = Box::new(MockAudio::new());
-        let mut apu = Apu::new(audio);
-
-        let mem = Rc::new(
-            RefCell::new(
-                Box::new(MockMemory::new()) as Box<Memory>));
-        apu.set_memory(mem);
-        apu
+        Apu::new(audio)
     }
 
+    // completes sample fetches like the memory bus would
     fn delay_dmc(apu: &mut Apu, count: u16) {
         for _ in 0..apu.dmc_channel.dmc_rate()*8*count {
             apu.execute_cycle();
+            if apu.dmc_dma_request().is_some() {
+                apu.dmc_dma_complete(0);
+            }
         }
     }
 
@@ -487,6 +499,32 @@ mod tests {
         assert!(!apu.frame_counter.interrupt_flag);
     }
 
+    #[test]
+    fn pal_frame_interrupt_is_raised_later_than_ntsc_frame_interrupt() {
+        let mut apu = create_test_apu();
+        apu.set_tv_system(&TvSystem::PAL);
+
+        apu.write(FRAME_COUNTER_REGISTER, 0x00);
+
+        for _ in 0..30000 {
+            apu.execute_cycle();
+        }
+        assert!(!apu.frame_counter.interrupt_flag);
+
+        for _ in 0..4000 {
+            apu.execute_cycle();
+        }
+        assert!(apu.frame_counter.interrupt_flag);
+    }
+
+    #[test]
+    fn pal_dmc_uses_pal_rate_table() {
+        let mut apu = create_test_apu();
+        apu.set_tv_system(&TvSystem::PAL);
+        apu.write(0x4010, 0x0F);
+        assert_eq!(50, apu.dmc_channel.dmc_rate());
+    }
+
     #[test]
     fn writing_to_0x4015_clears_dmc_interrupt_flag() {
         let mut apu = create_test_apu();
@@ -1,4 +1,5 @@
 use memory::Memory;
+use rom::TvSystem;
 
 use apu::timer::{Timer, TimerCycle};
 use apu::envelope::Envelope;
@@ -21,6 +22,7 @@ pub struct NoiseChannel {
     timer: Timer,
     mode_flag: bool,
     shift_register: u16,
+    rate_table: &'static [u16; 16],
 }
 
 
@@ -50,8 +52,7 @@ impl Memory for NoiseChannel {
             let rate_index = (0b0000_1111 & value);
 
             self.mode_flag = mode_flag;
-            // FIXME: Select NTSC/PAL rate correctly
-            let rate = NTSC_RATE[rate_index as usize];
+            let rate = self.rate_table[rate_index as usize];
             self.timer.set_period(rate);
 
         } else if address == 0x400F {
@@ -76,6 +77,7 @@ impl NoiseChannel {
             timer: Timer::new(),
             mode_flag: false,
             shift_register: 1, // value of reg after power up is 1
+            rate_table: &NTSC_RATE,
         }
     }
 
@@ -87,6 +89,13 @@ impl NoiseChannel {
         self.length_counter.cycle();
     }
 
+    pub fn set_tv_system(&mut self, tv_system: &TvSystem) {
+        self.rate_table = match *tv_system {
+            TvSystem::PAL => &PAL_RATE,
+            _ => &NTSC_RATE,
+        };
+    }
+
     pub fn cycle_timer(&mut self) {
         if self.timer.cycle() == TimerCycle::ZeroCycle {
 
@@ -7,28 +7,28 @@ use self::sdl2::audio::{AudioCallback, AudioSpecDesired, AudioDevice, AudioQueue
 use self::sdl2::keyboard::Keycode;
 use self::sdl2::event::Event;
 use std::time::Duration;
+use std::fs;
+use std::path::{Path, PathBuf};
 
-use memory::Memory;
+use mapper::create_mapper;
 use memory_bus::*;
 use cpu::Cpu;
+use cpu::trace::Tracer;
 use ppu::Ppu;
+use ppu::palette::Palette;
 use apu::{Apu, SDLAudio};
-use rom::read_rom;
+use rom::{read_rom, RomError, TvSystem};
 use ppu::renderer::*;
 use controller::Controller;
 
-
-use std::rc::Rc;
-use std::cell::RefCell;
-
 const SAMPLE_RATE: i32 = 44100;
 const SAMPLES: u16= 2048;
+// how often battery backed save ram is written to disk, if it has changed
+const SAVE_FLUSH_INTERVAL_NS: u64 = 5_000_000_000;
 
 struct Console<'a> {
-    cpu: Cpu<'a>,
-    ppu: Rc<RefCell<Ppu<'a>>>,
-    apu: Rc<RefCell<Apu<'a>>>,
-    controllers: Vec<Rc<RefCell<Controller>>>,
+    cpu: Cpu<MemoryBus<'a>>,
+    save_path: Option<PathBuf>, // None if the cartridge has no battery backed ram
 }
 // borrow checker workarounds
 struct CanvasStruct {
@@ -76,70 +76,84 @@ fn init_sdl() ->
 
 fn initialize_console<'a>(
     rom_path: &str,
+    region: Option<TvSystem>,
+    palette: Option<Palette>,
     canvas: &'a mut CanvasStruct,
     texture_creator: &'a TextureCreator<WindowContext>,
-    audio_queue: AudioQueue<f32>) -> Console<'a> {
-    let rom = Box::new(read_rom(rom_path));
+    audio_queue: AudioQueue<f32>,
+    tracer: Option<Tracer>) -> Result<Console<'a>, RomError> {
+    let rom = read_rom(rom_path)?;
 
-    let controller_one = Rc::new(RefCell::new(Controller::new(None)));
-    let controller_two = Rc::new(RefCell::new(Controller::new(None)));
-    let controllers = vec![controller_one.clone(), controller_two.clone()];
+    let controllers = vec![Controller::new(None), Controller::new(None)];
 
 
     println!("{:#?}", rom.header);
 
-    let tv_system = rom.header.tv_system.clone();
-    let mirroring = rom.header.mirroring.clone();
+    let tv_system = region.unwrap_or(rom.header.tv_system.clone());
+
+    let save_path = if rom.has_battery_backing() {
+        Some(Path::new(rom_path).with_extension("sav"))
+    } else {
+        None
+    };
 
-    let rom_mem = Rc::new(RefCell::new(rom as Box<Memory>));
+    let mapper = create_mapper(rom)?;
     let renderer = Box::new(SDLRenderer::new(
                 &mut canvas.canvas,
                 &texture_creator));
 
-    let ppu = Rc::new(RefCell::new(
-        Ppu::new(
+    let mut ppu = Ppu::new(
             renderer,
             tv_system.clone(),
-            mirroring,
-            rom_mem.clone())));
+            mapper);
+
+    if let Some(palette) = palette {
+        ppu.set_palette(palette);
+    }
 
     let audio_box = Box::new(SDLAudio::new(audio_queue));
-    let apu = Rc::new(RefCell::new(Apu::new(audio_box)));
-    apu.borrow_mut().samples(SAMPLES/2);
-
-    let mem = Rc::new(RefCell::new(
-        Box::new(
-            MemoryBus::new(
-                rom_mem.clone(),
-                ppu.clone(),
-                apu.clone(),
-                controllers.clone(),
-            )
-        ) as Box<Memory>));
-
-    apu.borrow_mut().set_memory(mem.clone());
-    let cpu = Cpu::new(&tv_system, mem.clone());
-
-    apu.borrow_mut()
+    let mut apu = Apu::new(audio_box);
+    apu.samples(SAMPLES/2);
+    apu.set_tv_system(&tv_system);
+
+    let bus = MemoryBus::new(ppu, apu, controllers);
+    let mut cpu = Cpu::new(&tv_system, bus);
+    cpu.set_tracer(tracer);
+
+    let cpu_clock_frequency = cpu.frequency.cpu_clock_frequency;
+    cpu.bus_mut()
+        .apu_mut()
         .set_sampling_rate(
-            cpu.frequency.cpu_clock_frequency,
+            cpu_clock_frequency,
             SAMPLE_RATE);
 
-    Console {
+    Ok(Console {
         cpu: cpu,
-        ppu: ppu.clone(),
-        apu: apu.clone(),
-        controllers: controllers.clone(),
-    }
+        save_path: save_path,
+    })
 }
 
-pub fn execute(rom_path: &str) {
+pub fn execute(
+    rom_path: &str,
+    region: Option<TvSystem>,
+    palette: Option<Palette>,
+    tracer: Option<Tracer>) {
     let (sdl_context, mut canvas, texture_creator, audio_queue) = init_sdl();
     audio_queue.resume();
-    let mut console = initialize_console(
+    let mut console = match initialize_console(
         rom_path,
+        region,
+        palette,
         &mut canvas,
-        &texture_creator, audio_queue);
+        &texture_creator,
+        audio_queue,
+        tracer) {
+        Ok(console) => console,
+        Err(e) => {
+            println!("Could not load the rom {}: {}", rom_path, e);
+            return;
+        }
+    };
 
     let cpu_cycle_time_in_nanoseconds = (1.0/(console.cpu.frequency.cpu_clock_frequency/1000.0)) as u64;
     println!("CPU frequency: {}", console.cpu.frequency.cpu_clock_frequency);
@@ -156,9 +170,15 @@ pub fn execute(rom_path: &str) {
     // PAL PPU executes exactly 3.2 cycles for each CPU cycle (vs exactly 3 cycles NTSC).
     // this means we need extra cycle every now an then when emulating PAL to maintaing timing
 
+    console.load_save_ram();
     console.cpu.reset();
 
     let mut time = time::precise_time_ns();
+    let mut last_save_flush = time;
+    let mut jam_reported = false;
+    // instructions take several cycles, so a tick may overshoot; the overshoot is taken off
+    // the next tick
+    let mut target_cycles = console.cpu.cycles();
     let cycle_time = cpu_cycle_time_in_nanoseconds * cpu_cycles_per_tick;
     println!("Nanoseconds between cycling: {}", cycle_time);
     'main_loop: loop {
@@ -167,12 +187,23 @@ pub fn execute(rom_path: &str) {
 
 
         if time_taken > cycle_time {
-            for _ in 0..cpu_cycles_per_tick {
+            target_cycles += cpu_cycles_per_tick;
+            while console.cpu.cycles() < target_cycles {
                 console.run_emulation_tick();
             }
             let consumed_time = time::precise_time_ns() - current_time;
 
             time = current_time - (time_taken - cycle_time);
+
+            if console.cpu.is_jammed() && !jam_reported {
+                println!("CPU jammed at ${:04X}", console.cpu.program_counter());
+                jam_reported = true;
+            }
+        }
+
+        if current_time - last_save_flush > SAVE_FLUSH_INTERVAL_NS {
+            console.flush_save_ram();
+            last_save_flush = current_time;
         }
 
         let mut event_pump = sdl_context.event_pump().unwrap();
@@ -182,56 +213,59 @@ pub fn execute(rom_path: &str) {
                 Event::Quit {..} | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
                     break 'main_loop;
                 },
+                Event::KeyDown { keycode: Some(Keycode::F12), repeat: false, .. } => {
+                    if let Some(tracer) = console.cpu.tracer_mut() {
+                        let enabled = !tracer.is_enabled();
+                        tracer.set_enabled(enabled);
+                        println!("Tracing {}", if enabled { "resumed" } else { "paused" });
+                    }
+                },
                 Event::KeyDown { keycode, ..} => {
                     if let Some(key) = keycode {
-                        console.controllers[0].borrow_mut().key_down(key);
-                        console.controllers[1].borrow_mut().key_down(key);
+                        for controller in console.cpu.bus_mut().controllers_mut() {
+                            controller.key_down(key);
+                        }
                     }
                 },
                 Event::KeyUp { keycode, ..} => {
                     if let Some(key) = keycode {
-                        console.controllers[0].borrow_mut().key_up(key);
-                        console.controllers[1].borrow_mut().key_up(key);
+                        for controller in console.cpu.bus_mut().controllers_mut() {
+                            controller.key_up(key);
+                        }
                     }
                 }
                 _ => {}
             }
         }
     }
+    console.flush_save_ram();
 }
 
 impl<'a> Console<'a> {
-    fn run_emulation_tick(&mut self) {
-        // ensure instruction timing
-        if self.cpu.wait_counter > 0 {
-            self.cpu.wait_counter -= 1;
-        } else {
-            // check for nmi from ppu
-            let nmi_occured = self.ppu.borrow_mut().nmi_occured();
-            let irq_line = self.apu.borrow_mut().pending_interrupt();
-            self.cpu.set_interrupt_line(irq_line);
-            if nmi_occured {
-                self.cpu.handle_nmi();
-            } else {
-                self.cpu.execute_instruction();
-                // lazy fix for off-by-one error in timings
-                if self.cpu.wait_counter > 0 {
-                    self.cpu.wait_counter -= 1;
-                }
+    fn load_save_ram(&mut self) {
+        if let Some(ref path) = self.save_path {
+            // missing save file is not an error; the game just has not been saved yet
+            if let Ok(data) = fs::read(path) {
+                self.cpu.bus_mut().mapper_mut().rom_mut().load_save_ram(&data);
             }
         }
-        // emulate PPU cycles. Executes 3 cycles (NTSC) or average 3.2 cycles (PAL) per cpu cycle.
-        // PAL executes 3 cycles with an additional cycle every few cpu cycles to remain in sync
-        self.ppu.borrow_mut().execute_cycles();
-        // apu strictly speaking cycles once for each two cpu cycle, but for
-        // timing reasons we cycle it once per cpu cycle. apu handles
-        // this difference internally
-        self.apu.borrow_mut().execute_cycle();
-        let delay = self.apu.borrow_mut().delay_cpu() as u16;
-        if  delay > 0 && self.cpu.wait_counter < delay {
-            self.cpu.wait_counter = delay ;
+    }
+
+    // writes battery backed ram to disk if it has changed since the last flush
+    fn flush_save_ram(&mut self) {
+        if let Some(ref path) = self.save_path {
+            if let Some(data) = self.cpu.bus_mut().mapper_mut().rom_mut().save_ram() {
+                if let Err(e) = fs::write(path, data) {
+                    println!("Could not write save file {}: {}", path.display(), e);
+                }
+            }
         }
+    }
 
+    // executes a single instruction or interrupt. The cpu clocks the rest of the system
+    // and polls interrupts through the memory bus as it goes
+    fn run_emulation_tick(&mut self) {
+        self.cpu.execute_instruction();
     }
 
 }
@@ -27,8 +27,8 @@ pub struct Controller {
 
 
 impl Memory for Controller {
-    // TODO: Upper 3 bits should maintain the value that the bus had previously; 
-    // this is currently not implemented. At least one game uses this (paperboy)
+    // Only the low bits are driven. The memory bus fills in the upper 3 bits with the value
+    // the bus had previously; at least one game relies on this (paperboy)
     fn read(&mut self, address: u16) -> u8 {
         assert!(address == 0x4016 || address == 0x4017);
         
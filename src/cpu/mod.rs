@@ -1,8 +1,9 @@
 
+pub mod trace;
+
 use rom::TvSystem;
-use memory::Memory;
-use std::rc::Rc;
-use std::cell::RefCell;
+use memory::{Memory, Bus};
+use self::trace::{Tracer, CpuState};
 // official opcodes: http://www.obelisk.demon.co.uk/6502/reference.html
 // addressing modes: http://www.obelisk.demon.co.uk/6502/addressing.html
 
@@ -13,128 +14,215 @@ use std::cell::RefCell;
 // The documentation on behaviour of unofficial opcodes is somewhat inconsistent.
 // Conflicts have been solved by observing existing emulator behaviour (hopefully they got it right)
 
-pub struct Cpu<'a> {
-    memory: Rc<RefCell<Box<Memory +'a>>>, // reference to memory, so that cpu can use it
+pub struct Cpu<B: Bus> {
+    bus: B,
     pub frequency: Frequency,
     program_counter:u16,
     stack_pointer:u8,
-    pub wait_counter: u16, // used by instructions that take more than 1 cycle to complete
+    cycles: u64, // every bus read and write takes one cycle
     status_flags:u8,
     a: u8,
     x: u8,
     y: u8,
-    is_odd_cycle: bool, // PPU OAM DMA timing depends on whether cpu is on odd\even cycle
-    interrupt_line: bool,
+    // Interrupts are polled at the end of every cycle, but the cpu acts on the state from the
+    // penultimate cycle of an instruction. The previous_* fields hold the state from the cycle
+    // before the latest one.
+    nmi_pending: bool,
+    previous_nmi_pending: bool,
+    irq_pending: bool,
+    previous_irq_pending: bool,
+    jammed: bool, // set by JAM opcodes; cpu does nothing until reset
+    tracer: Option<Tracer>,
 }
 
-impl<'a> Memory for Cpu<'a> {
+impl<B: Bus> Memory for Cpu<B> {
     fn write(&mut self, address:u16, value: u8) {
-        if address == 0x4014 { // PPU DMA register - transfer takes time
-            self.wait_counter = if self.is_odd_cycle {
-                514
-            } else {
-                513
-            }
+        self.cycles += 1;
+        self.bus.write(address, value);
+        self.poll_interrupts();
+        if address == 0x4014 {
+            self.do_oam_dma(value);
         }
-        self.memory.borrow_mut().write(address, value);
     }
 
     fn read(&mut self, address: u16) -> u8 {
-        self.memory.borrow_mut().read(address)
+        self.cycles += 1;
+        let value = self.bus.read(address);
+        self.poll_interrupts();
+        value
     }
 }
 
-impl<'a> Cpu<'a> {
-    pub fn new(tv_system: &TvSystem, memory: Rc<RefCell<Box<Memory + 'a>>>) -> Cpu<'a> {
+impl<B: Bus> Cpu<B> {
+    pub fn new(tv_system: &TvSystem, bus: B) -> Cpu<B> {
         Cpu {
-            memory: memory,
+            bus: bus,
             frequency: Frequency::new(&tv_system),
             program_counter: 0,
-            stack_pointer: 0xFD,
+            stack_pointer: 0x00, // reset sets this to 0xFD
             status_flags: 0x34, // unused 4 and 5 bits to 1; interrupt flag at 2 bit to 1
-            wait_counter: 0,
+            cycles: 0,
             a: 0,
             x: 0,
             y: 0,
-            is_odd_cycle: false,
-            interrupt_line: false
+            nmi_pending: false,
+            previous_nmi_pending: false,
+            irq_pending: false,
+            previous_irq_pending: false,
+            jammed: false,
+            tracer: None,
         }
     }
 
+    // Reset runs the interrupt sequence with the stack writes turned into reads, so it takes
+    // 7 cycles and moves the stack pointer down by 3 (0xFD on power up)
     pub fn reset(&mut self) {
+        self.jammed = false;
+        self.nmi_pending = false;
+        self.previous_nmi_pending = false;
+        self.irq_pending = false;
+        self.previous_irq_pending = false;
+        self.dummy_read_program_counter();
+        self.dummy_read_program_counter();
+        for _ in 0..3 {
+            self.dummy_read_stack();
+            self.stack_pointer = self.stack_pointer.wrapping_sub(1);
+        }
+        self.status_flags = self.status_flags | 0x04;
         self.program_counter = 0xFFFC;
         self.jump_absolute();
     }
 
-    pub fn handle_nmi(&mut self) {
+    fn interrupts_enabled(&self) -> bool {
+        return self.status_flags & 0x04 == 0;
+    }
+
+    // Nmi is edge triggered, so it stays pending until serviced. Irq is level triggered and is
+    // only acted upon while the interrupt disable flag is clear. As the flag is checked here,
+    // instructions changing it (cli, sei, plp) on their last cycle affect interrupts only after
+    // the next instruction
+    fn poll_interrupts(&mut self) {
+        self.previous_nmi_pending = self.nmi_pending;
+        self.previous_irq_pending = self.irq_pending;
+
+        if self.bus.nmi_occured() {
+            self.nmi_pending = true;
+        }
+        self.irq_pending = self.bus.irq_line() && self.interrupts_enabled();
+    }
+
+    // nmi and irq take 7 cycles like brk; the opcode fetch and the operand read are done
+    // but discarded, so the program counter is not incremented
+    fn do_hardware_interrupt(&mut self) {
+        self.dummy_read_program_counter();
+        self.dummy_read_program_counter();
+
         let return_address = self.program_counter;
         self.push_value_into_stack(((return_address & 0xFF00) >> 8) as u8);
         self.push_value_into_stack((return_address & 0xFF) as u8);
 
+        let vector = self.interrupt_vector();
         let flags = (self.status_flags & 0xEF) | 0x20; // bit 5 must be set and 4 must be clear
         self.push_value_into_stack(flags);
         // disable interrupts
         self.status_flags = self.status_flags | 0x04;
-        self.program_counter = 0xFFFA;
+        self.program_counter = vector;
         self.jump_absolute();
-        self.wait_counter = 7;
+        self.delay_nmi_until_next_instruction();
     }
 
-    pub fn set_interrupt_line(&mut self, line: bool) {
-        self.interrupt_line = line;
+    // The vector is chosen after the return address has been pushed. An nmi occuring before that
+    // hijacks irq and brk, which then jump to the nmi handler instead
+    fn interrupt_vector(&mut self) -> u16 {
+        if self.nmi_pending {
+            self.nmi_pending = false;
+            0xFFFA
+        } else {
+            0xFFFE
+        }
     }
 
-    fn interrupts_enabled(&self) -> bool {
-        return self.status_flags & 0x04 == 0;
+    // interrupts are not polled at the end of the interrupt sequence, so the first instruction
+    // of the handler is always executed before the next interrupt
+    fn delay_nmi_until_next_instruction(&mut self) {
+        self.previous_nmi_pending = false;
     }
 
-    pub fn handle_interrupt(&mut self) {
-        if self.interrupts_enabled() {
-           self.force_interrupt();
-        }
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    pub fn is_jammed(&self) -> bool {
+        self.jammed
+    }
+
+    pub fn program_counter(&self) -> u16 {
+        self.program_counter
+    }
+
+    pub fn bus_mut(&mut self) -> &mut B {
+        &mut self.bus
+    }
+
+    pub fn set_tracer(&mut self, tracer: Option<Tracer>) {
+        self.tracer = tracer;
+    }
+
+    pub fn tracer_mut(&mut self) -> Option<&mut Tracer> {
+        self.tracer.as_mut()
     }
 
     pub fn execute_instruction(&mut self) {
+        // a jammed cpu keeps reading the bus, so the rest of the system keeps running
+        if self.jammed {
+            self.read(0xFFFF);
+            return;
+        }
 
-        if self.interrupt_line && self.interrupts_enabled() {
-            self.handle_interrupt();
+        if self.previous_nmi_pending || self.previous_irq_pending {
+            self.do_hardware_interrupt();
             return;
         }
 
 
+        if let Some(ref mut tracer) = self.tracer {
+            let state = CpuState {
+                program_counter: self.program_counter,
+                a: self.a,
+                x: self.x,
+                y: self.y,
+                status_flags: self.status_flags,
+                stack_pointer: self.stack_pointer,
+                cycles: self.cycles,
+            };
+            tracer.trace(&state, &mut self.bus);
+        }
+
         let pc = self.program_counter;
         let instruction = self.read(pc);
-
-/*        println!("{:04X} Opcode:{:02X} A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X}",
-            self.program_counter,
-            instruction,
-            self.a,
-            self.x,
-            self.y,
-            self.status_flags & 0xEF,
-            self.stack_pointer,
-            );
-*/
         self.program_counter += 1;
         match instruction {
             0 => self.force_interrupt(),
             1 => self.inclusive_or_indirect_x(),
+            2 => self.unofficial_jam(),
             3 => self.unofficial_shift_left_memory_inclusive_or_acc_indirect_x(),
-            4 => self.unofficial_double_no_operation(3),
+            4 => self.unofficial_nop_zero_page(),
             5 => self.inclusive_or_zero_page(),
             6 => self.arithmetic_shift_left_zero_page(),
             7 => self.unofficial_shift_left_memory_inclusive_or_acc_zero_page(),
             8 => self.push_status_flags_into_stack(),
             9 => self.inclusive_or_immediate(),
             10 => self.arithmetic_shift_left_accumulator(),
-            12 => self.unofficial_triple_no_operation_no_page_penalty(4),
+            11 => self.unofficial_and_immediate_copy_negative_to_carry(),
+            12 => self.unofficial_nop_absolute(),
             13 => self.inclusive_or_absolute(),
             14 => self.arithmetic_shift_left_absolute(),
             15 => self.unofficial_shift_left_memory_inclusive_or_acc_absolute(),
             16 => self.branch_if_positive(),
             17 => self.inclusive_or_indirect_y(),
+            18 => self.unofficial_jam(),
             19 => self.unofficial_shift_left_memory_inclusive_or_acc_indirect_y(),
-            20 => self.unofficial_double_no_operation(4),
+            20 => self.unofficial_nop_zero_page_x(),
             21 => self.inclusive_or_zero_page_x(),
             22 => self.arithmetic_shift_left_zero_page_x(),
             23 => self.unofficial_shift_left_memory_inclusive_or_acc_zero_page_x(),
@@ -142,12 +230,13 @@ impl<'a> Cpu<'a> {
             25 => self.inclusive_or_absolute_y(),
             26 => self.unofficial_nop(),
             27 => self.unofficial_shift_left_memory_inclusive_or_acc_absolute_y(),
-            28 => self.unofficial_triple_no_operation_page_penalty(4),
+            28 => self.unofficial_nop_absolute_x(),
             29 => self.inclusive_or_absolute_x(),
             30 => self.arithmetic_shift_left_absolute_x(),
             31 => self.unofficial_shift_left_memory_inclusive_or_acc_absolute_x(),
             32 => self.jump_to_subroutine(),
             33 => self.and_indirect_x(),
+            34 => self.unofficial_jam(),
             35 => self.unofficial_rotate_left_memory_bitwise_and_acc_indirect_x(),
             36 => self.bit_test_zero_page(),
             37 => self.and_zero_page(),
@@ -156,14 +245,16 @@ impl<'a> Cpu<'a> {
             40 => self.pull_status_flags_from_stack(),
             41 => self.and_immediate(),
             42 => self.rotate_left_accumulator(),
+            43 => self.unofficial_and_immediate_copy_negative_to_carry(),
             44 => self.bit_test_absolute(),
             45 => self.and_absolute(),
             46 => self.rotate_left_absolute(),
             47 => self.unofficial_rotate_left_memory_bitwise_and_acc_absolute(),
             48 => self.branch_if_negative(),
             49 => self.and_indirect_y(),
+            50 => self.unofficial_jam(),
             51 => self.unofficial_rotate_left_memory_bitwise_and_acc_indirect_y(),
-            52 => self.unofficial_double_no_operation(4),
+            52 => self.unofficial_nop_zero_page_x(),
             53 => self.and_zero_page_x(),
             54 => self.rotate_left_zero_page_x(),
             55 => self.unofficial_rotate_left_memory_bitwise_and_acc_zero_page_x(),
@@ -171,28 +262,31 @@ impl<'a> Cpu<'a> {
             57 => self.and_absolute_y(),
             58 => self.unofficial_nop(),
             59 => self.unofficial_rotate_left_memory_bitwise_and_acc_absolute_y(),
-            60 => self.unofficial_triple_no_operation_page_penalty(4),
+            60 => self.unofficial_nop_absolute_x(),
             61 => self.and_absolute_x(),
             62 => self.rotate_left_absolute_x(),
             63 => self.unofficial_rotate_left_memory_bitwise_and_acc_absolute_x(),
             64 => self.return_from_interrupt(),
             65 => self.exclusive_or_indirect_x(),
+            66 => self.unofficial_jam(),
             67 => self.unofficial_shift_right_memory_xor_acc_indirect_x(),
-            68 => self.unofficial_double_no_operation(3),
+            68 => self.unofficial_nop_zero_page(),
             69 => self.exclusive_or_zero_page(),
             70 => self.logical_shift_right_zero_page(),
             71 => self.unofficial_shift_right_memory_xor_acc_zero_page(),
             72 => self.push_accumulator(),
             73 => self.exclusive_or_immediate(),
             74 => self.logical_shift_right_accumulator(),
+            75 => self.unofficial_and_immediate_shift_right_acc(),
             76 => self.jump_absolute(),
             77 => self.exclusive_or_absolute(),
             78 => self.logical_shift_right_absolute(),
             79 => self.unofficial_shift_right_memory_xor_acc_absolute(),
             80 => self.branch_if_overflow_clear(),
             81 => self.exclusive_or_indirect_y(),
+            82 => self.unofficial_jam(),
             83 => self.unofficial_shift_right_memory_xor_acc_indirect_y(),
-            84 => self.unofficial_double_no_operation(4),
+            84 => self.unofficial_nop_zero_page_x(),
             85 => self.exclusive_or_zero_page_x(),
             86 => self.logical_shift_right_zero_page_x(),
             87 => self.unofficial_shift_right_memory_xor_acc_zero_page_x(),
@@ -200,28 +294,31 @@ impl<'a> Cpu<'a> {
             89 => self.exclusive_or_absolute_y(),
             90 => self.unofficial_nop(),
             91 => self.unofficial_shift_right_memory_xor_acc_absolute_y(),
-            92 => self.unofficial_triple_no_operation_page_penalty(4),
+            92 => self.unofficial_nop_absolute_x(),
             93 => self.exclusive_or_absolute_x(),
             94 => self.logical_shift_right_absolute_x(),
             95 => self.unofficial_shift_right_memory_xor_acc_absolute_x(),
             96 => self.return_from_subroutine(),
             97 => self.add_indirect_x(),
+            98 => self.unofficial_jam(),
             99 => self.unofficial_rotate_right_memory_add_acc_indirect_x(),
-            100 => self.unofficial_double_no_operation(3),
+            100 => self.unofficial_nop_zero_page(),
             101 => self.add_zero_page(),
             102 => self.rotate_right_zero_page(),
             103 => self.unofficial_rotate_right_memory_add_acc_zero_page(),
             104 => self.pull_accumulator(),
             105 => self.add_immediate(),
             106 => self.rotate_right_accumulator(),
+            107 => self.unofficial_and_immediate_rotate_right_acc(),
             108 => self.jump_indirect(),
             109 => self.add_absolute(),
             110 => self.rotate_right_absolute(),
             111 => self.unofficial_rotate_right_memory_add_acc_absolute(),
             112 => self.branch_if_overflow_set(),
             113 => self.add_indirect_y(),
+            114 => self.unofficial_jam(),
             115 => self.unofficial_rotate_right_memory_add_acc_indirect_y(),
-            116 => self.unofficial_double_no_operation(4),
+            116 => self.unofficial_nop_zero_page_x(),
             117 => self.add_zero_page_x(),
             118 => self.rotate_right_zero_page_x(),
             119 => self.unofficial_rotate_right_memory_add_acc_zero_page_x(),
@@ -229,27 +326,30 @@ impl<'a> Cpu<'a> {
             121 => self.add_absolute_y(),
             122 => self.unofficial_nop(),
             123 => self.unofficial_rotate_right_memory_add_acc_absolute_y(),
-            124 => self.unofficial_triple_no_operation_page_penalty(4),
+            124 => self.unofficial_nop_absolute_x(),
             125 => self.add_absolute_x(),
             126 => self.rotate_right_absolute_x(),
             127 => self.unofficial_rotate_right_memory_add_acc_absolute_x(),
-            128 => self.unofficial_double_no_operation(2),
+            128 => self.unofficial_nop_immediate(),
             129 => self.store_a_indirect_x(),
-            130 => self.unofficial_double_no_operation(2),
+            130 => self.unofficial_nop_immediate(),
             131 => self.unofficial_and_a_with_x_store_result_indirect_x(),
             132 => self.store_y_zero_page(),
             133 => self.store_a_zero_page(),
             134 => self.store_x_zero_page(),
             135 => self.unofficial_and_a_with_x_store_result_zero_page(),
             136 => self.decrease_y(),
-            137 => self.unofficial_double_no_operation(2),
+            137 => self.unofficial_nop_immediate(),
             138 => self.transfer_x_to_accumulator(),
+            139 => self.unofficial_transfer_x_to_acc_and_immediate(),
             140 => self.store_y_absolute(),
             141 => self.store_a_absolute(),
             142 => self.store_x_absolute(),
             143 => self.unofficial_and_a_with_x_store_result_absolute(),
             144 => self.branch_if_carry_clear(),
             145 => self.store_a_indirect_y(),
+            146 => self.unofficial_jam(),
+            147 => self.unofficial_and_a_with_x_and_high_byte_store_result_indirect_y(),
             148 => self.store_y_zero_page_x(),
             149 => self.store_a_zero_page_x(),
             150 => self.store_x_zero_page_y(),
@@ -257,7 +357,11 @@ impl<'a> Cpu<'a> {
             152 => self.transfer_y_to_accumulator(),
             153 => self.store_a_absolute_y(),
             154 => self.transfer_x_to_stack_pointer(),
+            155 => self.unofficial_transfer_a_and_x_to_stack_pointer_store_result_absolute_y(),
+            156 => self.unofficial_and_y_with_high_byte_store_result_absolute_x(),
             157 => self.store_a_absolute_x(),
+            158 => self.unofficial_and_x_with_high_byte_store_result_absolute_y(),
+            159 => self.unofficial_and_a_with_x_and_high_byte_store_result_absolute_y(),
             160 => self.load_y_immediate(),
             161 => self.load_a_indirect_x(),
             162 => self.load_x_immediate(),
@@ -269,12 +373,14 @@ impl<'a> Cpu<'a> {
             168 => self.transfer_accumulator_to_y(),
             169 => self.load_a_immediate(),
             170 => self.transfer_accumulator_to_x(),
+            171 => self.unofficial_load_a_and_x_immediate(),
             172 => self.load_y_absolute(),
             173 => self.load_a_absolute(),
             174 => self.load_x_absolute(),
             175 => self.unofficial_load_a_and_x_absolute(),
             176 => self.branch_if_carry_set(),
             177 => self.load_a_indirect_y(),
+            178 => self.unofficial_jam(),
             179 => self.unofficial_load_a_and_x_indirect_y(),
             180 => self.load_y_zero_page_x(),
             181 => self.load_a_zero_page_x(),
@@ -283,13 +389,14 @@ impl<'a> Cpu<'a> {
             184 => self.clear_overflow_flag(),
             185 => self.load_a_absolute_y(),
             186 => self.transfer_stack_pointer_to_x(),
+            187 => self.unofficial_load_a_x_and_stack_pointer_absolute_y(),
             188 => self.load_y_absolute_x(),
             189 => self.load_a_absolute_x(),
             190 => self.load_x_absolute_y(),
             191 => self.unofficial_load_a_and_x_absolute_y(),
             192 => self.compare_y_immediate(),
             193 => self.compare_indirect_x(),
-            194 => self.unofficial_double_no_operation(2),
+            194 => self.unofficial_nop_immediate(),
             195 => self.unofficial_decrement_memory_and_compare_with_acc_indirect_x(),
             196 => self.compare_y_zero_page(),
             197 => self.compare_zero_page(),
@@ -298,14 +405,16 @@ impl<'a> Cpu<'a> {
             200 => self.increase_y(),
             201 => self.compare_immediate(),
             202 => self.decrease_x(),
+            203 => self.unofficial_and_a_with_x_subtract_immediate(),
             204 => self.compare_y_absolute(),
             205 => self.compare_absolute(),
             206 => self.decrement_memory_absolute(),
             207 => self.unofficial_decrement_memory_and_compare_with_acc_absolute(),
             208 => self.branch_if_not_equal(),
             209 => self.compare_indirect_y(),
+            210 => self.unofficial_jam(),
             211 => self.unofficial_decrement_memory_and_compare_with_acc_indirect_y(),
-            212 => self.unofficial_double_no_operation(4),
+            212 => self.unofficial_nop_zero_page_x(),
             213 => self.compare_zero_page_x(),
             214 => self.decrement_memory_zero_page_x(),
             215 => self.unofficial_decrement_memory_and_compare_with_acc_zero_page_x(),
@@ -313,13 +422,13 @@ impl<'a> Cpu<'a> {
             217 => self.compare_absolute_y(),
             218 => self.unofficial_nop(),
             219 => self.unofficial_decrement_memory_and_compare_with_acc_absolute_y(),
-            220 => self.unofficial_triple_no_operation_page_penalty(4),
+            220 => self.unofficial_nop_absolute_x(),
             221 => self.compare_absolute_x(),
             222 => self.decrement_memory_absolute_x(),
             223 => self.unofficial_decrement_memory_and_compare_with_acc_absolute_x(),
             224 => self.compare_x_immediate(),
             225 => self.subtract_indirect_x(),
-            226 => self.unofficial_double_no_operation(2),
+            226 => self.unofficial_nop_immediate(),
             227 => self.unofficial_increment_memory_subtract_acc_indirect_x(),
             228 => self.compare_x_zero_page(),
             229 => self.subtract_zero_page(),
@@ -335,8 +444,9 @@ impl<'a> Cpu<'a> {
             239 => self.unofficial_increment_memory_subtract_acc_absolute(),
             240 => self.branch_if_equal(),
             241 => self.subtract_indirect_y(),
+            242 => self.unofficial_jam(),
             243 => self.unofficial_increment_memory_subtract_acc_indirect_y(),
-            244 => self.unofficial_double_no_operation(4),
+            244 => self.unofficial_nop_zero_page_x(),
             245 => self.subtract_zero_page_x(),
             246 => self.increment_memory_zero_page_x(),
             247 => self.unofficial_increment_memory_subtract_acc_zero_page_x(),
@@ -344,16 +454,11 @@ impl<'a> Cpu<'a> {
             249 => self.subtract_absolute_y(),
             250 => self.unofficial_nop(),
             251 => self.unofficial_increment_memory_subtract_acc_absolute_y(),
-            252 => self.unofficial_triple_no_operation_page_penalty(4),
+            252 => self.unofficial_nop_absolute_x(),
             253 => self.subtract_absolute_x(),
             254 => self.increment_memory_absolute_x(),
             255 => self.unofficial_increment_memory_subtract_acc_absolute_x(),
-            _ => panic!("\n\nInvalid opcode {}\nInstruction PC: {}\n",
-                instruction,
-                self.program_counter - 1),
         }
-
-        self.is_odd_cycle = !self.is_odd_cycle;
     }
 
     fn set_negative_flag(&mut self, value: u8) {
@@ -381,8 +486,21 @@ impl<'a> Cpu<'a> {
         self.get_byte_operand() as u16
     }
 
+    // the unindexed address is read while the offset is added
     fn get_zero_page_address_with_offset(&mut self, offset: u16) -> u16 {
-        (self.get_zero_page_address() + offset)  & 0x00FF
+        let base_address = self.get_zero_page_address();
+        self.read(base_address);
+        (base_address + offset)  & 0x00FF
+    }
+
+    fn get_zero_page_x_address(&mut self) -> u16 {
+        let offset = self.x as u16;
+        self.get_zero_page_address_with_offset(offset)
+    }
+
+    fn get_zero_page_y_address(&mut self) -> u16 {
+        let offset = self.y as u16;
+        self.get_zero_page_address_with_offset(offset)
     }
 
     // must handle pc wrapping, as 0xFFFE\0xFFFF stores interrupt vector
@@ -397,37 +515,59 @@ impl<'a> Cpu<'a> {
          ((high_byte as u16) << 8) | low_byte as u16
     }
 
+    // writes and read-modify-write instructions always spend a cycle fixing the high byte.
+    // Before that, the address with the unfixed high byte is read
     fn get_absolute_address_with_offset(&mut self, offset: u16) -> u16 {
-        ((self.get_absolute_address() as u32 + offset as u32) & 0xFFFF) as u16
+        let base_address = self.get_absolute_address();
+        let address = ((base_address as u32 + offset as u32) & 0xFFFF) as u16;
+        self.read((base_address & 0xFF00) | (address & 0x00FF));
+        address
+    }
+
+    fn get_absolute_x_address(&mut self) -> u16 {
+        let offset = self.x as u16;
+        self.get_absolute_address_with_offset(offset)
+    }
+
+    fn get_absolute_y_address(&mut self) -> u16 {
+        let offset = self.y as u16;
+        self.get_absolute_address_with_offset(offset)
     }
 
     fn get_indirect_x_address(&mut self) -> u16 {
         let zero_page_address = self.get_byte_operand() as u16;
+        // pointer is read before x is added
+        self.read(zero_page_address);
         let x = self.x;
         let low_byte = self.read((zero_page_address + x as u16) & 0x00FF) as u16;
         let high_byte = self.read((zero_page_address + x as u16 + 1) & 0x00FF) as u16;
         (high_byte << 8) | low_byte
     }
 
+    // like absolute indexed addressing, writes always read the address with unfixed high byte
     fn get_indirect_y_address(&mut self) -> u16 {
+        let base_address = self.get_indirect_base_address();
+        let four_byte_address = base_address as u32 + self.y as u32;
+        let address = (four_byte_address & 0xFFFF) as u16;
+        self.read((base_address & 0xFF00) | (address & 0x00FF));
+        address
+    }
+
+    // address stored in zero page, before y is added
+    fn get_indirect_base_address(&mut self) -> u16 {
         let zero_page_address =  self.get_byte_operand() as u16;
 
         let low_byte = self.read(zero_page_address) as u16;
         let high_byte = self.read((zero_page_address + 1) & 0x00FF) as u16;
 
-        let base_address = (high_byte << 8) | low_byte;
-        let four_byte_address = base_address as u32 + self.y as u32;
-
-        (four_byte_address & 0xFFFF) as u16
+        (high_byte << 8) | low_byte
     }
 
     fn read_immediate(&mut self) -> u8 {
-        self.wait_counter = 2;
         self.get_byte_operand()
     }
 
     fn read_absolute(&mut self) -> u8 {
-        self.wait_counter = 4;
         let address = self.get_absolute_address();
         self.read(address)
     }
@@ -435,11 +575,14 @@ impl<'a> Cpu<'a> {
     fn read_absolute_with_offset(&mut self, offset: u16) -> u8 {
         let base = self.get_absolute_address();
         let address = ((base as u32 + offset as u32) & 0xFFFF) as u16;
-        // if page boundary is crossed, instruction takes 5 cycles. Otherwise it takes 4 cycles
-        if base & 0xFF00 == address & 0xFF00 {
-            self.wait_counter = 4;
-        } else {
-            self.wait_counter = 5;
+        self.read_indexed(base, address)
+    }
+
+    // if page boundary is crossed, the address with unfixed high byte is read first, which costs
+    // an extra cycle
+    fn read_indexed(&mut self, base_address: u16, address: u16) -> u8 {
+        if base_address & 0xFF00 != address & 0xFF00 {
+            self.read((base_address & 0xFF00) | (address & 0x00FF));
         }
         self.read(address)
     }
@@ -455,13 +598,11 @@ impl<'a> Cpu<'a> {
     }
 
     fn read_zero_page(&mut self) -> u8 {
-        self.wait_counter = 3;
         let address = self.get_zero_page_address();
         self.read(address as u16)
     }
 
     fn read_zero_page_with_offset(&mut self, offset: u16) -> u8 {
-        self.wait_counter = 4;
         let address = self.get_zero_page_address_with_offset(offset);
         self.read(address)
     }
@@ -477,31 +618,14 @@ impl<'a> Cpu<'a> {
     }
 
     fn read_indirect_x(&mut self) -> u8 {
-        self.wait_counter = 6;
         let address = self.get_indirect_x_address();
         self.read(address)
     }
-    // duplicates get_indirect_y_address_code because timing depends on whether
-    // the base address and final address are on the same page or not.
-    // this probably should be fixed.
     fn read_indirect_y(&mut self) -> u8 {
-        let zero_page_address =  self.get_byte_operand() as u16;
-
-        let low_byte = self.read(zero_page_address) as u16;
-        let high_byte = self.read((zero_page_address + 1) & 0x00FF) as u16;
-
-        let base_address = (high_byte << 8) | low_byte;
+        let base_address = self.get_indirect_base_address();
         let four_byte_address =  base_address as u32 + self.y as u32;
-
-        let final_address = (four_byte_address & 0xFFFF) as u16; // wrap around
-
-        if final_address & 0xFF00 == base_address & 0xFF00 {
-            self.wait_counter = 5;
-        } else {
-            self.wait_counter = 6;
-        }
-
-        self.read(final_address)
+        let address = (four_byte_address & 0xFFFF) as u16; // wrap around
+        self.read_indexed(base_address, address)
     }
 
     fn set_zero_negative_flags(&mut self, value: u8) {
@@ -525,53 +649,41 @@ impl<'a> Cpu<'a> {
     }
 
     fn do_zero_page_store(&mut self, value: u8) {
-        self.wait_counter = 3;
         let address = self.get_zero_page_address();
         self.write(address, value);
     }
 
     fn do_zero_page_x_store(&mut self, value: u8) {
-        let offset = self.x as u16;
-        self.wait_counter = 4;
-        let address = self.get_zero_page_address_with_offset(offset);
+        let address = self.get_zero_page_x_address();
         self.write(address, value);
     }
 
     fn do_zero_page_y_store(&mut self, value: u8) {
-        let offset = self.y as u16;
-        self.wait_counter = 4;
-        let address = self.get_zero_page_address_with_offset(offset);
+        let address = self.get_zero_page_y_address();
         self.write(address, value);
     }
 
     fn do_absolute_store(&mut self, value: u8) {
-        self.wait_counter = 4;
         let address = self.get_absolute_address();
         self.write(address, value);
     }
 
     fn do_absolute_x_store(&mut self, value: u8) {
-        self.wait_counter = 5;
-        let offset = self.x as u16;
-        let address = self.get_absolute_address_with_offset(offset);
+        let address = self.get_absolute_x_address();
         self.write(address, value);
     }
 
     fn do_absolute_y_store(&mut self, value: u8) {
-        self.wait_counter = 5;
-        let offset = self.y as u16;
-        let address = self.get_absolute_address_with_offset(offset);
+        let address = self.get_absolute_y_address();
         self.write(address, value);
     }
 
     fn do_indirect_x_store(&mut self, value: u8) {
-        self.wait_counter = 6;
         let address = self.get_indirect_x_address();
         self.write(address, value);
     }
 
     fn do_indirect_y_store(&mut self, value: u8) {
-        self.wait_counter = 6;
         let address = self.get_indirect_y_address();
         self.write(address, value);
     }
@@ -588,6 +700,29 @@ impl<'a> Cpu<'a> {
         self.read(0x0100 + sp)
     }
 
+    // implied and accumulator addressing modes still read the next byte
+    fn dummy_read_program_counter(&mut self) {
+        let pc = self.program_counter;
+        self.read(pc);
+    }
+
+    // instructions pulling from the stack read the current top of the stack before incrementing
+    // the stack pointer
+    fn dummy_read_stack(&mut self) {
+        let sp = self.stack_pointer as u16;
+        self.read(0x0100 + sp);
+    }
+
+    // The unmodified value is written back while the instruction is modifying it, which is
+    // visible to mappers and memory mapped registers
+    fn do_read_modify_write(&mut self, address: u16, operation: fn(&mut Self, u8) -> u8) -> u8 {
+        let value = self.read(address);
+        self.write(address, value);
+        let result = operation(self, value);
+        self.write(address, result);
+        result
+    }
+
     fn do_and(&mut self, operand: u8) {
         self.a = self.a & operand;
         let result = self.a;
@@ -623,6 +758,15 @@ impl<'a> Cpu<'a> {
     fn do_relative_jump_if(&mut self, condition: bool) {
         let offset = self.get_byte_operand() as u16;
         if  condition {
+            // Taken branches do not poll interrupts on the cycle where the offset is added. An irq
+            // that became pending during the operand fetch is therefore delayed by an instruction,
+            // unless the branch crosses a page and polls again
+            if self.irq_pending && !self.previous_irq_pending {
+                self.irq_pending = false;
+            }
+
+            // the next opcode is read while the offset is added
+            self.dummy_read_program_counter();
             let old_program_counter = self.program_counter;
 
             self.program_counter += offset;
@@ -633,14 +777,12 @@ impl<'a> Cpu<'a> {
                 self.program_counter -= 0x100;
             }
 
-            // timing depends on whether new address is on same or different memory page
-            if old_program_counter & 0xFF00 == self.program_counter & 0xFF00 {
-                self.wait_counter = 3;
-            } else {
-                self.wait_counter = 5;
+            // if the new address is on a different page, the address with unfixed high byte
+            // is read while the high byte is fixed
+            if old_program_counter & 0xFF00 != self.program_counter & 0xFF00 {
+                let unfixed_address = (old_program_counter & 0xFF00) | (self.program_counter & 0x00FF);
+                self.read(unfixed_address);
             }
-        } else {
-            self.wait_counter = 2;
         }
     }
 
@@ -880,12 +1022,10 @@ impl<'a> Cpu<'a> {
     }
 
     fn jump_absolute(&mut self) {
-        self.wait_counter = 3;
         self.program_counter = self.get_absolute_address();
     }
 
     fn jump_indirect(&mut self) {
-        self.wait_counter = 5;
         let indirect_address = self.get_absolute_address();
 
         // 6502 has a bug where high byte is fetched incorrectly when low byte resides
@@ -902,47 +1042,61 @@ impl<'a> Cpu<'a> {
         self.program_counter = address;
     }
 
+    // the high byte of the target address is read last, after the return address has been pushed
     fn jump_to_subroutine(&mut self) {
-        self.wait_counter = 6;
-        let address = self.get_absolute_address();
+        let low_byte = self.get_byte_operand() as u16;
+        self.dummy_read_stack();
 
-        let return_address = self.program_counter - 1;
+        let return_address = self.program_counter;
         self.push_value_into_stack(((return_address & 0xFF00) >> 8) as u8);
         self.push_value_into_stack((return_address & 0xFF) as u8);
-        self.program_counter = address;
+
+        let pc = self.program_counter;
+        let high_byte = self.read(pc) as u16;
+        self.program_counter = (high_byte << 8) | low_byte;
     }
 
     fn return_from_subroutine(&mut self) {
-        self.wait_counter = 6;
+        self.dummy_read_program_counter();
+        self.dummy_read_stack();
         let low_byte = self.pop_value_from_stack() as u16;
         let high_byte = self.pop_value_from_stack() as u16;
-        self.program_counter = ((high_byte << 8) | low_byte) + 1;
+        self.program_counter = (high_byte << 8) | low_byte;
+        // return address is incremented on its own cycle
+        self.dummy_read_program_counter();
+        self.program_counter += 1;
     }
 
+    // brk skips the byte following the opcode; it is read but ignored
     fn force_interrupt(&mut self) {
+        self.get_byte_operand();
+
         let return_address = self.program_counter;
         self.push_value_into_stack(((return_address & 0xFF00) >> 8) as u8);
         self.push_value_into_stack((return_address & 0xFF) as u8);
 
+        let vector = self.interrupt_vector();
         let flags = self.status_flags | 0x30; // bit 5 and 4 must be set
         self.push_value_into_stack(flags);
         // disable interrupts
         self.status_flags = self.status_flags | 0x04;
-        self.program_counter = 0xFFFE;
+        self.program_counter = vector;
 
         self.jump_absolute();
-        self.wait_counter = 7;
+        self.delay_nmi_until_next_instruction();
     }
 
     fn return_from_interrupt(&mut self) {
-        self.wait_counter = 6;
-
+        self.dummy_read_program_counter();
+        self.dummy_read_stack();
+        // flags are restored before the program counter is popped, so unlike cli and plp, the
+        // interrupt disable flag affects interrupt polling immediately
         let flags = self.pop_value_from_stack();
+        self.status_flags = flags & 0xCF | (self.status_flags & 0x30); // flags 4 & 5 are ignored
         let low_byte = self.pop_value_from_stack() as u16;
         let high_byte = self.pop_value_from_stack() as u16;
 
         self.program_counter = (high_byte << 8) | low_byte;
-        self.status_flags = flags & 0xCF | (self.status_flags & 0x30); // flags 4 & 5 are ignored
     }
 
 
@@ -957,216 +1111,153 @@ impl<'a> Cpu<'a> {
     }
 
     fn rotate_right_accumulator(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         let value = self.a;
         self.a = self.do_rotate_right(value);
     }
 
     fn rotate_right_zero_page(&mut self) {
-        let value = self.read_zero_page();
-        let result = self.do_rotate_right(value);
-        // decrement PC so that store works
-        self.program_counter -= 1;
-        self.do_zero_page_store(result);
-        self.wait_counter = 5;
+        let address = self.get_zero_page_address();
+        self.do_read_modify_write(address, Self::do_rotate_right);
     }
 
     fn rotate_right_zero_page_x(&mut self) {
-        let value = self.read_zero_page_x();
-        let result = self.do_rotate_right(value);
-        // decrement PC so that store works
-        self.program_counter -= 1;
-        self.do_zero_page_x_store(result);
-        self.wait_counter = 6;
+        let address = self.get_zero_page_x_address();
+        self.do_read_modify_write(address, Self::do_rotate_right);
     }
 
     fn rotate_right_absolute(&mut self) {
-        let value = self.read_absolute();
-        let result = self.do_rotate_right(value);
-        // decrement PC so that store works
-        self.program_counter -= 2;
-        self.do_absolute_store(result);
-        self.wait_counter = 6;
+        let address = self.get_absolute_address();
+        self.do_read_modify_write(address, Self::do_rotate_right);
     }
 
     fn rotate_right_absolute_x(&mut self) {
-        let value = self.read_absolute_x();
-        let result = self.do_rotate_right(value);
-        // decrement PC so that store works
-        self.program_counter -= 2;
-        self.do_absolute_x_store(result);
-        self.wait_counter = 7;
+        let address = self.get_absolute_x_address();
+        self.do_read_modify_write(address, Self::do_rotate_right);
     }
 
     fn logical_shift_right_accumulator(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         let value = self.a;
         self.a = self.do_logical_shift_right(value);
     }
 
     fn logical_shift_right_zero_page(&mut self) {
-        let value = self.read_zero_page();
-        let result = self.do_logical_shift_right(value);
-        // decrement PC so that store works
-        self.program_counter -= 1;
-        self.do_zero_page_store(result);
-        self.wait_counter = 5;
+        let address = self.get_zero_page_address();
+        self.do_read_modify_write(address, Self::do_logical_shift_right);
     }
 
     fn logical_shift_right_zero_page_x(&mut self) {
-        let value = self.read_zero_page_x();
-        let result = self.do_logical_shift_right(value);
-        // decrement PC so that store works
-        self.program_counter -= 1;
-        self.do_zero_page_x_store(result);
-        self.wait_counter = 6;
+        let address = self.get_zero_page_x_address();
+        self.do_read_modify_write(address, Self::do_logical_shift_right);
     }
 
     fn logical_shift_right_absolute(&mut self) {
-        let value = self.read_absolute();
-        let result = self.do_logical_shift_right(value);
-        // decrement PC so that store works
-        self.program_counter -= 2;
-        self.do_absolute_store(result);
-        self.wait_counter = 6;
+        let address = self.get_absolute_address();
+        self.do_read_modify_write(address, Self::do_logical_shift_right);
     }
 
     fn logical_shift_right_absolute_x(&mut self) {
-        let value = self.read_absolute_x();
-        let result = self.do_logical_shift_right(value);
-        // decrement PC so that store works
-        self.program_counter -= 2;
-        self.do_absolute_x_store(result);
-        self.wait_counter = 7;
+        let address = self.get_absolute_x_address();
+        self.do_read_modify_write(address, Self::do_logical_shift_right);
     }
 
     fn rotate_left_accumulator(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         let value = self.a;
         self.a = self.do_rotate_left(value);
     }
 
     fn rotate_left_zero_page(&mut self) {
-        let value = self.read_zero_page();
-        // move program counter back so that store works
-        self.program_counter -= 1;
-        let result = self.do_rotate_left(value);
-        self.do_zero_page_store(result);
-        self.wait_counter = 5;
+        let address = self.get_zero_page_address();
+        self.do_read_modify_write(address, Self::do_rotate_left);
     }
 
     fn rotate_left_zero_page_x(&mut self) {
-        let value = self.read_zero_page_x();
-        // move program counter back so that store works
-        self.program_counter -= 1;
-        let result = self.do_rotate_left(value);
-        self.do_zero_page_x_store(result);
-        self.wait_counter = 6;
+        let address = self.get_zero_page_x_address();
+        self.do_read_modify_write(address, Self::do_rotate_left);
     }
 
     fn rotate_left_absolute(&mut self) {
-        let value = self.read_absolute();
-        // move program counter back so that store works
-        self.program_counter -= 2;
-        let result = self.do_rotate_left(value);
-        self.do_absolute_store(result);
-        self.wait_counter = 6;
+        let address = self.get_absolute_address();
+        self.do_read_modify_write(address, Self::do_rotate_left);
     }
 
     fn rotate_left_absolute_x(&mut self) {
-        let value = self.read_absolute_x();
-        // move program counter back so that store works
-        self.program_counter -= 2;
-        let result = self.do_rotate_left(value);
-        self.do_absolute_x_store(result);
-        self.wait_counter = 7;
+        let address = self.get_absolute_x_address();
+        self.do_read_modify_write(address, Self::do_rotate_left);
     }
 
     fn arithmetic_shift_left_accumulator(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         let value = self.a;
         self.a = self.do_arithmetic_shift_left(value);
     }
 
     fn arithmetic_shift_left_zero_page(&mut self) {
-        let value = self.read_zero_page();
-        // move program counter back so that store works
-        self.program_counter -= 1;
-        let result = self.do_arithmetic_shift_left(value);
-        self.do_zero_page_store(result);
-        self.wait_counter = 5;
+        let address = self.get_zero_page_address();
+        self.do_read_modify_write(address, Self::do_arithmetic_shift_left);
     }
 
     fn arithmetic_shift_left_zero_page_x(&mut self) {
-        let value = self.read_zero_page_x();
-        // move program counter back so that store works
-        self.program_counter -= 1;
-        let result = self.do_arithmetic_shift_left(value);
-        self.do_zero_page_x_store(result);
-        self.wait_counter = 5;
+        let address = self.get_zero_page_x_address();
+        self.do_read_modify_write(address, Self::do_arithmetic_shift_left);
     }
 
     fn arithmetic_shift_left_absolute(&mut self) {
-        let value = self.read_absolute();
-        // move program counter back so that store works
-        self.program_counter -= 2;
-        let result = self.do_arithmetic_shift_left(value);
-        self.do_absolute_store(result);
-        self.wait_counter = 6;
+        let address = self.get_absolute_address();
+        self.do_read_modify_write(address, Self::do_arithmetic_shift_left);
     }
 
     fn arithmetic_shift_left_absolute_x(&mut self) {
-        let value = self.read_absolute_x();
-        // move program counter back so that store works
-        self.program_counter -= 2;
-        let result = self.do_arithmetic_shift_left(value);
-        self.do_absolute_x_store(result);
-        self.wait_counter = 7;
+        let address = self.get_absolute_x_address();
+        self.do_read_modify_write(address, Self::do_arithmetic_shift_left);
     }
 
     fn clear_carry_flag(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         self.status_flags = self.status_flags & 0xFE; // clear bi 0
     }
 
     fn set_carry_flag(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         self.status_flags = self.status_flags | 0x01;
     }
 
     fn clear_decimal_flag(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         self.status_flags = self.status_flags & 0xF7; // clear bit 3
     }
 
     fn set_decimal_flag(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         self.status_flags = self.status_flags | 0x08; // set bit 3
     }
 
     fn clear_interrupt_disable_flag(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         self.status_flags = self.status_flags & !0x04;
     }
 
     fn set_interrupt_disable_flag(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         self.status_flags = self.status_flags | 0x04; // set bit 2
     }
 
     fn clear_overflow_flag(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         self.status_flags = self.status_flags & 0xBF;
     }
 
     fn push_accumulator(&mut self) {
-        self.wait_counter = 3;
+        self.dummy_read_program_counter();
         let value = self.a;
         self.push_value_into_stack(value);
     }
 
     fn pull_accumulator(&mut self) {
-        self.wait_counter = 4;
+        self.dummy_read_program_counter();
+        self.dummy_read_stack();
         let value = self.pop_value_from_stack();
         self.a = value;
         self.set_zero_negative_flags(value);
@@ -1175,13 +1266,14 @@ impl<'a> Cpu<'a> {
     fn push_status_flags_into_stack(&mut self) {
         // This instruction sets bits 4 & 5 to 1 for the value that gets pushed into stack.
         // In contrast, irq or nmi will set bit 4 to 0.
-        self.wait_counter = 3;
+        self.dummy_read_program_counter();
         let flags = self.status_flags | 0x30;
         self.push_value_into_stack(flags);
     }
 
     fn pull_status_flags_from_stack(&mut self) {
-        self.wait_counter = 4;
+        self.dummy_read_program_counter();
+        self.dummy_read_stack();
         self.status_flags = self.pop_value_from_stack() | 0x30;
     }
 
@@ -1378,40 +1470,40 @@ impl<'a> Cpu<'a> {
     }
 
     fn transfer_x_to_stack_pointer(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         self.stack_pointer = self.x;
     }
 
     fn transfer_stack_pointer_to_x(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         self.x = self.stack_pointer;
         let value = self.x;
         self.set_zero_negative_flags(value);
     }
 
     fn transfer_x_to_accumulator(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         self.a = self.x;
         let value = self.a;
         self.set_zero_negative_flags(value);
     }
 
     fn transfer_accumulator_to_x(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         self.x = self.a;
         let value = self.x;
         self.set_zero_negative_flags(value);
     }
 
     fn transfer_y_to_accumulator(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         self.a = self.y;
         let value = self.a;
         self.set_zero_negative_flags(value);
     }
 
     fn transfer_accumulator_to_y(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         self.y = self.a;
         let value = self.y;
         self.set_zero_negative_flags(value);
@@ -1589,96 +1681,72 @@ impl<'a> Cpu<'a> {
     }
 
     fn increase_x(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         let value = self.x;
         self.x = self.do_increment(value);
     }
 
 
     fn decrease_x(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         let value = self.x;
         self.x = self.do_decrement(value);
     }
 
     fn increase_y(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         let value = self.y;
         self.y = self.do_increment(value);
     }
 
     fn decrease_y(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
         let value = self.y;
         self.y = self.do_decrement(value);
     }
 
     fn increment_memory_zero_page(&mut self) {
-        let value = self.read_zero_page();
-        let result = self.do_increment(value);
-        self.program_counter -= 1; // need the zero page address again
-        self.do_zero_page_store(result);
-        self.wait_counter = 5;
+        let address = self.get_zero_page_address();
+        self.do_read_modify_write(address, Self::do_increment);
     }
 
     fn increment_memory_zero_page_x(&mut self) {
-        let value = self.read_zero_page_x();
-        let result = self.do_increment(value);
-        self.program_counter -= 1; // need the zero page address again
-        self.do_zero_page_x_store(result);
-        self.wait_counter = 6;
+        let address = self.get_zero_page_x_address();
+        self.do_read_modify_write(address, Self::do_increment);
     }
 
     fn increment_memory_absolute(&mut self) {
-        let value = self.read_absolute();
-        let result = self.do_increment(value);
-        self.program_counter -= 2; // need the zero page address again
-        self.do_absolute_store(result);
-        self.wait_counter = 6;
+        let address = self.get_absolute_address();
+        self.do_read_modify_write(address, Self::do_increment);
     }
 
     fn increment_memory_absolute_x(&mut self) {
-        let value = self.read_absolute_x();
-        let result = self.do_increment(value);
-        self.program_counter -= 2; // need the zero page address again
-        self.do_absolute_x_store(result);
-        self.wait_counter = 7;
+        let address = self.get_absolute_x_address();
+        self.do_read_modify_write(address, Self::do_increment);
     }
 
     fn decrement_memory_zero_page(&mut self) {
-        let value = self.read_zero_page();
-        let result = self.do_decrement(value);
-        self.program_counter -= 1; // need the zero page address again
-        self.do_zero_page_store(result);
-        self.wait_counter = 5;
+        let address = self.get_zero_page_address();
+        self.do_read_modify_write(address, Self::do_decrement);
     }
 
     fn decrement_memory_zero_page_x(&mut self) {
-        let value = self.read_zero_page_x();
-        let result = self.do_decrement(value);
-        self.program_counter -= 1; // need the zero page address again
-        self.do_zero_page_x_store(result);
-        self.wait_counter = 6;
+        let address = self.get_zero_page_x_address();
+        self.do_read_modify_write(address, Self::do_decrement);
     }
 
     fn decrement_memory_absolute(&mut self) {
-        let value = self.read_absolute();
-        let result = self.do_decrement(value);
-        self.program_counter -= 2; // need the zero page address again
-        self.do_absolute_store(result);
-        self.wait_counter = 6;
+        let address = self.get_absolute_address();
+        self.do_read_modify_write(address, Self::do_decrement);
     }
 
     fn decrement_memory_absolute_x(&mut self) {
-        let value = self.read_absolute_x();
-        let result = self.do_decrement(value);
-        self.program_counter -= 2; // need the zero page address again
-        self.do_absolute_x_store(result);
-        self.wait_counter = 7;
+        let address = self.get_absolute_x_address();
+        self.do_read_modify_write(address, Self::do_decrement);
     }
 
     fn no_operation(&mut self) {
-        self.wait_counter = 2;
+        self.dummy_read_program_counter();
     }
 
     fn unofficial_and_a_with_x_store_result_zero_page(&mut self) {
@@ -1703,392 +1771,372 @@ impl<'a> Cpu<'a> {
 
 
     fn unofficial_decrement_memory_and_compare_with_acc_zero_page(&mut self) {
-        self.decrement_memory_zero_page();
-        self.program_counter -= 1;
-        let result = self.read_zero_page();
+        let address = self.get_zero_page_address();
+        let result = self.do_read_modify_write(address, Self::do_decrement);
         let register = self.a;
         self.do_compare(register, result);
-        self.wait_counter = 5;
     }
 
     fn unofficial_decrement_memory_and_compare_with_acc_zero_page_x(&mut self) {
-        self.decrement_memory_zero_page_x();
-        self.program_counter -= 1;
-        let result = self.read_zero_page_x();
+        let address = self.get_zero_page_x_address();
+        let result = self.do_read_modify_write(address, Self::do_decrement);
         let register = self.a;
         self.do_compare(register, result);
-        self.wait_counter = 6;
     }
 
     fn unofficial_decrement_memory_and_compare_with_acc_absolute(&mut self) {
-        self.decrement_memory_absolute();
-        self.program_counter -= 2;
-        let result = self.read_absolute();
+        let address = self.get_absolute_address();
+        let result = self.do_read_modify_write(address, Self::do_decrement);
         let register = self.a;
         self.do_compare(register, result);
-        self.wait_counter = 6;
     }
 
     fn unofficial_decrement_memory_and_compare_with_acc_absolute_x(&mut self) {
-        self.decrement_memory_absolute_x();
-        self.program_counter -= 2;
-        let result = self.read_absolute_x();
+        let address = self.get_absolute_x_address();
+        let result = self.do_read_modify_write(address, Self::do_decrement);
         let register = self.a;
         self.do_compare(register, result);
-        self.wait_counter = 7;
     }
 
     fn unofficial_decrement_memory_and_compare_with_acc_absolute_y(&mut self) {
-        let value = self.read_absolute_y();
-        let result = self.do_decrement(value);
-        self.program_counter -= 2;
-        self.do_absolute_y_store(result);
-
+        let address = self.get_absolute_y_address();
+        let result = self.do_read_modify_write(address, Self::do_decrement);
         let register = self.a;
         self.do_compare(register, result);
-        self.wait_counter = 7;
     }
 
     fn unofficial_decrement_memory_and_compare_with_acc_indirect_x(&mut self) {
-        let value = self.read_indirect_x();
-        let result = self.do_decrement(value);
-        self.program_counter -= 1;
-        self.do_indirect_x_store(result);
-
+        let address = self.get_indirect_x_address();
+        let result = self.do_read_modify_write(address, Self::do_decrement);
         let register = self.a;
         self.do_compare(register, result);
-        self.wait_counter = 8;
     }
 
     fn unofficial_decrement_memory_and_compare_with_acc_indirect_y(&mut self) {
-        let value = self.read_indirect_y();
-        let result = self.do_decrement(value);
-        self.program_counter -= 1;
-        self.do_indirect_y_store(result);
-
+        let address = self.get_indirect_y_address();
+        let result = self.do_read_modify_write(address, Self::do_decrement);
         let register = self.a;
         self.do_compare(register, result);
-        self.wait_counter = 8;
     }
 
     fn unofficial_increment_memory_subtract_acc_zero_page(&mut self) {
-        self.increment_memory_zero_page();
-        self.program_counter -= 1;
-        let result = self.read_zero_page();
+        let address = self.get_zero_page_address();
+        let result = self.do_read_modify_write(address, Self::do_increment);
         self.do_subtract(result);
-        self.wait_counter = 5;
     }
 
     fn unofficial_increment_memory_subtract_acc_zero_page_x(&mut self) {
-        self.increment_memory_zero_page_x();
-        self.program_counter -= 1;
-        let result = self.read_zero_page_x();
+        let address = self.get_zero_page_x_address();
+        let result = self.do_read_modify_write(address, Self::do_increment);
         self.do_subtract(result);
-        self.wait_counter = 6;
     }
 
     fn unofficial_increment_memory_subtract_acc_absolute(&mut self) {
-        self.increment_memory_absolute();
-        self.program_counter -= 2;
-        let result = self.read_absolute();
+        let address = self.get_absolute_address();
+        let result = self.do_read_modify_write(address, Self::do_increment);
         self.do_subtract(result);
-        self.wait_counter = 6;
     }
 
     fn unofficial_increment_memory_subtract_acc_absolute_x(&mut self) {
-        self.increment_memory_absolute_x();
-        self.program_counter -= 2;
-        let result = self.read_absolute_x();
+        let address = self.get_absolute_x_address();
+        let result = self.do_read_modify_write(address, Self::do_increment);
         self.do_subtract(result);
-        self.wait_counter = 7;
     }
 
     fn unofficial_increment_memory_subtract_acc_absolute_y(&mut self) {
-        let value = self.read_absolute_y();
-        let result = self.do_increment(value);
-        self.program_counter -= 2; // need the zero page address again
-        self.do_absolute_y_store(result);
-
+        let address = self.get_absolute_y_address();
+        let result = self.do_read_modify_write(address, Self::do_increment);
         self.do_subtract(result);
-        self.wait_counter = 7;
     }
 
     fn unofficial_increment_memory_subtract_acc_indirect_x(&mut self) {
-        let value = self.read_indirect_x();
-        let result = self.do_increment(value);
-        self.program_counter -= 1; // need the zero page address again
-        self.do_indirect_x_store(result);
-
+        let address = self.get_indirect_x_address();
+        let result = self.do_read_modify_write(address, Self::do_increment);
         self.do_subtract(result);
-        self.wait_counter = 8;
     }
 
     fn unofficial_increment_memory_subtract_acc_indirect_y(&mut self) {
-        let value = self.read_indirect_y();
-        let result = self.do_increment(value);
-        self.program_counter -= 1; // need the zero page address again
-        self.do_indirect_y_store(result);
-
+        let address = self.get_indirect_y_address();
+        let result = self.do_read_modify_write(address, Self::do_increment);
         self.do_subtract(result);
-        self.wait_counter = 8;
     }
 
     fn unofficial_shift_left_memory_inclusive_or_acc_zero_page(&mut self) {
-        let value = self.read_zero_page();
-        let result = self.do_arithmetic_shift_left(value);
+        let address = self.get_zero_page_address();
+        let result = self.do_read_modify_write(address, Self::do_arithmetic_shift_left);
         self.do_inclusive_or(result);
-        self.program_counter -= 1;
-        self.do_zero_page_store(result);
-        self.wait_counter = 5;
     }
 
     fn unofficial_shift_left_memory_inclusive_or_acc_zero_page_x(&mut self) {
-        let value = self.read_zero_page_x();
-        let result = self.do_arithmetic_shift_left(value);
+        let address = self.get_zero_page_x_address();
+        let result = self.do_read_modify_write(address, Self::do_arithmetic_shift_left);
         self.do_inclusive_or(result);
-        self.program_counter -= 1;
-        self.do_zero_page_x_store(result);
-        self.wait_counter = 6;
     }
 
     fn unofficial_shift_left_memory_inclusive_or_acc_absolute(&mut self) {
-        let value = self.read_absolute();
-        let result = self.do_arithmetic_shift_left(value);
+        let address = self.get_absolute_address();
+        let result = self.do_read_modify_write(address, Self::do_arithmetic_shift_left);
         self.do_inclusive_or(result);
-        self.program_counter -= 2;
-        self.do_absolute_store(result);
-        self.wait_counter = 6;
     }
 
     fn unofficial_shift_left_memory_inclusive_or_acc_absolute_x(&mut self) {
-        let value = self.read_absolute_x();
-        let result = self.do_arithmetic_shift_left(value);
+        let address = self.get_absolute_x_address();
+        let result = self.do_read_modify_write(address, Self::do_arithmetic_shift_left);
         self.do_inclusive_or(result);
-        self.program_counter -= 2;
-        self.do_absolute_x_store(result);
-        self.wait_counter = 7;
     }
 
     fn unofficial_shift_left_memory_inclusive_or_acc_absolute_y(&mut self) {
-        let value = self.read_absolute_y();
-        let result = self.do_arithmetic_shift_left(value);
+        let address = self.get_absolute_y_address();
+        let result = self.do_read_modify_write(address, Self::do_arithmetic_shift_left);
         self.do_inclusive_or(result);
-        self.program_counter -= 2;
-        self.do_absolute_y_store(result);
-        self.wait_counter = 7;
     }
 
     fn unofficial_shift_left_memory_inclusive_or_acc_indirect_x(&mut self) {
-        let value = self.read_indirect_x();
-        let result = self.do_arithmetic_shift_left(value);
+        let address = self.get_indirect_x_address();
+        let result = self.do_read_modify_write(address, Self::do_arithmetic_shift_left);
         self.do_inclusive_or(result);
-        self.program_counter -= 1;
-        self.do_indirect_x_store(result);
-        self.wait_counter = 8;
     }
 
     fn unofficial_shift_left_memory_inclusive_or_acc_indirect_y(&mut self) {
-        let value = self.read_indirect_y();
-        let result = self.do_arithmetic_shift_left(value);
+        let address = self.get_indirect_y_address();
+        let result = self.do_read_modify_write(address, Self::do_arithmetic_shift_left);
         self.do_inclusive_or(result);
-        self.program_counter -= 1;
-        self.do_indirect_y_store(result);
-        self.wait_counter = 8;
     }
 
     fn unofficial_rotate_left_memory_bitwise_and_acc_zero_page(&mut self) {
-        let value = self.read_zero_page();
-        let result = self.do_rotate_left(value);
-
+        let address = self.get_zero_page_address();
+        let result = self.do_read_modify_write(address, Self::do_rotate_left);
         self.do_and(result);
-        self.program_counter -= 1;
-        self.do_zero_page_store(result);
-        self.wait_counter = 5;
     }
 
     fn unofficial_rotate_left_memory_bitwise_and_acc_zero_page_x(&mut self) {
-        let value = self.read_zero_page_x();
-        let result = self.do_rotate_left(value);
+        let address = self.get_zero_page_x_address();
+        let result = self.do_read_modify_write(address, Self::do_rotate_left);
         self.do_and(result);
-        self.program_counter -= 1;
-        self.do_zero_page_x_store(result);
-        self.wait_counter = 6;
     }
 
     fn unofficial_rotate_left_memory_bitwise_and_acc_absolute(&mut self) {
-        let value = self.read_absolute();
-        let result = self.do_rotate_left(value);
+        let address = self.get_absolute_address();
+        let result = self.do_read_modify_write(address, Self::do_rotate_left);
         self.do_and(result);
-        self.program_counter -= 2;
-        self.do_absolute_store(result);
-        self.wait_counter = 6;
     }
 
     fn unofficial_rotate_left_memory_bitwise_and_acc_absolute_x(&mut self) {
-        let value = self.read_absolute_x();
-        let result = self.do_rotate_left(value);
+        let address = self.get_absolute_x_address();
+        let result = self.do_read_modify_write(address, Self::do_rotate_left);
         self.do_and(result);
-        self.program_counter -= 2;
-        self.do_absolute_x_store(result);
-        self.wait_counter = 7;
     }
 
     fn unofficial_rotate_left_memory_bitwise_and_acc_absolute_y(&mut self) {
-        let value = self.read_absolute_y();
-        let result = self.do_rotate_left(value);
+        let address = self.get_absolute_y_address();
+        let result = self.do_read_modify_write(address, Self::do_rotate_left);
         self.do_and(result);
-        self.program_counter -= 2;
-        self.do_absolute_y_store(result);
-        self.wait_counter = 7;
     }
 
     fn unofficial_rotate_left_memory_bitwise_and_acc_indirect_x(&mut self) {
-        let value = self.read_indirect_x();
-        let result = self.do_rotate_left(value);
+        let address = self.get_indirect_x_address();
+        let result = self.do_read_modify_write(address, Self::do_rotate_left);
         self.do_and(result);
-        self.program_counter -= 1;
-        self.do_indirect_x_store(result);
-        self.wait_counter = 8;
     }
 
     fn unofficial_rotate_left_memory_bitwise_and_acc_indirect_y(&mut self) {
-        let value = self.read_indirect_y();
-        let result = self.do_rotate_left(value);
+        let address = self.get_indirect_y_address();
+        let result = self.do_read_modify_write(address, Self::do_rotate_left);
         self.do_and(result);
-        self.program_counter -= 1;
-        self.do_indirect_y_store(result);
-        self.wait_counter = 8;
     }
 
     fn unofficial_shift_right_memory_xor_acc_zero_page(&mut self) {
-        let value = self.read_zero_page();
-        let result = self.do_logical_shift_right(value);
-
+        let address = self.get_zero_page_address();
+        let result = self.do_read_modify_write(address, Self::do_logical_shift_right);
         self.do_exclusive_or(result);
-        self.program_counter -= 1;
-        self.do_zero_page_store(result);
-        self.wait_counter = 5;
     }
 
-    fn  unofficial_shift_right_memory_xor_acc_zero_page_x(&mut self) {
-        let value = self.read_zero_page_x();
-        let result = self.do_logical_shift_right(value);
+    fn unofficial_shift_right_memory_xor_acc_zero_page_x(&mut self) {
+        let address = self.get_zero_page_x_address();
+        let result = self.do_read_modify_write(address, Self::do_logical_shift_right);
         self.do_exclusive_or(result);
-        self.program_counter -= 1;
-        self.do_zero_page_x_store(result);
-        self.wait_counter = 6;
     }
 
-    fn  unofficial_shift_right_memory_xor_acc_absolute(&mut self) {
-        let value = self.read_absolute();
-        let result = self.do_logical_shift_right(value);
+    fn unofficial_shift_right_memory_xor_acc_absolute(&mut self) {
+        let address = self.get_absolute_address();
+        let result = self.do_read_modify_write(address, Self::do_logical_shift_right);
         self.do_exclusive_or(result);
-        self.program_counter -= 2;
-        self.do_absolute_store(result);
-        self.wait_counter = 6;
     }
 
-    fn  unofficial_shift_right_memory_xor_acc_absolute_x(&mut self) {
-        let value = self.read_absolute_x();
-        let result = self.do_logical_shift_right(value);
+    fn unofficial_shift_right_memory_xor_acc_absolute_x(&mut self) {
+        let address = self.get_absolute_x_address();
+        let result = self.do_read_modify_write(address, Self::do_logical_shift_right);
         self.do_exclusive_or(result);
-        self.program_counter -= 2;
-        self.do_absolute_x_store(result);
-        self.wait_counter = 7;
     }
 
     fn unofficial_shift_right_memory_xor_acc_absolute_y(&mut self) {
-        let value = self.read_absolute_y();
-        let result = self.do_logical_shift_right(value);
+        let address = self.get_absolute_y_address();
+        let result = self.do_read_modify_write(address, Self::do_logical_shift_right);
         self.do_exclusive_or(result);
-        self.program_counter -= 2;
-        self.do_absolute_y_store(result);
-        self.wait_counter = 7;
     }
 
     fn unofficial_shift_right_memory_xor_acc_indirect_x(&mut self) {
-        let value = self.read_indirect_x();
-        let result = self.do_logical_shift_right(value);
+        let address = self.get_indirect_x_address();
+        let result = self.do_read_modify_write(address, Self::do_logical_shift_right);
         self.do_exclusive_or(result);
-        self.program_counter -= 1;
-        self.do_indirect_x_store(result);
-        self.wait_counter = 8;
     }
 
     fn unofficial_shift_right_memory_xor_acc_indirect_y(&mut self) {
-        let value = self.read_indirect_y();
-        let result = self.do_logical_shift_right(value);
+        let address = self.get_indirect_y_address();
+        let result = self.do_read_modify_write(address, Self::do_logical_shift_right);
         self.do_exclusive_or(result);
-        self.program_counter -= 1;
-        self.do_indirect_y_store(result);
-        self.wait_counter = 8;
     }
 
     fn unofficial_rotate_right_memory_add_acc_zero_page(&mut self) {
-        let value = self.read_zero_page();
-        let result = self.do_rotate_right(value);
-
+        let address = self.get_zero_page_address();
+        let result = self.do_read_modify_write(address, Self::do_rotate_right);
         self.do_add(result);
-        self.program_counter -= 1;
-        self.do_zero_page_store(result);
-        self.wait_counter = 5;
     }
 
     fn unofficial_rotate_right_memory_add_acc_zero_page_x(&mut self) {
-        let value = self.read_zero_page_x();
-        let result = self.do_rotate_right(value);
+        let address = self.get_zero_page_x_address();
+        let result = self.do_read_modify_write(address, Self::do_rotate_right);
         self.do_add(result);
-        self.program_counter -= 1;
-        self.do_zero_page_x_store(result);
-        self.wait_counter = 6;
     }
 
     fn unofficial_rotate_right_memory_add_acc_absolute(&mut self) {
-        let value = self.read_absolute();
-        let result = self.do_rotate_right(value);
+        let address = self.get_absolute_address();
+        let result = self.do_read_modify_write(address, Self::do_rotate_right);
         self.do_add(result);
-        self.program_counter -= 2;
-        self.do_absolute_store(result);
-        self.wait_counter = 6;
     }
 
     fn unofficial_rotate_right_memory_add_acc_absolute_x(&mut self) {
-        let value = self.read_absolute_x();
-        let result = self.do_rotate_right(value);
+        let address = self.get_absolute_x_address();
+        let result = self.do_read_modify_write(address, Self::do_rotate_right);
         self.do_add(result);
-        self.program_counter -= 2;
-        self.do_absolute_x_store(result);
-        self.wait_counter = 7;
     }
 
     fn unofficial_rotate_right_memory_add_acc_absolute_y(&mut self) {
-        let value = self.read_absolute_y();
-        let result = self.do_rotate_right(value);
+        let address = self.get_absolute_y_address();
+        let result = self.do_read_modify_write(address, Self::do_rotate_right);
         self.do_add(result);
-        self.program_counter -= 2;
-        self.do_absolute_y_store(result);
-        self.wait_counter = 7;
     }
 
     fn unofficial_rotate_right_memory_add_acc_indirect_x(&mut self) {
-        let value = self.read_indirect_x();
-        let result = self.do_rotate_right(value);
+        let address = self.get_indirect_x_address();
+        let result = self.do_read_modify_write(address, Self::do_rotate_right);
         self.do_add(result);
-        self.program_counter -= 1;
-        self.do_indirect_x_store(result);
-        self.wait_counter = 8;
     }
 
     fn unofficial_rotate_right_memory_add_acc_indirect_y(&mut self) {
-        let value = self.read_indirect_y();
-        let result = self.do_rotate_right(value);
+        let address = self.get_indirect_y_address();
+        let result = self.do_read_modify_write(address, Self::do_rotate_right);
         self.do_add(result);
-        self.program_counter -= 1;
-        self.do_indirect_y_store(result);
-        self.wait_counter = 8;
     }
 
 
+    fn unofficial_and_immediate_copy_negative_to_carry(&mut self) {
+        self.and_immediate();
+        self.status_flags = (self.status_flags & 0xFE) | ((self.a & 0x80) >> 7);
+    }
+
+    fn unofficial_and_immediate_shift_right_acc(&mut self) {
+        self.and_immediate();
+        let value = self.a;
+        self.a = self.do_logical_shift_right(value);
+    }
+
+    // rotation is done normally, but carry and overflow are set from bits 6 and 5 of the result
+    fn unofficial_and_immediate_rotate_right_acc(&mut self) {
+        self.and_immediate();
+        let value = self.a;
+        let result = self.do_rotate_right(value);
+        self.a = result;
+
+        let carry = (result & 0x40) >> 6;
+        let overflow = ((result & 0x40) ^ ((result & 0x20) << 1)) & 0x40;
+        self.status_flags = (self.status_flags & 0xBE) | overflow | carry;
+    }
+
+    // Unstable; the result depends on chip and temperature. Bits of A pass through a 'magic' constant
+    // (0xEE is commonly used) before the and operations
+    fn unofficial_transfer_x_to_acc_and_immediate(&mut self) {
+        let operand = self.read_immediate();
+        let result = (self.a | 0xEE) & self.x & operand;
+        self.load_a(result);
+    }
+
+    // Also unstable like above; the magic constant 0xFF is used as this matches the behaviour test
+    // roms expect, so effectively this loads the immediate value into A and X
+    fn unofficial_load_a_and_x_immediate(&mut self) {
+        let operand = self.read_immediate();
+        let result = (self.a | 0xFF) & operand;
+        self.load_a(result);
+        self.load_x(result);
+    }
+
+    fn unofficial_load_a_x_and_stack_pointer_absolute_y(&mut self) {
+        let value = self.read_absolute_y() & self.stack_pointer;
+        self.stack_pointer = value;
+        self.load_a(value);
+        self.load_x(value);
+    }
+
+    // (A & X) - operand is stored into X. Carry is set like with compare, overflow is not modified
+    fn unofficial_and_a_with_x_subtract_immediate(&mut self) {
+        let operand = self.read_immediate();
+        let register = self.a & self.x;
+        self.do_compare(register, operand);
+        self.x = register.wrapping_sub(operand);
+    }
+
+    fn unofficial_and_a_with_x_and_high_byte_store_result_indirect_y(&mut self) {
+        let base_address = self.get_indirect_base_address();
+        let register = self.a & self.x;
+        self.do_and_high_byte_store(base_address, self.y, register);
+    }
+
+    fn unofficial_and_a_with_x_and_high_byte_store_result_absolute_y(&mut self) {
+        let base_address = self.get_absolute_address();
+        let register = self.a & self.x;
+        self.do_and_high_byte_store(base_address, self.y, register);
+    }
+
+    fn unofficial_transfer_a_and_x_to_stack_pointer_store_result_absolute_y(&mut self) {
+        let base_address = self.get_absolute_address();
+        self.stack_pointer = self.a & self.x;
+        let register = self.stack_pointer;
+        self.do_and_high_byte_store(base_address, self.y, register);
+    }
+
+    fn unofficial_and_y_with_high_byte_store_result_absolute_x(&mut self) {
+        let base_address = self.get_absolute_address();
+        let register = self.y;
+        self.do_and_high_byte_store(base_address, self.x, register);
+    }
+
+    fn unofficial_and_x_with_high_byte_store_result_absolute_y(&mut self) {
+        let base_address = self.get_absolute_address();
+        let register = self.x;
+        self.do_and_high_byte_store(base_address, self.y, register);
+    }
+
+    // Stores register & (high byte of base address + 1). These are unstable as well: if adding
+    // the index crosses a page, the high byte of the target address is replaced with the stored value
+    fn do_and_high_byte_store(&mut self, base_address: u16, offset: u8, register: u8) {
+        let address = base_address.wrapping_add(offset as u16);
+        self.read((base_address & 0xFF00) | (address & 0x00FF));
+        let value = register & ((base_address >> 8) as u8).wrapping_add(1);
+
+        let address = if base_address & 0xFF00 != address & 0xFF00 {
+            ((value as u16) << 8) | (address & 0x00FF)
+        } else {
+            address
+        };
+        self.write(address, value);
+    }
+
+    // locks up the cpu; program counter is left pointing at the opcode
+    fn unofficial_jam(&mut self) {
+        self.jammed = true;
+        self.program_counter -= 1;
+    }
+
     // unofficial\illegal instructions may basically just do a read without
     // doing anything else with the result
 
@@ -2097,23 +2145,39 @@ impl<'a> Cpu<'a> {
         self.no_operation();
     }
 
-    fn unofficial_double_no_operation(&mut self, cycles: u16) {
-        self.wait_counter = cycles;
-        self.program_counter += 1;
+    fn unofficial_nop_immediate(&mut self) {
+        self.read_immediate();
     }
 
-    fn unofficial_triple_no_operation_no_page_penalty(&mut self, cycles: u16) {
-        self.wait_counter = cycles;
-        self.program_counter += 2;
+    fn unofficial_nop_zero_page(&mut self) {
+        self.read_zero_page();
     }
-    // add a cycle if page boundary is crossed
-    fn unofficial_triple_no_operation_page_penalty(&mut self, cycles: u16) {
-        let first = self.program_counter;
-        let second = self.program_counter + 1;
-        self.program_counter += 2;
-        self.wait_counter = cycles;
-        if first & 0xFF00 != second & 0xFF00 {
-            self.wait_counter += 1;
+
+    fn unofficial_nop_zero_page_x(&mut self) {
+        self.read_zero_page_x();
+    }
+
+    fn unofficial_nop_absolute(&mut self) {
+        self.read_absolute();
+    }
+
+    fn unofficial_nop_absolute_x(&mut self) {
+        self.read_absolute_x();
+    }
+
+    // The dma unit halts the cpu for a cycle, waits for an extra cycle if the transfer would
+    // start on an odd cycle, and then copies the page with 256 read/write pairs.
+    // The transfer takes 513 or 514 cycles in total
+    fn do_oam_dma(&mut self, page: u8) {
+        self.dummy_read_program_counter();
+        if self.cycles % 2 == 1 {
+            self.dummy_read_program_counter();
+        }
+
+        let start = (page as u16) << 8;
+        for address in start..(start + 0x100) {
+            let value = self.read(address);
+            self.write(0x2004, value);
         }
     }
 }
@@ -2142,56 +2206,237 @@ impl Frequency {
             TvSystem::NTSC => {
                 divisor = 12;
                 color_freq = 39375000.0/11.0 / 1000_000.0;
+            },
+            TvSystem::Dendy => {
+                // PAL master clock, but cpu runs faster so that cpu:ppu ratio is 1:3 as with NTSC
+                divisor = 15;
+                color_freq = 4433618.75 / 1000_000.0;
+            }
+        }
+
+        master_freq = 6.0*color_freq;
+
+        Frequency {
+            color_subcarrier_frequency: color_freq,
+            master_clock_frequency: master_freq,
+            clock_divisor: divisor,
+            cpu_clock_frequency: master_freq / divisor as f64
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use memory::{Memory, Bus};
+    use rom::TvSystem;
+    use std::io::{self, Write};
+    use std::rc::Rc;
+    use std::cell::RefCell;
+
+    // 64 kilobytes of memory, no mapped addresses
+    struct MockMemory {
+        ram: Vec<u8>
+    }
+
+    impl MockMemory {
+        fn new() -> MockMemory {
+            MockMemory {
+                ram: vec![0;0xFFFF + 1],
             }
         }
+    }
+
+    impl Memory for MockMemory {
+        fn read(&mut self, address: u16) -> u8 {
+            self.ram[address as usize]
+        }
+
+        fn write(&mut self, address: u16, value: u8) {
+            self.ram[address as usize] = value;
+        }
+    }
+
+    impl Bus for MockMemory {
+        fn nmi_occured(&mut self) -> bool {
+            false
+        }
+
+        fn irq_line(&mut self) -> bool {
+            false
+        }
+
+        fn peek(&mut self, address: u16) -> u8 {
+            self.ram[address as usize]
+        }
+
+        fn ppu_position(&self) -> (u16, u16) {
+            (0, 0)
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Access {
+        Read(u16),
+        Write(u16, u8),
+    }
+
+    // records bus accesses so that dummy reads and writes can be checked
+    struct RecordingMemory {
+        ram: Vec<u8>,
+        accesses: Rc<RefCell<Vec<Access>>>,
+    }
+
+    impl Memory for RecordingMemory {
+        fn read(&mut self, address: u16) -> u8 {
+            self.accesses.borrow_mut().push(Access::Read(address));
+            self.ram[address as usize]
+        }
+
+        fn write(&mut self, address: u16, value: u8) {
+            self.accesses.borrow_mut().push(Access::Write(address, value));
+            self.ram[address as usize] = value;
+        }
+    }
+
+    impl Bus for RecordingMemory {
+        fn nmi_occured(&mut self) -> bool {
+            false
+        }
+
+        fn irq_line(&mut self) -> bool {
+            false
+        }
+
+        fn peek(&mut self, address: u16) -> u8 {
+            self.ram[address as usize]
+        }
+
+        fn ppu_position(&self) -> (u16, u16) {
+            (0, 0)
+        }
+    }
+
+    // program is placed at 0x0200
+    fn create_recording_cpu(program: &[u8]) -> (Cpu<RecordingMemory>, Rc<RefCell<Vec<Access>>>) {
+        let accesses = Rc::new(RefCell::new(vec![]));
+        let mut ram = vec![0;0xFFFF + 1];
+        for (i, byte) in program.iter().enumerate() {
+            ram[0x200 + i] = *byte;
+        }
+
+        let memory = RecordingMemory {
+            ram: ram,
+            accesses: accesses.clone(),
+        };
+        let mut cpu = Cpu::new(&TvSystem::NTSC, memory);
+        cpu.program_counter = 0x200;
+        cpu.stack_pointer = 0xFD;
+        (cpu, accesses)
+    }
 
-        master_freq = 6.0*color_freq;
+    // drives the interrupt lines starting from the given bus access (1 is the first access)
+    struct InterruptMemory {
+        ram: Vec<u8>,
+        accesses: u64,
+        irq_from_access: Option<u64>,
+        nmi_at_access: Option<u64>,
+    }
 
-        Frequency {
-            color_subcarrier_frequency: color_freq,
-            master_clock_frequency: master_freq,
-            clock_divisor: divisor,
-            cpu_clock_frequency: master_freq / divisor as f64
+    impl Memory for InterruptMemory {
+        fn read(&mut self, address: u16) -> u8 {
+            self.accesses += 1;
+            self.ram[address as usize]
+        }
+
+        fn write(&mut self, address: u16, value: u8) {
+            self.accesses += 1;
+            self.ram[address as usize] = value;
         }
     }
-}
 
+    impl Bus for InterruptMemory {
+        fn nmi_occured(&mut self) -> bool {
+            self.nmi_at_access == Some(self.accesses)
+        }
 
+        fn irq_line(&mut self) -> bool {
+            match self.irq_from_access {
+                Some(access) => self.accesses >= access,
+                None => false,
+            }
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use memory::Memory;
-    use rom::TvSystem;
-    use std::rc::Rc;
-    use std::cell::RefCell;
+        fn peek(&mut self, address: u16) -> u8 {
+            self.ram[address as usize]
+        }
 
-    // 64 kilobytes of memory, no mapped addresses
-    struct MockMemory {
-        ram: Vec<u8>
+        fn ppu_position(&self) -> (u16, u16) {
+            (0, 0)
+        }
     }
 
-    impl MockMemory {
-        fn new() -> MockMemory {
-            MockMemory {
-                ram: vec![0;0xFFFF + 1],
-            }
+    // program is placed at 0x0200. Irq handler is at 0x0300 and nmi handler at 0x0400,
+    // both filled with NOPs
+    fn create_interrupt_test_cpu(
+        program: &[u8],
+        irq_from_access: Option<u64>,
+        nmi_at_access: Option<u64>) -> Cpu<InterruptMemory> {
+        let mut ram = vec![0xEA;0xFFFF + 1];
+        for (i, byte) in program.iter().enumerate() {
+            ram[0x200 + i] = *byte;
         }
+        ram[0xFFFA] = 0x00;
+        ram[0xFFFB] = 0x04;
+        ram[0xFFFE] = 0x00;
+        ram[0xFFFF] = 0x03;
+
+        let memory = InterruptMemory {
+            ram: ram,
+            accesses: 0,
+            irq_from_access: irq_from_access,
+            nmi_at_access: nmi_at_access,
+        };
+        let mut cpu = Cpu::new(&TvSystem::NTSC, memory);
+        cpu.program_counter = 0x200;
+        cpu.stack_pointer = 0xFF;
+        cpu
     }
 
-    impl Memory for MockMemory {
-        fn read(&mut self, address: u16) -> u8 {
-            self.ram[address as usize]
+    // lets tests read what the tracer has written
+    struct SharedOutput {
+        buffer: Rc<RefCell<Vec<u8>>>,
+    }
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.buffer.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
         }
 
-        fn write(&mut self, address: u16, value: u8) {
-            self.ram[address as usize] = value;
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
         }
     }
 
-    fn create_test_cpu<'a>() -> Cpu<'a> {
-        let memory = Rc::new(RefCell::new(Box::new(MockMemory::new()) as Box<Memory>));
-        Cpu::new(&TvSystem::NTSC, memory)
+    // stack pointer is set as if the cpu had been reset
+    fn create_test_cpu() -> Cpu<MockMemory> {
+        let mut cpu = Cpu::new(&TvSystem::NTSC, MockMemory::new());
+        cpu.stack_pointer = 0xFD;
+        cpu
+    }
+
+    fn handle_nmi<B: Bus>(cpu: &mut Cpu<B>) {
+        cpu.nmi_pending = true;
+        cpu.previous_nmi_pending = true;
+        cpu.execute_instruction();
+    }
+
+    // tests call the instruction functions directly, so the opcode fetch is not counted
+    fn instruction_cycles<B: Bus>(cpu: &Cpu<B>) -> u64 {
+        cpu.cycles + 1
     }
 
     #[test]
@@ -2262,7 +2507,7 @@ mod tests {
     fn get_byte_operand_gets_correct_value_and_updates_program_counter() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 24;
-        cpu.memory.borrow_mut().write(24, 0xAD);
+        cpu.bus.write(24, 0xAD);
         assert_eq!(0xAD, cpu.get_byte_operand());
         assert_eq!(25, cpu.program_counter);
     }
@@ -2272,7 +2517,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.stack_pointer = 0xFF;
         cpu.push_value_into_stack(23);
-        assert_eq!(23, cpu.memory.borrow_mut().read(0x01FF));
+        assert_eq!(23, cpu.bus.read(0x01FF));
     }
 
     #[test]
@@ -2287,7 +2532,7 @@ mod tests {
     fn pop_value_from_stack_returns_correct_value() {
         let mut cpu = create_test_cpu();
         cpu.stack_pointer = 0xCC;
-        cpu.memory.borrow_mut().write(0x0100 + 0xCD, 123);
+        cpu.bus.write(0x0100 + 0xCD, 123);
         assert_eq!(123, cpu.pop_value_from_stack());
     }
 
@@ -2358,7 +2603,7 @@ mod tests {
     fn get_zero_page_address_returns_correct_address() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x243;
-        cpu.memory.borrow_mut().write(0x243, 0xAF);
+        cpu.bus.write(0x243, 0xAF);
         assert_eq!(0x00AF, cpu.get_zero_page_address());
     }
 
@@ -2366,7 +2611,7 @@ mod tests {
     fn get_zero_page_address_with_offset_returns_correct_address_when_value_does_not_wrap_around() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x243;
-        cpu.memory.borrow_mut().write(0x243, 0xAF);
+        cpu.bus.write(0x243, 0xAF);
         assert_eq!(0x00AF + 0x12, cpu.get_zero_page_address_with_offset(0x12));
     }
 
@@ -2374,7 +2619,7 @@ mod tests {
     fn get_zero_page_address_with_offset_returns_correct_address_when_value_wraps_around() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x243;
-        cpu.memory.borrow_mut().write(0x243, 0xFF);
+        cpu.bus.write(0x243, 0xFF);
         assert_eq!(0x0011, cpu.get_zero_page_address_with_offset(0x12));
     }
 
@@ -2382,8 +2627,8 @@ mod tests {
     fn get_absolute_address_returns_correct_address() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x243;
-        cpu.memory.borrow_mut().write(0x243, 0xBE);
-        cpu.memory.borrow_mut().write(0x244, 0xBA);
+        cpu.bus.write(0x243, 0xBE);
+        cpu.bus.write(0x244, 0xBA);
         assert_eq!(0xBABE, cpu.get_absolute_address());
     }
 
@@ -2391,8 +2636,8 @@ mod tests {
     fn get_absolute_address_handles_program_counter_wrapping() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0xFFFE;
-        cpu.memory.borrow_mut().write(0xFFFE, 0xBE);
-        cpu.memory.borrow_mut().write(0xFFFF, 0xBA);
+        cpu.bus.write(0xFFFE, 0xBE);
+        cpu.bus.write(0xFFFF, 0xBA);
         assert_eq!(0xBABE, cpu.get_absolute_address());
         assert_eq!(0, cpu.program_counter);
     }
@@ -2401,8 +2646,8 @@ mod tests {
     fn get_absolute_address_with_offset_returns_correct_address() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x243;
-        cpu.memory.borrow_mut().write(0x243, 0xBE);
-        cpu.memory.borrow_mut().write(0x244, 0xBA);
+        cpu.bus.write(0x243, 0xBE);
+        cpu.bus.write(0x244, 0xBA);
         assert_eq!(0xBABE + 0x43, cpu.get_absolute_address_with_offset(0x43));
     }
     #[test]
@@ -2410,8 +2655,8 @@ mod tests {
     fn get_absolute_address_with_offset_hans() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x243;
-        cpu.memory.borrow_mut().write(0x243, 0xFF);
-        cpu.memory.borrow_mut().write(0x244, 0xFF);
+        cpu.bus.write(0x243, 0xFF);
+        cpu.bus.write(0x244, 0xFF);
         assert_eq!(0x42, cpu.get_absolute_address_with_offset(0x43));
     }
     #[test]
@@ -2419,10 +2664,10 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x243;
         cpu.x = 0x25;
-        cpu.memory.borrow_mut().write(0x243, 0xBE);
+        cpu.bus.write(0x243, 0xBE);
 
-        cpu.memory.borrow_mut().write(0xBE + 0x25 , 0xBA);
-        cpu.memory.borrow_mut().write(0xBE + 0x25 + 1, 0xAF);
+        cpu.bus.write(0xBE + 0x25 , 0xBA);
+        cpu.bus.write(0xBE + 0x25 + 1, 0xAF);
 
         assert_eq!(0xAFBA, cpu.get_indirect_x_address());
     }
@@ -2432,10 +2677,10 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x243;
         cpu.x = 0x1;
-        cpu.memory.borrow_mut().write(0x243, 0xFE);
+        cpu.bus.write(0x243, 0xFE);
 
-        cpu.memory.borrow_mut().write(0xFF, 0xBA);
-        cpu.memory.borrow_mut().write(0x00, 0xAF);
+        cpu.bus.write(0xFF, 0xBA);
+        cpu.bus.write(0x00, 0xAF);
 
         assert_eq!(0xAFBA, cpu.get_indirect_x_address());
     }
@@ -2445,10 +2690,10 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x243;
         cpu.y = 0x25;
-        cpu.memory.borrow_mut().write(0x243, 0xBE);
+        cpu.bus.write(0x243, 0xBE);
 
-        cpu.memory.borrow_mut().write(0xBE , 0xBA);
-        cpu.memory.borrow_mut().write(0xBE + 1, 0xAF);
+        cpu.bus.write(0xBE , 0xBA);
+        cpu.bus.write(0xBE + 1, 0xAF);
 
         assert_eq!(0xAFBA + 0x25, cpu.get_indirect_y_address());
     }
@@ -2458,10 +2703,10 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x243;
         cpu.y = 0x25;
-        cpu.memory.borrow_mut().write(0x243, 0xFF);
+        cpu.bus.write(0x243, 0xFF);
 
-        cpu.memory.borrow_mut().write(0xFF, 0xBA);
-        cpu.memory.borrow_mut().write(0x00, 0xAF);
+        cpu.bus.write(0xFF, 0xBA);
+        cpu.bus.write(0x00, 0xAF);
 
         assert_eq!(0xAFBA + 0x25, cpu.get_indirect_y_address());
     }
@@ -2471,10 +2716,10 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x243;
         cpu.y = 0x25;
-        cpu.memory.borrow_mut().write(0x243, 0xBE);
+        cpu.bus.write(0x243, 0xBE);
 
-        cpu.memory.borrow_mut().write(0xBE , 0xFF);
-        cpu.memory.borrow_mut().write(0xBE + 1, 0xFF);
+        cpu.bus.write(0xBE , 0xFF);
+        cpu.bus.write(0xBE + 1, 0xFF);
 
         assert_eq!(0x0024, cpu.get_indirect_y_address());
     }
@@ -2483,41 +2728,41 @@ mod tests {
     fn read_immediate_returns_value_pointed_by_program_counter() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0xFA);
+        cpu.bus.write(0x432, 0xFA);
         assert_eq!(0xFA, cpu.read_immediate());
     }
 
     #[test]
-    fn read_immediate_sets_wait_counter_to_2() {
+    fn read_immediate_takes_2_cycles() {
         let mut cpu = create_test_cpu();
         cpu.read_immediate();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
     fn read_absolute_returns_value_pointed_by_address_at_program_counter() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0xFA);
-        cpu.memory.borrow_mut().write(0x433, 0xE0);
-        cpu.memory.borrow_mut().write(0xE0FA, 0x52);
+        cpu.bus.write(0x432, 0xFA);
+        cpu.bus.write(0x433, 0xE0);
+        cpu.bus.write(0xE0FA, 0x52);
         assert_eq!(0x52, cpu.read_absolute());
     }
 
     #[test]
-    fn read_absolute_sets_wait_counter_to_4() {
+    fn read_absolute_takes_4_cycles() {
         let mut cpu = create_test_cpu();
         cpu.read_absolute();
-        assert_eq!(4, cpu.wait_counter);
+        assert_eq!(4, instruction_cycles(&cpu));
     }
 
     #[test]
     fn read_absolute_with_offset_return_correct_value() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0xFF);
-        cpu.memory.borrow_mut().write(0x433, 0xE0);
-        cpu.memory.borrow_mut().write(0xE100, 0xC5);
+        cpu.bus.write(0x432, 0xFF);
+        cpu.bus.write(0x433, 0xE0);
+        cpu.bus.write(0xE100, 0xC5);
         assert_eq!(0xC5, cpu.read_absolute_with_offset(0x01));
     }
 
@@ -2525,9 +2770,9 @@ mod tests {
     fn read_absolute_with_offset_handles_wrapping() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0xFF);
-        cpu.memory.borrow_mut().write(0x433, 0xFF);
-        cpu.memory.borrow_mut().write(0x0033, 0xC5);
+        cpu.bus.write(0x432, 0xFF);
+        cpu.bus.write(0x433, 0xFF);
+        cpu.bus.write(0x0033, 0xC5);
         assert_eq!(0xC5, cpu.read_absolute_with_offset(0x34));
     }
 
@@ -2535,30 +2780,30 @@ mod tests {
     fn read_absolute_with_offset_takes_4_cycles_if_page_boundary_is_not_crossed() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0x00);
-        cpu.memory.borrow_mut().write(0x433, 0xE0);
+        cpu.bus.write(0x432, 0x00);
+        cpu.bus.write(0x433, 0xE0);
         cpu.read_absolute_with_offset(0xFA);
-        assert_eq!(4, cpu.wait_counter);
+        assert_eq!(4, instruction_cycles(&cpu));
     }
 
     #[test]
     fn read_absolute_with_offset_takes_5_cycles_if_page_boundary_is_barely_crossed() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0xFF);
-        cpu.memory.borrow_mut().write(0x433, 0xE0);
+        cpu.bus.write(0x432, 0xFF);
+        cpu.bus.write(0x433, 0xE0);
         cpu.read_absolute_with_offset(0x01);
-        assert_eq!(5, cpu.wait_counter);
+        assert_eq!(5, instruction_cycles(&cpu));
     }
 
     #[test]
     fn read_absolute_with_offset_takes_5_cycles_if_page_boundary_is_crossed() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0xFA);
-        cpu.memory.borrow_mut().write(0x433, 0xE0);
+        cpu.bus.write(0x432, 0xFA);
+        cpu.bus.write(0x433, 0xE0);
         cpu.read_absolute_with_offset(0xFE);
-        assert_eq!(5, cpu.wait_counter);
+        assert_eq!(5, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -2566,9 +2811,9 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0xFA;
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0xFA);
-        cpu.memory.borrow_mut().write(0x433, 0xE0);
-        cpu.memory.borrow_mut().write(0xE0FA + 0x00FA, 0x52);
+        cpu.bus.write(0x432, 0xFA);
+        cpu.bus.write(0x433, 0xE0);
+        cpu.bus.write(0xE0FA + 0x00FA, 0x52);
         assert_eq!(0x52, cpu.read_absolute_x());
     }
 
@@ -2578,9 +2823,9 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.y = 0xFA;
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0xFA);
-        cpu.memory.borrow_mut().write(0x433, 0xE0);
-        cpu.memory.borrow_mut().write(0xE0FA + 0x00FA, 0x52);
+        cpu.bus.write(0x432, 0xFA);
+        cpu.bus.write(0x433, 0xE0);
+        cpu.bus.write(0xE0FA + 0x00FA, 0x52);
         assert_eq!(0x52, cpu.read_absolute_y());
     }
 
@@ -2588,24 +2833,24 @@ mod tests {
     fn read_zero_page_returns_value_at_zero_page_pointed_by_program_counter() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0xFA);
-        cpu.memory.borrow_mut().write(0x00FA, 0xAE);
+        cpu.bus.write(0x432, 0xFA);
+        cpu.bus.write(0x00FA, 0xAE);
         assert_eq!(0xAE, cpu.read_zero_page());
     }
 
     #[test]
-    fn read_zero_page_sets_wait_counter_to_3() {
+    fn read_zero_page_takes_3_cycles() {
         let mut cpu = create_test_cpu();
         cpu.read_zero_page();
-        assert_eq!(3, cpu.wait_counter);
+        assert_eq!(3, instruction_cycles(&cpu));
     }
 
     #[test]
     fn read_zero_page_with_offset_returns_value_at_zero_page_with_offset() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0x80);
-        cpu.memory.borrow_mut().write(0x008F, 0xAE);
+        cpu.bus.write(0x432, 0x80);
+        cpu.bus.write(0x008F, 0xAE);
         assert_eq!(0xAE, cpu.read_zero_page_with_offset(0x0F));
     }
 
@@ -2613,16 +2858,16 @@ mod tests {
     fn read_zero_page_x_handles_wrap_around() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0x80);
-        cpu.memory.borrow_mut().write(0x007F, 0xAE);
+        cpu.bus.write(0x432, 0x80);
+        cpu.bus.write(0x007F, 0xAE);
         assert_eq!(0xAE, cpu.read_zero_page_with_offset(0xFF));
     }
 
     #[test]
-    fn read_zero_page_with_offset_sets_wait_counter_to_4() {
+    fn read_zero_page_with_offset_takes_4_cycles() {
         let mut cpu = create_test_cpu();
         cpu.read_zero_page_with_offset(0x00);
-        assert_eq!(4, cpu.wait_counter);
+        assert_eq!(4, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -2630,8 +2875,8 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x0F;
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0x80);
-        cpu.memory.borrow_mut().write(0x008F, 0xAE);
+        cpu.bus.write(0x432, 0x80);
+        cpu.bus.write(0x008F, 0xAE);
         assert_eq!(0xAE, cpu.read_zero_page_x());
     }
 
@@ -2640,8 +2885,8 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.y = 0x0F;
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0x80);
-        cpu.memory.borrow_mut().write(0x008F, 0xAE);
+        cpu.bus.write(0x432, 0x80);
+        cpu.bus.write(0x008F, 0xAE);
         assert_eq!(0xAE, cpu.read_zero_page_y());
     }
 
@@ -2651,12 +2896,12 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x04;
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0x80);
+        cpu.bus.write(0x432, 0x80);
 
-        cpu.memory.borrow_mut().write(0x80+0x04, 0x80);
-        cpu.memory.borrow_mut().write(0x80+0x05, 0xAF);
+        cpu.bus.write(0x80+0x04, 0x80);
+        cpu.bus.write(0x80+0x05, 0xAF);
 
-        cpu.memory.borrow_mut().write(0xAF80, 0xAE);
+        cpu.bus.write(0xAF80, 0xAE);
 
         assert_eq!(0xAE, cpu.read_indirect_x());
     }
@@ -2666,21 +2911,21 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x04;
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0xFE);
+        cpu.bus.write(0x432, 0xFE);
 
-        cpu.memory.borrow_mut().write(0x02, 0x80);
-        cpu.memory.borrow_mut().write(0x03, 0xAF);
+        cpu.bus.write(0x02, 0x80);
+        cpu.bus.write(0x03, 0xAF);
 
-        cpu.memory.borrow_mut().write(0xAF80, 0xAE);
+        cpu.bus.write(0xAF80, 0xAE);
 
         assert_eq!(0xAE, cpu.read_indirect_x());
     }
 
     #[test]
-    fn read_indirect_x_sets_wait_counter_to_6() {
+    fn read_indirect_x_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.read_indirect_x();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -2689,12 +2934,12 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.y = 0x04;
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0x80);
+        cpu.bus.write(0x432, 0x80);
 
-        cpu.memory.borrow_mut().write(0x80, 0x80);
-        cpu.memory.borrow_mut().write(0x81, 0xAF);
+        cpu.bus.write(0x80, 0x80);
+        cpu.bus.write(0x81, 0xAF);
 
-        cpu.memory.borrow_mut().write(0xAF80 + 0x04, 0xAE);
+        cpu.bus.write(0xAF80 + 0x04, 0xAE);
 
         assert_eq!(0xAE, cpu.read_indirect_y());
     }
@@ -2705,12 +2950,12 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.y = 0x04;
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0xFF);
+        cpu.bus.write(0x432, 0xFF);
 
-        cpu.memory.borrow_mut().write(0xFF, 0xFF);
-        cpu.memory.borrow_mut().write(0x00, 0xAB);
+        cpu.bus.write(0xFF, 0xFF);
+        cpu.bus.write(0x00, 0xAB);
 
-        cpu.memory.borrow_mut().write(0x0ABFF + 0x04, 0xAE);
+        cpu.bus.write(0x0ABFF + 0x04, 0xAE);
 
         assert_eq!(0xAE, cpu.read_indirect_y());
     }
@@ -2721,12 +2966,12 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.y = 0x04;
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0x80);
+        cpu.bus.write(0x432, 0x80);
 
-        cpu.memory.borrow_mut().write(0x80, 0xFF);
-        cpu.memory.borrow_mut().write(0x81, 0xFF);
+        cpu.bus.write(0x80, 0xFF);
+        cpu.bus.write(0x81, 0xFF);
 
-        cpu.memory.borrow_mut().write(0x0003, 0xAE);
+        cpu.bus.write(0x0003, 0xAE);
 
         assert_eq!(0xAE, cpu.read_indirect_y());
     }
@@ -2736,12 +2981,12 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.y = 0x04;
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0x80);
+        cpu.bus.write(0x432, 0x80);
 
-        cpu.memory.borrow_mut().write(0x80, 0x80);
-        cpu.memory.borrow_mut().write(0x81, 0xAF);
+        cpu.bus.write(0x80, 0x80);
+        cpu.bus.write(0x81, 0xAF);
         cpu.read_indirect_y();
-        assert_eq!(5, cpu.wait_counter);
+        assert_eq!(5, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -2749,21 +2994,21 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.y = 0x04;
         cpu.program_counter = 0x432;
-        cpu.memory.borrow_mut().write(0x432, 0x80);
+        cpu.bus.write(0x432, 0x80);
 
-        cpu.memory.borrow_mut().write(0x80, 0xFE);
-        cpu.memory.borrow_mut().write(0x81, 0xAF);
+        cpu.bus.write(0x80, 0xFE);
+        cpu.bus.write(0x81, 0xAF);
         cpu.read_indirect_y();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
     fn do_zero_page_store_stores_value_into_memory_correctly() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x32;
-        cpu.memory.borrow_mut().write(0x32, 0x14);
+        cpu.bus.write(0x32, 0x14);
         cpu.do_zero_page_store(0x2F);
-        assert_eq!(0x2F, cpu.memory.borrow_mut().read(0x14));
+        assert_eq!(0x2F, cpu.bus.read(0x14));
     }
 
     #[test]
@@ -2777,7 +3022,7 @@ mod tests {
     fn  do_zero_page_store_takes_3_cycles() {
         let mut cpu = create_test_cpu();
         cpu.do_zero_page_store(0x12);
-        assert_eq!(3, cpu.wait_counter);
+        assert_eq!(3, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -2785,9 +3030,9 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x24;
         cpu.program_counter = 0x32;
-        cpu.memory.borrow_mut().write(0x32, 0x14);
+        cpu.bus.write(0x32, 0x14);
         cpu.do_zero_page_x_store(0x2F);
-        assert_eq!(0x2F, cpu.memory.borrow_mut().read(0x14 + 0x24));
+        assert_eq!(0x2F, cpu.bus.read(0x14 + 0x24));
     }
 
     #[test]
@@ -2801,7 +3046,7 @@ mod tests {
     fn  do_zero_page_x_store_takes_4_cycles() {
         let mut cpu = create_test_cpu();
         cpu.do_zero_page_x_store(0x12);
-        assert_eq!(4, cpu.wait_counter);
+        assert_eq!(4, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -2809,9 +3054,9 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.y = 0x24;
         cpu.program_counter = 0x32;
-        cpu.memory.borrow_mut().write(0x32, 0x14);
+        cpu.bus.write(0x32, 0x14);
         cpu.do_zero_page_y_store(0x2F);
-        assert_eq!(0x2F, cpu.memory.borrow_mut().read(0x14 + 0x24));
+        assert_eq!(0x2F, cpu.bus.read(0x14 + 0x24));
     }
 
     #[test]
@@ -2825,7 +3070,7 @@ mod tests {
     fn do_zero_page_y_store_takes_4_cycles() {
         let mut cpu = create_test_cpu();
         cpu.do_zero_page_y_store(0x12);
-        assert_eq!(4, cpu.wait_counter);
+        assert_eq!(4, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -2833,11 +3078,11 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x32;
-        cpu.memory.borrow_mut().write(0x32, 0x21);
-        cpu.memory.borrow_mut().write(0x33, 0x18);
+        cpu.bus.write(0x32, 0x21);
+        cpu.bus.write(0x33, 0x18);
 
         cpu.do_absolute_store(0x2F);
-        assert_eq!(0x2F, cpu.memory.borrow_mut().read(0x1821));
+        assert_eq!(0x2F, cpu.bus.read(0x1821));
     }
 
     #[test]
@@ -2851,7 +3096,7 @@ mod tests {
     fn do_absolute_store_takes_4_cycles() {
         let mut cpu = create_test_cpu();
         cpu.do_absolute_store(0x12);
-        assert_eq!(4, cpu.wait_counter);
+        assert_eq!(4, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -2859,11 +3104,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x25;
         cpu.program_counter = 0x32;
-        cpu.memory.borrow_mut().write(0x32, 0x21);
-        cpu.memory.borrow_mut().write(0x33, 0x18);
+        cpu.bus.write(0x32, 0x21);
+        cpu.bus.write(0x33, 0x18);
 
         cpu.do_absolute_x_store(0x2F);
-        assert_eq!(0x2F, cpu.memory.borrow_mut().read(0x1821 + 0x25));
+        assert_eq!(0x2F, cpu.bus.read(0x1821 + 0x25));
     }
 
     #[test]
@@ -2877,7 +3122,7 @@ mod tests {
     fn do_absolute_x_store_takes_5_cycles() {
         let mut cpu = create_test_cpu();
         cpu.do_absolute_x_store(0x12);
-        assert_eq!(5, cpu.wait_counter);
+        assert_eq!(5, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -2885,11 +3130,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.y = 0x25;
         cpu.program_counter = 0x32;
-        cpu.memory.borrow_mut().write(0x32, 0x21);
-        cpu.memory.borrow_mut().write(0x33, 0x18);
+        cpu.bus.write(0x32, 0x21);
+        cpu.bus.write(0x33, 0x18);
 
         cpu.do_absolute_y_store(0x2F);
-        assert_eq!(0x2F, cpu.memory.borrow_mut().read(0x1821 + 0x25));
+        assert_eq!(0x2F, cpu.bus.read(0x1821 + 0x25));
     }
 
     #[test]
@@ -2903,7 +3148,7 @@ mod tests {
     fn do_absolute_y_store_takes_5_cycles() {
         let mut cpu = create_test_cpu();
         cpu.do_absolute_y_store(0x12);
-        assert_eq!(5, cpu.wait_counter);
+        assert_eq!(5, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -2911,13 +3156,13 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x25;
         cpu.program_counter = 0x32;
-        cpu.memory.borrow_mut().write(0x32, 0x04);
+        cpu.bus.write(0x32, 0x04);
 
-        cpu.memory.borrow_mut().write(0x04 + 0x25, 0x18);
-        cpu.memory.borrow_mut().write(0x04 + 0x25 + 1, 0x0B);
+        cpu.bus.write(0x04 + 0x25, 0x18);
+        cpu.bus.write(0x04 + 0x25 + 1, 0x0B);
 
         cpu.do_indirect_x_store(0x2F);
-        assert_eq!(0x2F, cpu.memory.borrow_mut().read(0x0B18));
+        assert_eq!(0x2F, cpu.bus.read(0x0B18));
     }
 
     #[test]
@@ -2931,7 +3176,7 @@ mod tests {
     fn do_indirect_x_store_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.do_indirect_x_store(0x12);
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -2939,13 +3184,13 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.y = 0x25;
         cpu.program_counter = 0x32;
-        cpu.memory.borrow_mut().write(0x32, 0x04);
+        cpu.bus.write(0x32, 0x04);
 
-        cpu.memory.borrow_mut().write(0x04, 0x18);
-        cpu.memory.borrow_mut().write(0x04 + 1, 0x0B);
+        cpu.bus.write(0x04, 0x18);
+        cpu.bus.write(0x04 + 1, 0x0B);
 
         cpu.do_indirect_y_store(0x2F);
-        assert_eq!(0x2F, cpu.memory.borrow_mut().read(0x0B18 + 0x25));
+        assert_eq!(0x2F, cpu.bus.read(0x0B18 + 0x25));
     }
 
     #[test]
@@ -2959,7 +3204,7 @@ mod tests {
     fn do_indirect_y_store_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.do_indirect_y_store(0x12);
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -3051,10 +3296,10 @@ mod tests {
     }
 
     #[test]
-    fn do_and_does_not_modify_wait_counter() {
+    fn do_and_does_not_take_cycles() {
         let mut cpu = create_test_cpu();
         cpu.do_and(0x02);
-        assert_eq!(0, cpu.wait_counter);
+        assert_eq!(0, cpu.cycles);
     }
 
     #[test]
@@ -3110,10 +3355,10 @@ mod tests {
     }
 
     #[test]
-    fn do_inclusive_or_does_not_modify_wait_counter() {
+    fn do_inclusive_or_does_not_take_cycles() {
         let mut cpu = create_test_cpu();
         cpu.do_inclusive_or(0x02);
-        assert_eq!(0, cpu.wait_counter);
+        assert_eq!(0, cpu.cycles);
     }
 
     #[test]
@@ -3205,10 +3450,10 @@ mod tests {
     }
 
     #[test]
-    fn do_exclusive_or_does_not_modify_wait_counter() {
+    fn do_exclusive_or_does_not_take_cycles() {
         let mut cpu = create_test_cpu();
         cpu.do_exclusive_or(0x02);
-        assert_eq!(0, cpu.wait_counter);
+        assert_eq!(0, cpu.cycles);
     }
 
     #[test]
@@ -3291,7 +3536,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0xD3;
         cpu.program_counter = 0x20;
-        cpu.memory.borrow_mut().write(0x20, 0x10);
+        cpu.bus.write(0x20, 0x10);
         cpu.do_relative_jump_if(true);
         assert_eq!(0x21 + 0x10, cpu.program_counter);
     }
@@ -3301,7 +3546,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0xD3;
         cpu.program_counter = 0x20;
-        cpu.memory.borrow_mut().write(0x20, 0x10);
+        cpu.bus.write(0x20, 0x10);
         cpu.do_relative_jump_if(false);
         assert_eq!(0x21, cpu.program_counter);
     }
@@ -3310,7 +3555,7 @@ mod tests {
     fn do_relative_jump_if_can_jump_backwards() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 24;
-        cpu.memory.borrow_mut().write(24, 0xFC);
+        cpu.bus.write(24, 0xFC);
         cpu.do_relative_jump_if(true);
         assert_eq!(25 - 4, cpu.program_counter);
     }
@@ -3319,25 +3564,25 @@ mod tests {
     fn do_relative_jump_if_takes_2_cycles_if_condition_is_false() {
         let mut cpu = create_test_cpu();
         cpu.do_relative_jump_if(false);
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
     fn do_relative_jump_takes_3_cycles_if_branching_to_same_page() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x20;
-        cpu.memory.borrow_mut().write(0x20, 0x10);
+        cpu.bus.write(0x20, 0x10);
         cpu.do_relative_jump_if(true);
-        assert_eq!(3, cpu.wait_counter);
+        assert_eq!(3, instruction_cycles(&cpu));
     }
 
     #[test]
-    fn do_relative_jump_takes_5_cycles_if_branching_to_different_page() {
+    fn do_relative_jump_takes_4_cycles_if_branching_to_different_page() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0xEF;
-        cpu.memory.borrow_mut().write(0xEF, 0x7F);
+        cpu.bus.write(0xEF, 0x7F);
         cpu.do_relative_jump_if(true);
-        assert_eq!(5, cpu.wait_counter);
+        assert_eq!(4, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -3389,7 +3634,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.a = 0x0F;
         cpu.status_flags = 0x81;
-        cpu.memory.borrow_mut().write(0x1234, 0x12);
+        cpu.bus.write(0x1234, 0x12);
         cpu.do_bit_test(0x0F);
         assert_eq!(0x01, cpu.status_flags);
     }
@@ -4225,7 +4470,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.a = 0xE9;
         cpu.program_counter = 0x15;
-        cpu.memory.borrow_mut().write(0x15, 0x3E);
+        cpu.bus.write(0x15, 0x3E);
         cpu.and_immediate();
         assert_eq!(0x28, cpu.a);
     }
@@ -4243,8 +4488,8 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.a = 0xE9;
         cpu.program_counter = 0xABCD;
-        cpu.memory.borrow_mut().write(0xABCD, 0xFA);
-        cpu.memory.borrow_mut().write(0xFA, 0x3E);
+        cpu.bus.write(0xABCD, 0xFA);
+        cpu.bus.write(0xFA, 0x3E);
 
         cpu.and_zero_page();
         assert_eq!(0x28, cpu.a);
@@ -4256,8 +4501,8 @@ mod tests {
         cpu.a = 0xE9;
         cpu.x = 0x05;
         cpu.program_counter = 0x15;
-        cpu.memory.borrow_mut().write(0x15, 0x40);
-        cpu.memory.borrow_mut().write(0x40 + 0x05, 0x3E);
+        cpu.bus.write(0x15, 0x40);
+        cpu.bus.write(0x40 + 0x05, 0x3E);
         cpu.and_zero_page_x();
         assert_eq!(0x28, cpu.a);
     }
@@ -4268,8 +4513,8 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.a = 0xE9;
         cpu.program_counter = 0x15;
-        cpu.memory.borrow_mut().write(0x15, 0x40);
-        cpu.memory.borrow_mut().write(0x40, 0x3E);
+        cpu.bus.write(0x15, 0x40);
+        cpu.bus.write(0x40, 0x3E);
         cpu.and_absolute();
         assert_eq!(0x28, cpu.a);
     }
@@ -4280,9 +4525,9 @@ mod tests {
         cpu.a = 0xE9;
         cpu.x = 0x04;
         cpu.program_counter = 0x52;
-        cpu.memory.borrow_mut().write(0x52, 0x00);
-        cpu.memory.borrow_mut().write(0x53, 0x80);
-        cpu.memory.borrow_mut().write(0x8004, 0x3E);
+        cpu.bus.write(0x52, 0x00);
+        cpu.bus.write(0x53, 0x80);
+        cpu.bus.write(0x8004, 0x3E);
         cpu.and_absolute_x();
         assert_eq!(0x28, cpu.a);
     }
@@ -4294,9 +4539,9 @@ mod tests {
         cpu.a = 0xE9;
         cpu.y = 0x04;
         cpu.program_counter = 0x52;
-        cpu.memory.borrow_mut().write(0x52, 0x00);
-        cpu.memory.borrow_mut().write(0x53, 0x80);
-        cpu.memory.borrow_mut().write(0x8004, 0x3E);
+        cpu.bus.write(0x52, 0x00);
+        cpu.bus.write(0x53, 0x80);
+        cpu.bus.write(0x8004, 0x3E);
         cpu.and_absolute_y();
         assert_eq!(0x28, cpu.a);
     }
@@ -4308,12 +4553,12 @@ mod tests {
         cpu.x = 0x04;
 
         cpu.program_counter = 0x52;
-        cpu.memory.borrow_mut().write(0x52, 0x14);
+        cpu.bus.write(0x52, 0x14);
 
-        cpu.memory.borrow_mut().write(0x14 + 0x04, 0x00);
-        cpu.memory.borrow_mut().write(0x14 + 0x04 + 1, 0x80);
+        cpu.bus.write(0x14 + 0x04, 0x00);
+        cpu.bus.write(0x14 + 0x04 + 1, 0x80);
 
-        cpu.memory.borrow_mut().write(0x8000, 0x3E);
+        cpu.bus.write(0x8000, 0x3E);
         cpu.and_indirect_x();
         assert_eq!(0x28, cpu.a);
     }
@@ -4326,12 +4571,12 @@ mod tests {
         cpu.y = 0x04;
 
         cpu.program_counter = 0x52;
-        cpu.memory.borrow_mut().write(0x52, 0x14);
+        cpu.bus.write(0x52, 0x14);
 
-        cpu.memory.borrow_mut().write(0x14, 0x00);
-        cpu.memory.borrow_mut().write(0x14 + 1, 0x80);
+        cpu.bus.write(0x14, 0x00);
+        cpu.bus.write(0x14 + 1, 0x80);
 
-        cpu.memory.borrow_mut().write(0x8000 + 0x04, 0x3E);
+        cpu.bus.write(0x8000 + 0x04, 0x3E);
         cpu.and_indirect_y();
         assert_eq!(0x28, cpu.a);
     }
@@ -4341,7 +4586,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.a = 0x81;
         cpu.program_counter = 0x1234;
-        cpu.memory.borrow_mut().write(0x1234, 0x7A);
+        cpu.bus.write(0x1234, 0x7A);
         cpu.inclusive_or_immediate();
         assert_eq!(0xFB, cpu.a);
     }
@@ -4351,8 +4596,8 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.a = 0x81;
         cpu.program_counter = 0x1234;
-        cpu.memory.borrow_mut().write(0x1234, 0x45);
-        cpu.memory.borrow_mut().write(0x0045, 0x7A);
+        cpu.bus.write(0x1234, 0x45);
+        cpu.bus.write(0x0045, 0x7A);
         cpu.inclusive_or_zero_page();
         assert_eq!(0xFB, cpu.a);
     }
@@ -4363,8 +4608,8 @@ mod tests {
         cpu.a = 0x81;
         cpu.x = 0x10;
         cpu.program_counter = 0x1234;
-        cpu.memory.borrow_mut().write(0x1234, 0x45);
-        cpu.memory.borrow_mut().write(0x0045 + 0x10, 0x7A);
+        cpu.bus.write(0x1234, 0x45);
+        cpu.bus.write(0x0045 + 0x10, 0x7A);
         cpu.inclusive_or_zero_page_x();
         assert_eq!(0xFB, cpu.a);
     }
@@ -4374,10 +4619,10 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.a = 0x81;
         cpu.program_counter = 0x1234;
-        cpu.memory.borrow_mut().write(0x1234, 0x45);
-        cpu.memory.borrow_mut().write(0x1235, 0xAF);
+        cpu.bus.write(0x1234, 0x45);
+        cpu.bus.write(0x1235, 0xAF);
 
-        cpu.memory.borrow_mut().write(0xAF45 , 0x7A);
+        cpu.bus.write(0xAF45 , 0x7A);
         cpu.inclusive_or_absolute();
         assert_eq!(0xFB, cpu.a);
     }
@@ -4388,10 +4633,10 @@ mod tests {
         cpu.a = 0x81;
         cpu.x = 0x15;
         cpu.program_counter = 0x1234;
-        cpu.memory.borrow_mut().write(0x1234, 0x45);
-        cpu.memory.borrow_mut().write(0x1235, 0xAF);
+        cpu.bus.write(0x1234, 0x45);
+        cpu.bus.write(0x1235, 0xAF);
 
-        cpu.memory.borrow_mut().write(0xAF45 + 0x15, 0x7A);
+        cpu.bus.write(0xAF45 + 0x15, 0x7A);
         cpu.inclusive_or_absolute_x();
         assert_eq!(0xFB, cpu.a);
     }
@@ -4402,10 +4647,10 @@ mod tests {
         cpu.a = 0x81;
         cpu.y = 0x15;
         cpu.program_counter = 0x1234;
-        cpu.memory.borrow_mut().write(0x1234, 0x45);
-        cpu.memory.borrow_mut().write(0x1235, 0xAF);
+        cpu.bus.write(0x1234, 0x45);
+        cpu.bus.write(0x1235, 0xAF);
 
-        cpu.memory.borrow_mut().write(0xAF45 + 0x15, 0x7A);
+        cpu.bus.write(0xAF45 + 0x15, 0x7A);
         cpu.inclusive_or_absolute_y();
         assert_eq!(0xFB, cpu.a);
     }
@@ -4416,12 +4661,12 @@ mod tests {
         cpu.a = 0x81;
         cpu.x = 0x15;
         cpu.program_counter = 0x1234;
-        cpu.memory.borrow_mut().write(0x1234, 0x20);
+        cpu.bus.write(0x1234, 0x20);
 
-        cpu.memory.borrow_mut().write(0x20 + 0x15, 0x45);
-        cpu.memory.borrow_mut().write(0x20 + 0x15 + 1, 0xAF);
+        cpu.bus.write(0x20 + 0x15, 0x45);
+        cpu.bus.write(0x20 + 0x15 + 1, 0xAF);
 
-        cpu.memory.borrow_mut().write(0xAF45, 0x7A);
+        cpu.bus.write(0xAF45, 0x7A);
         cpu.inclusive_or_indirect_x();
         assert_eq!(0xFB, cpu.a);
     }
@@ -4432,12 +4677,12 @@ mod tests {
         cpu.a = 0x81;
         cpu.y = 0x15;
         cpu.program_counter = 0x1234;
-        cpu.memory.borrow_mut().write(0x1234, 0x20);
+        cpu.bus.write(0x1234, 0x20);
 
-        cpu.memory.borrow_mut().write(0x20, 0x45);
-        cpu.memory.borrow_mut().write(0x20 + 1, 0xAF);
+        cpu.bus.write(0x20, 0x45);
+        cpu.bus.write(0x20 + 1, 0xAF);
 
-        cpu.memory.borrow_mut().write(0xAF45 + 0x15, 0x7A);
+        cpu.bus.write(0xAF45 + 0x15, 0x7A);
         cpu.inclusive_or_indirect_y();
         assert_eq!(0xFB, cpu.a);
     }
@@ -4448,7 +4693,7 @@ mod tests {
         cpu.a = 0x81;
 
         cpu.program_counter = 0xFF;
-        cpu.memory.borrow_mut().write(0xFF, 0xAF);
+        cpu.bus.write(0xFF, 0xAF);
 
         cpu.exclusive_or_immediate();
         assert_eq!(0x2E, cpu.a);
@@ -4460,8 +4705,8 @@ mod tests {
         cpu.a = 0x81;
 
         cpu.program_counter = 0xFF;
-        cpu.memory.borrow_mut().write(0xFF, 0x29);
-        cpu.memory.borrow_mut().write(0x29, 0xAF);
+        cpu.bus.write(0xFF, 0x29);
+        cpu.bus.write(0x29, 0xAF);
 
         cpu.exclusive_or_zero_page();
         assert_eq!(0x2E, cpu.a);
@@ -4474,8 +4719,8 @@ mod tests {
         cpu.x = 0x25;
 
         cpu.program_counter = 0xFF;
-        cpu.memory.borrow_mut().write(0xFF, 0x29);
-        cpu.memory.borrow_mut().write(0x29 + 0x25, 0xAF);
+        cpu.bus.write(0xFF, 0x29);
+        cpu.bus.write(0x29 + 0x25, 0xAF);
 
         cpu.exclusive_or_zero_page_x();
         assert_eq!(0x2E, cpu.a);
@@ -4487,9 +4732,9 @@ mod tests {
         cpu.a = 0x81;
 
         cpu.program_counter = 0xFF;
-        cpu.memory.borrow_mut().write(0xFF, 0x29);
-        cpu.memory.borrow_mut().write(0x100, 0xEF);
-        cpu.memory.borrow_mut().write(0xEF29, 0xAF);
+        cpu.bus.write(0xFF, 0x29);
+        cpu.bus.write(0x100, 0xEF);
+        cpu.bus.write(0xEF29, 0xAF);
 
         cpu.exclusive_or_absolute();
         assert_eq!(0x2E, cpu.a);
@@ -4502,9 +4747,9 @@ mod tests {
         cpu.x = 0xFA;
 
         cpu.program_counter = 0xFF;
-        cpu.memory.borrow_mut().write(0xFF, 0x29);
-        cpu.memory.borrow_mut().write(0x100, 0xEF);
-        cpu.memory.borrow_mut().write(0xEF29 + 0xFA, 0xAF);
+        cpu.bus.write(0xFF, 0x29);
+        cpu.bus.write(0x100, 0xEF);
+        cpu.bus.write(0xEF29 + 0xFA, 0xAF);
 
         cpu.exclusive_or_absolute_x();
         assert_eq!(0x2E, cpu.a);
@@ -4517,9 +4762,9 @@ mod tests {
         cpu.y = 0xFA;
 
         cpu.program_counter = 0xFF;
-        cpu.memory.borrow_mut().write(0xFF, 0x29);
-        cpu.memory.borrow_mut().write(0x100, 0xEF);
-        cpu.memory.borrow_mut().write(0xEF29 + 0xFA, 0xAF);
+        cpu.bus.write(0xFF, 0x29);
+        cpu.bus.write(0x100, 0xEF);
+        cpu.bus.write(0xEF29 + 0xFA, 0xAF);
 
         cpu.exclusive_or_absolute_y();
         assert_eq!(0x2E, cpu.a);
@@ -4533,12 +4778,12 @@ mod tests {
         cpu.x = 0x04;
 
         cpu.program_counter = 0xFF;
-        cpu.memory.borrow_mut().write(0xFF, 0x29);
+        cpu.bus.write(0xFF, 0x29);
 
-        cpu.memory.borrow_mut().write(0x29 + 0x04, 0x29);
-        cpu.memory.borrow_mut().write(0x29 + 0x04 + 1, 0xEF);
+        cpu.bus.write(0x29 + 0x04, 0x29);
+        cpu.bus.write(0x29 + 0x04 + 1, 0xEF);
 
-        cpu.memory.borrow_mut().write(0xEF29 , 0xAF);
+        cpu.bus.write(0xEF29 , 0xAF);
 
         cpu.exclusive_or_indirect_x();
         assert_eq!(0x2E, cpu.a);
@@ -4551,12 +4796,12 @@ mod tests {
         cpu.y = 0x04;
 
         cpu.program_counter = 0xFF;
-        cpu.memory.borrow_mut().write(0xFF, 0x29);
+        cpu.bus.write(0xFF, 0x29);
 
-        cpu.memory.borrow_mut().write(0x29, 0x29);
-        cpu.memory.borrow_mut().write(0x29 + 1, 0xEF);
+        cpu.bus.write(0x29, 0x29);
+        cpu.bus.write(0x29 + 1, 0xEF);
 
-        cpu.memory.borrow_mut().write(0xEF29 + 0x04, 0xAF);
+        cpu.bus.write(0xEF29 + 0x04, 0xAF);
 
         cpu.exclusive_or_indirect_y();
         assert_eq!(0x2E, cpu.a);
@@ -4567,7 +4812,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0x80;
         cpu.program_counter = 0x20;
-        cpu.memory.borrow_mut().write(0x20, 0x10);
+        cpu.bus.write(0x20, 0x10);
         cpu.branch_if_carry_clear();
         // 0x21 as the instruction reads the offset, thus modifying the pc
         assert_eq!(0x21 + 0x10, cpu.program_counter);
@@ -4578,7 +4823,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0x43;
         cpu.program_counter = 0x20;
-        cpu.memory.borrow_mut().write(0x20, 0x10);
+        cpu.bus.write(0x20, 0x10);
         cpu.branch_if_carry_clear();
         assert_eq!(0x21, cpu.program_counter);
     }
@@ -4588,7 +4833,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0x01;
         cpu.program_counter = 0x20;
-        cpu.memory.borrow_mut().write(0x20, 0x10);
+        cpu.bus.write(0x20, 0x10);
         cpu.branch_if_carry_set();
         // 0x21 as the instruction reads the offset, thus modifying the pc
         assert_eq!(0x21 + 0x10, cpu.program_counter);
@@ -4599,7 +4844,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0x00;
         cpu.program_counter = 0x20;
-        cpu.memory.borrow_mut().write(0x20, 0x10);
+        cpu.bus.write(0x20, 0x10);
         cpu.branch_if_carry_set();
         assert_eq!(0x21, cpu.program_counter);
     }
@@ -4609,7 +4854,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0xD3;
         cpu.program_counter = 0x20;
-        cpu.memory.borrow_mut().write(0x20, 0x10);
+        cpu.bus.write(0x20, 0x10);
         cpu.branch_if_equal();
         assert_eq!(0x21 + 0x10, cpu.program_counter);
     }
@@ -4619,7 +4864,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0x00;
         cpu.program_counter = 0x20;
-        cpu.memory.borrow_mut().write(0x20, 0x10);
+        cpu.bus.write(0x20, 0x10);
         cpu.branch_if_equal();
         assert_eq!(0x21, cpu.program_counter);
     }
@@ -4629,7 +4874,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0xD4;
         cpu.program_counter = 0x20;
-        cpu.memory.borrow_mut().write(0x20, 0x10);
+        cpu.bus.write(0x20, 0x10);
         cpu.branch_if_not_equal();
         assert_eq!(0x21 + 0x10, cpu.program_counter);
     }
@@ -4639,7 +4884,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0x80;
         cpu.program_counter = 0x20;
-        cpu.memory.borrow_mut().write(0x20, 0x10);
+        cpu.bus.write(0x20, 0x10);
         cpu.branch_if_negative();
         assert_eq!(0x21 + 0x10, cpu.program_counter);
     }
@@ -4649,7 +4894,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0x7F;
         cpu.program_counter = 0x20;
-        cpu.memory.borrow_mut().write(0x20, 0x10);
+        cpu.bus.write(0x20, 0x10);
         cpu.branch_if_negative();
         assert_eq!(0x21, cpu.program_counter);
     }
@@ -4658,7 +4903,7 @@ mod tests {
     fn branch_if_positive_jumps_to_relative_address_on_nonzero_positive_number() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 24;
-        cpu.memory.borrow_mut().write(24, 0x6C);
+        cpu.bus.write(24, 0x6C);
         cpu.set_negative_flag(0x32);
         cpu.branch_if_positive();
         assert_eq!(25 + 0x6C, cpu.program_counter);
@@ -4668,7 +4913,7 @@ mod tests {
     fn branch_if_positive_jumps_to_address_on_zero() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 24;
-        cpu.memory.borrow_mut().write(24, 0x02);
+        cpu.bus.write(24, 0x02);
         cpu.set_negative_flag(0x00);
         cpu.branch_if_positive();
 
@@ -4679,7 +4924,7 @@ mod tests {
     fn branch_if_positive_does_not_jump_on_negative_number() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 24;
-        cpu.memory.borrow_mut().write(24, 0xBC);
+        cpu.bus.write(24, 0xBC);
         cpu.set_negative_flag(0xff);
         cpu.branch_if_positive();
         assert_eq!(25, cpu.program_counter);
@@ -4690,7 +4935,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0xBF;
         cpu.program_counter = 0x20;
-        cpu.memory.borrow_mut().write(0x20, 0x10);
+        cpu.bus.write(0x20, 0x10);
         cpu.branch_if_overflow_clear();
         assert_eq!(0x21 + 0x10, cpu.program_counter);
     }
@@ -4700,7 +4945,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0x40;
         cpu.program_counter = 0x20;
-        cpu.memory.borrow_mut().write(0x20, 0x10);
+        cpu.bus.write(0x20, 0x10);
         cpu.branch_if_overflow_clear();
         assert_eq!(0x21, cpu.program_counter);
     }
@@ -4710,7 +4955,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0xD0;
         cpu.program_counter = 0x20;
-        cpu.memory.borrow_mut().write(0x20, 0x10);
+        cpu.bus.write(0x20, 0x10);
         cpu.branch_if_overflow_set();
         assert_eq!(0x21 + 0x10, cpu.program_counter);
     }
@@ -4720,7 +4965,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0x00;
         cpu.program_counter = 0x20;
-        cpu.memory.borrow_mut().write(0x20, 0x10);
+        cpu.bus.write(0x20, 0x10);
         cpu.branch_if_overflow_set();
         assert_eq!(0x21, cpu.program_counter);
     }
@@ -4729,29 +4974,29 @@ mod tests {
     fn jump_absolute_sets_program_counter_to_new_value() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0;
-        cpu.memory.borrow_mut().write(0, 0x15);
-        cpu.memory.borrow_mut().write(1, 0xF0);
+        cpu.bus.write(0, 0x15);
+        cpu.bus.write(1, 0xF0);
         cpu.jump_absolute();
         assert_eq!(0xF015, cpu.program_counter);
     }
 
     #[test]
-    fn jump_absolute_sets_wait_counter_correctly() {
+    fn jump_absolute_takes_correct_number_of_cycles() {
         let mut cpu = create_test_cpu();
 
         cpu.jump_absolute();
-        assert_eq!(3, cpu.wait_counter);
+        assert_eq!(3, instruction_cycles(&cpu));
     }
 
     #[test]
     fn jump_indirect_sets_program_counter_to_new_value() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 5;
-        cpu.memory.borrow_mut().write(5, 0x15);
-        cpu.memory.borrow_mut().write(6, 0xF0);
+        cpu.bus.write(5, 0x15);
+        cpu.bus.write(6, 0xF0);
 
-        cpu.memory.borrow_mut().write(0xF015, 0xBA);
-        cpu.memory.borrow_mut().write(0xF016, 0x0D);
+        cpu.bus.write(0xF015, 0xBA);
+        cpu.bus.write(0xF016, 0x0D);
 
         cpu.jump_indirect();
         assert_eq!(0x0DBA, cpu.program_counter);
@@ -4761,23 +5006,23 @@ mod tests {
     fn jump_indirect_handles_6502_indirect_bug() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 5;
-        cpu.memory.borrow_mut().write(5, 0xFF);
-        cpu.memory.borrow_mut().write(6, 0xF0);
+        cpu.bus.write(5, 0xFF);
+        cpu.bus.write(6, 0xF0);
 
-        cpu.memory.borrow_mut().write(0xF0FF, 0xBA);
-        cpu.memory.borrow_mut().write(0xF100, 0x0D);
-        cpu.memory.borrow_mut().write(0xF000, 0xDB);
+        cpu.bus.write(0xF0FF, 0xBA);
+        cpu.bus.write(0xF100, 0x0D);
+        cpu.bus.write(0xF000, 0xDB);
 
         cpu.jump_indirect();
         assert_eq!(0xDBBA, cpu.program_counter);
     }
 
     #[test]
-    fn jump_indirect_sets_wait_counter_correctly() {
+    fn jump_indirect_takes_correct_number_of_cycles() {
         let mut cpu = create_test_cpu();
 
         cpu.jump_indirect();
-        assert_eq!(5, cpu.wait_counter);
+        assert_eq!(5, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -4785,8 +5030,8 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0xABCD;
         cpu.stack_pointer = 0xFF;
-        cpu.memory.borrow_mut().write(0xABCD, 0x09);
-        cpu.memory.borrow_mut().write(0xABCD + 1, 0xFC);
+        cpu.bus.write(0xABCD, 0x09);
+        cpu.bus.write(0xABCD + 1, 0xFC);
         cpu.jump_to_subroutine();
         // return address - 1 is pushed into stack in little endian form.
         // in this case, it's 0xABCE as the instruction takes two values from the instruction stream
@@ -4799,8 +5044,8 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0xABCD;
         cpu.stack_pointer = 0xFF;
-        cpu.memory.borrow_mut().write(0xABCD, 0x09);
-        cpu.memory.borrow_mut().write(0xABCD + 1, 0xFC);
+        cpu.bus.write(0xABCD, 0x09);
+        cpu.bus.write(0xABCD + 1, 0xFC);
         cpu.jump_to_subroutine();
         assert_eq!(0xFC09, cpu.program_counter);
     }
@@ -4821,7 +5066,7 @@ mod tests {
         cpu.program_counter = 15;
         cpu.stack_pointer = 0xFF;
         cpu.jump_to_subroutine();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -4831,8 +5076,8 @@ mod tests {
         cpu.stack_pointer = 0x40;
         cpu.program_counter = 0x40;
 
-        cpu.memory.borrow_mut().write(0xFFFE, 0x20);
-        cpu.memory.borrow_mut().write(0xFFFF, 0xA3);
+        cpu.bus.write(0xFFFE, 0x20);
+        cpu.bus.write(0xFFFF, 0xA3);
 
         cpu.force_interrupt();
         assert_eq!(0xA320, cpu.program_counter);
@@ -4865,7 +5110,8 @@ mod tests {
 
         cpu.pop_value_from_stack();
 
-        assert_eq!(0xEF, cpu.pop_value_from_stack());
+        // the byte following the brk opcode is skipped
+        assert_eq!(0xF0, cpu.pop_value_from_stack());
         assert_eq!(0xA0, cpu.pop_value_from_stack());
     }
 
@@ -4883,7 +5129,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.stack_pointer = 0x80;
         cpu.force_interrupt();
-        assert_eq!(7, cpu.wait_counter);
+        assert_eq!(7, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -4918,7 +5164,7 @@ mod tests {
     fn return_from_subroutine_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.return_from_subroutine();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -4961,7 +5207,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.stack_pointer = 0x10;
         cpu.return_from_interrupt();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     // to a large degree, these bit_test test the same things that some more general tests
@@ -4975,8 +5221,8 @@ mod tests {
         cpu.status_flags = 0x00;
         cpu.a = 0xCA;
         cpu.program_counter = 0x1234;
-        cpu.memory.borrow_mut().write(0x1234, 0x07);
-        cpu.memory.borrow_mut().write(0x07, 0xF0);
+        cpu.bus.write(0x1234, 0x07);
+        cpu.bus.write(0x07, 0xF0);
         cpu.bit_test_zero_page();
         assert_eq!(0xC0, cpu.status_flags);
     }
@@ -4993,7 +5239,7 @@ mod tests {
     fn bit_test_zero_page_takes_3_cycles() {
         let mut cpu = create_test_cpu();
         cpu.bit_test_zero_page();
-        assert_eq!(3, cpu.wait_counter);
+        assert_eq!(3, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5002,10 +5248,10 @@ mod tests {
         cpu.status_flags = 0x00;
         cpu.a = 0xCA;
         cpu.program_counter = 0x1234;
-        cpu.memory.borrow_mut().write(0x1234, 0xFE);
-        cpu.memory.borrow_mut().write(0x1235, 0xCA);
+        cpu.bus.write(0x1234, 0xFE);
+        cpu.bus.write(0x1235, 0xCA);
 
-        cpu.memory.borrow_mut().write(0xCAFE, 0xF0);
+        cpu.bus.write(0xCAFE, 0xF0);
         cpu.bit_test_absolute();
         assert_eq!(0xC0, cpu.status_flags);
     }
@@ -5022,7 +5268,7 @@ mod tests {
     fn bit_test_absolute_takes_4_cycles() {
         let mut cpu = create_test_cpu();
         cpu.bit_test_absolute();
-        assert_eq!(4, cpu.wait_counter);
+        assert_eq!(4, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5046,7 +5292,7 @@ mod tests {
     fn rotate_right_accumulator_takes_2_cycles() {
         let mut cpu = create_test_cpu();
         cpu.rotate_right_accumulator();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5054,10 +5300,10 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x0F40;
         cpu.status_flags = 0x01;
-        cpu.memory.borrow_mut().write(0x0F40, 0x70);
-        cpu.memory.borrow_mut().write(0x70, 0xE6);
+        cpu.bus.write(0x0F40, 0x70);
+        cpu.bus.write(0x70, 0xE6);
         cpu.rotate_right_zero_page();
-        assert_eq!(0xF3, cpu.memory.borrow_mut().read(0x70));
+        assert_eq!(0xF3, cpu.bus.read(0x70));
     }
 
     #[test]
@@ -5072,7 +5318,7 @@ mod tests {
     fn rotate_right_zero_page_takes_5_cycles() {
         let mut cpu = create_test_cpu();
         cpu.rotate_right_zero_page();
-        assert_eq!(5, cpu.wait_counter);
+        assert_eq!(5, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5081,10 +5327,10 @@ mod tests {
         cpu.x = 0x20;
         cpu.status_flags = 0x01;
         cpu.program_counter = 0x0F40;
-        cpu.memory.borrow_mut().write(0x0F40, 0x70);
-        cpu.memory.borrow_mut().write(0x70 + 0x20, 0xE6);
+        cpu.bus.write(0x0F40, 0x70);
+        cpu.bus.write(0x70 + 0x20, 0xE6);
         cpu.rotate_right_zero_page_x();
-        assert_eq!(0xF3, cpu.memory.borrow_mut().read(0x70 + 0x20));
+        assert_eq!(0xF3, cpu.bus.read(0x70 + 0x20));
     }
 
     #[test]
@@ -5099,7 +5345,7 @@ mod tests {
     fn rotate_right_zero_page_x_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.rotate_right_zero_page_x();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5107,11 +5353,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x0F40;
         cpu.status_flags = 0x01;
-        cpu.memory.borrow_mut().write(0x0F40, 0x70);
-        cpu.memory.borrow_mut().write(0x0F41, 0xB1);
-        cpu.memory.borrow_mut().write(0xB170, 0xE6);
+        cpu.bus.write(0x0F40, 0x70);
+        cpu.bus.write(0x0F41, 0xB1);
+        cpu.bus.write(0xB170, 0xE6);
         cpu.rotate_right_absolute();
-        assert_eq!(0xF3, cpu.memory.borrow_mut().read(0xB170));
+        assert_eq!(0xF3, cpu.bus.read(0xB170));
     }
 
     #[test]
@@ -5126,7 +5372,7 @@ mod tests {
     fn rotate_right_absolute_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.rotate_right_absolute();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
 
@@ -5136,11 +5382,11 @@ mod tests {
         cpu.x = 0x20;
         cpu.status_flags = 0x01;
         cpu.program_counter = 0x0F40;
-        cpu.memory.borrow_mut().write(0x0F40, 0x70);
-        cpu.memory.borrow_mut().write(0x0F41, 0xB1);
-        cpu.memory.borrow_mut().write(0xB170 + 0x20, 0xE6);
+        cpu.bus.write(0x0F40, 0x70);
+        cpu.bus.write(0x0F41, 0xB1);
+        cpu.bus.write(0xB170 + 0x20, 0xE6);
         cpu.rotate_right_absolute_x();
-        assert_eq!(0xF3, cpu.memory.borrow_mut().read(0xB170 + 0x20));
+        assert_eq!(0xF3, cpu.bus.read(0xB170 + 0x20));
     }
 
     #[test]
@@ -5155,7 +5401,7 @@ mod tests {
     fn rotate_right_absolute_x_takes_7_cycles() {
         let mut cpu = create_test_cpu();
         cpu.rotate_right_absolute_x();
-        assert_eq!(7, cpu.wait_counter);
+        assert_eq!(7, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5178,17 +5424,17 @@ mod tests {
     fn logical_right_shift_accumulator_takes_2_cycles() {
         let mut cpu = create_test_cpu();
         cpu.logical_shift_right_accumulator();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
     fn logical_right_shift_zero_page_modifies_memory() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x0F40;
-        cpu.memory.borrow_mut().write(0x0F40, 0x70);
-        cpu.memory.borrow_mut().write(0x70, 0xE6);
+        cpu.bus.write(0x0F40, 0x70);
+        cpu.bus.write(0x70, 0xE6);
         cpu.logical_shift_right_zero_page();
-        assert_eq!(0x73, cpu.memory.borrow_mut().read(0x70));
+        assert_eq!(0x73, cpu.bus.read(0x70));
     }
 
     #[test]
@@ -5203,7 +5449,7 @@ mod tests {
     fn logical_right_shift_zero_page_takes_5_cycles() {
         let mut cpu = create_test_cpu();
         cpu.logical_shift_right_zero_page();
-        assert_eq!(5, cpu.wait_counter);
+        assert_eq!(5, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5211,10 +5457,10 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x20;
         cpu.program_counter = 0x0F40;
-        cpu.memory.borrow_mut().write(0x0F40, 0x70);
-        cpu.memory.borrow_mut().write(0x70 + 0x20, 0xE6);
+        cpu.bus.write(0x0F40, 0x70);
+        cpu.bus.write(0x70 + 0x20, 0xE6);
         cpu.logical_shift_right_zero_page_x();
-        assert_eq!(0x73, cpu.memory.borrow_mut().read(0x70 + 0x20));
+        assert_eq!(0x73, cpu.bus.read(0x70 + 0x20));
     }
 
     #[test]
@@ -5229,18 +5475,18 @@ mod tests {
     fn logical_right_shift_zero_page_x_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.logical_shift_right_zero_page_x();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
     fn logical_right_shift_absolute_modifies_memory() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x0F40;
-        cpu.memory.borrow_mut().write(0x0F40, 0x70);
-        cpu.memory.borrow_mut().write(0x0F41, 0xB1);
-        cpu.memory.borrow_mut().write(0xB170, 0xE6);
+        cpu.bus.write(0x0F40, 0x70);
+        cpu.bus.write(0x0F41, 0xB1);
+        cpu.bus.write(0xB170, 0xE6);
         cpu.logical_shift_right_absolute();
-        assert_eq!(0x73, cpu.memory.borrow_mut().read(0xB170));
+        assert_eq!(0x73, cpu.bus.read(0xB170));
     }
 
     #[test]
@@ -5255,7 +5501,7 @@ mod tests {
     fn logical_right_absolute_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.logical_shift_right_absolute();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5263,11 +5509,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x20;
         cpu.program_counter = 0x0F40;
-        cpu.memory.borrow_mut().write(0x0F40, 0x70);
-        cpu.memory.borrow_mut().write(0x0F41, 0xB1);
-        cpu.memory.borrow_mut().write(0xB170 + 0x20, 0xE6);
+        cpu.bus.write(0x0F40, 0x70);
+        cpu.bus.write(0x0F41, 0xB1);
+        cpu.bus.write(0xB170 + 0x20, 0xE6);
         cpu.logical_shift_right_absolute_x();
-        assert_eq!(0x73, cpu.memory.borrow_mut().read(0xB170 + 0x20));
+        assert_eq!(0x73, cpu.bus.read(0xB170 + 0x20));
     }
 
     #[test]
@@ -5282,7 +5528,7 @@ mod tests {
     fn logical_right_absolute_x_takes_7_cycles() {
         let mut cpu = create_test_cpu();
         cpu.logical_shift_right_absolute_x();
-        assert_eq!(7, cpu.wait_counter);
+        assert_eq!(7, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5306,7 +5552,7 @@ mod tests {
     fn rotate_left_accumulator_takes_2_cycles() {
         let mut cpu = create_test_cpu();
         cpu.rotate_left_accumulator();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5314,11 +5560,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0x01;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0xFE);
-        cpu.memory.borrow_mut().write(0xFE, 0x35);
+        cpu.bus.write(0x234, 0xFE);
+        cpu.bus.write(0xFE, 0x35);
 
         cpu.rotate_left_zero_page();
-        assert_eq!(0x6B, cpu.memory.borrow_mut().read(0xFE));
+        assert_eq!(0x6B, cpu.bus.read(0xFE));
     }
 
     #[test]
@@ -5333,7 +5579,7 @@ mod tests {
     fn rotate_left_zero_page_takes_5_cycles() {
         let mut cpu = create_test_cpu();
         cpu.rotate_left_zero_page();
-        assert_eq!(5, cpu.wait_counter);
+        assert_eq!(5, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5342,11 +5588,11 @@ mod tests {
         cpu.x = 0x13;
         cpu.status_flags = 0x01;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0xAE);
-        cpu.memory.borrow_mut().write(0xAE + 0x13, 0x35);
+        cpu.bus.write(0x234, 0xAE);
+        cpu.bus.write(0xAE + 0x13, 0x35);
 
         cpu.rotate_left_zero_page_x();
-        assert_eq!(0x6B, cpu.memory.borrow_mut().read(0xAE + 0x13));
+        assert_eq!(0x6B, cpu.bus.read(0xAE + 0x13));
     }
 
     #[test]
@@ -5361,7 +5607,7 @@ mod tests {
     fn rotate_left_zero_page_x_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.rotate_left_zero_page_x();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5369,12 +5615,12 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0x01;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0xAE);
-        cpu.memory.borrow_mut().write(0x235, 0xF1);
-        cpu.memory.borrow_mut().write(0xF1AE, 0x35);
+        cpu.bus.write(0x234, 0xAE);
+        cpu.bus.write(0x235, 0xF1);
+        cpu.bus.write(0xF1AE, 0x35);
 
         cpu.rotate_left_absolute();
-        assert_eq!(0x6B, cpu.memory.borrow_mut().read(0xF1AE));
+        assert_eq!(0x6B, cpu.bus.read(0xF1AE));
     }
 
     #[test]
@@ -5389,7 +5635,7 @@ mod tests {
     fn rotate_left_absolute_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.rotate_left_absolute();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5398,12 +5644,12 @@ mod tests {
         cpu.status_flags = 0x01;
         cpu.x = 0x20;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0xAE);
-        cpu.memory.borrow_mut().write(0x235, 0xF1);
-        cpu.memory.borrow_mut().write(0xF1AE + 0x20, 0x35);
+        cpu.bus.write(0x234, 0xAE);
+        cpu.bus.write(0x235, 0xF1);
+        cpu.bus.write(0xF1AE + 0x20, 0x35);
 
         cpu.rotate_left_absolute_x();
-        assert_eq!(0x6B, cpu.memory.borrow_mut().read(0xF1AE + 0x20));
+        assert_eq!(0x6B, cpu.bus.read(0xF1AE + 0x20));
     }
 
     #[test]
@@ -5418,7 +5664,7 @@ mod tests {
     fn rotate_left_absolute_x_takes_7_cycles() {
         let mut cpu = create_test_cpu();
         cpu.rotate_left_absolute_x();
-        assert_eq!(7, cpu.wait_counter);
+        assert_eq!(7, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5442,7 +5688,7 @@ mod tests {
     fn arithmetic_shift_left_accumulator_takes_2_cycles() {
         let mut cpu = create_test_cpu();
         cpu.arithmetic_shift_left_accumulator();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5450,11 +5696,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0x01;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0xFE);
-        cpu.memory.borrow_mut().write(0xFE, 0x35);
+        cpu.bus.write(0x234, 0xFE);
+        cpu.bus.write(0xFE, 0x35);
 
         cpu.arithmetic_shift_left_zero_page();
-        assert_eq!(0x6A, cpu.memory.borrow_mut().read(0xFE));
+        assert_eq!(0x6A, cpu.bus.read(0xFE));
     }
 
     #[test]
@@ -5469,7 +5715,7 @@ mod tests {
     fn arithmetic_shift_left_zero_page_takes_5_cycles() {
         let mut cpu = create_test_cpu();
         cpu.arithmetic_shift_left_zero_page();
-        assert_eq!(5, cpu.wait_counter);
+        assert_eq!(5, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5478,11 +5724,11 @@ mod tests {
         cpu.status_flags = 0x01;
         cpu.x = 0x24;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0xAE);
-        cpu.memory.borrow_mut().write(0xAE + 0x24, 0x35);
+        cpu.bus.write(0x234, 0xAE);
+        cpu.bus.write(0xAE + 0x24, 0x35);
 
         cpu.arithmetic_shift_left_zero_page_x();
-        assert_eq!(0x6A, cpu.memory.borrow_mut().read(0xAE + 0x24));
+        assert_eq!(0x6A, cpu.bus.read(0xAE + 0x24));
     }
 
     #[test]
@@ -5494,10 +5740,10 @@ mod tests {
     }
 
     #[test]
-    fn arithmetic_shift_left_zero_x_page_takes_5_cycles() {
+    fn arithmetic_shift_left_zero_x_page_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.arithmetic_shift_left_zero_page_x();
-        assert_eq!(5, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5505,12 +5751,12 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0x01;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0xFE);
-        cpu.memory.borrow_mut().write(0x235, 0xCA);
-        cpu.memory.borrow_mut().write(0xCAFE, 0x35);
+        cpu.bus.write(0x234, 0xFE);
+        cpu.bus.write(0x235, 0xCA);
+        cpu.bus.write(0xCAFE, 0x35);
 
         cpu.arithmetic_shift_left_absolute();
-        assert_eq!(0x6A, cpu.memory.borrow_mut().read(0xCAFE));
+        assert_eq!(0x6A, cpu.bus.read(0xCAFE));
     }
 
     #[test]
@@ -5525,7 +5771,7 @@ mod tests {
     fn arithmetic_shift_absolute_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.arithmetic_shift_left_absolute();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5534,12 +5780,12 @@ mod tests {
         cpu.status_flags = 0x01;
         cpu.x = 0x65;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0xFE);
-        cpu.memory.borrow_mut().write(0x235, 0xCA);
-        cpu.memory.borrow_mut().write(0xCAFE + 0x65, 0x35);
+        cpu.bus.write(0x234, 0xFE);
+        cpu.bus.write(0x235, 0xCA);
+        cpu.bus.write(0xCAFE + 0x65, 0x35);
 
         cpu.arithmetic_shift_left_absolute_x();
-        assert_eq!(0x6A, cpu.memory.borrow_mut().read(0xCAFE + 0x65));
+        assert_eq!(0x6A, cpu.bus.read(0xCAFE + 0x65));
     }
 
     #[test]
@@ -5554,7 +5800,7 @@ mod tests {
     fn arithmetic_shift_absolute_x_takes_7_cycles() {
         let mut cpu = create_test_cpu();
         cpu.arithmetic_shift_left_absolute_x();
-        assert_eq!(7, cpu.wait_counter);
+        assert_eq!(7, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5577,7 +5823,7 @@ mod tests {
     fn clear_carry_flag_takes_2_cycles() {
         let mut cpu = create_test_cpu();
         cpu.clear_carry_flag();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5613,7 +5859,7 @@ mod tests {
         cpu.program_counter = 15;
         cpu.stack_pointer = 0xFF;
         cpu.set_carry_flag();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5633,10 +5879,10 @@ mod tests {
     }
 
     #[test]
-    fn clear_decimal_flags_sets_wait_counter_correctly() {
+    fn clear_decimal_flags_takes_correct_number_of_cycles() {
         let mut cpu = create_test_cpu();
         cpu.clear_decimal_flag();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5668,7 +5914,7 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0xAB12;
         cpu.set_decimal_flag();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5688,10 +5934,10 @@ mod tests {
     }
 
     #[test]
-    fn setting_interrupt_disable_flag_sets_wait_counter_correctly() {
+    fn setting_interrupt_disable_flag_takes_correct_number_of_cycles() {
         let mut cpu = create_test_cpu();
         cpu.set_interrupt_disable_flag();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5711,10 +5957,10 @@ mod tests {
     }
 
     #[test]
-    fn clear_interrupt_disable_flag_sets_wait_counter_correctly() {
+    fn clear_interrupt_disable_flag_takes_correct_number_of_cycles() {
         let mut cpu = create_test_cpu();
         cpu.clear_interrupt_disable_flag();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5737,7 +5983,7 @@ mod tests {
     fn clear_overflow_flag_takes_2_cycles() {
         let mut cpu = create_test_cpu();
         cpu.clear_overflow_flag();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5776,7 +6022,7 @@ mod tests {
     fn push_accumulator_takes_3_cycles() {
         let mut cpu = create_test_cpu();
         cpu.push_accumulator();
-        assert_eq!(3, cpu.wait_counter);
+        assert_eq!(3, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5848,7 +6094,7 @@ mod tests {
     fn pull_accumulator_takes_4_cycles() {
         let mut cpu = create_test_cpu();
         cpu.pull_accumulator();
-        assert_eq!(4, cpu.wait_counter);
+        assert_eq!(4, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5871,7 +6117,7 @@ mod tests {
     fn push_status_flags_into_stack_takes_3_cycles() {
         let mut cpu = create_test_cpu();
         cpu.push_status_flags_into_stack();
-        assert_eq!(3, cpu.wait_counter);
+        assert_eq!(3, instruction_cycles(&cpu));
 
     }
 
@@ -5914,7 +6160,7 @@ mod tests {
     fn pull_status_flags_from_stack_takes_4_cycles() {
         let mut cpu = create_test_cpu();
         cpu.pull_status_flags_from_stack();
-        assert_eq!(4, cpu.wait_counter);
+        assert_eq!(4, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -5922,7 +6168,7 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 25;
-        cpu.memory.borrow_mut().write(25, 0x23);
+        cpu.bus.write(25, 0x23);
         cpu.load_a_immediate();
         assert_eq!(0x23, cpu.a);
     }
@@ -5932,8 +6178,8 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 25;
-        cpu.memory.borrow_mut().write(25, 0x23);
-        cpu.memory.borrow_mut().write(0x23, 0xFA);
+        cpu.bus.write(25, 0x23);
+        cpu.bus.write(0x23, 0xFA);
         cpu.load_a_zero_page();
         assert_eq!(0xFA, cpu.a);
     }
@@ -5944,8 +6190,8 @@ mod tests {
 
         cpu.x = 0x12;
         cpu.program_counter = 25;
-        cpu.memory.borrow_mut().write(25, 0x23);
-        cpu.memory.borrow_mut().write(0x23 + 0x12, 0xFA);
+        cpu.bus.write(25, 0x23);
+        cpu.bus.write(0x23 + 0x12, 0xFA);
         cpu.load_a_zero_page_x();
         assert_eq!(0xFA, cpu.a);
     }
@@ -5954,9 +6200,9 @@ mod tests {
     fn load_a_absolute_loads_correct_value_from_memory() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 25;
-        cpu.memory.borrow_mut().write(25, 0xB1);
-        cpu.memory.borrow_mut().write(26, 0xF0);
-        cpu.memory.borrow_mut().write(0xF0B1, 42);
+        cpu.bus.write(25, 0xB1);
+        cpu.bus.write(26, 0xF0);
+        cpu.bus.write(0xF0B1, 42);
 
         cpu.load_a_absolute();
         assert_eq!(42, cpu.a);
@@ -5967,9 +6213,9 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x14;
         cpu.program_counter = 25;
-        cpu.memory.borrow_mut().write(25, 0xB1);
-        cpu.memory.borrow_mut().write(26, 0xF0);
-        cpu.memory.borrow_mut().write(0xF0B1 + 0x14, 42);
+        cpu.bus.write(25, 0xB1);
+        cpu.bus.write(26, 0xF0);
+        cpu.bus.write(0xF0B1 + 0x14, 42);
 
         cpu.load_a_absolute_x();
         assert_eq!(42, cpu.a);
@@ -5980,9 +6226,9 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.y = 0x14;
         cpu.program_counter = 25;
-        cpu.memory.borrow_mut().write(25, 0xB1);
-        cpu.memory.borrow_mut().write(26, 0xF0);
-        cpu.memory.borrow_mut().write(0xF0B1 + 0x14, 42);
+        cpu.bus.write(25, 0xB1);
+        cpu.bus.write(26, 0xF0);
+        cpu.bus.write(0xF0B1 + 0x14, 42);
 
         cpu.load_a_absolute_y();
         assert_eq!(42, cpu.a);
@@ -5994,12 +6240,12 @@ mod tests {
         cpu.x = 0x14;
 
         cpu.program_counter = 25;
-        cpu.memory.borrow_mut().write(25, 0xB1);
+        cpu.bus.write(25, 0xB1);
 
-        cpu.memory.borrow_mut().write(0xB1 + 0x14, 0xEF);
-        cpu.memory.borrow_mut().write(0xB1 + 0x14 + 1, 0x02);
+        cpu.bus.write(0xB1 + 0x14, 0xEF);
+        cpu.bus.write(0xB1 + 0x14 + 1, 0x02);
 
-        cpu.memory.borrow_mut().write(0x02EF, 0xAF);
+        cpu.bus.write(0x02EF, 0xAF);
 
         cpu.load_a_indirect_x();
         assert_eq!(0xAF, cpu.a);
@@ -6011,12 +6257,12 @@ mod tests {
         cpu.y = 0x14;
 
         cpu.program_counter = 25;
-        cpu.memory.borrow_mut().write(25, 0xB1);
+        cpu.bus.write(25, 0xB1);
 
-        cpu.memory.borrow_mut().write(0xB1, 0xEF);
-        cpu.memory.borrow_mut().write(0xB1 + 1, 0x02);
+        cpu.bus.write(0xB1, 0xEF);
+        cpu.bus.write(0xB1 + 1, 0x02);
 
-        cpu.memory.borrow_mut().write(0x02EF + 0x14, 0xAF);
+        cpu.bus.write(0x02EF + 0x14, 0xAF);
 
         cpu.load_a_indirect_y();
         assert_eq!(0xAF, cpu.a);
@@ -6027,9 +6273,9 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.a = 0x2F;
         cpu.program_counter = 0x32;
-        cpu.memory.borrow_mut().write(0x32, 0x14);
+        cpu.bus.write(0x32, 0x14);
         cpu.store_a_zero_page();
-        assert_eq!(0x2F, cpu.memory.borrow_mut().read(0x14));
+        assert_eq!(0x2F, cpu.bus.read(0x14));
     }
 
     #[test]
@@ -6038,9 +6284,9 @@ mod tests {
         cpu.a = 0x2F;
         cpu.x = 0xBF;
         cpu.program_counter = 0x32;
-        cpu.memory.borrow_mut().write(0x32, 0x14);
+        cpu.bus.write(0x32, 0x14);
         cpu.store_a_zero_page_x();
-        assert_eq!(0x2F, cpu.memory.borrow_mut().read(0x14 + 0xBF));
+        assert_eq!(0x2F, cpu.bus.read(0x14 + 0xBF));
     }
 
     #[test]
@@ -6049,11 +6295,11 @@ mod tests {
         cpu.a = 0x2F;
 
         cpu.program_counter = 0x32;
-        cpu.memory.borrow_mut().write(0x32, 0xAF);
-        cpu.memory.borrow_mut().write(0x33, 0x07);
+        cpu.bus.write(0x32, 0xAF);
+        cpu.bus.write(0x33, 0x07);
 
         cpu.store_a_absolute();
-        assert_eq!(0x2F, cpu.memory.borrow_mut().read(0x07AF));
+        assert_eq!(0x2F, cpu.bus.read(0x07AF));
     }
 
     #[test]
@@ -6062,11 +6308,11 @@ mod tests {
         cpu.a = 0x2F;
         cpu.x = 0x14;
         cpu.program_counter = 0x32;
-        cpu.memory.borrow_mut().write(0x32, 0xAF);
-        cpu.memory.borrow_mut().write(0x33, 0x07);
+        cpu.bus.write(0x32, 0xAF);
+        cpu.bus.write(0x33, 0x07);
 
         cpu.store_a_absolute_x();
-        assert_eq!(0x2F, cpu.memory.borrow_mut().read(0x07AF + 0x14));
+        assert_eq!(0x2F, cpu.bus.read(0x07AF + 0x14));
     }
 
     #[test]
@@ -6075,11 +6321,11 @@ mod tests {
         cpu.a = 0x2F;
         cpu.y = 0x14;
         cpu.program_counter = 0x32;
-        cpu.memory.borrow_mut().write(0x32, 0xAF);
-        cpu.memory.borrow_mut().write(0x33, 0x07);
+        cpu.bus.write(0x32, 0xAF);
+        cpu.bus.write(0x33, 0x07);
 
         cpu.store_a_absolute_y();
-        assert_eq!(0x2F, cpu.memory.borrow_mut().read(0x07AF + 0x14));
+        assert_eq!(0x2F, cpu.bus.read(0x07AF + 0x14));
     }
 
     #[test]
@@ -6089,14 +6335,14 @@ mod tests {
         cpu.x = 0x14;
         cpu.program_counter = 0x32;
 
-        cpu.memory.borrow_mut().write(0x32, 0xAF);
+        cpu.bus.write(0x32, 0xAF);
 
 
-        cpu.memory.borrow_mut().write(0xAF + 0x14 , 0x07);
-        cpu.memory.borrow_mut().write(0xAF + 0x14 + 1 , 0x20);
+        cpu.bus.write(0xAF + 0x14 , 0x07);
+        cpu.bus.write(0xAF + 0x14 + 1 , 0x20);
 
         cpu.store_a_indirect_x();
-        assert_eq!(0x2F, cpu.memory.borrow_mut().read(0x2007));
+        assert_eq!(0x2F, cpu.bus.read(0x2007));
     }
 
     #[test]
@@ -6106,13 +6352,13 @@ mod tests {
         cpu.y = 0x14;
         cpu.program_counter = 0x32;
 
-        cpu.memory.borrow_mut().write(0x32, 0xAF);
+        cpu.bus.write(0x32, 0xAF);
 
-        cpu.memory.borrow_mut().write(0xAF, 0x07);
-        cpu.memory.borrow_mut().write(0xAF + 1 , 0x20);
+        cpu.bus.write(0xAF, 0x07);
+        cpu.bus.write(0xAF + 1 , 0x20);
 
         cpu.store_a_indirect_y();
-        assert_eq!(0x2F, cpu.memory.borrow_mut().read(0x2007 + 0x14));
+        assert_eq!(0x2F, cpu.bus.read(0x2007 + 0x14));
     }
 
     #[test]
@@ -6120,7 +6366,7 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 25;
-        cpu.memory.borrow_mut().write(25, 0x23);
+        cpu.bus.write(25, 0x23);
         cpu.load_x_immediate();
         assert_eq!(0x23, cpu.x);
     }
@@ -6130,9 +6376,9 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x25;
-        cpu.memory.borrow_mut().write(0x25, 0xBE);
+        cpu.bus.write(0x25, 0xBE);
 
-        cpu.memory.borrow_mut().write(0xBE, 0x09);
+        cpu.bus.write(0xBE, 0x09);
 
         cpu.load_x_zero_page();
         assert_eq!(0x09, cpu.x);
@@ -6144,9 +6390,9 @@ mod tests {
 
         cpu.y = 0x13;
         cpu.program_counter = 0x25;
-        cpu.memory.borrow_mut().write(0x25, 0xBE);
+        cpu.bus.write(0x25, 0xBE);
 
-        cpu.memory.borrow_mut().write(0xBE + 0x13, 0x09);
+        cpu.bus.write(0xBE + 0x13, 0x09);
 
         cpu.load_x_zero_page_y();
         assert_eq!(0x09, cpu.x);
@@ -6157,10 +6403,10 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x25;
-        cpu.memory.borrow_mut().write(0x25, 0xBE);
-        cpu.memory.borrow_mut().write(0x26, 0xAB);
+        cpu.bus.write(0x25, 0xBE);
+        cpu.bus.write(0x26, 0xAB);
 
-        cpu.memory.borrow_mut().write(0xABBE, 0x09);
+        cpu.bus.write(0xABBE, 0x09);
 
         cpu.load_x_absolute();
         assert_eq!(0x09, cpu.x);
@@ -6172,10 +6418,10 @@ mod tests {
 
         cpu.y = 0x13;
         cpu.program_counter = 0x25;
-        cpu.memory.borrow_mut().write(0x25, 0xBE);
-        cpu.memory.borrow_mut().write(0x26, 0xAB);
+        cpu.bus.write(0x25, 0xBE);
+        cpu.bus.write(0x26, 0xAB);
 
-        cpu.memory.borrow_mut().write(0xABBE + 0x13, 0x09);
+        cpu.bus.write(0xABBE + 0x13, 0x09);
 
         cpu.load_x_absolute_y();
         assert_eq!(0x09, cpu.x);
@@ -6186,9 +6432,9 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x2f;
         cpu.program_counter = 0x32;
-        cpu.memory.borrow_mut().write(0x32, 0x14);
+        cpu.bus.write(0x32, 0x14);
         cpu.store_x_zero_page();
-        assert_eq!(0x2f, cpu.memory.borrow_mut().read(0x14));
+        assert_eq!(0x2f, cpu.bus.read(0x14));
     }
 
     #[test]
@@ -6197,9 +6443,9 @@ mod tests {
         cpu.x = 0x2f;
         cpu.y = 0x53;
         cpu.program_counter = 0x32;
-        cpu.memory.borrow_mut().write(0x32, 0x14);
+        cpu.bus.write(0x32, 0x14);
         cpu.store_x_zero_page_y();
-        assert_eq!(0x2f, cpu.memory.borrow_mut().read(0x14 + 0x53));
+        assert_eq!(0x2f, cpu.bus.read(0x14 + 0x53));
     }
 
     #[test]
@@ -6207,11 +6453,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x2f;
         cpu.program_counter = 0x32;
-        cpu.memory.borrow_mut().write(0x32, 0x14);
-        cpu.memory.borrow_mut().write(0x33, 0x08);
+        cpu.bus.write(0x32, 0x14);
+        cpu.bus.write(0x33, 0x08);
 
         cpu.store_x_absolute();
-        assert_eq!(0x2f, cpu.memory.borrow_mut().read(0x0814));
+        assert_eq!(0x2f, cpu.bus.read(0x0814));
     }
 
     #[test]
@@ -6219,7 +6465,7 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x70;
-        cpu.memory.borrow_mut().write(0x70, 0x2F);
+        cpu.bus.write(0x70, 0x2F);
 
         cpu.load_y_immediate();
         assert_eq!(0x2F, cpu.y);
@@ -6230,8 +6476,8 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x720;
-        cpu.memory.borrow_mut().write(0x720, 0x40);
-        cpu.memory.borrow_mut().write(0x40, 0x2F);
+        cpu.bus.write(0x720, 0x40);
+        cpu.bus.write(0x40, 0x2F);
 
         cpu.load_y_zero_page();
         assert_eq!(0x2F, cpu.y);
@@ -6243,8 +6489,8 @@ mod tests {
 
         cpu.x = 0x14;
         cpu.program_counter = 0x720;
-        cpu.memory.borrow_mut().write(0x720, 0x40);
-        cpu.memory.borrow_mut().write(0x40 + 0x14, 0x2F);
+        cpu.bus.write(0x720, 0x40);
+        cpu.bus.write(0x40 + 0x14, 0x2F);
 
         cpu.load_y_zero_page_x();
         assert_eq!(0x2F, cpu.y);
@@ -6255,9 +6501,9 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x720;
-        cpu.memory.borrow_mut().write(0x720, 0x40);
-        cpu.memory.borrow_mut().write(0x721, 0xBE);
-        cpu.memory.borrow_mut().write(0xBE40, 0x2F);
+        cpu.bus.write(0x720, 0x40);
+        cpu.bus.write(0x721, 0xBE);
+        cpu.bus.write(0xBE40, 0x2F);
 
         cpu.load_y_absolute();
         assert_eq!(0x2F, cpu.y);
@@ -6269,9 +6515,9 @@ mod tests {
 
         cpu.x = 0x25;
         cpu.program_counter = 0x720;
-        cpu.memory.borrow_mut().write(0x720, 0x40);
-        cpu.memory.borrow_mut().write(0x721, 0xBE);
-        cpu.memory.borrow_mut().write(0xBE40 + 0x25, 0x2F);
+        cpu.bus.write(0x720, 0x40);
+        cpu.bus.write(0x721, 0xBE);
+        cpu.bus.write(0xBE40 + 0x25, 0x2F);
 
         cpu.load_y_absolute_x();
         assert_eq!(0x2F, cpu.y);
@@ -6283,9 +6529,9 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.y = 0x2f;
         cpu.program_counter = 0x32;
-        cpu.memory.borrow_mut().write(0x32, 0x14);
+        cpu.bus.write(0x32, 0x14);
         cpu.store_y_zero_page();
-        assert_eq!(0x2f, cpu.memory.borrow_mut().read(0x14));
+        assert_eq!(0x2f, cpu.bus.read(0x14));
     }
 
     #[test]
@@ -6294,9 +6540,9 @@ mod tests {
         cpu.y = 0x2f;
         cpu.x = 0x53;
         cpu.program_counter = 0x32;
-        cpu.memory.borrow_mut().write(0x32, 0x14);
+        cpu.bus.write(0x32, 0x14);
         cpu.store_y_zero_page_x();
-        assert_eq!(0x2f, cpu.memory.borrow_mut().read(0x14 + 0x53));
+        assert_eq!(0x2f, cpu.bus.read(0x14 + 0x53));
     }
 
     #[test]
@@ -6304,11 +6550,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.y = 0x2f;
         cpu.program_counter = 0x32;
-        cpu.memory.borrow_mut().write(0x32, 0x14);
-        cpu.memory.borrow_mut().write(0x33, 0x08);
+        cpu.bus.write(0x32, 0x14);
+        cpu.bus.write(0x33, 0x08);
 
         cpu.store_y_absolute();
-        assert_eq!(0x2f, cpu.memory.borrow_mut().read(0x0814));
+        assert_eq!(0x2f, cpu.bus.read(0x0814));
     }
 
     #[test]
@@ -6317,8 +6563,8 @@ mod tests {
         cpu.program_counter = 0x32;
         cpu.x = 0;
         cpu.a = 0;
-        cpu.memory.borrow_mut().write(0x32, 0x14);
-        cpu.memory.borrow_mut().write(0x14, 0xDF);
+        cpu.bus.write(0x32, 0x14);
+        cpu.bus.write(0x14, 0xDF);
         cpu.unofficial_load_a_and_x_zero_page();
         assert_eq!(0xDF, cpu.a);
         assert_eq!(0xDF, cpu.x);
@@ -6331,8 +6577,8 @@ mod tests {
         cpu.x = 0;
         cpu.a = 0;
         cpu.y = 0x42;
-        cpu.memory.borrow_mut().write(0x32, 0x14);
-        cpu.memory.borrow_mut().write(0x14 + 0x42, 0xDF);
+        cpu.bus.write(0x32, 0x14);
+        cpu.bus.write(0x14 + 0x42, 0xDF);
         cpu.unofficial_load_a_and_x_zero_page_y();
         assert_eq!(0xDF, cpu.a);
         assert_eq!(0xDF, cpu.x);
@@ -6344,9 +6590,9 @@ mod tests {
         cpu.program_counter = 0x32;
         cpu.x = 0;
         cpu.a = 0;
-        cpu.memory.borrow_mut().write(0x32, 0x14);
-        cpu.memory.borrow_mut().write(0x33, 0xAF);
-        cpu.memory.borrow_mut().write(0xAF14, 0xDF);
+        cpu.bus.write(0x32, 0x14);
+        cpu.bus.write(0x33, 0xAF);
+        cpu.bus.write(0xAF14, 0xDF);
 
         cpu.unofficial_load_a_and_x_absolute();
         assert_eq!(0xDF, cpu.a);
@@ -6360,9 +6606,9 @@ mod tests {
         cpu.x = 0;
         cpu.a = 0;
         cpu.y = 0x12;
-        cpu.memory.borrow_mut().write(0x32, 0x14);
-        cpu.memory.borrow_mut().write(0x33, 0xAF);
-        cpu.memory.borrow_mut().write(0xAF14 + 0x12, 0xDF);
+        cpu.bus.write(0x32, 0x14);
+        cpu.bus.write(0x33, 0xAF);
+        cpu.bus.write(0xAF14 + 0x12, 0xDF);
 
         cpu.unofficial_load_a_and_x_absolute_y();
         assert_eq!(0xDF, cpu.a);
@@ -6375,12 +6621,12 @@ mod tests {
         cpu.program_counter = 0x32;
         cpu.x = 0x23;
         cpu.a = 0;
-        cpu.memory.borrow_mut().write(0x32, 0x20);
+        cpu.bus.write(0x32, 0x20);
 
-        cpu.memory.borrow_mut().write(0x20 + 0x23, 0x14);
-        cpu.memory.borrow_mut().write(0x21 + 0x23, 0xAF);
+        cpu.bus.write(0x20 + 0x23, 0x14);
+        cpu.bus.write(0x21 + 0x23, 0xAF);
 
-        cpu.memory.borrow_mut().write(0xAF14, 0xDF);
+        cpu.bus.write(0xAF14, 0xDF);
 
         cpu.unofficial_load_a_and_x_indirect_x();
         assert_eq!(0xDF, cpu.a);
@@ -6394,12 +6640,12 @@ mod tests {
         cpu.x = 0x23;
         cpu.y = 0x5D;
         cpu.a = 0;
-        cpu.memory.borrow_mut().write(0x32, 0x20);
+        cpu.bus.write(0x32, 0x20);
 
-        cpu.memory.borrow_mut().write(0x20, 0x14);
-        cpu.memory.borrow_mut().write(0x21, 0xAF);
+        cpu.bus.write(0x20, 0x14);
+        cpu.bus.write(0x21, 0xAF);
 
-        cpu.memory.borrow_mut().write(0xAF14 + 0x5D, 0xDF);
+        cpu.bus.write(0xAF14 + 0x5D, 0xDF);
 
         cpu.unofficial_load_a_and_x_indirect_y();
         assert_eq!(0xDF, cpu.a);
@@ -6424,10 +6670,10 @@ mod tests {
     }
 
     #[test]
-    fn transfer_x_to_stack_pointer_sets_wait_counter_correct() {
+    fn transfer_x_to_stack_pointer_takes_correct_number_of_cycles() {
         let mut cpu = create_test_cpu();
         cpu.transfer_x_to_stack_pointer();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -6483,7 +6729,7 @@ mod tests {
     fn transfer_stack_pointer_to_x_takes_2_cycles() {
         let mut cpu = create_test_cpu();
         cpu.transfer_stack_pointer_to_x();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -6540,7 +6786,7 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.transfer_x_to_accumulator();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -6596,7 +6842,7 @@ mod tests {
     fn transfer_accumulator_to_x_takes_2_cycles() {
         let mut cpu = create_test_cpu();
         cpu.transfer_accumulator_to_x();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -6653,7 +6899,7 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.transfer_y_to_accumulator();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -6710,7 +6956,7 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.transfer_accumulator_to_y();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -6718,7 +6964,7 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x12);
+        cpu.bus.write(0x123, 0x12);
         cpu.status_flags = 0x00;
         cpu.a = 0x4F;
 
@@ -6731,7 +6977,7 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x40);
+        cpu.bus.write(0x123, 0x40);
         cpu.status_flags = 0x00;
         cpu.a = 0x40;
 
@@ -6745,7 +6991,7 @@ mod tests {
 
         cpu.status_flags = 0x03;
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x40);
+        cpu.bus.write(0x123, 0x40);
         cpu.a = 0x39;
 
         cpu.compare_immediate();
@@ -6757,8 +7003,8 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x50, 0x12);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x50, 0x12);
         cpu.status_flags = 0x00;
         cpu.a = 0x4F;
 
@@ -6771,8 +7017,8 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x50, 0x40);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x50, 0x40);
         cpu.status_flags = 0x00;
         cpu.a = 0x40;
 
@@ -6786,8 +7032,8 @@ mod tests {
 
         cpu.status_flags = 0x03;
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x50, 0x40);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x50, 0x40);
         cpu.a = 0x39;
 
         cpu.compare_zero_page();
@@ -6800,8 +7046,8 @@ mod tests {
 
         cpu.x = 0x25;
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x50 + 0x25, 0x12);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x50 + 0x25, 0x12);
         cpu.status_flags = 0x00;
         cpu.a = 0x4F;
 
@@ -6815,8 +7061,8 @@ mod tests {
 
         cpu.x = 0x25;
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x50 + 0x25, 0x40);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x50 + 0x25, 0x40);
         cpu.status_flags = 0x00;
         cpu.a = 0x40;
 
@@ -6831,8 +7077,8 @@ mod tests {
         cpu.status_flags = 0x03;
         cpu.x = 0x25;
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x50 + 0x25, 0x40);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x50 + 0x25, 0x40);
         cpu.a = 0x39;
 
         cpu.compare_zero_page_x();
@@ -6844,9 +7090,9 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x124, 0x80);
-        cpu.memory.borrow_mut().write(0x8050, 0x12);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x124, 0x80);
+        cpu.bus.write(0x8050, 0x12);
         cpu.status_flags = 0x00;
         cpu.a = 0x2F;
 
@@ -6859,9 +7105,9 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x124, 0x80);
-        cpu.memory.borrow_mut().write(0x8050, 0x40);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x124, 0x80);
+        cpu.bus.write(0x8050, 0x40);
         cpu.status_flags = 0x00;
         cpu.a = 0x40;
 
@@ -6875,9 +7121,9 @@ mod tests {
 
         cpu.status_flags = 0x03;
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x124, 0x80);
-        cpu.memory.borrow_mut().write(0x8050, 0x40);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x124, 0x80);
+        cpu.bus.write(0x8050, 0x40);
         cpu.a = 0x39;
 
         cpu.compare_absolute();
@@ -6890,9 +7136,9 @@ mod tests {
 
         cpu.x = 0xFA;
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x124, 0x80);
-        cpu.memory.borrow_mut().write(0x8050 + 0xFA, 0x12);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x124, 0x80);
+        cpu.bus.write(0x8050 + 0xFA, 0x12);
         cpu.status_flags = 0x00;
         cpu.a = 0x4F;
 
@@ -6906,9 +7152,9 @@ mod tests {
 
         cpu.x = 0xFA;
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x124, 0x80);
-        cpu.memory.borrow_mut().write(0x8050 + 0xFA, 0x40);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x124, 0x80);
+        cpu.bus.write(0x8050 + 0xFA, 0x40);
         cpu.status_flags = 0x00;
         cpu.a = 0x40;
 
@@ -6923,9 +7169,9 @@ mod tests {
         cpu.x = 0xFA;
         cpu.status_flags = 0x103;
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x124, 0x80);
-        cpu.memory.borrow_mut().write(0x8050 + 0xFA, 0x40);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x124, 0x80);
+        cpu.bus.write(0x8050 + 0xFA, 0x40);
         cpu.a = 0x39;
 
         cpu.compare_absolute_x();
@@ -6938,9 +7184,9 @@ mod tests {
 
         cpu.y = 0xFA;
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x124, 0x80);
-        cpu.memory.borrow_mut().write(0x8050 + 0xFA, 0x12);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x124, 0x80);
+        cpu.bus.write(0x8050 + 0xFA, 0x12);
         cpu.status_flags = 0x00;
         cpu.a = 0x4F;
 
@@ -6954,9 +7200,9 @@ mod tests {
 
         cpu.y = 0xFA;
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x124, 0x80);
-        cpu.memory.borrow_mut().write(0x8050 + 0xFA, 0x40);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x124, 0x80);
+        cpu.bus.write(0x8050 + 0xFA, 0x40);
         cpu.status_flags = 0x00;
         cpu.a = 0x40;
 
@@ -6971,9 +7217,9 @@ mod tests {
         cpu.y = 0xFA;
         cpu.status_flags = 0x03;
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x124, 0x80);
-        cpu.memory.borrow_mut().write(0x8050 + 0xFA, 0x40);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x124, 0x80);
+        cpu.bus.write(0x8050 + 0xFA, 0x40);
         cpu.a = 0x39;
 
         cpu.compare_absolute_y();
@@ -6986,14 +7232,14 @@ mod tests {
 
         cpu.x = 0xBA;
         cpu.program_counter = 0x1010;
-        cpu.memory.borrow_mut().write(0x1010, 0x0E);
+        cpu.bus.write(0x1010, 0x0E);
 
-        cpu.memory.borrow_mut().write(0x1010, 0x0E);
+        cpu.bus.write(0x1010, 0x0E);
 
-        cpu.memory.borrow_mut().write(0x0E + 0xBA, 0x50);
-        cpu.memory.borrow_mut().write(0x0E + 0xBA + 1, 0x80);
+        cpu.bus.write(0x0E + 0xBA, 0x50);
+        cpu.bus.write(0x0E + 0xBA + 1, 0x80);
 
-        cpu.memory.borrow_mut().write(0x8050, 0x12);
+        cpu.bus.write(0x8050, 0x12);
         cpu.status_flags = 0x00;
         cpu.a = 0x4F;
 
@@ -7007,12 +7253,12 @@ mod tests {
 
         cpu.x = 0xBA;
         cpu.program_counter = 0x1010;
-        cpu.memory.borrow_mut().write(0x1010, 0x0E);
+        cpu.bus.write(0x1010, 0x0E);
 
-        cpu.memory.borrow_mut().write(0x0E + 0xBA, 0x50);
-        cpu.memory.borrow_mut().write(0x0E + 0xBA + 1, 0x80);
+        cpu.bus.write(0x0E + 0xBA, 0x50);
+        cpu.bus.write(0x0E + 0xBA + 1, 0x80);
 
-        cpu.memory.borrow_mut().write(0x8050, 0x40);
+        cpu.bus.write(0x8050, 0x40);
         cpu.status_flags = 0x00;
         cpu.a = 0x40;
 
@@ -7027,12 +7273,12 @@ mod tests {
         cpu.x = 0xBA;
         cpu.status_flags = 0x03;
         cpu.program_counter = 0x1010;
-        cpu.memory.borrow_mut().write(0x1010, 0x0E);
+        cpu.bus.write(0x1010, 0x0E);
 
-        cpu.memory.borrow_mut().write(0x0E + 0xBA, 0x50);
-        cpu.memory.borrow_mut().write(0x0E + 0xBA + 1, 0x80);
+        cpu.bus.write(0x0E + 0xBA, 0x50);
+        cpu.bus.write(0x0E + 0xBA + 1, 0x80);
 
-        cpu.memory.borrow_mut().write(0x8050, 0x40);
+        cpu.bus.write(0x8050, 0x40);
         cpu.a = 0x39;
 
         cpu.compare_indirect_x();
@@ -7045,14 +7291,14 @@ mod tests {
 
         cpu.y = 0xBA;
         cpu.program_counter = 0x1010;
-        cpu.memory.borrow_mut().write(0x1010, 0x0E);
+        cpu.bus.write(0x1010, 0x0E);
 
-        cpu.memory.borrow_mut().write(0x1010, 0x0E);
+        cpu.bus.write(0x1010, 0x0E);
 
-        cpu.memory.borrow_mut().write(0x0E, 0x50);
-        cpu.memory.borrow_mut().write(0x0E + 1, 0x80);
+        cpu.bus.write(0x0E, 0x50);
+        cpu.bus.write(0x0E + 1, 0x80);
 
-        cpu.memory.borrow_mut().write(0x8050 + 0xBA, 0x12);
+        cpu.bus.write(0x8050 + 0xBA, 0x12);
         cpu.status_flags = 0x00;
         cpu.a = 0x40;
 
@@ -7066,12 +7312,12 @@ mod tests {
 
         cpu.y = 0xBA;
         cpu.program_counter = 0x1010;
-        cpu.memory.borrow_mut().write(0x1010, 0x0E);
+        cpu.bus.write(0x1010, 0x0E);
 
-        cpu.memory.borrow_mut().write(0x0E, 0x50);
-        cpu.memory.borrow_mut().write(0x0E + 1, 0x80);
+        cpu.bus.write(0x0E, 0x50);
+        cpu.bus.write(0x0E + 1, 0x80);
 
-        cpu.memory.borrow_mut().write(0x8050 + 0xBA, 0x40);
+        cpu.bus.write(0x8050 + 0xBA, 0x40);
         cpu.status_flags = 0x00;
         cpu.a = 0x40;
 
@@ -7086,12 +7332,12 @@ mod tests {
         cpu.y = 0xBA;
         cpu.status_flags = 0x03;
         cpu.program_counter = 0x1010;
-        cpu.memory.borrow_mut().write(0x1010, 0x0E);
+        cpu.bus.write(0x1010, 0x0E);
 
-        cpu.memory.borrow_mut().write(0x0E, 0x50);
-        cpu.memory.borrow_mut().write(0x0E + 1, 0x80);
+        cpu.bus.write(0x0E, 0x50);
+        cpu.bus.write(0x0E + 1, 0x80);
 
-        cpu.memory.borrow_mut().write(0x8050 + 0xBA, 0x40);
+        cpu.bus.write(0x8050 + 0xBA, 0x40);
         cpu.a = 0x39;
 
         cpu.compare_indirect_y();
@@ -7102,7 +7348,7 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x12);
+        cpu.bus.write(0x123, 0x12);
         cpu.status_flags = 0x00;
         cpu.x = 0x4F;
 
@@ -7115,7 +7361,7 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x40);
+        cpu.bus.write(0x123, 0x40);
         cpu.status_flags = 0x00;
         cpu.x = 0x40;
 
@@ -7129,7 +7375,7 @@ mod tests {
 
         cpu.status_flags = 0x03;
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x40);
+        cpu.bus.write(0x123, 0x40);
         cpu.x = 0x39;
 
         cpu.compare_x_immediate();
@@ -7141,8 +7387,8 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x50, 0x12);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x50, 0x12);
         cpu.status_flags = 0x00;
         cpu.x = 0x4F;
 
@@ -7155,8 +7401,8 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x50, 0x40);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x50, 0x40);
         cpu.status_flags = 0x00;
         cpu.x = 0x40;
 
@@ -7170,8 +7416,8 @@ mod tests {
 
         cpu.status_flags = 0x03;
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x50, 0x40);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x50, 0x40);
         cpu.x = 0x39;
 
         cpu.compare_x_zero_page();
@@ -7183,9 +7429,9 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x124, 0x80);
-        cpu.memory.borrow_mut().write(0x8050, 0x12);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x124, 0x80);
+        cpu.bus.write(0x8050, 0x12);
         cpu.status_flags = 0x00;
         cpu.x = 0x2F;
 
@@ -7198,9 +7444,9 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x124, 0x80);
-        cpu.memory.borrow_mut().write(0x8050, 0x40);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x124, 0x80);
+        cpu.bus.write(0x8050, 0x40);
         cpu.status_flags = 0x00;
         cpu.x = 0x40;
 
@@ -7214,9 +7460,9 @@ mod tests {
 
         cpu.status_flags = 0x03;
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x124, 0x80);
-        cpu.memory.borrow_mut().write(0x8050, 0x40);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x124, 0x80);
+        cpu.bus.write(0x8050, 0x40);
         cpu.x = 0x39;
 
         cpu.compare_x_absolute();
@@ -7229,7 +7475,7 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x12);
+        cpu.bus.write(0x123, 0x12);
         cpu.status_flags = 0x00;
         cpu.y = 0x4F;
 
@@ -7242,7 +7488,7 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x40);
+        cpu.bus.write(0x123, 0x40);
         cpu.status_flags = 0x00;
         cpu.y = 0x40;
 
@@ -7256,7 +7502,7 @@ mod tests {
 
         cpu.status_flags = 0x03;
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x40);
+        cpu.bus.write(0x123, 0x40);
         cpu.y = 0x39;
 
         cpu.compare_y_immediate();
@@ -7268,8 +7514,8 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x50, 0x12);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x50, 0x12);
         cpu.status_flags = 0x00;
         cpu.y = 0x4F;
 
@@ -7282,8 +7528,8 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x50, 0x40);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x50, 0x40);
         cpu.status_flags = 0x00;
         cpu.y = 0x40;
 
@@ -7297,8 +7543,8 @@ mod tests {
 
         cpu.status_flags = 0x03;
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x50, 0x40);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x50, 0x40);
         cpu.y = 0x39;
 
         cpu.compare_y_zero_page();
@@ -7310,9 +7556,9 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x124, 0x80);
-        cpu.memory.borrow_mut().write(0x8050, 0x12);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x124, 0x80);
+        cpu.bus.write(0x8050, 0x12);
         cpu.status_flags = 0x00;
         cpu.y = 0x2F;
 
@@ -7325,9 +7571,9 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x124, 0x80);
-        cpu.memory.borrow_mut().write(0x8050, 0x40);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x124, 0x80);
+        cpu.bus.write(0x8050, 0x40);
         cpu.status_flags = 0x00;
         cpu.y = 0x40;
 
@@ -7341,9 +7587,9 @@ mod tests {
 
         cpu.status_flags = 0x03;
         cpu.program_counter = 0x123;
-        cpu.memory.borrow_mut().write(0x123, 0x50);
-        cpu.memory.borrow_mut().write(0x124, 0x80);
-        cpu.memory.borrow_mut().write(0x8050, 0x40);
+        cpu.bus.write(0x123, 0x50);
+        cpu.bus.write(0x124, 0x80);
+        cpu.bus.write(0x8050, 0x40);
         cpu.y = 0x39;
 
         cpu.compare_y_absolute();
@@ -7357,7 +7603,7 @@ mod tests {
         cpu.a = 49;
 
         cpu.program_counter = 0x30;
-        cpu.memory.borrow_mut().write(0x30, 20);
+        cpu.bus.write(0x30, 20);
         cpu.add_immediate();
         assert_eq!(69, cpu.a);
     }
@@ -7369,8 +7615,8 @@ mod tests {
         cpu.a = 49;
 
         cpu.program_counter = 0x30;
-        cpu.memory.borrow_mut().write(0x30, 0x20);
-        cpu.memory.borrow_mut().write(0x20, 29);
+        cpu.bus.write(0x30, 0x20);
+        cpu.bus.write(0x20, 29);
 
         cpu.add_zero_page();
         assert_eq!(78, cpu.a);
@@ -7383,8 +7629,8 @@ mod tests {
         cpu.a = 49;
         cpu.x = 0x40;
         cpu.program_counter = 0x30;
-        cpu.memory.borrow_mut().write(0x30, 0x20);
-        cpu.memory.borrow_mut().write(0x20 + 0x40, 29);
+        cpu.bus.write(0x30, 0x20);
+        cpu.bus.write(0x20 + 0x40, 29);
 
         cpu.add_zero_page_x();
         assert_eq!(78, cpu.a);
@@ -7396,9 +7642,9 @@ mod tests {
 
         cpu.a = 49;
         cpu.program_counter = 0x30;
-        cpu.memory.borrow_mut().write(0x30, 0x20);
-        cpu.memory.borrow_mut().write(0x31, 0xDE);
-        cpu.memory.borrow_mut().write(0xDE20, 29);
+        cpu.bus.write(0x30, 0x20);
+        cpu.bus.write(0x31, 0xDE);
+        cpu.bus.write(0xDE20, 29);
 
         cpu.add_absolute();
         assert_eq!(78, cpu.a);
@@ -7411,9 +7657,9 @@ mod tests {
         cpu.a = 49;
         cpu.x = 0x42;
         cpu.program_counter = 0x30;
-        cpu.memory.borrow_mut().write(0x30, 0x20);
-        cpu.memory.borrow_mut().write(0x31, 0xDE);
-        cpu.memory.borrow_mut().write(0xDE20 + 0x42, 29);
+        cpu.bus.write(0x30, 0x20);
+        cpu.bus.write(0x31, 0xDE);
+        cpu.bus.write(0xDE20 + 0x42, 29);
 
         cpu.add_absolute_x();
         assert_eq!(78, cpu.a);
@@ -7426,9 +7672,9 @@ mod tests {
         cpu.a = 49;
         cpu.y = 0x42;
         cpu.program_counter = 0x30;
-        cpu.memory.borrow_mut().write(0x30, 0x20);
-        cpu.memory.borrow_mut().write(0x31, 0xDE);
-        cpu.memory.borrow_mut().write(0xDE20 + 0x42, 29);
+        cpu.bus.write(0x30, 0x20);
+        cpu.bus.write(0x31, 0xDE);
+        cpu.bus.write(0xDE20 + 0x42, 29);
 
         cpu.add_absolute_y();
         assert_eq!(78, cpu.a);
@@ -7441,12 +7687,12 @@ mod tests {
         cpu.a = 49;
         cpu.x = 0x42;
         cpu.program_counter = 0x30;
-        cpu.memory.borrow_mut().write(0x30, 0x20);
+        cpu.bus.write(0x30, 0x20);
 
-        cpu.memory.borrow_mut().write(0x20 + 0x42, 0xDE);
-        cpu.memory.borrow_mut().write(0x20 + 0x42 + 1, 0x29);
+        cpu.bus.write(0x20 + 0x42, 0xDE);
+        cpu.bus.write(0x20 + 0x42 + 1, 0x29);
 
-        cpu.memory.borrow_mut().write(0x29DE, 29);
+        cpu.bus.write(0x29DE, 29);
 
         cpu.add_indirect_x();
         assert_eq!(78, cpu.a);
@@ -7460,12 +7706,12 @@ mod tests {
         cpu.a = 49;
         cpu.y = 0x42;
         cpu.program_counter = 0x30;
-        cpu.memory.borrow_mut().write(0x30, 0x20);
+        cpu.bus.write(0x30, 0x20);
 
-        cpu.memory.borrow_mut().write(0x20, 0xDE);
-        cpu.memory.borrow_mut().write(0x20 + 1, 0x29);
+        cpu.bus.write(0x20, 0xDE);
+        cpu.bus.write(0x20 + 1, 0x29);
 
-        cpu.memory.borrow_mut().write(0x29DE + 0x42, 29);
+        cpu.bus.write(0x29DE + 0x42, 29);
 
         cpu.add_indirect_y();
         assert_eq!(78, cpu.a);
@@ -7479,7 +7725,7 @@ mod tests {
         cpu.a = 49;
         cpu.status_flags = 0x01;
         cpu.program_counter = 0x30;
-        cpu.memory.borrow_mut().write(0x30, 19);
+        cpu.bus.write(0x30, 19);
         cpu.subtract_immediate();
         assert_eq!(30, cpu.a);
     }
@@ -7491,8 +7737,8 @@ mod tests {
         cpu.a = 49;
         cpu.status_flags = 0x01;
         cpu.program_counter = 0x08F0;
-        cpu.memory.borrow_mut().write(0x08F0, 0x30);
-        cpu.memory.borrow_mut().write(0x30, 19);
+        cpu.bus.write(0x08F0, 0x30);
+        cpu.bus.write(0x30, 19);
 
         cpu.subtract_zero_page();
         assert_eq!(30, cpu.a);
@@ -7506,8 +7752,8 @@ mod tests {
         cpu.x = 0x20;
         cpu.status_flags = 0x01;
         cpu.program_counter = 0x08F0;
-        cpu.memory.borrow_mut().write(0x08F0, 0x30);
-        cpu.memory.borrow_mut().write(0x30 + 0x20, 19);
+        cpu.bus.write(0x08F0, 0x30);
+        cpu.bus.write(0x30 + 0x20, 19);
 
         cpu.subtract_zero_page_x();
         assert_eq!(30, cpu.a);
@@ -7520,10 +7766,10 @@ mod tests {
         cpu.a = 49;
         cpu.status_flags = 0x01;
         cpu.program_counter = 0x08F0;
-        cpu.memory.borrow_mut().write(0x08F0, 0x30);
-        cpu.memory.borrow_mut().write(0x08F1, 0xB0);
+        cpu.bus.write(0x08F0, 0x30);
+        cpu.bus.write(0x08F1, 0xB0);
 
-        cpu.memory.borrow_mut().write(0xB030, 19);
+        cpu.bus.write(0xB030, 19);
 
         cpu.subtract_absolute();
         assert_eq!(30, cpu.a);
@@ -7537,10 +7783,10 @@ mod tests {
         cpu.x = 0x70;
         cpu.status_flags = 0x01;
         cpu.program_counter = 0x08F0;
-        cpu.memory.borrow_mut().write(0x08F0, 0x30);
-        cpu.memory.borrow_mut().write(0x08F1, 0xB0);
+        cpu.bus.write(0x08F0, 0x30);
+        cpu.bus.write(0x08F1, 0xB0);
 
-        cpu.memory.borrow_mut().write(0xB030 + 0x70, 19);
+        cpu.bus.write(0xB030 + 0x70, 19);
 
         cpu.subtract_absolute_x();
         assert_eq!(30, cpu.a);
@@ -7554,10 +7800,10 @@ mod tests {
         cpu.y = 0x70;
         cpu.status_flags = 0x01;
         cpu.program_counter = 0x08F0;
-        cpu.memory.borrow_mut().write(0x08F0, 0x30);
-        cpu.memory.borrow_mut().write(0x08F1, 0xB0);
+        cpu.bus.write(0x08F0, 0x30);
+        cpu.bus.write(0x08F1, 0xB0);
 
-        cpu.memory.borrow_mut().write(0xB030 + 0x70, 19);
+        cpu.bus.write(0xB030 + 0x70, 19);
 
         cpu.subtract_absolute_y();
         assert_eq!(30, cpu.a);
@@ -7571,12 +7817,12 @@ mod tests {
         cpu.x = 0x05;
         cpu.status_flags = 0x01;
         cpu.program_counter = 0x08F0;
-        cpu.memory.borrow_mut().write(0x08F0, 0x70);
+        cpu.bus.write(0x08F0, 0x70);
 
-        cpu.memory.borrow_mut().write(0x70 + 0x05, 0x30);
-        cpu.memory.borrow_mut().write(0x71 + 0x05, 0xB0);
+        cpu.bus.write(0x70 + 0x05, 0x30);
+        cpu.bus.write(0x71 + 0x05, 0xB0);
 
-        cpu.memory.borrow_mut().write(0xB030, 19);
+        cpu.bus.write(0xB030, 19);
 
         cpu.subtract_indirect_x();
         assert_eq!(30, cpu.a);
@@ -7590,12 +7836,12 @@ mod tests {
         cpu.y = 0x05;
         cpu.status_flags = 0x01;
         cpu.program_counter = 0x08F0;
-        cpu.memory.borrow_mut().write(0x08F0, 0x70);
+        cpu.bus.write(0x08F0, 0x70);
 
-        cpu.memory.borrow_mut().write(0x70, 0x30);
-        cpu.memory.borrow_mut().write(0x71, 0xB0);
+        cpu.bus.write(0x70, 0x30);
+        cpu.bus.write(0x71, 0xB0);
 
-        cpu.memory.borrow_mut().write(0xB030 + 0x05, 19);
+        cpu.bus.write(0xB030 + 0x05, 19);
 
         cpu.subtract_indirect_y();
         assert_eq!(30, cpu.a);
@@ -7614,7 +7860,7 @@ mod tests {
     fn increase_x_takes_2_cycles() {
         let mut cpu = create_test_cpu();
         cpu.increase_x();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -7629,7 +7875,7 @@ mod tests {
     fn increase_y_takes_2_cycles() {
         let mut cpu = create_test_cpu();
         cpu.increase_y();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -7644,17 +7890,17 @@ mod tests {
     fn decrease_y_takes_2_cycles() {
         let mut cpu = create_test_cpu();
         cpu.decrease_y();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
     fn increment_memory_zero_page_increases_value_in_memory() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0xABC;
-        cpu.memory.borrow_mut().write(0xABC, 0x70);
-        cpu.memory.borrow_mut().write(0x70, 43);
+        cpu.bus.write(0xABC, 0x70);
+        cpu.bus.write(0x70, 43);
         cpu.increment_memory_zero_page();
-        assert_eq!(44, cpu.memory.borrow_mut().read(0x70));
+        assert_eq!(44, cpu.bus.read(0x70));
     }
 
     #[test]
@@ -7669,7 +7915,7 @@ mod tests {
     fn increment_memory_zero_page_takes_5_cycles() {
         let mut cpu = create_test_cpu();
         cpu.increment_memory_zero_page();
-        assert_eq!(5, cpu.wait_counter);
+        assert_eq!(5, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -7677,10 +7923,10 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x24;
         cpu.program_counter = 0xABC;
-        cpu.memory.borrow_mut().write(0xABC, 0x70);
-        cpu.memory.borrow_mut().write(0x70 + 0x24, 43);
+        cpu.bus.write(0xABC, 0x70);
+        cpu.bus.write(0x70 + 0x24, 43);
         cpu.increment_memory_zero_page_x();
-        assert_eq!(44, cpu.memory.borrow_mut().read(0x70 + 0x24));
+        assert_eq!(44, cpu.bus.read(0x70 + 0x24));
     }
 
     #[test]
@@ -7695,19 +7941,19 @@ mod tests {
     fn increment_memory_zero_page_x_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.increment_memory_zero_page_x();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
     fn increment_memory_absolute_increases_value_in_memory() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0xABC;
-        cpu.memory.borrow_mut().write(0xABC, 0x70);
-        cpu.memory.borrow_mut().write(0xABD, 0x02);
+        cpu.bus.write(0xABC, 0x70);
+        cpu.bus.write(0xABD, 0x02);
 
-        cpu.memory.borrow_mut().write(0x0270, 43);
+        cpu.bus.write(0x0270, 43);
         cpu.increment_memory_absolute();
-        assert_eq!(44, cpu.memory.borrow_mut().read(0x0270));
+        assert_eq!(44, cpu.bus.read(0x0270));
     }
 
     #[test]
@@ -7722,7 +7968,7 @@ mod tests {
     fn increment_memory_absolute_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.increment_memory_absolute();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -7730,12 +7976,12 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0xABC;
         cpu.x = 0x53;
-        cpu.memory.borrow_mut().write(0xABC, 0x70);
-        cpu.memory.borrow_mut().write(0xABD, 0x02);
+        cpu.bus.write(0xABC, 0x70);
+        cpu.bus.write(0xABD, 0x02);
 
-        cpu.memory.borrow_mut().write(0x0270 + 0x53, 43);
+        cpu.bus.write(0x0270 + 0x53, 43);
         cpu.increment_memory_absolute_x();
-        assert_eq!(44, cpu.memory.borrow_mut().read(0x0270 + 0x53));
+        assert_eq!(44, cpu.bus.read(0x0270 + 0x53));
     }
 
     #[test]
@@ -7750,17 +7996,17 @@ mod tests {
     fn increment_memory_absolute_x_takes_7_cycles() {
         let mut cpu = create_test_cpu();
         cpu.increment_memory_absolute_x();
-        assert_eq!(7, cpu.wait_counter);
+        assert_eq!(7, instruction_cycles(&cpu));
     }
 
     #[test]
     fn decrement_memory_zero_page_decreases_value_in_memory() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0xABC;
-        cpu.memory.borrow_mut().write(0xABC, 0x70);
-        cpu.memory.borrow_mut().write(0x70, 43);
+        cpu.bus.write(0xABC, 0x70);
+        cpu.bus.write(0x70, 43);
         cpu.decrement_memory_zero_page();
-        assert_eq!(42, cpu.memory.borrow_mut().read(0x70));
+        assert_eq!(42, cpu.bus.read(0x70));
     }
 
     #[test]
@@ -7775,7 +8021,7 @@ mod tests {
     fn decrement_memory_zero_page_takes_5_cycles() {
         let mut cpu = create_test_cpu();
         cpu.decrement_memory_zero_page();
-        assert_eq!(5, cpu.wait_counter);
+        assert_eq!(5, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -7783,10 +8029,10 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x24;
         cpu.program_counter = 0xABC;
-        cpu.memory.borrow_mut().write(0xABC, 0x70);
-        cpu.memory.borrow_mut().write(0x70 + 0x24, 43);
+        cpu.bus.write(0xABC, 0x70);
+        cpu.bus.write(0x70 + 0x24, 43);
         cpu.decrement_memory_zero_page_x();
-        assert_eq!(42, cpu.memory.borrow_mut().read(0x70 + 0x24));
+        assert_eq!(42, cpu.bus.read(0x70 + 0x24));
     }
 
     #[test]
@@ -7801,19 +8047,19 @@ mod tests {
     fn decrement_memory_zero_page_x_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.decrement_memory_zero_page_x();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
     fn decrement_memory_absolute_decreases_value_in_memory() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0xABC;
-        cpu.memory.borrow_mut().write(0xABC, 0x70);
-        cpu.memory.borrow_mut().write(0xABD, 0x02);
+        cpu.bus.write(0xABC, 0x70);
+        cpu.bus.write(0xABD, 0x02);
 
-        cpu.memory.borrow_mut().write(0x0270, 43);
+        cpu.bus.write(0x0270, 43);
         cpu.decrement_memory_absolute();
-        assert_eq!(42, cpu.memory.borrow_mut().read(0x0270));
+        assert_eq!(42, cpu.bus.read(0x0270));
     }
 
     #[test]
@@ -7828,7 +8074,7 @@ mod tests {
     fn decrement_memory_absolute_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.decrement_memory_absolute();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -7836,12 +8082,12 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0xABC;
         cpu.x = 0x53;
-        cpu.memory.borrow_mut().write(0xABC, 0x70);
-        cpu.memory.borrow_mut().write(0xABD, 0x02);
+        cpu.bus.write(0xABC, 0x70);
+        cpu.bus.write(0xABD, 0x02);
 
-        cpu.memory.borrow_mut().write(0x0270 + 0x53, 43);
+        cpu.bus.write(0x0270 + 0x53, 43);
         cpu.decrement_memory_absolute_x();
-        assert_eq!(42, cpu.memory.borrow_mut().read(0x0270 + 0x53));
+        assert_eq!(42, cpu.bus.read(0x0270 + 0x53));
     }
 
     #[test]
@@ -7856,7 +8102,7 @@ mod tests {
     fn decrement_memory_absolute_x_takes_7_cycles() {
         let mut cpu = create_test_cpu();
         cpu.decrement_memory_absolute_x();
-        assert_eq!(7, cpu.wait_counter);
+        assert_eq!(7, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -7885,9 +8131,9 @@ mod tests {
         cpu.a = 0x43;
         cpu.x = 0xFA;
         cpu.program_counter = 0xABC;
-        cpu.memory.borrow_mut().write(0xABC, 0x70);
+        cpu.bus.write(0xABC, 0x70);
         cpu.unofficial_and_a_with_x_store_result_zero_page();
-        assert_eq!(0x42, cpu.memory.borrow_mut().read(0x70));
+        assert_eq!(0x42, cpu.bus.read(0x70));
     }
 
     #[test]
@@ -7917,9 +8163,9 @@ mod tests {
         cpu.x = 0xFA;
         cpu.y = 0x5D;
         cpu.program_counter = 0xABC;
-        cpu.memory.borrow_mut().write(0xABC, 0x70);
+        cpu.bus.write(0xABC, 0x70);
         cpu.unofficial_and_a_with_x_store_result_zero_page_y();
-        assert_eq!(0x42, cpu.memory.borrow_mut().read(0x70 + 0x5D));
+        assert_eq!(0x42, cpu.bus.read(0x70 + 0x5D));
     }
 
     #[test]
@@ -7949,11 +8195,11 @@ mod tests {
         cpu.x = 0xFA;
         cpu.y = 0x5D;
         cpu.program_counter = 0xABC;
-        cpu.memory.borrow_mut().write(0xABC, 0x02);
-        cpu.memory.borrow_mut().write(0xABD, 0x7F);
+        cpu.bus.write(0xABC, 0x02);
+        cpu.bus.write(0xABD, 0x7F);
 
         cpu.unofficial_and_a_with_x_store_result_absolute();
-        assert_eq!(0x42, cpu.memory.borrow_mut().read(0x7F02));
+        assert_eq!(0x42, cpu.bus.read(0x7F02));
     }
 
     #[test]
@@ -7984,12 +8230,12 @@ mod tests {
         cpu.x = 0xFA;
         cpu.y = 0x5D;
         cpu.program_counter = 0xABC;
-        cpu.memory.borrow_mut().write(0xABC, 0x02);
-        cpu.memory.borrow_mut().write(0x02 + 0xFA, 0xAF);
-        cpu.memory.borrow_mut().write(0x03 + 0xFA, 0xEF);
+        cpu.bus.write(0xABC, 0x02);
+        cpu.bus.write(0x02 + 0xFA, 0xAF);
+        cpu.bus.write(0x03 + 0xFA, 0xEF);
 
         cpu.unofficial_and_a_with_x_store_result_indirect_x();
-        assert_eq!(0x42, cpu.memory.borrow_mut().read(0xEFAF));
+        assert_eq!(0x42, cpu.bus.read(0xEFAF));
     }
 
     #[test]
@@ -7997,11 +8243,11 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x32;
-        cpu.memory.borrow_mut().write(0x32, 0xAF);
-        cpu.memory.borrow_mut().write(0xAF, 0x4F);
+        cpu.bus.write(0x32, 0xAF);
+        cpu.bus.write(0xAF, 0x4F);
 
         cpu.unofficial_decrement_memory_and_compare_with_acc_zero_page();
-        assert_eq!(0x4E, cpu.memory.borrow_mut().read(0xAF));
+        assert_eq!(0x4E, cpu.bus.read(0xAF));
     }
 
     #[test]
@@ -8010,8 +8256,8 @@ mod tests {
         cpu.a = 0xF0;
         cpu.program_counter = 0x32;
         cpu.status_flags = 0x00;
-        cpu.memory.borrow_mut().write(0x32, 0xAF);
-        cpu.memory.borrow_mut().write(0xAF, 0x4F);
+        cpu.bus.write(0x32, 0xAF);
+        cpu.bus.write(0xAF, 0x4F);
 
         cpu.unofficial_decrement_memory_and_compare_with_acc_zero_page();
         assert_eq!(0x81, cpu.status_flags);
@@ -8029,7 +8275,7 @@ mod tests {
     fn unofficial_decrement_memory_and_compare_with_acc_zero_page_takes_5_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_decrement_memory_and_compare_with_acc_zero_page();
-        assert_eq!(5, cpu.wait_counter);
+        assert_eq!(5, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8038,11 +8284,11 @@ mod tests {
 
         cpu.program_counter = 0x32;
         cpu.x = 0x10;
-        cpu.memory.borrow_mut().write(0x32, 0xAF);
-        cpu.memory.borrow_mut().write(0xAF + 0x10, 0x4F);
+        cpu.bus.write(0x32, 0xAF);
+        cpu.bus.write(0xAF + 0x10, 0x4F);
 
         cpu.unofficial_decrement_memory_and_compare_with_acc_zero_page_x();
-        assert_eq!(0x4E, cpu.memory.borrow_mut().read(0xAF + 0x10));
+        assert_eq!(0x4E, cpu.bus.read(0xAF + 0x10));
     }
 
     #[test]
@@ -8052,8 +8298,8 @@ mod tests {
         cpu.x = 0x10;
         cpu.program_counter = 0x32;
         cpu.status_flags = 0x83;
-        cpu.memory.borrow_mut().write(0x32, 0xAF);
-        cpu.memory.borrow_mut().write(0xAF + 0x10, 0x4F);
+        cpu.bus.write(0x32, 0xAF);
+        cpu.bus.write(0xAF + 0x10, 0x4F);
 
         cpu.unofficial_decrement_memory_and_compare_with_acc_zero_page_x();
         assert_eq!(0x80, cpu.status_flags);
@@ -8071,7 +8317,7 @@ mod tests {
     fn unofficial_decrement_memory_and_compare_with_acc_zero_page_x_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_decrement_memory_and_compare_with_acc_zero_page_x();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8080,12 +8326,12 @@ mod tests {
 
         cpu.program_counter = 0x32;
 
-        cpu.memory.borrow_mut().write(0x32, 0x8F);
-        cpu.memory.borrow_mut().write(0x33, 0x09);
-        cpu.memory.borrow_mut().write(0x098F, 0x4F);
+        cpu.bus.write(0x32, 0x8F);
+        cpu.bus.write(0x33, 0x09);
+        cpu.bus.write(0x098F, 0x4F);
 
         cpu.unofficial_decrement_memory_and_compare_with_acc_absolute();
-        assert_eq!(0x4E, cpu.memory.borrow_mut().read(0x098F));
+        assert_eq!(0x4E, cpu.bus.read(0x098F));
     }
 
     #[test]
@@ -8094,9 +8340,9 @@ mod tests {
         cpu.a = 0x00;
         cpu.program_counter = 0x32;
         cpu.status_flags = 0x83;
-        cpu.memory.borrow_mut().write(0x32, 0x8F);
-        cpu.memory.borrow_mut().write(0x33, 0x09);
-        cpu.memory.borrow_mut().write(0x098F, 0xFE);
+        cpu.bus.write(0x32, 0x8F);
+        cpu.bus.write(0x33, 0x09);
+        cpu.bus.write(0x098F, 0xFE);
 
         cpu.unofficial_decrement_memory_and_compare_with_acc_absolute();
         assert_eq!(0x00, cpu.status_flags);
@@ -8114,7 +8360,7 @@ mod tests {
     fn unofficial_decrement_memory_and_compare_with_acc_absolute_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_decrement_memory_and_compare_with_acc_absolute();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8124,12 +8370,12 @@ mod tests {
         cpu.program_counter = 0x32;
         cpu.x = 0x42;
 
-        cpu.memory.borrow_mut().write(0x32, 0x8F);
-        cpu.memory.borrow_mut().write(0x33, 0x09);
-        cpu.memory.borrow_mut().write(0x098F + 0x42, 0x4F);
+        cpu.bus.write(0x32, 0x8F);
+        cpu.bus.write(0x33, 0x09);
+        cpu.bus.write(0x098F + 0x42, 0x4F);
 
         cpu.unofficial_decrement_memory_and_compare_with_acc_absolute_x();
-        assert_eq!(0x4E, cpu.memory.borrow_mut().read(0x098F + 0x42));
+        assert_eq!(0x4E, cpu.bus.read(0x098F + 0x42));
     }
 
     #[test]
@@ -8139,9 +8385,9 @@ mod tests {
         cpu.x = 0x20;
         cpu.program_counter = 0x32;
         cpu.status_flags = 0x83;
-        cpu.memory.borrow_mut().write(0x32, 0x8F);
-        cpu.memory.borrow_mut().write(0x33, 0x09);
-        cpu.memory.borrow_mut().write(0x098F + 0x20, 0x3E);
+        cpu.bus.write(0x32, 0x8F);
+        cpu.bus.write(0x33, 0x09);
+        cpu.bus.write(0x098F + 0x20, 0x3E);
 
         cpu.unofficial_decrement_memory_and_compare_with_acc_absolute_x();
         assert_eq!(0x01, cpu.status_flags);
@@ -8159,7 +8405,7 @@ mod tests {
     fn unofficial_decrement_memory_and_compare_with_acc_absolute_x_takes_7_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_decrement_memory_and_compare_with_acc_absolute_x();
-        assert_eq!(7, cpu.wait_counter);
+        assert_eq!(7, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8169,12 +8415,12 @@ mod tests {
         cpu.program_counter = 0x32;
         cpu.y = 0x42;
 
-        cpu.memory.borrow_mut().write(0x32, 0x8F);
-        cpu.memory.borrow_mut().write(0x33, 0x09);
-        cpu.memory.borrow_mut().write(0x098F + 0x42, 0x4F);
+        cpu.bus.write(0x32, 0x8F);
+        cpu.bus.write(0x33, 0x09);
+        cpu.bus.write(0x098F + 0x42, 0x4F);
 
         cpu.unofficial_decrement_memory_and_compare_with_acc_absolute_y();
-        assert_eq!(0x4E, cpu.memory.borrow_mut().read(0x098F + 0x42));
+        assert_eq!(0x4E, cpu.bus.read(0x098F + 0x42));
     }
 
     #[test]
@@ -8184,9 +8430,9 @@ mod tests {
         cpu.y = 0x20;
         cpu.program_counter = 0x32;
         cpu.status_flags = 0x00;
-        cpu.memory.borrow_mut().write(0x32, 0x8F);
-        cpu.memory.borrow_mut().write(0x33, 0x09);
-        cpu.memory.borrow_mut().write(0x098F + 0x20, 0x3E);
+        cpu.bus.write(0x32, 0x8F);
+        cpu.bus.write(0x33, 0x09);
+        cpu.bus.write(0x098F + 0x20, 0x3E);
 
         cpu.unofficial_decrement_memory_and_compare_with_acc_absolute_y();
         assert_eq!(0x01, cpu.status_flags);
@@ -8204,7 +8450,7 @@ mod tests {
     fn unofficial_decrement_memory_and_compare_with_acc_absolute_y_takes_7_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_decrement_memory_and_compare_with_acc_absolute_y();
-        assert_eq!(7, cpu.wait_counter);
+        assert_eq!(7, instruction_cycles(&cpu));
     }
 
 
@@ -8214,14 +8460,14 @@ mod tests {
 
         cpu.program_counter = 0x32;
         cpu.x = 0x20;
-        cpu.memory.borrow_mut().write(0x32, 0x3E);
+        cpu.bus.write(0x32, 0x3E);
 
-        cpu.memory.borrow_mut().write(0x3E + 0x20, 0x07);
-        cpu.memory.borrow_mut().write(0x3F + 0x20, 0x3F);
-        cpu.memory.borrow_mut().write(0x3F07, 0x4F);
+        cpu.bus.write(0x3E + 0x20, 0x07);
+        cpu.bus.write(0x3F + 0x20, 0x3F);
+        cpu.bus.write(0x3F07, 0x4F);
 
         cpu.unofficial_decrement_memory_and_compare_with_acc_indirect_x();
-        assert_eq!(0x4E, cpu.memory.borrow_mut().read(0x3F07));
+        assert_eq!(0x4E, cpu.bus.read(0x3F07));
     }
 
     #[test]
@@ -8231,11 +8477,11 @@ mod tests {
         cpu.x = 0x20;
         cpu.program_counter = 0x32;
         cpu.status_flags = 0x00;
-        cpu.memory.borrow_mut().write(0x32, 0x3E);
+        cpu.bus.write(0x32, 0x3E);
 
-        cpu.memory.borrow_mut().write(0x3E + 0x20, 0x07);
-        cpu.memory.borrow_mut().write(0x3F + 0x20, 0x3F);
-        cpu.memory.borrow_mut().write(0x3F07, 0x4F);
+        cpu.bus.write(0x3E + 0x20, 0x07);
+        cpu.bus.write(0x3F + 0x20, 0x3F);
+        cpu.bus.write(0x3F07, 0x4F);
 
         cpu.unofficial_decrement_memory_and_compare_with_acc_indirect_x();
         assert_eq!(0x03, cpu.status_flags);
@@ -8253,7 +8499,7 @@ mod tests {
     fn unofficial_decrement_memory_and_compare_with_acc_indirect_x_takes_8_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_decrement_memory_and_compare_with_acc_indirect_x();
-        assert_eq!(8, cpu.wait_counter);
+        assert_eq!(8, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8262,14 +8508,14 @@ mod tests {
 
         cpu.program_counter = 0x32;
         cpu.y = 0x20;
-        cpu.memory.borrow_mut().write(0x32, 0x3E);
+        cpu.bus.write(0x32, 0x3E);
 
-        cpu.memory.borrow_mut().write(0x3E, 0x07);
-        cpu.memory.borrow_mut().write(0x3F, 0x3F);
-        cpu.memory.borrow_mut().write(0x3F07 + 0x20, 0x4F);
+        cpu.bus.write(0x3E, 0x07);
+        cpu.bus.write(0x3F, 0x3F);
+        cpu.bus.write(0x3F07 + 0x20, 0x4F);
 
         cpu.unofficial_decrement_memory_and_compare_with_acc_indirect_y();
-        assert_eq!(0x4E, cpu.memory.borrow_mut().read(0x3F07 + 0x20));
+        assert_eq!(0x4E, cpu.bus.read(0x3F07 + 0x20));
     }
 
     #[test]
@@ -8279,11 +8525,11 @@ mod tests {
         cpu.y = 0x20;
         cpu.program_counter = 0x32;
         cpu.status_flags = 0x00;
-        cpu.memory.borrow_mut().write(0x32, 0x3E);
+        cpu.bus.write(0x32, 0x3E);
 
-        cpu.memory.borrow_mut().write(0x3E, 0x07);
-        cpu.memory.borrow_mut().write(0x3F, 0x3F);
-        cpu.memory.borrow_mut().write(0x3F07 + 0x20, 0x4F);
+        cpu.bus.write(0x3E, 0x07);
+        cpu.bus.write(0x3F, 0x3F);
+        cpu.bus.write(0x3F07 + 0x20, 0x4F);
 
         cpu.unofficial_decrement_memory_and_compare_with_acc_indirect_y();
         assert_eq!(0x03, cpu.status_flags);
@@ -8301,7 +8547,7 @@ mod tests {
     fn unofficial_decrement_memory_and_compare_with_acc_indirect_y_takes_8_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_decrement_memory_and_compare_with_acc_indirect_y();
-        assert_eq!(8, cpu.wait_counter);
+        assert_eq!(8, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8309,11 +8555,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x234;
 
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x4F, 0x50);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x4F, 0x50);
 
         cpu.unofficial_increment_memory_subtract_acc_zero_page();
-        assert_eq!(0x51, cpu.memory.borrow_mut().read(0x4F));
+        assert_eq!(0x51, cpu.bus.read(0x4F));
     }
 
     #[test]
@@ -8323,8 +8569,8 @@ mod tests {
         cpu.x = 0x71;
         cpu.a = 0x80;
         cpu.status_flags = 0x01;
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x4F, 0x50);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x4F, 0x50);
 
         cpu.unofficial_increment_memory_subtract_acc_zero_page();
         assert_eq!(0x80 - 0x51, cpu.a); // carry not set === borrow set
@@ -8342,7 +8588,7 @@ mod tests {
     fn unofficial_increment_memory_subtract_acc_zero_page_takes_5_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_increment_memory_subtract_acc_zero_page();
-        assert_eq!(5, cpu.wait_counter);
+        assert_eq!(5, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8351,11 +8597,11 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.x = 0x25;
 
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x4F + 0x25, 0x50);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x4F + 0x25, 0x50);
 
         cpu.unofficial_increment_memory_subtract_acc_zero_page_x();
-        assert_eq!(0x51, cpu.memory.borrow_mut().read(0x4F + 0x25));
+        assert_eq!(0x51, cpu.bus.read(0x4F + 0x25));
     }
 
     #[test]
@@ -8365,8 +8611,8 @@ mod tests {
         cpu.x = 0x71;
         cpu.a = 0x80;
         cpu.status_flags = 0x01;
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x4F + 0x71, 0x50);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x4F + 0x71, 0x50);
 
         cpu.unofficial_increment_memory_subtract_acc_zero_page_x();
         assert_eq!(0x80 - 0x51, cpu.a); // carry not set === borrow set
@@ -8384,7 +8630,7 @@ mod tests {
     fn unofficial_increment_memory_subtract_acc_zero_page_x_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_increment_memory_subtract_acc_zero_page_x();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8392,12 +8638,12 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x234;
 
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x235, 0x12);
-        cpu.memory.borrow_mut().write(0x124F, 0x50);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x235, 0x12);
+        cpu.bus.write(0x124F, 0x50);
 
         cpu.unofficial_increment_memory_subtract_acc_absolute();
-        assert_eq!(0x51, cpu.memory.borrow_mut().read(0x124F));
+        assert_eq!(0x51, cpu.bus.read(0x124F));
     }
 
     #[test]
@@ -8407,9 +8653,9 @@ mod tests {
         cpu.x = 0x71;
         cpu.a = 0x80;
 
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x235, 0x12);
-        cpu.memory.borrow_mut().write(0x124F, 0x50);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x235, 0x12);
+        cpu.bus.write(0x124F, 0x50);
 
         cpu.unofficial_increment_memory_subtract_acc_absolute();
         assert_eq!(0x80 - 0x51 - 1, cpu.a); // carry not set === borrow set
@@ -8427,7 +8673,7 @@ mod tests {
     fn unofficial_increment_memory_subtract_acc_absolute_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_increment_memory_subtract_acc_absolute();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8436,12 +8682,12 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.x = 0x71;
 
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x235, 0x12);
-        cpu.memory.borrow_mut().write(0x124F + 0x71, 0x50);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x235, 0x12);
+        cpu.bus.write(0x124F + 0x71, 0x50);
 
         cpu.unofficial_increment_memory_subtract_acc_absolute_x();
-        assert_eq!(0x51, cpu.memory.borrow_mut().read(0x124F + 0x71));
+        assert_eq!(0x51, cpu.bus.read(0x124F + 0x71));
     }
 
     #[test]
@@ -8451,9 +8697,9 @@ mod tests {
         cpu.x = 0x71;
         cpu.a = 0x80;
 
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x235, 0x12);
-        cpu.memory.borrow_mut().write(0x124F + 0x71, 0x50);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x235, 0x12);
+        cpu.bus.write(0x124F + 0x71, 0x50);
 
         cpu.unofficial_increment_memory_subtract_acc_absolute_x();
         assert_eq!(0x80 - 0x51 - 1, cpu.a); // carry not set === borrow set
@@ -8471,7 +8717,7 @@ mod tests {
     fn unofficial_increment_memory_subtract_acc_absolute_x_takes_7_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_increment_memory_subtract_acc_absolute_x();
-        assert_eq!(7, cpu.wait_counter);
+        assert_eq!(7, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8480,12 +8726,12 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.y = 0x71;
 
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x235, 0x12);
-        cpu.memory.borrow_mut().write(0x124F + 0x71, 0x50);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x235, 0x12);
+        cpu.bus.write(0x124F + 0x71, 0x50);
 
         cpu.unofficial_increment_memory_subtract_acc_absolute_y();
-        assert_eq!(0x51, cpu.memory.borrow_mut().read(0x124F + 0x71));
+        assert_eq!(0x51, cpu.bus.read(0x124F + 0x71));
     }
 
     #[test]
@@ -8495,9 +8741,9 @@ mod tests {
         cpu.y = 0x71;
         cpu.a = 0x80;
         cpu.status_flags = 0x01;
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x235, 0x12);
-        cpu.memory.borrow_mut().write(0x124F + 0x71, 0x50);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x235, 0x12);
+        cpu.bus.write(0x124F + 0x71, 0x50);
 
         cpu.unofficial_increment_memory_subtract_acc_absolute_y();
         assert_eq!(0x80 - 0x51, cpu.a);
@@ -8515,7 +8761,7 @@ mod tests {
     fn unofficial_increment_memory_subtract_acc_absolute_y_takes_7_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_increment_memory_subtract_acc_absolute_y();
-        assert_eq!(7, cpu.wait_counter);
+        assert_eq!(7, instruction_cycles(&cpu));
     }
 
 
@@ -8525,15 +8771,15 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.x = 0x30;
 
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
+        cpu.bus.write(0x234, 0x4F);
 
-        cpu.memory.borrow_mut().write(0x4F + 0x30, 0xA1);
-        cpu.memory.borrow_mut().write(0x50 + 0x30, 0xB2);
+        cpu.bus.write(0x4F + 0x30, 0xA1);
+        cpu.bus.write(0x50 + 0x30, 0xB2);
 
-        cpu.memory.borrow_mut().write(0xB2A1, 0x50);
+        cpu.bus.write(0xB2A1, 0x50);
 
         cpu.unofficial_increment_memory_subtract_acc_indirect_x();
-        assert_eq!(0x51, cpu.memory.borrow_mut().read(0xB2A1));
+        assert_eq!(0x51, cpu.bus.read(0xB2A1));
     }
 
     #[test]
@@ -8543,11 +8789,11 @@ mod tests {
 
         cpu.a = 0x80;
         cpu.x = 0x30;
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
+        cpu.bus.write(0x234, 0x4F);
 
-        cpu.memory.borrow_mut().write(0x4F + 0x30, 0xA1);
-        cpu.memory.borrow_mut().write(0x50 + 0x30, 0xB2);
-        cpu.memory.borrow_mut().write(0xB2A1, 0x50);
+        cpu.bus.write(0x4F + 0x30, 0xA1);
+        cpu.bus.write(0x50 + 0x30, 0xB2);
+        cpu.bus.write(0xB2A1, 0x50);
 
         cpu.unofficial_increment_memory_subtract_acc_indirect_x();
         assert_eq!(0x80 - 0x51 - 1, cpu.a);
@@ -8565,7 +8811,7 @@ mod tests {
     fn unofficial_increment_memory_subtract_acc_indirect_x_takes_7_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_increment_memory_subtract_acc_indirect_x();
-        assert_eq!(8, cpu.wait_counter);
+        assert_eq!(8, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8574,15 +8820,15 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.y = 0x30;
 
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
+        cpu.bus.write(0x234, 0x4F);
 
-        cpu.memory.borrow_mut().write(0x4F, 0xA1);
-        cpu.memory.borrow_mut().write(0x50, 0xB2);
+        cpu.bus.write(0x4F, 0xA1);
+        cpu.bus.write(0x50, 0xB2);
 
-        cpu.memory.borrow_mut().write(0xB2A1 + 0x30, 0x50);
+        cpu.bus.write(0xB2A1 + 0x30, 0x50);
 
         cpu.unofficial_increment_memory_subtract_acc_indirect_y();
-        assert_eq!(0x51, cpu.memory.borrow_mut().read(0xB2A1 + 0x30));
+        assert_eq!(0x51, cpu.bus.read(0xB2A1 + 0x30));
     }
 
     #[test]
@@ -8592,11 +8838,11 @@ mod tests {
 
         cpu.a = 0x80;
         cpu.y = 0x30;
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
+        cpu.bus.write(0x234, 0x4F);
 
-        cpu.memory.borrow_mut().write(0x4F, 0xA1);
-        cpu.memory.borrow_mut().write(0x50, 0xB2);
-        cpu.memory.borrow_mut().write(0xB2A1 + 0x30, 0x50);
+        cpu.bus.write(0x4F, 0xA1);
+        cpu.bus.write(0x50, 0xB2);
+        cpu.bus.write(0xB2A1 + 0x30, 0x50);
 
         cpu.unofficial_increment_memory_subtract_acc_indirect_y();
         assert_eq!(0x80 - 0x51 - 1, cpu.a);
@@ -8614,7 +8860,7 @@ mod tests {
     fn unofficial_increment_memory_subtract_acc_indirect_y_takes_7_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_increment_memory_subtract_acc_indirect_y();
-        assert_eq!(8, cpu.wait_counter);
+        assert_eq!(8, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8622,10 +8868,10 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x4F, 0x5A);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x4F, 0x5A);
         cpu.unofficial_shift_left_memory_inclusive_or_acc_zero_page();
-        assert_eq!(0xB4, cpu.memory.borrow_mut().read(0x4F));
+        assert_eq!(0xB4, cpu.bus.read(0x4F));
     }
 
     #[test]
@@ -8634,8 +8880,8 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
 
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x4F, 0x5A);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x4F, 0x5A);
         cpu.unofficial_shift_left_memory_inclusive_or_acc_zero_page();
         assert_eq!(0xF6, cpu.a);
     }
@@ -8644,7 +8890,7 @@ mod tests {
     fn unofficial_shift_left_memory_inclusive_or_acc_zero_page_takes_5_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_shift_left_memory_inclusive_or_acc_zero_page();
-        assert_eq!(5, cpu.wait_counter);
+        assert_eq!(5, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8652,10 +8898,10 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x13;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x4F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x4F + 0x13, 0x5A);
         cpu.unofficial_shift_left_memory_inclusive_or_acc_zero_page_x();
-        assert_eq!(0xB4, cpu.memory.borrow_mut().read(0x4F + 0x13));
+        assert_eq!(0xB4, cpu.bus.read(0x4F + 0x13));
     }
 
     #[test]
@@ -8664,8 +8910,8 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.x = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x4F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x4F + 0x13, 0x5A);
         cpu.unofficial_shift_left_memory_inclusive_or_acc_zero_page_x();
         assert_eq!(0xF6, cpu.a);
     }
@@ -8674,7 +8920,7 @@ mod tests {
     fn unofficial_shift_left_memory_inclusive_or_acc_zero_page_x_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_shift_left_memory_inclusive_or_acc_absolute();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8682,11 +8928,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x13;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F, 0x5A);
         cpu.unofficial_shift_left_memory_inclusive_or_acc_absolute();
-        assert_eq!(0xB4, cpu.memory.borrow_mut().read(0x431F));
+        assert_eq!(0xB4, cpu.bus.read(0x431F));
     }
 
     #[test]
@@ -8695,9 +8941,9 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.x = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F, 0x5A);
         cpu.unofficial_shift_left_memory_inclusive_or_acc_absolute();
         assert_eq!(0xF6, cpu.a);
     }
@@ -8706,7 +8952,7 @@ mod tests {
     fn unofficial_shift_left_memory_inclusive_or_acc_absolute_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_shift_left_memory_inclusive_or_acc_absolute();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8714,11 +8960,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x13;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F + 0x13, 0x5A);
         cpu.unofficial_shift_left_memory_inclusive_or_acc_absolute_x();
-        assert_eq!(0xB4, cpu.memory.borrow_mut().read(0x431F + 0x13));
+        assert_eq!(0xB4, cpu.bus.read(0x431F + 0x13));
     }
 
     #[test]
@@ -8727,9 +8973,9 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.x = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F + 0x13, 0x5A);
         cpu.unofficial_shift_left_memory_inclusive_or_acc_absolute_x();
         assert_eq!(0xF6, cpu.a);
     }
@@ -8738,7 +8984,7 @@ mod tests {
     fn unofficial_shift_left_memory_inclusive_or_acc_absolute_x_takes_7_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_shift_left_memory_inclusive_or_acc_absolute_x();
-        assert_eq!(7, cpu.wait_counter);
+        assert_eq!(7, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8746,11 +8992,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.y = 0x13;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F + 0x13, 0x5A);
         cpu.unofficial_shift_left_memory_inclusive_or_acc_absolute_y();
-        assert_eq!(0xB4, cpu.memory.borrow_mut().read(0x431F + 0x13));
+        assert_eq!(0xB4, cpu.bus.read(0x431F + 0x13));
     }
 
     #[test]
@@ -8759,9 +9005,9 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.y = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F + 0x13, 0x5A);
         cpu.unofficial_shift_left_memory_inclusive_or_acc_absolute_y();
         assert_eq!(0xF6, cpu.a);
     }
@@ -8770,7 +9016,7 @@ mod tests {
     fn unofficial_shift_left_memory_inclusive_or_acc_absolute_y_takes_7_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_shift_left_memory_inclusive_or_acc_absolute_y();
-        assert_eq!(7, cpu.wait_counter);
+        assert_eq!(7, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8779,13 +9025,13 @@ mod tests {
         cpu.x = 0x13;
         cpu.program_counter = 0x234;
 
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x1F + 0x13, 0x02);
-        cpu.memory.borrow_mut().write(0x20 + 0x13, 0x0A);
-        cpu.memory.borrow_mut().write(0x0A02, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x1F + 0x13, 0x02);
+        cpu.bus.write(0x20 + 0x13, 0x0A);
+        cpu.bus.write(0x0A02, 0x5A);
 
         cpu.unofficial_shift_left_memory_inclusive_or_acc_indirect_x();
-        assert_eq!(0xB4, cpu.memory.borrow_mut().read(0x0A02));
+        assert_eq!(0xB4, cpu.bus.read(0x0A02));
     }
 
     #[test]
@@ -8794,10 +9040,10 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.x = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x1F + 0x13, 0x02);
-        cpu.memory.borrow_mut().write(0x20 + 0x13, 0x0A);
-        cpu.memory.borrow_mut().write(0x0A02, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x1F + 0x13, 0x02);
+        cpu.bus.write(0x20 + 0x13, 0x0A);
+        cpu.bus.write(0x0A02, 0x5A);
 
         cpu.unofficial_shift_left_memory_inclusive_or_acc_indirect_x();
         assert_eq!(0xF6, cpu.a);
@@ -8807,7 +9053,7 @@ mod tests {
     fn unofficial_shift_left_memory_inclusive_or_acc_indirect_x_takes_8_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_shift_left_memory_inclusive_or_acc_indirect_x();
-        assert_eq!(8, cpu.wait_counter);
+        assert_eq!(8, instruction_cycles(&cpu));
     }
 
 
@@ -8817,13 +9063,13 @@ mod tests {
         cpu.y = 0x13;
         cpu.program_counter = 0x234;
 
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x1F, 0x02);
-        cpu.memory.borrow_mut().write(0x20, 0x0A);
-        cpu.memory.borrow_mut().write(0x0A02 + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x1F, 0x02);
+        cpu.bus.write(0x20, 0x0A);
+        cpu.bus.write(0x0A02 + 0x13, 0x5A);
 
         cpu.unofficial_shift_left_memory_inclusive_or_acc_indirect_y();
-        assert_eq!(0xB4, cpu.memory.borrow_mut().read(0x0A02 + 0x13));
+        assert_eq!(0xB4, cpu.bus.read(0x0A02 + 0x13));
     }
 
     #[test]
@@ -8832,10 +9078,10 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.y = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x1F, 0x02);
-        cpu.memory.borrow_mut().write(0x20, 0x0A);
-        cpu.memory.borrow_mut().write(0x0A02 + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x1F, 0x02);
+        cpu.bus.write(0x20, 0x0A);
+        cpu.bus.write(0x0A02 + 0x13, 0x5A);
 
         cpu.unofficial_shift_left_memory_inclusive_or_acc_indirect_y();
         assert_eq!(0xF6, cpu.a);
@@ -8845,7 +9091,7 @@ mod tests {
     fn unofficial_shift_left_memory_inclusive_or_acc_indirect_y_takes_8_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_shift_left_memory_inclusive_or_acc_indirect_y();
-        assert_eq!(8, cpu.wait_counter);
+        assert_eq!(8, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8853,10 +9099,10 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x4F, 0x5A);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x4F, 0x5A);
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_zero_page();
-        assert_eq!(0xB4, cpu.memory.borrow_mut().read(0x4F));
+        assert_eq!(0xB4, cpu.bus.read(0x4F));
     }
 
     #[test]
@@ -8865,8 +9111,8 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
 
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x4F, 0x5A);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x4F, 0x5A);
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_zero_page();
         assert_eq!(0x30, cpu.a);
     }
@@ -8875,7 +9121,7 @@ mod tests {
     fn unofficial_rotate_left_memory_bitwise_and_acc_zero_page_takes_5_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_zero_page();
-        assert_eq!(5, cpu.wait_counter);
+        assert_eq!(5, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8883,10 +9129,10 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x13;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x4F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x4F + 0x13, 0x5A);
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_zero_page_x();
-        assert_eq!(0xB4, cpu.memory.borrow_mut().read(0x4F + 0x13));
+        assert_eq!(0xB4, cpu.bus.read(0x4F + 0x13));
     }
 
     #[test]
@@ -8895,8 +9141,8 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.x = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x4F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x4F + 0x13, 0x5A);
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_zero_page_x();
         assert_eq!(0x30, cpu.a);
     }
@@ -8905,7 +9151,7 @@ mod tests {
     fn unofficial_rotate_left_memory_bitwise_and_acc_zero_page_x_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_absolute();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8913,11 +9159,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x13;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F, 0x5A);
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_absolute();
-        assert_eq!(0xB4, cpu.memory.borrow_mut().read(0x431F));
+        assert_eq!(0xB4, cpu.bus.read(0x431F));
     }
 
     #[test]
@@ -8926,9 +9172,9 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.x = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F, 0x5A);
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_absolute();
         assert_eq!(0x30, cpu.a);
     }
@@ -8937,7 +9183,7 @@ mod tests {
     fn unofficial_rotate_left_memory_bitwise_and_acc_absolute_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_absolute();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8945,11 +9191,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x13;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F + 0x13, 0x5A);
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_absolute_x();
-        assert_eq!(0xB4, cpu.memory.borrow_mut().read(0x431F + 0x13));
+        assert_eq!(0xB4, cpu.bus.read(0x431F + 0x13));
     }
 
     #[test]
@@ -8958,9 +9204,9 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.x = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F + 0x13, 0x5A);
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_absolute_x();
         assert_eq!(0x30, cpu.a);
     }
@@ -8969,7 +9215,7 @@ mod tests {
     fn unofficial_rotate_left_memory_bitwise_and_acc_absolute_x_takes_7_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_absolute_x();
-        assert_eq!(7, cpu.wait_counter);
+        assert_eq!(7, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -8977,11 +9223,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.y = 0x13;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F + 0x13, 0x5A);
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_absolute_y();
-        assert_eq!(0xB4, cpu.memory.borrow_mut().read(0x431F + 0x13));
+        assert_eq!(0xB4, cpu.bus.read(0x431F + 0x13));
     }
 
     #[test]
@@ -8990,9 +9236,9 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.y = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F + 0x13, 0x5A);
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_absolute_y();
         assert_eq!(0x30, cpu.a);
     }
@@ -9001,7 +9247,7 @@ mod tests {
     fn unofficial_rotate_left_memory_bitwise_and_acc_absolute_y_takes_7_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_absolute_y();
-        assert_eq!(7, cpu.wait_counter);
+        assert_eq!(7, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -9010,13 +9256,13 @@ mod tests {
         cpu.x = 0x13;
         cpu.program_counter = 0x234;
 
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x1F + 0x13, 0x02);
-        cpu.memory.borrow_mut().write(0x20 + 0x13, 0x0A);
-        cpu.memory.borrow_mut().write(0x0A02, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x1F + 0x13, 0x02);
+        cpu.bus.write(0x20 + 0x13, 0x0A);
+        cpu.bus.write(0x0A02, 0x5A);
 
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_indirect_x();
-        assert_eq!(0xB4, cpu.memory.borrow_mut().read(0x0A02));
+        assert_eq!(0xB4, cpu.bus.read(0x0A02));
     }
 
     #[test]
@@ -9025,10 +9271,10 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.x = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x1F + 0x13, 0x02);
-        cpu.memory.borrow_mut().write(0x20 + 0x13, 0x0A);
-        cpu.memory.borrow_mut().write(0x0A02, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x1F + 0x13, 0x02);
+        cpu.bus.write(0x20 + 0x13, 0x0A);
+        cpu.bus.write(0x0A02, 0x5A);
 
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_indirect_x();
         assert_eq!(0x30, cpu.a);
@@ -9038,7 +9284,7 @@ mod tests {
     fn unofficial_rotate_left_memory_bitwise_and_acc_indirect_x_takes_8_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_indirect_x();
-        assert_eq!(8, cpu.wait_counter);
+        assert_eq!(8, instruction_cycles(&cpu));
     }
 
 
@@ -9048,13 +9294,13 @@ mod tests {
         cpu.y = 0x13;
         cpu.program_counter = 0x234;
 
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x1F, 0x02);
-        cpu.memory.borrow_mut().write(0x20, 0x0A);
-        cpu.memory.borrow_mut().write(0x0A02 + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x1F, 0x02);
+        cpu.bus.write(0x20, 0x0A);
+        cpu.bus.write(0x0A02 + 0x13, 0x5A);
 
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_indirect_y();
-        assert_eq!(0xB4, cpu.memory.borrow_mut().read(0x0A02 + 0x13));
+        assert_eq!(0xB4, cpu.bus.read(0x0A02 + 0x13));
     }
 
     #[test]
@@ -9063,10 +9309,10 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.y = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x1F, 0x02);
-        cpu.memory.borrow_mut().write(0x20, 0x0A);
-        cpu.memory.borrow_mut().write(0x0A02 + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x1F, 0x02);
+        cpu.bus.write(0x20, 0x0A);
+        cpu.bus.write(0x0A02 + 0x13, 0x5A);
 
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_indirect_y();
         assert_eq!(0x30, cpu.a);
@@ -9076,7 +9322,7 @@ mod tests {
     fn unofficial_rotate_left_memory_bitwise_and_acc_indirect_y_takes_8_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_rotate_left_memory_bitwise_and_acc_indirect_y();
-        assert_eq!(8, cpu.wait_counter);
+        assert_eq!(8, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -9084,10 +9330,10 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x4F, 0x5A);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x4F, 0x5A);
         cpu.unofficial_shift_right_memory_xor_acc_zero_page();
-        assert_eq!(0x2D, cpu.memory.borrow_mut().read(0x4F));
+        assert_eq!(0x2D, cpu.bus.read(0x4F));
     }
 
     #[test]
@@ -9096,8 +9342,8 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
 
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x4F, 0x5A);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x4F, 0x5A);
         cpu.unofficial_shift_right_memory_xor_acc_zero_page();
         assert_eq!(0x5F,  cpu.a);
     }
@@ -9106,7 +9352,7 @@ mod tests {
     fn unofficial_shift_right_memory_xor_acc_zero_page_takes_5_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_shift_right_memory_xor_acc_zero_page();
-        assert_eq!(5, cpu.wait_counter);
+        assert_eq!(5, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -9114,10 +9360,10 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x13;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x4F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x4F + 0x13, 0x5A);
         cpu.unofficial_shift_right_memory_xor_acc_zero_page_x();
-        assert_eq!(0x2D, cpu.memory.borrow_mut().read(0x4F + 0x13));
+        assert_eq!(0x2D, cpu.bus.read(0x4F + 0x13));
     }
 
     #[test]
@@ -9126,8 +9372,8 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.x = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x4F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x4F + 0x13, 0x5A);
         cpu.unofficial_shift_right_memory_xor_acc_zero_page_x();
         assert_eq!(0x5F, cpu.a);
     }
@@ -9136,7 +9382,7 @@ mod tests {
     fn unofficial_shift_right_memory_xor_acc_zero_page_x_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_shift_right_memory_xor_acc_absolute();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -9144,11 +9390,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x13;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F, 0x5A);
         cpu.unofficial_shift_right_memory_xor_acc_absolute();
-        assert_eq!(0x2D, cpu.memory.borrow_mut().read(0x431F));
+        assert_eq!(0x2D, cpu.bus.read(0x431F));
     }
 
     #[test]
@@ -9157,9 +9403,9 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.x = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F, 0x5A);
         cpu.unofficial_shift_right_memory_xor_acc_absolute();
         assert_eq!(0x5F, cpu.a);
     }
@@ -9168,7 +9414,7 @@ mod tests {
     fn unofficial_shift_right_memory_xor_acc_absolute_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_shift_right_memory_xor_acc_absolute();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -9176,11 +9422,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x13;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F + 0x13, 0x5A);
         cpu.unofficial_shift_right_memory_xor_acc_absolute_x();
-        assert_eq!(0x2D, cpu.memory.borrow_mut().read(0x431F + 0x13));
+        assert_eq!(0x2D, cpu.bus.read(0x431F + 0x13));
     }
 
     #[test]
@@ -9189,9 +9435,9 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.x = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F + 0x13, 0x5A);
         cpu.unofficial_shift_right_memory_xor_acc_absolute_x();
         assert_eq!(0x5F, cpu.a);
     }
@@ -9200,7 +9446,7 @@ mod tests {
     fn unofficial_shift_right_memory_xor_acc_absolute_x_takes_7_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_shift_right_memory_xor_acc_absolute_x();
-        assert_eq!(7, cpu.wait_counter);
+        assert_eq!(7, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -9208,11 +9454,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.y = 0x13;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F + 0x13, 0x5A);
         cpu.unofficial_shift_right_memory_xor_acc_absolute_y();
-        assert_eq!(0x2D, cpu.memory.borrow_mut().read(0x431F + 0x13));
+        assert_eq!(0x2D, cpu.bus.read(0x431F + 0x13));
     }
 
     #[test]
@@ -9221,9 +9467,9 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.y = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F + 0x13, 0x5A);
         cpu.unofficial_shift_right_memory_xor_acc_absolute_y();
         assert_eq!(0x5F, cpu.a);
     }
@@ -9232,7 +9478,7 @@ mod tests {
     fn unofficial_shift_right_memory_xor_acc_absolute_y_takes_7_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_shift_right_memory_xor_acc_absolute_y();
-        assert_eq!(7, cpu.wait_counter);
+        assert_eq!(7, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -9241,13 +9487,13 @@ mod tests {
         cpu.x = 0x13;
         cpu.program_counter = 0x234;
 
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x1F + 0x13, 0x02);
-        cpu.memory.borrow_mut().write(0x20 + 0x13, 0x0A);
-        cpu.memory.borrow_mut().write(0x0A02, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x1F + 0x13, 0x02);
+        cpu.bus.write(0x20 + 0x13, 0x0A);
+        cpu.bus.write(0x0A02, 0x5A);
 
         cpu.unofficial_shift_right_memory_xor_acc_indirect_x();
-        assert_eq!(0x2D, cpu.memory.borrow_mut().read(0x0A02));
+        assert_eq!(0x2D, cpu.bus.read(0x0A02));
     }
 
     #[test]
@@ -9256,10 +9502,10 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.x = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x1F + 0x13, 0x02);
-        cpu.memory.borrow_mut().write(0x20 + 0x13, 0x0A);
-        cpu.memory.borrow_mut().write(0x0A02, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x1F + 0x13, 0x02);
+        cpu.bus.write(0x20 + 0x13, 0x0A);
+        cpu.bus.write(0x0A02, 0x5A);
 
         cpu.unofficial_shift_right_memory_xor_acc_indirect_x();
         assert_eq!(0x5F, cpu.a);
@@ -9269,7 +9515,7 @@ mod tests {
     fn unofficial_shift_right_memory_xor_acc_indirect_x_takes_8_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_shift_right_memory_xor_acc_indirect_x();
-        assert_eq!(8, cpu.wait_counter);
+        assert_eq!(8, instruction_cycles(&cpu));
     }
 
 
@@ -9279,13 +9525,13 @@ mod tests {
         cpu.y = 0x13;
         cpu.program_counter = 0x234;
 
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x1F, 0x02);
-        cpu.memory.borrow_mut().write(0x20, 0x0A);
-        cpu.memory.borrow_mut().write(0x0A02 + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x1F, 0x02);
+        cpu.bus.write(0x20, 0x0A);
+        cpu.bus.write(0x0A02 + 0x13, 0x5A);
 
         cpu.unofficial_shift_right_memory_xor_acc_indirect_y();
-        assert_eq!(0x2D, cpu.memory.borrow_mut().read(0x0A02 + 0x13));
+        assert_eq!(0x2D, cpu.bus.read(0x0A02 + 0x13));
     }
 
     #[test]
@@ -9294,10 +9540,10 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.y = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x1F, 0x02);
-        cpu.memory.borrow_mut().write(0x20, 0x0A);
-        cpu.memory.borrow_mut().write(0x0A02 + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x1F, 0x02);
+        cpu.bus.write(0x20, 0x0A);
+        cpu.bus.write(0x0A02 + 0x13, 0x5A);
 
         cpu.unofficial_shift_right_memory_xor_acc_indirect_y();
         assert_eq!(0x5F, cpu.a);
@@ -9307,7 +9553,7 @@ mod tests {
     fn unofficial_shift_right_memory_xor_acc_indirect_y_takes_8_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_shift_right_memory_xor_acc_indirect_y();
-        assert_eq!(8, cpu.wait_counter);
+        assert_eq!(8, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -9315,10 +9561,10 @@ mod tests {
         let mut cpu = create_test_cpu();
 
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x4F, 0x5A);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x4F, 0x5A);
         cpu.unofficial_rotate_right_memory_add_acc_zero_page();
-        assert_eq!(0x2D, cpu.memory.borrow_mut().read(0x4F));
+        assert_eq!(0x2D, cpu.bus.read(0x4F));
     }
 
     #[test]
@@ -9327,8 +9573,8 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
 
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x4F, 0x5A);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x4F, 0x5A);
         cpu.unofficial_rotate_right_memory_add_acc_zero_page();
         assert_eq!(0x72 + 0x2D,  cpu.a);
     }
@@ -9337,7 +9583,7 @@ mod tests {
     fn unofficial_rotate_right_memory_add_acc_zero_page_takes_5_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_rotate_right_memory_add_acc_zero_page();
-        assert_eq!(5, cpu.wait_counter);
+        assert_eq!(5, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -9345,10 +9591,10 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x13;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x4F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x4F + 0x13, 0x5A);
         cpu.unofficial_rotate_right_memory_add_acc_zero_page_x();
-        assert_eq!(0x2D, cpu.memory.borrow_mut().read(0x4F + 0x13));
+        assert_eq!(0x2D, cpu.bus.read(0x4F + 0x13));
     }
 
     #[test]
@@ -9357,8 +9603,8 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.x = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x4F);
-        cpu.memory.borrow_mut().write(0x4F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x4F);
+        cpu.bus.write(0x4F + 0x13, 0x5A);
         cpu.unofficial_rotate_right_memory_add_acc_zero_page_x();
         assert_eq!(0x72 + 0x2D, cpu.a);
     }
@@ -9367,7 +9613,7 @@ mod tests {
     fn unofficial_rotate_right_memory_add_acc_zero_page_x_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_rotate_right_memory_add_acc_absolute();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -9375,11 +9621,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x13;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F, 0x5A);
         cpu.unofficial_rotate_right_memory_add_acc_absolute();
-        assert_eq!(0x2D, cpu.memory.borrow_mut().read(0x431F));
+        assert_eq!(0x2D, cpu.bus.read(0x431F));
     }
 
     #[test]
@@ -9388,9 +9634,9 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.x = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F, 0x5A);
         cpu.unofficial_rotate_right_memory_add_acc_absolute();
         assert_eq!(0x72 + 0x2D, cpu.a);
     }
@@ -9399,7 +9645,7 @@ mod tests {
     fn unofficial_rotate_right_memory_add_acc_absolute_takes_6_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_rotate_right_memory_add_acc_absolute();
-        assert_eq!(6, cpu.wait_counter);
+        assert_eq!(6, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -9407,11 +9653,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.x = 0x13;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F + 0x13, 0x5A);
         cpu.unofficial_rotate_right_memory_add_acc_absolute_x();
-        assert_eq!(0x2D, cpu.memory.borrow_mut().read(0x431F + 0x13));
+        assert_eq!(0x2D, cpu.bus.read(0x431F + 0x13));
     }
 
     #[test]
@@ -9420,9 +9666,9 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.x = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F + 0x13, 0x5A);
         cpu.unofficial_rotate_right_memory_add_acc_absolute_x();
         assert_eq!(0x72 + 0x2D, cpu.a);
     }
@@ -9431,7 +9677,7 @@ mod tests {
     fn unofficial_rotate_right_memory_add_acc_absolute_x_takes_7_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_rotate_right_memory_add_acc_absolute_x();
-        assert_eq!(7, cpu.wait_counter);
+        assert_eq!(7, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -9439,11 +9685,11 @@ mod tests {
         let mut cpu = create_test_cpu();
         cpu.y = 0x13;
         cpu.program_counter = 0x234;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F + 0x13, 0x5A);
         cpu.unofficial_rotate_right_memory_add_acc_absolute_y();
-        assert_eq!(0x2D, cpu.memory.borrow_mut().read(0x431F + 0x13));
+        assert_eq!(0x2D, cpu.bus.read(0x431F + 0x13));
     }
 
     #[test]
@@ -9452,9 +9698,9 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.y = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x235, 0x43);
-        cpu.memory.borrow_mut().write(0x431F + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x235, 0x43);
+        cpu.bus.write(0x431F + 0x13, 0x5A);
         cpu.unofficial_rotate_right_memory_add_acc_absolute_y();
         assert_eq!(0x72 + 0x2D, cpu.a);
     }
@@ -9463,7 +9709,7 @@ mod tests {
     fn unofficial_rotate_right_memory_add_acc_absolute_y_takes_7_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_rotate_right_memory_add_acc_absolute_y();
-        assert_eq!(7, cpu.wait_counter);
+        assert_eq!(7, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -9472,13 +9718,13 @@ mod tests {
         cpu.x = 0x13;
         cpu.program_counter = 0x234;
 
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x1F + 0x13, 0x02);
-        cpu.memory.borrow_mut().write(0x20 + 0x13, 0x0A);
-        cpu.memory.borrow_mut().write(0x0A02, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x1F + 0x13, 0x02);
+        cpu.bus.write(0x20 + 0x13, 0x0A);
+        cpu.bus.write(0x0A02, 0x5A);
 
         cpu.unofficial_rotate_right_memory_add_acc_indirect_x();
-        assert_eq!(0x2D, cpu.memory.borrow_mut().read(0x0A02));
+        assert_eq!(0x2D, cpu.bus.read(0x0A02));
     }
 
     #[test]
@@ -9487,10 +9733,10 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.x = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x1F + 0x13, 0x02);
-        cpu.memory.borrow_mut().write(0x20 + 0x13, 0x0A);
-        cpu.memory.borrow_mut().write(0x0A02, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x1F + 0x13, 0x02);
+        cpu.bus.write(0x20 + 0x13, 0x0A);
+        cpu.bus.write(0x0A02, 0x5A);
 
         cpu.unofficial_rotate_right_memory_add_acc_indirect_x();
         assert_eq!(0x72 + 0x2D, cpu.a);
@@ -9500,7 +9746,7 @@ mod tests {
     fn unofficial_rotate_right_memory_add_acc_indirect_x_takes_8_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_rotate_right_memory_add_acc_indirect_x();
-        assert_eq!(8, cpu.wait_counter);
+        assert_eq!(8, instruction_cycles(&cpu));
     }
 
     #[test]
@@ -9509,13 +9755,13 @@ mod tests {
         cpu.y = 0x13;
         cpu.program_counter = 0x234;
 
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x1F, 0x02);
-        cpu.memory.borrow_mut().write(0x20, 0x0A);
-        cpu.memory.borrow_mut().write(0x0A02 + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x1F, 0x02);
+        cpu.bus.write(0x20, 0x0A);
+        cpu.bus.write(0x0A02 + 0x13, 0x5A);
 
         cpu.unofficial_rotate_right_memory_add_acc_indirect_y();
-        assert_eq!(0x2D, cpu.memory.borrow_mut().read(0x0A02 + 0x13));
+        assert_eq!(0x2D, cpu.bus.read(0x0A02 + 0x13));
     }
 
     #[test]
@@ -9524,10 +9770,10 @@ mod tests {
         cpu.program_counter = 0x234;
         cpu.a = 0x72;
         cpu.y = 0x13;
-        cpu.memory.borrow_mut().write(0x234, 0x1F);
-        cpu.memory.borrow_mut().write(0x1F, 0x02);
-        cpu.memory.borrow_mut().write(0x20, 0x0A);
-        cpu.memory.borrow_mut().write(0x0A02 + 0x13, 0x5A);
+        cpu.bus.write(0x234, 0x1F);
+        cpu.bus.write(0x1F, 0x02);
+        cpu.bus.write(0x20, 0x0A);
+        cpu.bus.write(0x0A02 + 0x13, 0x5A);
 
         cpu.unofficial_rotate_right_memory_add_acc_indirect_y();
         assert_eq!(0x72 + 0x2D, cpu.a);
@@ -9537,53 +9783,65 @@ mod tests {
     fn unofficial_rotate_right_memory_add_acc_indirect_y_takes_8_cycles() {
         let mut cpu = create_test_cpu();
         cpu.unofficial_rotate_right_memory_add_acc_indirect_y();
-        assert_eq!(8, cpu.wait_counter);
+        assert_eq!(8, instruction_cycles(&cpu));
     }
 
     #[test]
     fn no_operation_waits_2_cycles() {
         let mut cpu = create_test_cpu();
         cpu.no_operation();
-        assert_eq!(2, cpu.wait_counter);
+        assert_eq!(2, instruction_cycles(&cpu));
     }
 
     #[test]
-    fn unofficial_double_no_operation_increments_pc() {
+    fn unofficial_nop_immediate_increments_pc() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x13;
-        cpu.unofficial_double_no_operation(2);
+        cpu.unofficial_nop_immediate();
         assert_eq!(0x14, cpu.program_counter);
     }
 
     #[test]
-    fn unofficial_triple_no_operation_page_penalty_does_not_add_a_cycle_when_page_boundary_is_not_crossed() {
+    fn unofficial_nop_zero_page_x_takes_4_cycles() {
+        let mut cpu = create_test_cpu();
+        cpu.program_counter = 0x13;
+        cpu.unofficial_nop_zero_page_x();
+        assert_eq!(4, instruction_cycles(&cpu));
+    }
+
+    #[test]
+    fn unofficial_nop_absolute_x_takes_4_cycles_when_page_boundary_is_not_crossed() {
         let mut cpu = create_test_cpu();
-        cpu.program_counter = 0x0023;
-        cpu.unofficial_triple_no_operation_page_penalty(2);
-        assert_eq!(2, cpu.wait_counter);
+        cpu.program_counter = 0x13;
+        cpu.x = 0x01;
+        cpu.bus.write(0x13, 0x23);
+        cpu.unofficial_nop_absolute_x();
+        assert_eq!(4, instruction_cycles(&cpu));
     }
 
     #[test]
-    fn unofficial_triple_no_operation_page_penalty_adds_a_cycle_when_page_boundary_is_crossed() {
+    fn unofficial_nop_absolute_x_takes_5_cycles_when_page_boundary_is_crossed() {
         let mut cpu = create_test_cpu();
-        cpu.program_counter = 0x00FF;
-        cpu.unofficial_triple_no_operation_page_penalty(2);
-        assert_eq!(3, cpu.wait_counter);
+        cpu.program_counter = 0x13;
+        cpu.x = 0x01;
+        cpu.bus.write(0x13, 0xff);
+        cpu.unofficial_nop_absolute_x();
+        assert_eq!(5, instruction_cycles(&cpu));
     }
 
     #[test]
-    fn unofficial_triple_no_operation_no_page_penalty_increments_pc_twice() {
+    fn unofficial_nop_absolute_increments_pc_twice() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x13;
-        cpu.unofficial_triple_no_operation_no_page_penalty(3);
+        cpu.unofficial_nop_absolute();
         assert_eq!(0x15, cpu.program_counter);
     }
 
     #[test]
-    fn unofficial_triple_no_operation_page_penalty_increments_pc_twice() {
+    fn unofficial_nop_absolute_x_increments_pc_twice() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x13;
-        cpu.unofficial_triple_no_operation_page_penalty(3);
+        cpu.unofficial_nop_absolute_x();
         assert_eq!(0x15, cpu.program_counter);
     }
 
@@ -9592,7 +9850,7 @@ mod tests {
     fn nmi_handler_pushes_status_flags_with_bit_4_unset_and_bit_5_set() {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0xDA;
-        cpu.handle_nmi();
+        handle_nmi(&mut cpu);
         assert_eq!(0xEA, cpu.pop_value_from_stack());
     }
 
@@ -9600,7 +9858,7 @@ mod tests {
     fn nmi_handler_pushes_old_program_counter_into_stack() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x0FAE;
-        cpu.handle_nmi();
+        handle_nmi(&mut cpu);
         cpu.pop_value_from_stack(); // pop flags
         let mut address:u16 = cpu.pop_value_from_stack() as u16; // low byte
         address = address | ((cpu.pop_value_from_stack() as u16) << 8); // high byete
@@ -9612,9 +9870,9 @@ mod tests {
     fn nmi_handler_set_progam_counter_to_value_stored_at_nmi_vector() {
         let mut cpu = create_test_cpu();
         cpu.program_counter = 0x0FAE;
-        cpu.memory.borrow_mut().write(0xFFFA, 0xFE);
-        cpu.memory.borrow_mut().write(0xFFFB, 0xCA);
-        cpu.handle_nmi();
+        cpu.bus.write(0xFFFA, 0xFE);
+        cpu.bus.write(0xFFFB, 0xCA);
+        handle_nmi(&mut cpu);
         assert_eq!(0xCAFE, cpu.program_counter);
     }
 
@@ -9622,30 +9880,538 @@ mod tests {
     fn nmi_handler_disables_interrupts() {
         let mut cpu = create_test_cpu();
         cpu.status_flags = 0xDA;
-        cpu.handle_nmi();
+        handle_nmi(&mut cpu);
         assert_eq!(0xDE, cpu.status_flags);
     }
 
     #[test]
     fn nmi_handler_takes_7_cycles() {
         let mut cpu = create_test_cpu();
-        cpu.handle_nmi();
-        assert_eq!(7, cpu.wait_counter);
+        handle_nmi(&mut cpu);
+        assert_eq!(7, cpu.cycles);
     }
 
     #[test]
     fn write_to_ppu_oam_register_takes_513_on_even_cycle() {
         let mut cpu = create_test_cpu();
-        cpu.is_odd_cycle = false;
+        cpu.cycles = 0;
         cpu.write(0x4014, 0x12);
-        assert_eq!(513, cpu.wait_counter);
+        assert_eq!(1 + 513, cpu.cycles);
     }
 
     #[test]
     fn write_to_ppu_oam_register_takes_514_on_odd_cycle() {
         let mut cpu = create_test_cpu();
-        cpu.is_odd_cycle = true;
+        cpu.cycles = 1;
         cpu.write(0x4014, 0x12);
-        assert_eq!(514, cpu.wait_counter);
+        assert_eq!(2 + 514, cpu.cycles);
+    }
+
+    #[test]
+    fn write_to_ppu_oam_register_copies_page_to_oam_data_register() {
+        let mut cpu = create_test_cpu();
+        cpu.bus.write(0x1200, 0x34);
+        cpu.bus.write(0x12ff, 0x56);
+        cpu.write(0x4014, 0x12);
+        assert_eq!(0x56, cpu.bus.read(0x2004));
+    }
+
+    #[test]
+    fn unofficial_and_immediate_copy_negative_to_carry_sets_carry_if_result_is_negative() {
+        let mut cpu = create_test_cpu();
+        cpu.a = 0xF0;
+        cpu.status_flags = 0x00;
+        cpu.program_counter = 0x100;
+        cpu.bus.write(0x100, 0x8F);
+        cpu.unofficial_and_immediate_copy_negative_to_carry();
+        assert_eq!(0x80, cpu.a);
+        assert_eq!(0x81, cpu.status_flags);
+    }
+
+    #[test]
+    fn unofficial_and_immediate_copy_negative_to_carry_clears_carry_if_result_is_positive() {
+        let mut cpu = create_test_cpu();
+        cpu.a = 0xF0;
+        cpu.status_flags = 0x01;
+        cpu.program_counter = 0x100;
+        cpu.bus.write(0x100, 0x0F);
+        cpu.unofficial_and_immediate_copy_negative_to_carry();
+        assert_eq!(0x00, cpu.a);
+        assert_eq!(0x02, cpu.status_flags);
+    }
+
+    #[test]
+    fn unofficial_and_immediate_shift_right_acc_sets_acc_and_carry() {
+        let mut cpu = create_test_cpu();
+        cpu.a = 0xFF;
+        cpu.status_flags = 0x00;
+        cpu.program_counter = 0x100;
+        cpu.bus.write(0x100, 0x83);
+        cpu.unofficial_and_immediate_shift_right_acc();
+        assert_eq!(0x41, cpu.a);
+        assert_eq!(0x01, cpu.status_flags);
+    }
+
+    #[test]
+    fn unofficial_and_immediate_rotate_right_acc_rotates_carry_in() {
+        let mut cpu = create_test_cpu();
+        cpu.a = 0xFF;
+        cpu.status_flags = 0x01;
+        cpu.program_counter = 0x100;
+        cpu.bus.write(0x100, 0x02);
+        cpu.unofficial_and_immediate_rotate_right_acc();
+        assert_eq!(0x81, cpu.a);
+        assert_eq!(0x80, cpu.status_flags);
+    }
+
+    #[test]
+    fn unofficial_and_immediate_rotate_right_acc_sets_carry_from_bit_6_and_overflow_from_bits_6_and_5() {
+        let mut cpu = create_test_cpu();
+        cpu.a = 0xFF;
+        cpu.status_flags = 0x00;
+        cpu.program_counter = 0x100;
+        cpu.bus.write(0x100, 0x80);
+        cpu.unofficial_and_immediate_rotate_right_acc();
+        assert_eq!(0x40, cpu.a);
+        assert_eq!(0x41, cpu.status_flags);
+
+        cpu.status_flags = 0x00;
+        cpu.a = 0xFF;
+        cpu.program_counter = 0x100;
+        cpu.bus.write(0x100, 0xC0);
+        cpu.unofficial_and_immediate_rotate_right_acc();
+        assert_eq!(0x60, cpu.a);
+        assert_eq!(0x01, cpu.status_flags);
+    }
+
+    #[test]
+    fn unofficial_transfer_x_to_acc_and_immediate_uses_magic_constant() {
+        let mut cpu = create_test_cpu();
+        cpu.a = 0x00;
+        cpu.x = 0xFF;
+        cpu.program_counter = 0x100;
+        cpu.bus.write(0x100, 0xFF);
+        cpu.unofficial_transfer_x_to_acc_and_immediate();
+        assert_eq!(0xEE, cpu.a);
+    }
+
+    #[test]
+    fn unofficial_load_a_and_x_immediate_loads_correct_values() {
+        let mut cpu = create_test_cpu();
+        cpu.a = 0x12;
+        cpu.x = 0x34;
+        cpu.program_counter = 0x100;
+        cpu.bus.write(0x100, 0xC5);
+        cpu.unofficial_load_a_and_x_immediate();
+        assert_eq!(0xC5, cpu.a);
+        assert_eq!(0xC5, cpu.x);
+        assert_eq!(0x80, cpu.status_flags & 0x82);
+        assert_eq!(2, instruction_cycles(&cpu));
+    }
+
+    #[test]
+    fn unofficial_load_a_x_and_stack_pointer_absolute_y_loads_memory_and_stack_pointer() {
+        let mut cpu = create_test_cpu();
+        cpu.stack_pointer = 0xF3;
+        cpu.y = 0x04;
+        cpu.program_counter = 0x100;
+        cpu.bus.write(0x100, 0x20);
+        cpu.bus.write(0x101, 0x05);
+        cpu.bus.write(0x0524, 0x3E);
+        cpu.unofficial_load_a_x_and_stack_pointer_absolute_y();
+        assert_eq!(0x32, cpu.a);
+        assert_eq!(0x32, cpu.x);
+        assert_eq!(0x32, cpu.stack_pointer);
+    }
+
+    #[test]
+    fn unofficial_and_a_with_x_subtract_immediate_stores_result_in_x() {
+        let mut cpu = create_test_cpu();
+        cpu.a = 0x3F;
+        cpu.x = 0xF5;
+        cpu.status_flags = 0x40;
+        cpu.program_counter = 0x100;
+        cpu.bus.write(0x100, 0x05);
+        cpu.unofficial_and_a_with_x_subtract_immediate();
+        assert_eq!(0x30, cpu.x);
+        assert_eq!(0x3F, cpu.a);
+        assert_eq!(0x41, cpu.status_flags);
+    }
+
+    #[test]
+    fn unofficial_and_a_with_x_subtract_immediate_clears_carry_on_borrow() {
+        let mut cpu = create_test_cpu();
+        cpu.a = 0x01;
+        cpu.x = 0x01;
+        cpu.status_flags = 0x01;
+        cpu.program_counter = 0x100;
+        cpu.bus.write(0x100, 0x02);
+        cpu.unofficial_and_a_with_x_subtract_immediate();
+        assert_eq!(0xFF, cpu.x);
+        assert_eq!(0x80, cpu.status_flags);
+    }
+
+    #[test]
+    fn unofficial_and_y_with_high_byte_store_result_absolute_x_stores_y_and_high_byte_plus_one() {
+        let mut cpu = create_test_cpu();
+        cpu.y = 0xFF;
+        cpu.x = 0x01;
+        cpu.program_counter = 0x100;
+        cpu.bus.write(0x100, 0x20);
+        cpu.bus.write(0x101, 0x05);
+        cpu.unofficial_and_y_with_high_byte_store_result_absolute_x();
+        assert_eq!(0x06, cpu.bus.read(0x0521));
+        assert_eq!(5, instruction_cycles(&cpu));
+    }
+
+    #[test]
+    fn unofficial_and_x_with_high_byte_store_result_absolute_y_replaces_high_byte_when_page_is_crossed() {
+        let mut cpu = create_test_cpu();
+        cpu.x = 0x03;
+        cpu.y = 0x10;
+        cpu.program_counter = 0x100;
+        cpu.bus.write(0x100, 0xF8);
+        cpu.bus.write(0x101, 0x06);
+        cpu.unofficial_and_x_with_high_byte_store_result_absolute_y();
+        // 0x03 & 0x07 = 0x03, which becomes the high byte of the address 0x0708
+        assert_eq!(0x03, cpu.bus.read(0x0308));
+        assert_eq!(0x00, cpu.bus.read(0x0708));
+    }
+
+    #[test]
+    fn unofficial_and_a_with_x_and_high_byte_store_result_absolute_y_stores_correct_value() {
+        let mut cpu = create_test_cpu();
+        cpu.a = 0xF3;
+        cpu.x = 0x3F;
+        cpu.y = 0x02;
+        cpu.program_counter = 0x100;
+        cpu.bus.write(0x100, 0x20);
+        cpu.bus.write(0x101, 0x1F);
+        cpu.unofficial_and_a_with_x_and_high_byte_store_result_absolute_y();
+        assert_eq!(0x20, cpu.bus.read(0x1F22));
+        assert_eq!(5, instruction_cycles(&cpu));
+    }
+
+    #[test]
+    fn unofficial_and_a_with_x_and_high_byte_store_result_indirect_y_stores_correct_value() {
+        let mut cpu = create_test_cpu();
+        cpu.a = 0xF3;
+        cpu.x = 0x3F;
+        cpu.y = 0x02;
+        cpu.program_counter = 0x100;
+        cpu.bus.write(0x100, 0x40);
+        cpu.bus.write(0x40, 0x20);
+        cpu.bus.write(0x41, 0x1F);
+        cpu.unofficial_and_a_with_x_and_high_byte_store_result_indirect_y();
+        assert_eq!(0x20, cpu.bus.read(0x1F22));
+        assert_eq!(6, instruction_cycles(&cpu));
+    }
+
+    #[test]
+    fn unofficial_transfer_a_and_x_to_stack_pointer_store_result_absolute_y_sets_stack_pointer() {
+        let mut cpu = create_test_cpu();
+        cpu.a = 0xF3;
+        cpu.x = 0x3F;
+        cpu.y = 0x02;
+        cpu.program_counter = 0x100;
+        cpu.bus.write(0x100, 0x20);
+        cpu.bus.write(0x101, 0x1F);
+        cpu.unofficial_transfer_a_and_x_to_stack_pointer_store_result_absolute_y();
+        assert_eq!(0x33, cpu.stack_pointer);
+        assert_eq!(0x20, cpu.bus.read(0x1F22));
+    }
+
+    #[test]
+    fn jam_opcodes_jam_the_cpu() {
+        for opcode in [0x02, 0x12, 0x22, 0x32, 0x42, 0x52, 0x62, 0x72, 0x92, 0xB2, 0xD2, 0xF2].iter() {
+            let mut cpu = create_test_cpu();
+            cpu.program_counter = 0x200;
+            cpu.bus.write(0x200, *opcode);
+            cpu.execute_instruction();
+            assert_eq!(true, cpu.is_jammed());
+            assert_eq!(0x200, cpu.program_counter());
+        }
+    }
+
+    #[test]
+    fn jammed_cpu_does_not_execute_instructions() {
+        let mut cpu = create_test_cpu();
+        cpu.jammed = true;
+        cpu.a = 0x00;
+        cpu.program_counter = 0x200;
+        cpu.bus.write(0x200, 0xA9); // LDA #$12
+        cpu.bus.write(0x201, 0x12);
+        cpu.execute_instruction();
+        assert_eq!(0x00, cpu.a);
+        assert_eq!(0x200, cpu.program_counter);
+    }
+
+    #[test]
+    fn jammed_cpu_does_not_handle_nmi() {
+        let mut cpu = create_test_cpu();
+        cpu.jammed = true;
+        cpu.program_counter = 0x200;
+        cpu.bus.write(0xFFFA, 0xFE);
+        cpu.bus.write(0xFFFB, 0xCA);
+        handle_nmi(&mut cpu);
+        assert_eq!(0x200, cpu.program_counter);
+    }
+
+    #[test]
+    fn reset_takes_7_cycles() {
+        let mut cpu = create_test_cpu();
+        cpu.reset();
+        assert_eq!(7, cpu.cycles());
+    }
+
+    #[test]
+    fn reset_on_power_up_leaves_stack_pointer_at_0xfd() {
+        let mut cpu = Cpu::new(&TvSystem::NTSC, MockMemory::new());
+        cpu.reset();
+        assert_eq!(0xFD, cpu.stack_pointer);
+    }
+
+    #[test]
+    fn reset_disables_interrupts() {
+        let mut cpu = create_test_cpu();
+        cpu.status_flags = 0x20;
+        cpu.reset();
+        assert_eq!(0x04, cpu.status_flags & 0x04);
+    }
+
+    #[test]
+    fn reset_sets_program_counter_to_value_stored_at_reset_vector() {
+        let mut cpu = create_test_cpu();
+        cpu.bus.write(0xFFFC, 0x34);
+        cpu.bus.write(0xFFFD, 0xC0);
+        cpu.reset();
+        assert_eq!(0xC034, cpu.program_counter());
+    }
+
+    #[test]
+    fn execute_instruction_traces_state_before_executing_instruction() {
+        let buffer = Rc::new(RefCell::new(vec![]));
+        let mut cpu = create_test_cpu();
+        cpu.set_tracer(Some(Tracer::new(Box::new(SharedOutput { buffer: buffer.clone() }))));
+        cpu.program_counter = 0x200;
+        cpu.bus.write(0x200, 0xA9); // LDA #$12
+        cpu.bus.write(0x201, 0x12);
+        cpu.execute_instruction();
+
+        let output = String::from_utf8(buffer.borrow().clone()).unwrap();
+        assert!(output.starts_with("0200  A9 12     LDA #$12"));
+        assert!(output.contains(" A:00 "));
+    }
+
+    #[test]
+    fn reset_clears_jammed_state() {
+        let mut cpu = create_test_cpu();
+        cpu.jammed = true;
+        cpu.reset();
+        assert_eq!(false, cpu.is_jammed());
+    }
+
+    #[test]
+    fn jammed_cpu_keeps_reading_the_bus() {
+        let mut cpu = create_test_cpu();
+        cpu.jammed = true;
+        cpu.execute_instruction();
+        assert_eq!(1, cpu.cycles());
+    }
+
+    #[test]
+    fn implied_instruction_reads_next_byte() {
+        let (mut cpu, accesses) = create_recording_cpu(&[0xE8]); // INX
+        cpu.execute_instruction();
+        assert_eq!(vec![Access::Read(0x200), Access::Read(0x201)], *accesses.borrow());
+    }
+
+    #[test]
+    fn read_modify_write_writes_unmodified_value_before_result() {
+        let (mut cpu, accesses) = create_recording_cpu(&[0xE6, 0x10]); // INC $10
+        cpu.bus.write(0x10, 0x41);
+        accesses.borrow_mut().clear();
+        cpu.execute_instruction();
+        assert_eq!(vec![
+            Access::Read(0x200),
+            Access::Read(0x201),
+            Access::Read(0x10),
+            Access::Write(0x10, 0x41),
+            Access::Write(0x10, 0x42)],
+            *accesses.borrow());
+    }
+
+    #[test]
+    fn absolute_x_read_reads_unfixed_address_when_page_boundary_is_crossed() {
+        let (mut cpu, accesses) = create_recording_cpu(&[0xBD, 0xff, 0x20]); // LDA $20FF,X
+        cpu.x = 0x02;
+        cpu.execute_instruction();
+        assert_eq!(vec![
+            Access::Read(0x200),
+            Access::Read(0x201),
+            Access::Read(0x202),
+            Access::Read(0x2001),
+            Access::Read(0x2101)],
+            *accesses.borrow());
+    }
+
+    #[test]
+    fn absolute_x_store_reads_target_address_when_page_boundary_is_not_crossed() {
+        let (mut cpu, accesses) = create_recording_cpu(&[0x9D, 0x00, 0x20]); // STA $2000,X
+        cpu.x = 0x07;
+        cpu.a = 0x12;
+        cpu.execute_instruction();
+        assert_eq!(vec![
+            Access::Read(0x200),
+            Access::Read(0x201),
+            Access::Read(0x202),
+            Access::Read(0x2007),
+            Access::Write(0x2007, 0x12)],
+            *accesses.borrow());
+    }
+
+    #[test]
+    fn taken_branch_reads_unfixed_address_when_page_boundary_is_crossed() {
+        let (mut cpu, accesses) = create_recording_cpu(&[0xD0, 0xFD]); // BNE -3
+        cpu.status_flags = 0x00;
+        cpu.execute_instruction();
+        assert_eq!(vec![
+            Access::Read(0x200),
+            Access::Read(0x201),
+            Access::Read(0x202),
+            Access::Read(0x2ff)],
+            *accesses.borrow());
+        assert_eq!(0x1ff, cpu.program_counter);
+    }
+
+    #[test]
+    fn interrupt_request_pushes_status_flags_with_bit_4_unset() {
+        let mut cpu = create_test_cpu();
+        cpu.status_flags = 0x80;
+        cpu.stack_pointer = 0xFF;
+        cpu.program_counter = 0x1234;
+        cpu.irq_pending = true;
+        cpu.previous_irq_pending = true;
+        cpu.execute_instruction();
+        assert_eq!(7, cpu.cycles());
+        assert_eq!(0xa0, cpu.pop_value_from_stack());
+        assert_eq!(0x34, cpu.pop_value_from_stack());
+    }
+
+    #[test]
+    fn irq_is_serviced_after_instruction_during_which_it_was_raised() {
+        let mut cpu = create_interrupt_test_cpu(&[0xEA, 0xEA], Some(1), None);
+        cpu.status_flags = 0x00;
+        cpu.execute_instruction();
+        assert_eq!(0x201, cpu.program_counter);
+        cpu.execute_instruction();
+        assert_eq!(0x300, cpu.program_counter);
+    }
+
+    #[test]
+    fn irq_raised_on_last_cycle_is_serviced_after_next_instruction() {
+        let mut cpu = create_interrupt_test_cpu(&[0xEA, 0xEA], Some(2), None);
+        cpu.status_flags = 0x00;
+        cpu.execute_instruction();
+        cpu.execute_instruction();
+        assert_eq!(0x202, cpu.program_counter);
+        cpu.execute_instruction();
+        assert_eq!(0x300, cpu.program_counter);
+    }
+
+    #[test]
+    fn clear_interrupt_disable_flag_delays_irq_by_one_instruction() {
+        let mut cpu = create_interrupt_test_cpu(&[0x58, 0xEA, 0xEA], Some(1), None); // CLI
+        cpu.status_flags = 0x04;
+        cpu.execute_instruction();
+        cpu.execute_instruction();
+        assert_eq!(0x202, cpu.program_counter);
+        cpu.execute_instruction();
+        assert_eq!(0x300, cpu.program_counter);
+    }
+
+    #[test]
+    fn return_from_interrupt_clearing_interrupt_disable_flag_does_not_delay_irq() {
+        let mut cpu = create_interrupt_test_cpu(&[0x40], Some(1), None); // RTI
+        cpu.status_flags = 0x04;
+        cpu.push_value_into_stack(0x02); // return address 0x0250
+        cpu.push_value_into_stack(0x50);
+        cpu.push_value_into_stack(0x20); // interrupt disable flag clear
+        cpu.execute_instruction();
+        assert_eq!(0x250, cpu.program_counter);
+        cpu.execute_instruction();
+        assert_eq!(0x300, cpu.program_counter);
+    }
+
+    #[test]
+    fn irq_pending_before_set_interrupt_disable_flag_is_serviced_after_it() {
+        let mut cpu = create_interrupt_test_cpu(&[0x78, 0xEA], Some(1), None); // SEI
+        cpu.status_flags = 0x00;
+        cpu.execute_instruction();
+        cpu.execute_instruction();
+        assert_eq!(0x300, cpu.program_counter);
+        // interrupt disable flag was already set when flags were pushed
+        assert_eq!(0x24, cpu.pop_value_from_stack());
+    }
+
+    #[test]
+    fn pull_status_flags_delays_irq_by_one_instruction() {
+        let mut cpu = create_interrupt_test_cpu(&[0x28, 0xEA, 0xEA], Some(1), None); // PLP
+        cpu.status_flags = 0x04;
+        cpu.push_value_into_stack(0x00);
+        cpu.execute_instruction();
+        cpu.execute_instruction();
+        assert_eq!(0x202, cpu.program_counter);
+        cpu.execute_instruction();
+        assert_eq!(0x300, cpu.program_counter);
+    }
+
+    #[test]
+    fn taken_branch_without_page_cross_delays_irq_by_one_instruction() {
+        let mut cpu = create_interrupt_test_cpu(&[0xD0, 0x00, 0xEA, 0xEA], Some(2), None); // BNE +0
+        cpu.status_flags = 0x00;
+        cpu.execute_instruction();
+        cpu.execute_instruction();
+        assert_eq!(0x203, cpu.program_counter);
+        cpu.execute_instruction();
+        assert_eq!(0x300, cpu.program_counter);
+    }
+
+    #[test]
+    fn taken_branch_with_page_cross_does_not_delay_irq() {
+        let mut cpu = create_interrupt_test_cpu(&[0xD0, 0xFD], Some(2), None); // BNE -3
+        cpu.status_flags = 0x00;
+        cpu.execute_instruction();
+        cpu.execute_instruction();
+        assert_eq!(0x300, cpu.program_counter);
+    }
+
+    #[test]
+    fn nmi_hijacks_brk_if_it_occurs_before_flags_are_pushed() {
+        let mut cpu = create_interrupt_test_cpu(&[0x00, 0x00], None, Some(4));
+        cpu.execute_instruction();
+        assert_eq!(0x400, cpu.program_counter);
+        // brk flag is still set
+        assert_eq!(0x34, cpu.pop_value_from_stack() & 0x34);
+    }
+
+    #[test]
+    fn nmi_hijacks_irq_if_it_occurs_before_flags_are_pushed() {
+        let mut cpu = create_interrupt_test_cpu(&[0xEA], Some(1), Some(4));
+        cpu.status_flags = 0x00;
+        cpu.execute_instruction();
+        cpu.execute_instruction();
+        assert_eq!(0x400, cpu.program_counter);
+    }
+
+    #[test]
+    fn nmi_occuring_late_in_brk_is_serviced_after_first_handler_instruction() {
+        let mut cpu = create_interrupt_test_cpu(&[0x00, 0x00], None, Some(6));
+        cpu.execute_instruction();
+        assert_eq!(0x300, cpu.program_counter);
+        cpu.execute_instruction();
+        assert_eq!(0x301, cpu.program_counter);
+        cpu.execute_instruction();
+        assert_eq!(0x400, cpu.program_counter);
     }
 }
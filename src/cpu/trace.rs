@@ -0,0 +1,711 @@
+use memory::Bus;
+
+use std::io::Write;
+
+use self::AddressingMode::*;
+
+// Writes a line per executed instruction in the format used by Nintendulator (and the nestest.log
+// reference log), so that traces can be diffed against other emulators:
+//
+// C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7
+//
+// Operand values are peeked from the bus before the instruction executes. Unofficial opcodes are
+// marked with '*'.
+pub struct Tracer {
+    output: Box<dyn Write>,
+    enabled: bool,
+    pc_ranges: Vec<(u16, u16)>, // inclusive; everything is traced if empty
+}
+
+// cpu state at the start of an instruction
+pub struct CpuState {
+    pub program_counter: u16,
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub status_flags: u8,
+    pub stack_pointer: u8,
+    pub cycles: u64,
+}
+
+impl Tracer {
+    pub fn new(output: Box<dyn Write>) -> Tracer {
+        Tracer {
+            output: output,
+            enabled: true,
+            pc_ranges: vec![],
+        }
+    }
+
+    pub fn add_pc_range(&mut self, start: u16, end: u16) {
+        self.pc_ranges.push((start, end));
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    fn should_trace(&self, program_counter: u16) -> bool {
+        self.enabled && (self.pc_ranges.is_empty() || self.pc_ranges.iter().any(
+            |&(start, end)| program_counter >= start && program_counter <= end))
+    }
+
+    pub fn trace<B: Bus>(&mut self, state: &CpuState, bus: &mut B) {
+        if !self.should_trace(state.program_counter) {
+            return;
+        }
+
+        let line = format_line(state, bus);
+        if let Err(e) = writeln!(self.output, "{}", line) {
+            println!("Could not write trace, tracing disabled: {}", e);
+            self.enabled = false;
+        }
+    }
+}
+
+// parses an inclusive <start>-<end> range of hex addresses, such as C000-C0FF
+pub fn parse_pc_range(value: &str) -> Option<(u16, u16)> {
+    let parts : Vec<&str> = value.split('-').collect();
+    if parts.len() != 2 {
+        return None;
+    }
+
+    let parse = |part: &str| u16::from_str_radix(part.trim().trim_start_matches('$'), 16).ok();
+    match (parse(parts[0]), parse(parts[1])) {
+        (Some(start), Some(end)) if start <= end => Some((start, end)),
+        _ => None,
+    }
+}
+
+fn format_line<B: Bus>(state: &CpuState, bus: &mut B) -> String {
+    let pc = state.program_counter;
+    let opcode = &OPCODES[bus.peek(pc) as usize];
+    let bytes : Vec<String> = (0..opcode.mode.length())
+        .map(|i| format!("{:02X}", bus.peek(pc.wrapping_add(i))))
+        .collect();
+
+    let (scanline, dot) = bus.ppu_position();
+    format!("{:04X}  {:<8} {}{:<32}A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} PPU:{:>3},{:>3} CYC:{}",
+        pc,
+        bytes.join(" "),
+        if opcode.official { ' ' } else { '*' },
+        disassemble(opcode, state, bus),
+        state.a,
+        state.x,
+        state.y,
+        (state.status_flags & 0xEF) | 0x20, // bit 5 is always set and 4 only exists on the stack
+        state.stack_pointer,
+        scanline,
+        dot,
+        state.cycles)
+}
+
+fn disassemble<B: Bus>(opcode: &Opcode, state: &CpuState, bus: &mut B) -> String {
+    let pc = state.program_counter;
+    let byte = bus.peek(pc.wrapping_add(1));
+    let word = byte as u16 | (bus.peek(pc.wrapping_add(2)) as u16) << 8;
+
+    let operand = match opcode.mode {
+        Implied => String::new(),
+        Accumulator => "A".to_string(),
+        Immediate => format!("#${:02X}", byte),
+        ZeroPage => format!("${:02X} = {:02X}", byte, bus.peek(byte as u16)),
+        ZeroPageX => {
+            let address = byte.wrapping_add(state.x);
+            format!("${:02X},X @ {:02X} = {:02X}", byte, address, bus.peek(address as u16))
+        },
+        ZeroPageY => {
+            let address = byte.wrapping_add(state.y);
+            format!("${:02X},Y @ {:02X} = {:02X}", byte, address, bus.peek(address as u16))
+        },
+        // jumps do not access the operand address
+        Absolute if opcode.mnemonic == "JMP" || opcode.mnemonic == "JSR" => format!("${:04X}", word),
+        Absolute => format!("${:04X} = {:02X}", word, bus.peek(word)),
+        AbsoluteX => {
+            let address = word.wrapping_add(state.x as u16);
+            format!("${:04X},X @ {:04X} = {:02X}", word, address, bus.peek(address))
+        },
+        AbsoluteY => {
+            let address = word.wrapping_add(state.y as u16);
+            format!("${:04X},Y @ {:04X} = {:02X}", word, address, bus.peek(address))
+        },
+        Indirect => format!("(${:04X}) = {:04X}", word, peek_word_within_page(bus, word)),
+        IndirectX => {
+            let pointer = byte.wrapping_add(state.x);
+            let address = peek_word_within_page(bus, pointer as u16);
+            format!("(${:02X},X) @ {:02X} = {:04X} = {:02X}", byte, pointer, address, bus.peek(address))
+        },
+        IndirectY => {
+            let base = peek_word_within_page(bus, byte as u16);
+            let address = base.wrapping_add(state.y as u16);
+            format!("(${:02X}),Y = {:04X} @ {:04X} = {:02X}", byte, base, address, bus.peek(address))
+        },
+        Relative => format!("${:04X}", pc.wrapping_add(2).wrapping_add(byte as i8 as u16)),
+    };
+
+    if operand.is_empty() {
+        opcode.mnemonic.to_string()
+    } else {
+        format!("{} {}", opcode.mnemonic, operand)
+    }
+}
+
+// the high byte of a pointer is read from the start of the page if the low byte is at the end
+// of the page; this also covers zero page pointers wrapping around
+fn peek_word_within_page<B: Bus>(bus: &mut B, address: u16) -> u16 {
+    let high_address = (address & 0xFF00) | (address.wrapping_add(1) & 0x00FF);
+    bus.peek(address) as u16 | (bus.peek(high_address) as u16) << 8
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum AddressingMode {
+    Implied,
+    Accumulator,
+    Immediate,
+    ZeroPage,
+    ZeroPageX,
+    ZeroPageY,
+    Absolute,
+    AbsoluteX,
+    AbsoluteY,
+    Indirect,
+    IndirectX,
+    IndirectY,
+    Relative,
+}
+
+impl AddressingMode {
+    // instruction length in bytes, including the opcode
+    fn length(&self) -> u16 {
+        match *self {
+            Implied | Accumulator => 1,
+            Absolute | AbsoluteX | AbsoluteY | Indirect => 3,
+            _ => 2,
+        }
+    }
+}
+
+struct Opcode {
+    mnemonic: &'static str,
+    mode: AddressingMode,
+    official: bool,
+}
+
+const OPCODES: [Opcode; 256] = [
+    Opcode { mnemonic: "BRK", mode: Implied, official: true }, // 0x00
+    Opcode { mnemonic: "ORA", mode: IndirectX, official: true }, // 0x01
+    Opcode { mnemonic: "JAM", mode: Implied, official: false }, // 0x02
+    Opcode { mnemonic: "SLO", mode: IndirectX, official: false }, // 0x03
+    Opcode { mnemonic: "NOP", mode: ZeroPage, official: false }, // 0x04
+    Opcode { mnemonic: "ORA", mode: ZeroPage, official: true }, // 0x05
+    Opcode { mnemonic: "ASL", mode: ZeroPage, official: true }, // 0x06
+    Opcode { mnemonic: "SLO", mode: ZeroPage, official: false }, // 0x07
+    Opcode { mnemonic: "PHP", mode: Implied, official: true }, // 0x08
+    Opcode { mnemonic: "ORA", mode: Immediate, official: true }, // 0x09
+    Opcode { mnemonic: "ASL", mode: Accumulator, official: true }, // 0x0A
+    Opcode { mnemonic: "ANC", mode: Immediate, official: false }, // 0x0B
+    Opcode { mnemonic: "NOP", mode: Absolute, official: false }, // 0x0C
+    Opcode { mnemonic: "ORA", mode: Absolute, official: true }, // 0x0D
+    Opcode { mnemonic: "ASL", mode: Absolute, official: true }, // 0x0E
+    Opcode { mnemonic: "SLO", mode: Absolute, official: false }, // 0x0F
+    Opcode { mnemonic: "BPL", mode: Relative, official: true }, // 0x10
+    Opcode { mnemonic: "ORA", mode: IndirectY, official: true }, // 0x11
+    Opcode { mnemonic: "JAM", mode: Implied, official: false }, // 0x12
+    Opcode { mnemonic: "SLO", mode: IndirectY, official: false }, // 0x13
+    Opcode { mnemonic: "NOP", mode: ZeroPageX, official: false }, // 0x14
+    Opcode { mnemonic: "ORA", mode: ZeroPageX, official: true }, // 0x15
+    Opcode { mnemonic: "ASL", mode: ZeroPageX, official: true }, // 0x16
+    Opcode { mnemonic: "SLO", mode: ZeroPageX, official: false }, // 0x17
+    Opcode { mnemonic: "CLC", mode: Implied, official: true }, // 0x18
+    Opcode { mnemonic: "ORA", mode: AbsoluteY, official: true }, // 0x19
+    Opcode { mnemonic: "NOP", mode: Implied, official: false }, // 0x1A
+    Opcode { mnemonic: "SLO", mode: AbsoluteY, official: false }, // 0x1B
+    Opcode { mnemonic: "NOP", mode: AbsoluteX, official: false }, // 0x1C
+    Opcode { mnemonic: "ORA", mode: AbsoluteX, official: true }, // 0x1D
+    Opcode { mnemonic: "ASL", mode: AbsoluteX, official: true }, // 0x1E
+    Opcode { mnemonic: "SLO", mode: AbsoluteX, official: false }, // 0x1F
+    Opcode { mnemonic: "JSR", mode: Absolute, official: true }, // 0x20
+    Opcode { mnemonic: "AND", mode: IndirectX, official: true }, // 0x21
+    Opcode { mnemonic: "JAM", mode: Implied, official: false }, // 0x22
+    Opcode { mnemonic: "RLA", mode: IndirectX, official: false }, // 0x23
+    Opcode { mnemonic: "BIT", mode: ZeroPage, official: true }, // 0x24
+    Opcode { mnemonic: "AND", mode: ZeroPage, official: true }, // 0x25
+    Opcode { mnemonic: "ROL", mode: ZeroPage, official: true }, // 0x26
+    Opcode { mnemonic: "RLA", mode: ZeroPage, official: false }, // 0x27
+    Opcode { mnemonic: "PLP", mode: Implied, official: true }, // 0x28
+    Opcode { mnemonic: "AND", mode: Immediate, official: true }, // 0x29
+    Opcode { mnemonic: "ROL", mode: Accumulator, official: true }, // 0x2A
+    Opcode { mnemonic: "ANC", mode: Immediate, official: false }, // 0x2B
+    Opcode { mnemonic: "BIT", mode: Absolute, official: true }, // 0x2C
+    Opcode { mnemonic: "AND", mode: Absolute, official: true }, // 0x2D
+    Opcode { mnemonic: "ROL", mode: Absolute, official: true }, // 0x2E
+    Opcode { mnemonic: "RLA", mode: Absolute, official: false }, // 0x2F
+    Opcode { mnemonic: "BMI", mode: Relative, official: true }, // 0x30
+    Opcode { mnemonic: "AND", mode: IndirectY, official: true }, // 0x31
+    Opcode { mnemonic: "JAM", mode: Implied, official: false }, // 0x32
+    Opcode { mnemonic: "RLA", mode: IndirectY, official: false }, // 0x33
+    Opcode { mnemonic: "NOP", mode: ZeroPageX, official: false }, // 0x34
+    Opcode { mnemonic: "AND", mode: ZeroPageX, official: true }, // 0x35
+    Opcode { mnemonic: "ROL", mode: ZeroPageX, official: true }, // 0x36
+    Opcode { mnemonic: "RLA", mode: ZeroPageX, official: false }, // 0x37
+    Opcode { mnemonic: "SEC", mode: Implied, official: true }, // 0x38
+    Opcode { mnemonic: "AND", mode: AbsoluteY, official: true }, // 0x39
+    Opcode { mnemonic: "NOP", mode: Implied, official: false }, // 0x3A
+    Opcode { mnemonic: "RLA", mode: AbsoluteY, official: false }, // 0x3B
+    Opcode { mnemonic: "NOP", mode: AbsoluteX, official: false }, // 0x3C
+    Opcode { mnemonic: "AND", mode: AbsoluteX, official: true }, // 0x3D
+    Opcode { mnemonic: "ROL", mode: AbsoluteX, official: true }, // 0x3E
+    Opcode { mnemonic: "RLA", mode: AbsoluteX, official: false }, // 0x3F
+    Opcode { mnemonic: "RTI", mode: Implied, official: true }, // 0x40
+    Opcode { mnemonic: "EOR", mode: IndirectX, official: true }, // 0x41
+    Opcode { mnemonic: "JAM", mode: Implied, official: false }, // 0x42
+    Opcode { mnemonic: "SRE", mode: IndirectX, official: false }, // 0x43
+    Opcode { mnemonic: "NOP", mode: ZeroPage, official: false }, // 0x44
+    Opcode { mnemonic: "EOR", mode: ZeroPage, official: true }, // 0x45
+    Opcode { mnemonic: "LSR", mode: ZeroPage, official: true }, // 0x46
+    Opcode { mnemonic: "SRE", mode: ZeroPage, official: false }, // 0x47
+    Opcode { mnemonic: "PHA", mode: Implied, official: true }, // 0x48
+    Opcode { mnemonic: "EOR", mode: Immediate, official: true }, // 0x49
+    Opcode { mnemonic: "LSR", mode: Accumulator, official: true }, // 0x4A
+    Opcode { mnemonic: "ALR", mode: Immediate, official: false }, // 0x4B
+    Opcode { mnemonic: "JMP", mode: Absolute, official: true }, // 0x4C
+    Opcode { mnemonic: "EOR", mode: Absolute, official: true }, // 0x4D
+    Opcode { mnemonic: "LSR", mode: Absolute, official: true }, // 0x4E
+    Opcode { mnemonic: "SRE", mode: Absolute, official: false }, // 0x4F
+    Opcode { mnemonic: "BVC", mode: Relative, official: true }, // 0x50
+    Opcode { mnemonic: "EOR", mode: IndirectY, official: true }, // 0x51
+    Opcode { mnemonic: "JAM", mode: Implied, official: false }, // 0x52
+    Opcode { mnemonic: "SRE", mode: IndirectY, official: false }, // 0x53
+    Opcode { mnemonic: "NOP", mode: ZeroPageX, official: false }, // 0x54
+    Opcode { mnemonic: "EOR", mode: ZeroPageX, official: true }, // 0x55
+    Opcode { mnemonic: "LSR", mode: ZeroPageX, official: true }, // 0x56
+    Opcode { mnemonic: "SRE", mode: ZeroPageX, official: false }, // 0x57
+    Opcode { mnemonic: "CLI", mode: Implied, official: true }, // 0x58
+    Opcode { mnemonic: "EOR", mode: AbsoluteY, official: true }, // 0x59
+    Opcode { mnemonic: "NOP", mode: Implied, official: false }, // 0x5A
+    Opcode { mnemonic: "SRE", mode: AbsoluteY, official: false }, // 0x5B
+    Opcode { mnemonic: "NOP", mode: AbsoluteX, official: false }, // 0x5C
+    Opcode { mnemonic: "EOR", mode: AbsoluteX, official: true }, // 0x5D
+    Opcode { mnemonic: "LSR", mode: AbsoluteX, official: true }, // 0x5E
+    Opcode { mnemonic: "SRE", mode: AbsoluteX, official: false }, // 0x5F
+    Opcode { mnemonic: "RTS", mode: Implied, official: true }, // 0x60
+    Opcode { mnemonic: "ADC", mode: IndirectX, official: true }, // 0x61
+    Opcode { mnemonic: "JAM", mode: Implied, official: false }, // 0x62
+    Opcode { mnemonic: "RRA", mode: IndirectX, official: false }, // 0x63
+    Opcode { mnemonic: "NOP", mode: ZeroPage, official: false }, // 0x64
+    Opcode { mnemonic: "ADC", mode: ZeroPage, official: true }, // 0x65
+    Opcode { mnemonic: "ROR", mode: ZeroPage, official: true }, // 0x66
+    Opcode { mnemonic: "RRA", mode: ZeroPage, official: false }, // 0x67
+    Opcode { mnemonic: "PLA", mode: Implied, official: true }, // 0x68
+    Opcode { mnemonic: "ADC", mode: Immediate, official: true }, // 0x69
+    Opcode { mnemonic: "ROR", mode: Accumulator, official: true }, // 0x6A
+    Opcode { mnemonic: "ARR", mode: Immediate, official: false }, // 0x6B
+    Opcode { mnemonic: "JMP", mode: Indirect, official: true }, // 0x6C
+    Opcode { mnemonic: "ADC", mode: Absolute, official: true }, // 0x6D
+    Opcode { mnemonic: "ROR", mode: Absolute, official: true }, // 0x6E
+    Opcode { mnemonic: "RRA", mode: Absolute, official: false }, // 0x6F
+    Opcode { mnemonic: "BVS", mode: Relative, official: true }, // 0x70
+    Opcode { mnemonic: "ADC", mode: IndirectY, official: true }, // 0x71
+    Opcode { mnemonic: "JAM", mode: Implied, official: false }, // 0x72
+    Opcode { mnemonic: "RRA", mode: IndirectY, official: false }, // 0x73
+    Opcode { mnemonic: "NOP", mode: ZeroPageX, official: false }, // 0x74
+    Opcode { mnemonic: "ADC", mode: ZeroPageX, official: true }, // 0x75
+    Opcode { mnemonic: "ROR", mode: ZeroPageX, official: true }, // 0x76
+    Opcode { mnemonic: "RRA", mode: ZeroPageX, official: false }, // 0x77
+    Opcode { mnemonic: "SEI", mode: Implied, official: true }, // 0x78
+    Opcode { mnemonic: "ADC", mode: AbsoluteY, official: true }, // 0x79
+    Opcode { mnemonic: "NOP", mode: Implied, official: false }, // 0x7A
+    Opcode { mnemonic: "RRA", mode: AbsoluteY, official: false }, // 0x7B
+    Opcode { mnemonic: "NOP", mode: AbsoluteX, official: false }, // 0x7C
+    Opcode { mnemonic: "ADC", mode: AbsoluteX, official: true }, // 0x7D
+    Opcode { mnemonic: "ROR", mode: AbsoluteX, official: true }, // 0x7E
+    Opcode { mnemonic: "RRA", mode: AbsoluteX, official: false }, // 0x7F
+    Opcode { mnemonic: "NOP", mode: Immediate, official: false }, // 0x80
+    Opcode { mnemonic: "STA", mode: IndirectX, official: true }, // 0x81
+    Opcode { mnemonic: "NOP", mode: Immediate, official: false }, // 0x82
+    Opcode { mnemonic: "SAX", mode: IndirectX, official: false }, // 0x83
+    Opcode { mnemonic: "STY", mode: ZeroPage, official: true }, // 0x84
+    Opcode { mnemonic: "STA", mode: ZeroPage, official: true }, // 0x85
+    Opcode { mnemonic: "STX", mode: ZeroPage, official: true }, // 0x86
+    Opcode { mnemonic: "SAX", mode: ZeroPage, official: false }, // 0x87
+    Opcode { mnemonic: "DEY", mode: Implied, official: true }, // 0x88
+    Opcode { mnemonic: "NOP", mode: Immediate, official: false }, // 0x89
+    Opcode { mnemonic: "TXA", mode: Implied, official: true }, // 0x8A
+    Opcode { mnemonic: "XAA", mode: Immediate, official: false }, // 0x8B
+    Opcode { mnemonic: "STY", mode: Absolute, official: true }, // 0x8C
+    Opcode { mnemonic: "STA", mode: Absolute, official: true }, // 0x8D
+    Opcode { mnemonic: "STX", mode: Absolute, official: true }, // 0x8E
+    Opcode { mnemonic: "SAX", mode: Absolute, official: false }, // 0x8F
+    Opcode { mnemonic: "BCC", mode: Relative, official: true }, // 0x90
+    Opcode { mnemonic: "STA", mode: IndirectY, official: true }, // 0x91
+    Opcode { mnemonic: "JAM", mode: Implied, official: false }, // 0x92
+    Opcode { mnemonic: "AHX", mode: IndirectY, official: false }, // 0x93
+    Opcode { mnemonic: "STY", mode: ZeroPageX, official: true }, // 0x94
+    Opcode { mnemonic: "STA", mode: ZeroPageX, official: true }, // 0x95
+    Opcode { mnemonic: "STX", mode: ZeroPageY, official: true }, // 0x96
+    Opcode { mnemonic: "SAX", mode: ZeroPageY, official: false }, // 0x97
+    Opcode { mnemonic: "TYA", mode: Implied, official: true }, // 0x98
+    Opcode { mnemonic: "STA", mode: AbsoluteY, official: true }, // 0x99
+    Opcode { mnemonic: "TXS", mode: Implied, official: true }, // 0x9A
+    Opcode { mnemonic: "TAS", mode: AbsoluteY, official: false }, // 0x9B
+    Opcode { mnemonic: "SHY", mode: AbsoluteX, official: false }, // 0x9C
+    Opcode { mnemonic: "STA", mode: AbsoluteX, official: true }, // 0x9D
+    Opcode { mnemonic: "SHX", mode: AbsoluteY, official: false }, // 0x9E
+    Opcode { mnemonic: "AHX", mode: AbsoluteY, official: false }, // 0x9F
+    Opcode { mnemonic: "LDY", mode: Immediate, official: true }, // 0xA0
+    Opcode { mnemonic: "LDA", mode: IndirectX, official: true }, // 0xA1
+    Opcode { mnemonic: "LDX", mode: Immediate, official: true }, // 0xA2
+    Opcode { mnemonic: "LAX", mode: IndirectX, official: false }, // 0xA3
+    Opcode { mnemonic: "LDY", mode: ZeroPage, official: true }, // 0xA4
+    Opcode { mnemonic: "LDA", mode: ZeroPage, official: true }, // 0xA5
+    Opcode { mnemonic: "LDX", mode: ZeroPage, official: true }, // 0xA6
+    Opcode { mnemonic: "LAX", mode: ZeroPage, official: false }, // 0xA7
+    Opcode { mnemonic: "TAY", mode: Implied, official: true }, // 0xA8
+    Opcode { mnemonic: "LDA", mode: Immediate, official: true }, // 0xA9
+    Opcode { mnemonic: "TAX", mode: Implied, official: true }, // 0xAA
+    Opcode { mnemonic: "LAX", mode: Immediate, official: false }, // 0xAB
+    Opcode { mnemonic: "LDY", mode: Absolute, official: true }, // 0xAC
+    Opcode { mnemonic: "LDA", mode: Absolute, official: true }, // 0xAD
+    Opcode { mnemonic: "LDX", mode: Absolute, official: true }, // 0xAE
+    Opcode { mnemonic: "LAX", mode: Absolute, official: false }, // 0xAF
+    Opcode { mnemonic: "BCS", mode: Relative, official: true }, // 0xB0
+    Opcode { mnemonic: "LDA", mode: IndirectY, official: true }, // 0xB1
+    Opcode { mnemonic: "JAM", mode: Implied, official: false }, // 0xB2
+    Opcode { mnemonic: "LAX", mode: IndirectY, official: false }, // 0xB3
+    Opcode { mnemonic: "LDY", mode: ZeroPageX, official: true }, // 0xB4
+    Opcode { mnemonic: "LDA", mode: ZeroPageX, official: true }, // 0xB5
+    Opcode { mnemonic: "LDX", mode: ZeroPageY, official: true }, // 0xB6
+    Opcode { mnemonic: "LAX", mode: ZeroPageY, official: false }, // 0xB7
+    Opcode { mnemonic: "CLV", mode: Implied, official: true }, // 0xB8
+    Opcode { mnemonic: "LDA", mode: AbsoluteY, official: true }, // 0xB9
+    Opcode { mnemonic: "TSX", mode: Implied, official: true }, // 0xBA
+    Opcode { mnemonic: "LAS", mode: AbsoluteY, official: false }, // 0xBB
+    Opcode { mnemonic: "LDY", mode: AbsoluteX, official: true }, // 0xBC
+    Opcode { mnemonic: "LDA", mode: AbsoluteX, official: true }, // 0xBD
+    Opcode { mnemonic: "LDX", mode: AbsoluteY, official: true }, // 0xBE
+    Opcode { mnemonic: "LAX", mode: AbsoluteY, official: false }, // 0xBF
+    Opcode { mnemonic: "CPY", mode: Immediate, official: true }, // 0xC0
+    Opcode { mnemonic: "CMP", mode: IndirectX, official: true }, // 0xC1
+    Opcode { mnemonic: "NOP", mode: Immediate, official: false }, // 0xC2
+    Opcode { mnemonic: "DCP", mode: IndirectX, official: false }, // 0xC3
+    Opcode { mnemonic: "CPY", mode: ZeroPage, official: true }, // 0xC4
+    Opcode { mnemonic: "CMP", mode: ZeroPage, official: true }, // 0xC5
+    Opcode { mnemonic: "DEC", mode: ZeroPage, official: true }, // 0xC6
+    Opcode { mnemonic: "DCP", mode: ZeroPage, official: false }, // 0xC7
+    Opcode { mnemonic: "INY", mode: Implied, official: true }, // 0xC8
+    Opcode { mnemonic: "CMP", mode: Immediate, official: true }, // 0xC9
+    Opcode { mnemonic: "DEX", mode: Implied, official: true }, // 0xCA
+    Opcode { mnemonic: "AXS", mode: Immediate, official: false }, // 0xCB
+    Opcode { mnemonic: "CPY", mode: Absolute, official: true }, // 0xCC
+    Opcode { mnemonic: "CMP", mode: Absolute, official: true }, // 0xCD
+    Opcode { mnemonic: "DEC", mode: Absolute, official: true }, // 0xCE
+    Opcode { mnemonic: "DCP", mode: Absolute, official: false }, // 0xCF
+    Opcode { mnemonic: "BNE", mode: Relative, official: true }, // 0xD0
+    Opcode { mnemonic: "CMP", mode: IndirectY, official: true }, // 0xD1
+    Opcode { mnemonic: "JAM", mode: Implied, official: false }, // 0xD2
+    Opcode { mnemonic: "DCP", mode: IndirectY, official: false }, // 0xD3
+    Opcode { mnemonic: "NOP", mode: ZeroPageX, official: false }, // 0xD4
+    Opcode { mnemonic: "CMP", mode: ZeroPageX, official: true }, // 0xD5
+    Opcode { mnemonic: "DEC", mode: ZeroPageX, official: true }, // 0xD6
+    Opcode { mnemonic: "DCP", mode: ZeroPageX, official: false }, // 0xD7
+    Opcode { mnemonic: "CLD", mode: Implied, official: true }, // 0xD8
+    Opcode { mnemonic: "CMP", mode: AbsoluteY, official: true }, // 0xD9
+    Opcode { mnemonic: "NOP", mode: Implied, official: false }, // 0xDA
+    Opcode { mnemonic: "DCP", mode: AbsoluteY, official: false }, // 0xDB
+    Opcode { mnemonic: "NOP", mode: AbsoluteX, official: false }, // 0xDC
+    Opcode { mnemonic: "CMP", mode: AbsoluteX, official: true }, // 0xDD
+    Opcode { mnemonic: "DEC", mode: AbsoluteX, official: true }, // 0xDE
+    Opcode { mnemonic: "DCP", mode: AbsoluteX, official: false }, // 0xDF
+    Opcode { mnemonic: "CPX", mode: Immediate, official: true }, // 0xE0
+    Opcode { mnemonic: "SBC", mode: IndirectX, official: true }, // 0xE1
+    Opcode { mnemonic: "NOP", mode: Immediate, official: false }, // 0xE2
+    Opcode { mnemonic: "ISB", mode: IndirectX, official: false }, // 0xE3
+    Opcode { mnemonic: "CPX", mode: ZeroPage, official: true }, // 0xE4
+    Opcode { mnemonic: "SBC", mode: ZeroPage, official: true }, // 0xE5
+    Opcode { mnemonic: "INC", mode: ZeroPage, official: true }, // 0xE6
+    Opcode { mnemonic: "ISB", mode: ZeroPage, official: false }, // 0xE7
+    Opcode { mnemonic: "INX", mode: Implied, official: true }, // 0xE8
+    Opcode { mnemonic: "SBC", mode: Immediate, official: true }, // 0xE9
+    Opcode { mnemonic: "NOP", mode: Implied, official: true }, // 0xEA
+    Opcode { mnemonic: "SBC", mode: Immediate, official: false }, // 0xEB
+    Opcode { mnemonic: "CPX", mode: Absolute, official: true }, // 0xEC
+    Opcode { mnemonic: "SBC", mode: Absolute, official: true }, // 0xED
+    Opcode { mnemonic: "INC", mode: Absolute, official: true }, // 0xEE
+    Opcode { mnemonic: "ISB", mode: Absolute, official: false }, // 0xEF
+    Opcode { mnemonic: "BEQ", mode: Relative, official: true }, // 0xF0
+    Opcode { mnemonic: "SBC", mode: IndirectY, official: true }, // 0xF1
+    Opcode { mnemonic: "JAM", mode: Implied, official: false }, // 0xF2
+    Opcode { mnemonic: "ISB", mode: IndirectY, official: false }, // 0xF3
+    Opcode { mnemonic: "NOP", mode: ZeroPageX, official: false }, // 0xF4
+    Opcode { mnemonic: "SBC", mode: ZeroPageX, official: true }, // 0xF5
+    Opcode { mnemonic: "INC", mode: ZeroPageX, official: true }, // 0xF6
+    Opcode { mnemonic: "ISB", mode: ZeroPageX, official: false }, // 0xF7
+    Opcode { mnemonic: "SED", mode: Implied, official: true }, // 0xF8
+    Opcode { mnemonic: "SBC", mode: AbsoluteY, official: true }, // 0xF9
+    Opcode { mnemonic: "NOP", mode: Implied, official: false }, // 0xFA
+    Opcode { mnemonic: "ISB", mode: AbsoluteY, official: false }, // 0xFB
+    Opcode { mnemonic: "NOP", mode: AbsoluteX, official: false }, // 0xFC
+    Opcode { mnemonic: "SBC", mode: AbsoluteX, official: true }, // 0xFD
+    Opcode { mnemonic: "INC", mode: AbsoluteX, official: true }, // 0xFE
+    Opcode { mnemonic: "ISB", mode: AbsoluteX, official: false }, // 0xFF
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use memory::{Memory, Bus};
+    use std::io;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+
+    // 64 kilobytes of memory, no mapped addresses
+    struct MockBus {
+        ram: Vec<u8>,
+    }
+
+    impl Memory for MockBus {
+        fn read(&mut self, address: u16) -> u8 {
+            self.ram[address as usize]
+        }
+
+        fn write(&mut self, address: u16, value: u8) {
+            self.ram[address as usize] = value;
+        }
+    }
+
+    impl Bus for MockBus {
+        fn nmi_occured(&mut self) -> bool {
+            false
+        }
+
+        fn irq_line(&mut self) -> bool {
+            false
+        }
+
+        fn peek(&mut self, address: u16) -> u8 {
+            self.ram[address as usize]
+        }
+
+        fn ppu_position(&self) -> (u16, u16) {
+            (0, 21)
+        }
+    }
+
+    // lets the test read what the tracer has written
+    struct SharedOutput {
+        buffer: Rc<RefCell<Vec<u8>>>,
+    }
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.buffer.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // instruction bytes are placed at 0xC000
+    fn create_test_bus(instruction: &[u8]) -> MockBus {
+        let mut ram = vec![0;0xFFFF + 1];
+        for (i, byte) in instruction.iter().enumerate() {
+            ram[0xC000 + i] = *byte;
+        }
+        MockBus { ram: ram }
+    }
+
+    fn create_test_state() -> CpuState {
+        CpuState {
+            program_counter: 0xC000,
+            a: 0x00,
+            x: 0x00,
+            y: 0x00,
+            status_flags: 0x24,
+            stack_pointer: 0xFD,
+            cycles: 7,
+        }
+    }
+
+    fn create_test_tracer() -> (Tracer, Rc<RefCell<Vec<u8>>>) {
+        let buffer = Rc::new(RefCell::new(vec![]));
+        let tracer = Tracer::new(Box::new(SharedOutput { buffer: buffer.clone() }));
+        (tracer, buffer)
+    }
+
+    fn disassemble_instruction(bus: &mut MockBus, state: &CpuState) -> String {
+        let opcode = &OPCODES[bus.peek(state.program_counter) as usize];
+        disassemble(opcode, state, bus)
+    }
+
+    #[test]
+    fn line_matches_nestest_format() {
+        let mut bus = create_test_bus(&[0x4C, 0xF5, 0xC5]);
+        assert_eq!(
+            "C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7",
+            format_line(&create_test_state(), &mut bus));
+    }
+
+    #[test]
+    fn line_marks_unofficial_opcodes() {
+        let mut bus = create_test_bus(&[0x04, 0xA9]);
+        bus.ram[0xA9] = 0x3C;
+        assert_eq!(
+            "C000  04 A9    *NOP $A9 = 3C                    A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7",
+            format_line(&create_test_state(), &mut bus));
+    }
+
+    #[test]
+    fn line_shows_status_flags_with_bit_5_set_and_bit_4_clear() {
+        let mut bus = create_test_bus(&[0xEA]);
+        let mut state = create_test_state();
+        state.status_flags = 0x10;
+        assert!(format_line(&state, &mut bus).contains(" P:20 "));
+    }
+
+    #[test]
+    fn accumulator_mode_is_disassembled_correctly() {
+        let mut bus = create_test_bus(&[0x4A]);
+        assert_eq!("LSR A", disassemble_instruction(&mut bus, &create_test_state()));
+    }
+
+    #[test]
+    fn immediate_mode_is_disassembled_correctly() {
+        let mut bus = create_test_bus(&[0xA9, 0x5B]);
+        assert_eq!("LDA #$5B", disassemble_instruction(&mut bus, &create_test_state()));
+    }
+
+    #[test]
+    fn zero_page_x_mode_wraps_around_zero_page() {
+        let mut bus = create_test_bus(&[0xB5, 0xF0]);
+        bus.ram[0x0010] = 0xAB;
+        let mut state = create_test_state();
+        state.x = 0x20;
+        assert_eq!("LDA $F0,X @ 10 = AB", disassemble_instruction(&mut bus, &state));
+    }
+
+    #[test]
+    fn absolute_mode_shows_value_at_address() {
+        let mut bus = create_test_bus(&[0x8D, 0x00, 0x02]);
+        bus.ram[0x0200] = 0x7F;
+        assert_eq!("STA $0200 = 7F", disassemble_instruction(&mut bus, &create_test_state()));
+    }
+
+    #[test]
+    fn absolute_y_mode_shows_effective_address_and_value() {
+        let mut bus = create_test_bus(&[0xB9, 0xFF, 0x02]);
+        bus.ram[0x0301] = 0x89;
+        let mut state = create_test_state();
+        state.y = 0x02;
+        assert_eq!("LDA $02FF,Y @ 0301 = 89", disassemble_instruction(&mut bus, &state));
+    }
+
+    #[test]
+    fn indirect_jump_reads_high_byte_from_same_page() {
+        let mut bus = create_test_bus(&[0x6C, 0xFF, 0x02]);
+        bus.ram[0x02FF] = 0x7E;
+        bus.ram[0x0200] = 0xDB;
+        assert_eq!("JMP ($02FF) = DB7E", disassemble_instruction(&mut bus, &create_test_state()));
+    }
+
+    #[test]
+    fn indirect_x_mode_shows_pointer_address_and_value() {
+        let mut bus = create_test_bus(&[0xA1, 0x80]);
+        bus.ram[0x0082] = 0x00;
+        bus.ram[0x0083] = 0x02;
+        bus.ram[0x0200] = 0x5A;
+        let mut state = create_test_state();
+        state.x = 0x02;
+        assert_eq!("LDA ($80,X) @ 82 = 0200 = 5A", disassemble_instruction(&mut bus, &state));
+    }
+
+    #[test]
+    fn indirect_y_mode_shows_base_effective_address_and_value() {
+        let mut bus = create_test_bus(&[0xB1, 0xFF]);
+        bus.ram[0x00FF] = 0x00;
+        bus.ram[0x0000] = 0x03;
+        bus.ram[0x0304] = 0x89;
+        let mut state = create_test_state();
+        state.y = 0x04;
+        assert_eq!("LDA ($FF),Y = 0300 @ 0304 = 89", disassemble_instruction(&mut bus, &state));
+    }
+
+    #[test]
+    fn relative_mode_shows_branch_target() {
+        let mut bus = create_test_bus(&[0xD0, 0xFC]);
+        assert_eq!("BNE $BFFE", disassemble_instruction(&mut bus, &create_test_state()));
+    }
+
+    #[test]
+    fn parse_pc_range_parses_hex_range() {
+        assert_eq!(Some((0xC000, 0xC0FF)), parse_pc_range("C000-C0FF"));
+        assert_eq!(Some((0x8000, 0x8000)), parse_pc_range("$8000-$8000"));
+    }
+
+    #[test]
+    fn parse_pc_range_rejects_missing_end() {
+        assert_eq!(None, parse_pc_range("C000-"));
+        assert_eq!(None, parse_pc_range("C000"));
+    }
+
+    #[test]
+    fn parse_pc_range_rejects_extra_parts() {
+        assert_eq!(None, parse_pc_range("C000-XYZ-C0FF"));
+        assert_eq!(None, parse_pc_range("C000-C080-C0FF"));
+    }
+
+    #[test]
+    fn parse_pc_range_rejects_invalid_hex() {
+        assert_eq!(None, parse_pc_range("C000-XYZ"));
+        assert_eq!(None, parse_pc_range("10000-10001"));
+    }
+
+    #[test]
+    fn parse_pc_range_rejects_start_after_end() {
+        assert_eq!(None, parse_pc_range("C0FF-C000"));
+    }
+
+    #[test]
+    fn tracer_writes_one_line_per_trace() {
+        let (mut tracer, buffer) = create_test_tracer();
+        let mut bus = create_test_bus(&[0xEA]);
+        tracer.trace(&create_test_state(), &mut bus);
+        tracer.trace(&create_test_state(), &mut bus);
+        let output = String::from_utf8(buffer.borrow().clone()).unwrap();
+        assert_eq!(2, output.lines().count());
+        assert!(output.starts_with("C000  EA        NOP"));
+    }
+
+    #[test]
+    fn disabled_tracer_writes_nothing() {
+        let (mut tracer, buffer) = create_test_tracer();
+        let mut bus = create_test_bus(&[0xEA]);
+        tracer.set_enabled(false);
+        tracer.trace(&create_test_state(), &mut bus);
+        assert!(buffer.borrow().is_empty());
+    }
+
+    #[test]
+    fn tracer_writes_instructions_inside_pc_range() {
+        let (mut tracer, buffer) = create_test_tracer();
+        let mut bus = create_test_bus(&[0xEA]);
+        tracer.add_pc_range(0xC000, 0xC0FF);
+        tracer.trace(&create_test_state(), &mut bus);
+        assert!(!buffer.borrow().is_empty());
+    }
+
+    #[test]
+    fn tracer_skips_instructions_outside_pc_ranges() {
+        let (mut tracer, buffer) = create_test_tracer();
+        let mut bus = create_test_bus(&[0xEA]);
+        tracer.add_pc_range(0x8000, 0x80FF);
+        tracer.add_pc_range(0xC001, 0xC0FF);
+        tracer.trace(&create_test_state(), &mut bus);
+        assert!(buffer.borrow().is_empty());
+    }
+}
@@ -7,9 +7,15 @@ mod memory;
 mod memory_bus;
 mod ram;
 mod rom;
+mod mapper;
 mod controller;
 
 use std::env;
+use std::fs::File;
+use std::io::BufWriter;
+use rom::TvSystem;
+use ppu::palette::{Palette, NtscPaletteParameters};
+use cpu::trace::{Tracer, parse_pc_range};
 
 fn main() {
     let args : Vec<_> = env::args().collect();
@@ -17,5 +23,118 @@ fn main() {
         println!("Program name expected as cmd line arg");
         return;
     }
-    console::execute(&args[1]);
+
+    // optional --region ntsc|pal|dendy overrides the region given in the rom header
+    // optional --palette <file.pal> loads a 64 or 512 color palette file
+    // optional --ntsc-palette <hue>,<saturation>,<contrast>,<brightness> generates the palette;
+    // trailing parameters can be left out to use their defaults
+    // optional --trace <file> writes a nestest style cpu trace; F12 pauses and resumes tracing
+    // optional --trace-range <start>-<end> limits tracing to a hex pc range; can be repeated
+    let mut region = None;
+    let mut palette = None;
+    let mut trace_path = None;
+    let mut trace_ranges = vec![];
+    let mut options = args[2..].iter();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--region" => {
+                region = match options.next().map(|value| value.to_lowercase()) {
+                    Some(ref value) if value == "ntsc" => Some(TvSystem::NTSC),
+                    Some(ref value) if value == "pal" => Some(TvSystem::PAL),
+                    Some(ref value) if value == "dendy" => Some(TvSystem::Dendy),
+                    value => {
+                        println!("Invalid region {:?}: expected ntsc, pal or dendy", value);
+                        return;
+                    }
+                };
+            },
+            "--palette" => {
+                let file_path = match options.next() {
+                    Some(file_path) => file_path,
+                    None => {
+                        println!("Palette file expected");
+                        return;
+                    }
+                };
+
+                palette = match Palette::from_file(file_path) {
+                    Ok(palette) => Some(palette),
+                    Err(e) => {
+                        println!("Could not load the palette {}: {}", file_path, e);
+                        return;
+                    }
+                };
+            },
+            "--ntsc-palette" => {
+                let values : Vec<f64> = match options.next()
+                    .map(|value| value.split(',').map(|v| v.trim().parse::<f64>()).collect()) {
+                    Some(Ok(values)) => values,
+                    _ => {
+                        println!("Invalid palette parameters: expected <hue>,<saturation>,<contrast>,<brightness>");
+                        return;
+                    }
+                };
+
+                if values.len() > 4 {
+                    println!("Invalid palette parameters: expected <hue>,<saturation>,<contrast>,<brightness>");
+                    return;
+                }
+
+                let defaults = NtscPaletteParameters::new();
+                let parameters = NtscPaletteParameters {
+                    hue: *values.get(0).unwrap_or(&defaults.hue),
+                    saturation: *values.get(1).unwrap_or(&defaults.saturation),
+                    contrast: *values.get(2).unwrap_or(&defaults.contrast),
+                    brightness: *values.get(3).unwrap_or(&defaults.brightness),
+                };
+                palette = Some(Palette::generate_ntsc(&parameters));
+            },
+            "--trace" => {
+                trace_path = match options.next() {
+                    Some(file_path) => Some(file_path),
+                    None => {
+                        println!("Trace file expected");
+                        return;
+                    }
+                };
+            },
+            "--trace-range" => {
+                match options.next().and_then(|value| parse_pc_range(value)) {
+                    Some(range) => trace_ranges.push(range),
+                    None => {
+                        println!("Invalid trace range: expected <start>-<end> in hex, such as C000-C0FF");
+                        return;
+                    }
+                }
+            },
+            _ => {
+                println!("Unknown option {}", option);
+                return;
+            }
+        }
+    }
+
+    if trace_path.is_none() && !trace_ranges.is_empty() {
+        println!("--trace-range requires a trace file given with --trace");
+        return;
+    }
+
+    let tracer = match trace_path {
+        Some(file_path) => match File::create(file_path) {
+            Ok(file) => {
+                let mut tracer = Tracer::new(Box::new(BufWriter::new(file)));
+                for &(start, end) in trace_ranges.iter() {
+                    tracer.add_pc_range(start, end);
+                }
+                Some(tracer)
+            },
+            Err(e) => {
+                println!("Could not create the trace file {}: {}", file_path, e);
+                return;
+            }
+        },
+        None => None,
+    };
+
+    console::execute(&args[1], region, palette, tracer);
 }
@@ -0,0 +1,128 @@
+// http://wiki.nesdev.com/w/index.php/AxROM
+use mapper::Mapper;
+use rom::{Rom, Mirroring};
+
+const PRG_BANK_SIZE: usize = 0x8000;
+
+pub struct Axrom {
+    rom: Rom,
+    bank_select: u8,
+}
+
+impl Axrom {
+    pub fn new(rom: Rom) -> Axrom {
+        Axrom {
+            rom: rom,
+            bank_select: 0,
+        }
+    }
+}
+
+/*
+    Bank select ($8000-$FFFF)
+
+    7  bit  0
+    ---- ----
+    xxxM xPPP
+       |  |||
+       |  +++- Select 32 KB PRG ROM bank for CPU $8000-$FFFF
+       +------ Select 1 KB VRAM page for all 4 nametables
+
+    Only ANROM boards (NES 2.0 submapper 2) have bus conflicts; AOROM, the more common variant, does
+    not. iNES headers cannot tell these apart, so bus conflicts are not emulated unless the submapper
+    says so.
+*/
+impl Mapper for Axrom {
+    fn cpu_read(&mut self, address: u16) -> u8 {
+        if address >= 0x8000 {
+            let bank = (self.bank_select & 0x07) as usize;
+            self.rom.prg_rom_read(bank * PRG_BANK_SIZE + (address - 0x8000) as usize)
+        } else if address >= 0x6000 {
+            self.rom.work_ram_read((address - 0x6000) as usize)
+        } else {
+            0
+        }
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) {
+        if address >= 0x8000 {
+            self.bank_select = if self.rom.header.submapper == 2 {
+                value & self.cpu_read(address)
+            } else {
+                value
+            };
+        } else if address >= 0x6000 {
+            self.rom.work_ram_write((address - 0x6000) as usize, value);
+        }
+    }
+
+    fn ppu_read(&mut self, address: u16) -> u8 {
+        self.rom.chr_read(address as usize)
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        self.rom.chr_write(address as usize, value);
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        if self.bank_select & 0x10 == 0 {
+            Mirroring::SingleScreenLower
+        } else {
+            Mirroring::SingleScreenUpper
+        }
+    }
+
+    fn rom_mut(&mut self) -> &mut Rom {
+        &mut self.rom
+    }
+
+    // work ram at $6000 - $7FFF only drives the bus if the header says the board has prg ram
+    fn cpu_read_is_driven(&self, address: u16) -> bool {
+        address >= 0x8000 || (address >= 0x6000 && self.rom.has_prg_ram())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mapper::Mapper;
+    use rom::{Rom, Mirroring};
+
+    // each 32kb prg bank begins with its own bank number
+    fn create_test_axrom(prg_banks: usize) -> Axrom {
+        let mut prg = vec![0; PRG_BANK_SIZE*prg_banks];
+        for bank in 0..prg_banks {
+            prg[PRG_BANK_SIZE*bank] = bank as u8;
+        }
+        Axrom::new(Rom::with_data(prg, vec![0; 0x2000]))
+    }
+
+    #[test]
+    fn write_switches_32kb_prg_bank() {
+        let mut axrom = create_test_axrom(8);
+        axrom.cpu_write(0x8000, 0x05);
+        assert_eq!(5, axrom.cpu_read(0x8000));
+    }
+
+    #[test]
+    fn lower_nametable_is_selected_on_power_up() {
+        let axrom = create_test_axrom(8);
+        assert_eq!(Mirroring::SingleScreenLower, axrom.mirroring());
+    }
+
+    #[test]
+    fn bit_4_selects_upper_nametable() {
+        let mut axrom = create_test_axrom(8);
+        axrom.cpu_write(0x8000, 0x10);
+        assert_eq!(Mirroring::SingleScreenUpper, axrom.mirroring());
+    }
+
+    #[test]
+    fn written_value_is_anded_with_rom_value_on_anrom() {
+        let mut axrom = create_test_axrom(8);
+        axrom.rom.header.submapper = 2;
+        axrom.cpu_write(0x8000, 0x15); // rom contains 0x00 here
+        assert_eq!(0, axrom.cpu_read(0x8000));
+        assert_eq!(Mirroring::SingleScreenLower, axrom.mirroring());
+    }
+}
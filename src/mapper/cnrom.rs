@@ -0,0 +1,102 @@
+// http://wiki.nesdev.com/w/index.php/CNROM
+use mapper::Mapper;
+use rom::{Rom, Mirroring};
+
+const CHR_BANK_SIZE: usize = 0x2000;
+
+pub struct Cnrom {
+    rom: Rom,
+    chr_bank: u8,
+}
+
+impl Cnrom {
+    pub fn new(rom: Rom) -> Cnrom {
+        Cnrom {
+            rom: rom,
+            chr_bank: 0,
+        }
+    }
+}
+
+impl Mapper for Cnrom {
+    fn cpu_read(&mut self, address: u16) -> u8 {
+        if address >= 0x8000 {
+            // prg rom is not banked; 16kb roms are mirrored as with NROM
+            self.rom.prg_rom_read((address - 0x8000) as usize)
+        } else if address >= 0x6000 {
+            self.rom.work_ram_read((address - 0x6000) as usize)
+        } else {
+            0
+        }
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) {
+        if address >= 0x8000 {
+            // rom is not disabled during the write, so the value is ANDed with the rom byte (bus conflict)
+            self.chr_bank = value & self.cpu_read(address);
+        } else if address >= 0x6000 {
+            self.rom.work_ram_write((address - 0x6000) as usize, value);
+        }
+    }
+
+    fn ppu_read(&mut self, address: u16) -> u8 {
+        self.rom.chr_read(self.chr_bank as usize * CHR_BANK_SIZE + address as usize)
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        let offset = self.chr_bank as usize * CHR_BANK_SIZE + address as usize;
+        self.rom.chr_write(offset, value);
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.rom.header.mirroring
+    }
+
+    fn rom_mut(&mut self) -> &mut Rom {
+        &mut self.rom
+    }
+
+    // work ram at $6000 - $7FFF only drives the bus if the header says the board has prg ram
+    fn cpu_read_is_driven(&self, address: u16) -> bool {
+        address >= 0x8000 || (address >= 0x6000 && self.rom.has_prg_ram())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mapper::Mapper;
+    use rom::Rom;
+
+    // each chr bank begins with its own bank number
+    fn create_test_cnrom(chr_banks: usize) -> Cnrom {
+        let mut prg = vec![0xFF; 0x8000];
+        prg[0] = 0x01;
+        let mut chr = vec![0; CHR_BANK_SIZE*chr_banks];
+        for bank in 0..chr_banks {
+            chr[CHR_BANK_SIZE*bank] = bank as u8;
+        }
+        Cnrom::new(Rom::with_data(prg, chr))
+    }
+
+    #[test]
+    fn write_switches_chr_bank() {
+        let mut cnrom = create_test_cnrom(4);
+        cnrom.cpu_write(0x8001, 2);
+        assert_eq!(2, cnrom.ppu_read(0x0000));
+    }
+
+    #[test]
+    fn written_value_is_anded_with_rom_value_on_bus_conflict() {
+        let mut cnrom = create_test_cnrom(4);
+        cnrom.cpu_write(0x8000, 3); // rom contains 0x01 here
+        assert_eq!(1, cnrom.ppu_read(0x0000));
+    }
+
+    #[test]
+    fn prg_rom_is_not_banked() {
+        let mut cnrom = create_test_cnrom(4);
+        assert_eq!(0x01, cnrom.cpu_read(0x8000));
+        assert_eq!(0xFF, cnrom.cpu_read(0xFFFF));
+    }
+}
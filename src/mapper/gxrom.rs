@@ -0,0 +1,112 @@
+// http://wiki.nesdev.com/w/index.php/GxROM
+use mapper::Mapper;
+use rom::{Rom, Mirroring};
+
+const PRG_BANK_SIZE: usize = 0x8000;
+const CHR_BANK_SIZE: usize = 0x2000;
+
+pub struct Gxrom {
+    rom: Rom,
+    bank_select: u8,
+}
+
+impl Gxrom {
+    pub fn new(rom: Rom) -> Gxrom {
+        Gxrom {
+            rom: rom,
+            bank_select: 0,
+        }
+    }
+}
+
+/*
+    Bank select ($8000-$FFFF)
+
+    7  bit  0
+    ---- ----
+    xxPP xxCC
+      ||   ||
+      ||   ++- Select 8 KB CHR ROM bank for PPU $0000-$1FFF
+      ++------ Select 32 KB PRG ROM bank for CPU $8000-$FFFF
+*/
+impl Mapper for Gxrom {
+    fn cpu_read(&mut self, address: u16) -> u8 {
+        if address >= 0x8000 {
+            let bank = ((self.bank_select >> 4) & 0x03) as usize;
+            self.rom.prg_rom_read(bank * PRG_BANK_SIZE + (address - 0x8000) as usize)
+        } else if address >= 0x6000 {
+            self.rom.work_ram_read((address - 0x6000) as usize)
+        } else {
+            0
+        }
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) {
+        if address >= 0x8000 {
+            // rom is not disabled during the write, so the value is ANDed with the rom byte (bus conflict)
+            self.bank_select = value & self.cpu_read(address);
+        } else if address >= 0x6000 {
+            self.rom.work_ram_write((address - 0x6000) as usize, value);
+        }
+    }
+
+    fn ppu_read(&mut self, address: u16) -> u8 {
+        let bank = (self.bank_select & 0x03) as usize;
+        self.rom.chr_read(bank * CHR_BANK_SIZE + address as usize)
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        let bank = (self.bank_select & 0x03) as usize;
+        self.rom.chr_write(bank * CHR_BANK_SIZE + address as usize, value);
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.rom.header.mirroring
+    }
+
+    fn rom_mut(&mut self) -> &mut Rom {
+        &mut self.rom
+    }
+
+    // work ram at $6000 - $7FFF only drives the bus if the header says the board has prg ram
+    fn cpu_read_is_driven(&self, address: u16) -> bool {
+        address >= 0x8000 || (address >= 0x6000 && self.rom.has_prg_ram())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mapper::Mapper;
+    use rom::Rom;
+
+    // each prg and chr bank begins with its own bank number; rest of the prg rom is 0xFF so that
+    // bus conflicts do not interfere with bank switching
+    fn create_test_gxrom() -> Gxrom {
+        let mut prg = vec![0xFF; PRG_BANK_SIZE*4];
+        for bank in 0..4 {
+            prg[PRG_BANK_SIZE*bank] = bank as u8;
+        }
+        let mut chr = vec![0; CHR_BANK_SIZE*4];
+        for bank in 0..4 {
+            chr[CHR_BANK_SIZE*bank] = bank as u8;
+        }
+        Gxrom::new(Rom::with_data(prg, chr))
+    }
+
+    #[test]
+    fn write_switches_prg_and_chr_banks() {
+        let mut gxrom = create_test_gxrom();
+        gxrom.cpu_write(0x8001, 0x21);
+        assert_eq!(2, gxrom.cpu_read(0x8000));
+        assert_eq!(1, gxrom.ppu_read(0x0000));
+    }
+
+    #[test]
+    fn written_value_is_anded_with_rom_value_on_bus_conflict() {
+        let mut gxrom = create_test_gxrom();
+        gxrom.cpu_write(0x8000, 0x33); // rom contains 0x00 here
+        assert_eq!(0, gxrom.cpu_read(0x8000));
+        assert_eq!(0, gxrom.ppu_read(0x0000));
+    }
+}
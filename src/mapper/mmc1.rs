@@ -0,0 +1,358 @@
+// http://wiki.nesdev.com/w/index.php/MMC1
+use mapper::Mapper;
+use rom::{Rom, Mirroring};
+
+const PRG_BANK_SIZE: usize = 0x4000;
+const CHR_BANK_SIZE: usize = 0x1000;
+
+pub struct Mmc1 {
+    rom: Rom,
+    shift_register: u8,
+    write_count: u8,
+    control: u8,
+    chr_bank_0: u8,
+    chr_bank_1: u8,
+    prg_bank: u8,
+    cycle: u64,
+    last_write_cycle: Option<u64>,
+}
+
+impl Mmc1 {
+    pub fn new(rom: Rom) -> Mmc1 {
+        Mmc1 {
+            rom: rom,
+            shift_register: 0,
+            write_count: 0,
+            control: 0x0C, // prg rom mode 3 on power up; last bank is fixed at 0xC000
+            chr_bank_0: 0,
+            chr_bank_1: 0,
+            prg_bank: 0,
+            cycle: 0,
+            last_write_cycle: None,
+        }
+    }
+
+    fn prg_ram_enabled(&self) -> bool {
+        self.prg_bank & 0x10 == 0
+    }
+
+    /*
+        Load register ($8000-$FFFF)
+
+        7  bit  0
+        ---- ----
+        Rxxx xxxD
+        |       |
+        |       +- Data bit to be shifted into shift register, LSB first
+        +--------- 1: Reset shift register and write Control with (Control OR $0C),
+                      locking PRG ROM at $C000-$FFFF to the last bank.
+
+        On the fifth write, the contents of the shift register are copied into the internal
+        register selected by bits 13 and 14 of the address
+    */
+    fn load_register_write(&mut self, address: u16, value: u8) {
+        // MMC1 ignores writes on consecutive cycles; only the first one is acknowledged. Read-modify-write
+        // instructions write twice in a row, and some games rely on the second write being ignored.
+        let consecutive_write = match self.last_write_cycle {
+            Some(cycle) => self.cycle - cycle <= 1,
+            None => false,
+        };
+        self.last_write_cycle = Some(self.cycle);
+
+        if consecutive_write {
+            return;
+        }
+
+        if value & 0x80 != 0 {
+            self.shift_register = 0;
+            self.write_count = 0;
+            self.control = self.control | 0x0C;
+            return;
+        }
+
+        self.shift_register = (self.shift_register >> 1) | ((value & 0x01) << 4);
+        self.write_count += 1;
+
+        if self.write_count == 5 {
+            let register_value = self.shift_register;
+            match (address >> 13) & 0x03 {
+                0 => self.control = register_value,
+                1 => self.chr_bank_0 = register_value,
+                2 => self.chr_bank_1 = register_value,
+                3 => self.prg_bank = register_value,
+                _ => unreachable!(),
+            }
+            self.shift_register = 0;
+            self.write_count = 0;
+        }
+    }
+
+    /*
+        Control register ($8000-$9FFF)
+
+        4bit0
+        -----
+        CPPMM
+        |||||
+        |||++- Mirroring (0: one-screen, lower bank; 1: one-screen, upper bank;
+        |||               2: vertical; 3: horizontal)
+        |++--- PRG ROM bank mode (0, 1: switch 32 KB at $8000, ignoring low bit of bank number;
+        |                         2: fix first bank at $8000 and switch 16 KB bank at $C000;
+        |                         3: fix last bank at $C000 and switch 16 KB bank at $8000)
+        +----- CHR ROM bank mode (0: switch 8 KB at a time; 1: switch two separate 4 KB banks)
+    */
+    fn prg_rom_offset(&self, address: u16) -> usize {
+        // 512kb boards (SUROM) use chr bank bit 4 to select the 256kb prg rom half
+        let outer_bank = if self.rom.prg_rom_size() > 0x40000 {
+            (self.chr_bank_0 & 0x10) as usize * PRG_BANK_SIZE
+        } else {
+            0
+        };
+
+        let bank = (self.prg_bank & 0x0F) as usize;
+        // images smaller than a bank are mirrored
+        let last_bank = (self.rom.prg_rom_size().min(0x40000) / PRG_BANK_SIZE).saturating_sub(1);
+        let offset = (address & 0x3FFF) as usize;
+
+        let selected_bank = match (self.control >> 2) & 0x03 {
+            0 | 1 => (bank & 0x0E) + ((address as usize - 0x8000) / PRG_BANK_SIZE),
+            2 => if address < 0xC000 { 0 } else { bank },
+            _ => if address < 0xC000 { bank } else { last_bank },
+        };
+
+        outer_bank + selected_bank * PRG_BANK_SIZE + offset
+    }
+
+    fn chr_offset(&self, address: u16) -> usize {
+        let offset = (address & 0x0FFF) as usize;
+        if self.control & 0x10 == 0 {
+            // 8kb mode; low bit of the bank number is ignored
+            let bank = (self.chr_bank_0 & 0x1E) as usize;
+            bank * CHR_BANK_SIZE + address as usize
+        } else if address < 0x1000 {
+            self.chr_bank_0 as usize * CHR_BANK_SIZE + offset
+        } else {
+            self.chr_bank_1 as usize * CHR_BANK_SIZE + offset
+        }
+    }
+}
+
+impl Mapper for Mmc1 {
+    fn cpu_read(&mut self, address: u16) -> u8 {
+        if address >= 0x8000 {
+            let offset = self.prg_rom_offset(address);
+            self.rom.prg_rom_read(offset)
+        } else if address >= 0x6000 && self.prg_ram_enabled() {
+            self.rom.work_ram_read((address - 0x6000) as usize)
+        } else {
+            0
+        }
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) {
+        if address >= 0x8000 {
+            self.load_register_write(address, value);
+        } else if address >= 0x6000 && self.prg_ram_enabled() {
+            self.rom.work_ram_write((address - 0x6000) as usize, value);
+        }
+    }
+
+    fn ppu_read(&mut self, address: u16) -> u8 {
+        let offset = self.chr_offset(address);
+        self.rom.chr_read(offset)
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        let offset = self.chr_offset(address);
+        self.rom.chr_write(offset, value);
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        match self.control & 0x03 {
+            0 => Mirroring::SingleScreenLower,
+            1 => Mirroring::SingleScreenUpper,
+            2 => Mirroring::VerticalMirroring,
+            _ => Mirroring::HorizontalMirroring,
+        }
+    }
+
+    fn rom_mut(&mut self) -> &mut Rom {
+        &mut self.rom
+    }
+
+    fn cpu_read_is_driven(&self, address: u16) -> bool {
+        address >= 0x8000 || (address >= 0x6000 && self.prg_ram_enabled())
+    }
+
+    fn cpu_cycle(&mut self) {
+        self.cycle += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mapper::Mapper;
+    use rom::{Rom, Mirroring};
+
+    // each 16kb prg bank and 4kb chr bank begins with its own bank number
+    fn create_test_mmc1(prg_banks: usize, chr_banks: usize) -> Mmc1 {
+        let mut prg = vec![0; PRG_BANK_SIZE*prg_banks];
+        for bank in 0..prg_banks {
+            prg[PRG_BANK_SIZE*bank] = bank as u8;
+        }
+        let mut chr = vec![0; CHR_BANK_SIZE*chr_banks];
+        for bank in 0..chr_banks {
+            chr[CHR_BANK_SIZE*bank] = bank as u8;
+        }
+        Mmc1::new(Rom::with_data(prg, chr))
+    }
+
+    // writes the 5 bit value serially, with enough cycles in between so that writes are not ignored
+    fn write_register(mmc1: &mut Mmc1, address: u16, value: u8) {
+        for i in 0..5 {
+            mmc1.cpu_write(address, (value >> i) & 0x01);
+            mmc1.cpu_cycle();
+            mmc1.cpu_cycle();
+        }
+    }
+
+    #[test]
+    fn last_prg_bank_is_fixed_at_0xc000_on_power_up() {
+        let mut mmc1 = create_test_mmc1(8, 2);
+        assert_eq!(7, mmc1.cpu_read(0xC000));
+    }
+
+    #[test]
+    fn fifth_write_updates_register() {
+        let mut mmc1 = create_test_mmc1(8, 2);
+        write_register(&mut mmc1, 0xE000, 0x03);
+        assert_eq!(0x03, mmc1.prg_bank);
+    }
+
+    #[test]
+    fn fourth_write_does_not_update_register() {
+        let mut mmc1 = create_test_mmc1(8, 2);
+        for _ in 0..4 {
+            mmc1.cpu_write(0xE000, 0x01);
+            mmc1.cpu_cycle();
+            mmc1.cpu_cycle();
+        }
+        assert_eq!(0x00, mmc1.prg_bank);
+    }
+
+    #[test]
+    fn write_with_bit_7_set_resets_shift_register_and_sets_prg_mode_3() {
+        let mut mmc1 = create_test_mmc1(8, 2);
+        write_register(&mut mmc1, 0x8000, 0x00);
+        mmc1.cpu_write(0xE000, 0x01);
+        mmc1.cpu_cycle();
+        mmc1.cpu_cycle();
+        mmc1.cpu_write(0x8000, 0x80);
+        assert_eq!(0, mmc1.write_count);
+        assert_eq!(0, mmc1.shift_register);
+        assert_eq!(0x0C, mmc1.control);
+    }
+
+    #[test]
+    fn write_on_consecutive_cycle_is_ignored() {
+        let mut mmc1 = create_test_mmc1(8, 2);
+        mmc1.cpu_write(0xE000, 0x01);
+        mmc1.cpu_cycle();
+        mmc1.cpu_write(0xE000, 0x01);
+        assert_eq!(1, mmc1.write_count);
+    }
+
+    #[test]
+    fn prg_mode_3_switches_bank_at_0x8000() {
+        let mut mmc1 = create_test_mmc1(8, 2);
+        write_register(&mut mmc1, 0xE000, 0x05);
+        assert_eq!(5, mmc1.cpu_read(0x8000));
+        assert_eq!(7, mmc1.cpu_read(0xC000));
+    }
+
+    #[test]
+    fn prg_mode_2_fixes_first_bank_at_0x8000_and_switches_bank_at_0xc000() {
+        let mut mmc1 = create_test_mmc1(8, 2);
+        write_register(&mut mmc1, 0x8000, 0x08);
+        write_register(&mut mmc1, 0xE000, 0x05);
+        assert_eq!(0, mmc1.cpu_read(0x8000));
+        assert_eq!(5, mmc1.cpu_read(0xC000));
+    }
+
+    #[test]
+    fn prg_mode_0_switches_32kb_ignoring_low_bit() {
+        let mut mmc1 = create_test_mmc1(8, 2);
+        write_register(&mut mmc1, 0x8000, 0x00);
+        write_register(&mut mmc1, 0xE000, 0x05);
+        assert_eq!(4, mmc1.cpu_read(0x8000));
+        assert_eq!(5, mmc1.cpu_read(0xC000));
+    }
+
+    #[test]
+    fn chr_8kb_mode_ignores_low_bit_of_bank_0() {
+        let mut mmc1 = create_test_mmc1(2, 8);
+        write_register(&mut mmc1, 0x8000, 0x0C);
+        write_register(&mut mmc1, 0xA000, 0x05);
+        assert_eq!(4, mmc1.ppu_read(0x0000));
+        assert_eq!(5, mmc1.ppu_read(0x1000));
+    }
+
+    #[test]
+    fn chr_4kb_mode_switches_banks_separately() {
+        let mut mmc1 = create_test_mmc1(2, 8);
+        write_register(&mut mmc1, 0x8000, 0x1C);
+        write_register(&mut mmc1, 0xA000, 0x03);
+        write_register(&mut mmc1, 0xC000, 0x06);
+        assert_eq!(3, mmc1.ppu_read(0x0000));
+        assert_eq!(6, mmc1.ppu_read(0x1000));
+    }
+
+    #[test]
+    fn control_register_selects_mirroring() {
+        let mut mmc1 = create_test_mmc1(2, 2);
+        write_register(&mut mmc1, 0x8000, 0x00);
+        assert_eq!(Mirroring::SingleScreenLower, mmc1.mirroring());
+        write_register(&mut mmc1, 0x8000, 0x01);
+        assert_eq!(Mirroring::SingleScreenUpper, mmc1.mirroring());
+        write_register(&mut mmc1, 0x8000, 0x02);
+        assert_eq!(Mirroring::VerticalMirroring, mmc1.mirroring());
+        write_register(&mut mmc1, 0x8000, 0x03);
+        assert_eq!(Mirroring::HorizontalMirroring, mmc1.mirroring());
+    }
+
+    #[test]
+    fn prg_ram_can_be_disabled() {
+        let mut mmc1 = create_test_mmc1(2, 2);
+        mmc1.cpu_write(0x6000, 0x12);
+        write_register(&mut mmc1, 0xE000, 0x10);
+        assert_eq!(0, mmc1.cpu_read(0x6000));
+        write_register(&mut mmc1, 0xE000, 0x00);
+        assert_eq!(0x12, mmc1.cpu_read(0x6000));
+    }
+
+    #[test]
+    fn prg_rom_smaller_than_16kb_is_mirrored() {
+        let mut prg = vec![0; 0x2000];
+        prg[0] = 0x12;
+        let mut mmc1 = Mmc1::new(Rom::with_data(prg, vec![0; CHR_BANK_SIZE*2]));
+        assert_eq!(0x12, mmc1.cpu_read(0x8000));
+        assert_eq!(0x12, mmc1.cpu_read(0xC000));
+    }
+
+    #[test]
+    fn disabled_prg_ram_does_not_drive_the_bus() {
+        let mut mmc1 = create_test_mmc1(2, 2);
+        write_register(&mut mmc1, 0xE000, 0x10);
+        assert_eq!(false, mmc1.cpu_read_is_driven(0x6000));
+        assert_eq!(true, mmc1.cpu_read_is_driven(0x8000));
+    }
+
+    #[test]
+    fn chr_bank_0_bit_4_selects_prg_rom_half_on_512kb_boards() {
+        let mut mmc1 = create_test_mmc1(32, 2);
+        assert_eq!(15, mmc1.cpu_read(0xC000));
+        write_register(&mut mmc1, 0xA000, 0x10);
+        assert_eq!(31, mmc1.cpu_read(0xC000));
+    }
+}
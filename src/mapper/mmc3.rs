@@ -0,0 +1,388 @@
+// http://wiki.nesdev.com/w/index.php/MMC3
+use mapper::Mapper;
+use rom::{Rom, Mirroring};
+
+use std::cmp;
+
+const PRG_BANK_SIZE: usize = 0x2000;
+const CHR_BANK_SIZE: usize = 0x0400;
+
+pub struct Mmc3 {
+    rom: Rom,
+    bank_select: u8,
+    bank_registers: [u8; 8],
+    mirroring: Mirroring,
+    prg_ram_enabled: bool,
+    prg_ram_write_protected: bool,
+    irq_latch: u8,
+    irq_counter: u8,
+    irq_reload: bool,
+    irq_enabled: bool,
+    irq_pending: bool,
+}
+
+impl Mmc3 {
+    pub fn new(rom: Rom) -> Mmc3 {
+        let mirroring = rom.header.mirroring;
+        Mmc3 {
+            rom: rom,
+            bank_select: 0,
+            bank_registers: [0, 2, 4, 5, 6, 7, 0, 1],
+            mirroring: mirroring,
+            prg_ram_enabled: true,
+            prg_ram_write_protected: false,
+            irq_latch: 0,
+            irq_counter: 0,
+            irq_reload: false,
+            irq_enabled: false,
+            irq_pending: false,
+        }
+    }
+
+    /*
+        Registers are selected by the address range and whether the address is even or odd
+
+        $8000-$9FFE even: bank select        $8001-$9FFF odd: bank data
+        $A000-$BFFE even: mirroring          $A001-$BFFF odd: prg ram protect
+        $C000-$DFFE even: irq latch          $C001-$DFFF odd: irq reload
+        $E000-$FFFE even: irq disable        $E001-$FFFF odd: irq enable
+    */
+    fn register_write(&mut self, address: u16, value: u8) {
+        match (address & 0xE000, address & 0x01) {
+            (0x8000, 0) => self.bank_select = value,
+            (0x8000, _) => self.bank_registers[(self.bank_select & 0x07) as usize] = value,
+            (0xA000, 0) => self.mirroring_write(value),
+            (0xA000, _) => {
+                self.prg_ram_enabled = value & 0x80 != 0;
+                self.prg_ram_write_protected = value & 0x40 != 0;
+            },
+            (0xC000, 0) => self.irq_latch = value,
+            (0xC000, _) => {
+                // counter is reloaded on the next rising edge of ppu A12
+                self.irq_counter = 0;
+                self.irq_reload = true;
+            },
+            (0xE000, 0) => {
+                // disabling also acknowledges any pending interrupt
+                self.irq_enabled = false;
+                self.irq_pending = false;
+            },
+            (0xE000, _) => self.irq_enabled = true,
+            _ => unreachable!(),
+        }
+    }
+
+    fn mirroring_write(&mut self, value: u8) {
+        // boards with four screen vram ignore the mirroring register
+        if self.mirroring == Mirroring::FourScreenVRAM {
+            return;
+        }
+
+        self.mirroring = if value & 0x01 == 0 {
+            Mirroring::VerticalMirroring
+        } else {
+            Mirroring::HorizontalMirroring
+        };
+    }
+
+    /*
+        Bank select bit 6 selects the prg rom bank mode
+
+        CPU address | mode 0  | mode 1
+        $8000-$9FFF | R6      | (-2)
+        $A000-$BFFF | R7      | R7
+        $C000-$DFFF | (-2)    | R6
+        $E000-$FFFF | (-1)    | (-1)
+
+        where (-1) is the last bank and (-2) the second to last bank
+    */
+    fn prg_rom_offset(&self, address: u16) -> usize {
+        // images smaller than two banks have their banks mirrored, just like banks past the end
+        let bank_count = cmp::max(1, self.rom.prg_rom_size() / PRG_BANK_SIZE);
+        let last_bank = bank_count - 1;
+        let second_to_last_bank = (last_bank + bank_count - 1) % bank_count;
+        let swap_mode = self.bank_select & 0x40 != 0;
+
+        // upper two bits are ignored by the hardware
+        let r6 = (self.bank_registers[6] & 0x3F) as usize;
+        let r7 = (self.bank_registers[7] & 0x3F) as usize;
+
+        let bank = match ((address - 0x8000) / PRG_BANK_SIZE as u16, swap_mode) {
+            (0, false) => r6,
+            (0, true) => second_to_last_bank,
+            (1, _) => r7,
+            (2, false) => second_to_last_bank,
+            (2, true) => r6,
+            _ => last_bank,
+        };
+
+        bank * PRG_BANK_SIZE + (address as usize & (PRG_BANK_SIZE - 1))
+    }
+
+    /*
+        Bank select bit 7 inverts the chr address line 12; the 2kb banks are at $1000 instead of $0000
+        when it is set
+
+        PPU address | 1kb bank
+        $0000-$07FF | R0 (low bit ignored, 2kb)
+        $0800-$0FFF | R1 (low bit ignored, 2kb)
+        $1000-$13FF | R2
+        $1400-$17FF | R3
+        $1800-$1BFF | R4
+        $1C00-$1FFF | R5
+    */
+    fn chr_offset(&self, address: u16) -> usize {
+        let address = if self.bank_select & 0x80 == 0 {
+            address
+        } else {
+            address ^ 0x1000
+        };
+
+        let bank = match (address >> 10) & 0x07 {
+            0 => self.bank_registers[0] & 0xFE,
+            1 => self.bank_registers[0] | 0x01,
+            2 => self.bank_registers[1] & 0xFE,
+            3 => self.bank_registers[1] | 0x01,
+            slot => self.bank_registers[slot as usize - 2],
+        } as usize;
+
+        bank * CHR_BANK_SIZE + (address as usize & (CHR_BANK_SIZE - 1))
+    }
+}
+
+impl Mapper for Mmc3 {
+    fn cpu_read(&mut self, address: u16) -> u8 {
+        if address >= 0x8000 {
+            let offset = self.prg_rom_offset(address);
+            self.rom.prg_rom_read(offset)
+        } else if address >= 0x6000 && self.prg_ram_enabled {
+            self.rom.work_ram_read((address - 0x6000) as usize)
+        } else {
+            0
+        }
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) {
+        if address >= 0x8000 {
+            self.register_write(address, value);
+        } else if address >= 0x6000 && self.prg_ram_enabled && !self.prg_ram_write_protected {
+            self.rom.work_ram_write((address - 0x6000) as usize, value);
+        }
+    }
+
+    fn ppu_read(&mut self, address: u16) -> u8 {
+        let offset = self.chr_offset(address);
+        self.rom.chr_read(offset)
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        let offset = self.chr_offset(address);
+        self.rom.chr_write(offset, value);
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn rom_mut(&mut self) -> &mut Rom {
+        &mut self.rom
+    }
+
+    fn cpu_read_is_driven(&self, address: u16) -> bool {
+        address >= 0x8000 || (address >= 0x6000 && self.prg_ram_enabled)
+    }
+
+    fn irq_pending(&self) -> bool {
+        self.irq_pending
+    }
+
+    // the counter is clocked once per scanline when rendering, as sprites and background use
+    // different pattern tables
+    fn ppu_a12_rising_edge(&mut self) {
+        if self.irq_counter == 0 || self.irq_reload {
+            self.irq_counter = self.irq_latch;
+            self.irq_reload = false;
+        } else {
+            self.irq_counter -= 1;
+        }
+
+        if self.irq_counter == 0 && self.irq_enabled {
+            self.irq_pending = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mapper::Mapper;
+    use rom::{Rom, Mirroring};
+
+    // each 8kb prg bank and 1kb chr bank begins with its own bank number
+    fn create_test_mmc3(prg_banks: usize, chr_banks: usize) -> Mmc3 {
+        let mut prg = vec![0; PRG_BANK_SIZE*prg_banks];
+        for bank in 0..prg_banks {
+            prg[PRG_BANK_SIZE*bank] = bank as u8;
+        }
+        let mut chr = vec![0; CHR_BANK_SIZE*chr_banks];
+        for bank in 0..chr_banks {
+            chr[CHR_BANK_SIZE*bank] = bank as u8;
+        }
+        Mmc3::new(Rom::with_data(prg, chr))
+    }
+
+    fn write_bank_register(mmc3: &mut Mmc3, register: u8, value: u8) {
+        let bank_select = (mmc3.bank_select & 0xC0) | register;
+        mmc3.cpu_write(0x8000, bank_select);
+        mmc3.cpu_write(0x8001, value);
+    }
+
+    #[test]
+    fn fixed_banks_are_mirrored_with_single_8kb_prg_bank() {
+        let mut mmc3 = create_test_mmc3(1, 8);
+        assert_eq!(0, mmc3.cpu_read(0xC000));
+        assert_eq!(0, mmc3.cpu_read(0xE000));
+        mmc3.cpu_write(0x8000, 0x40);
+        assert_eq!(0, mmc3.cpu_read(0x8000));
+    }
+
+    #[test]
+    fn prg_mode_0_switches_0x8000_and_fixes_second_to_last_bank_at_0xc000() {
+        let mut mmc3 = create_test_mmc3(16, 8);
+        write_bank_register(&mut mmc3, 6, 3);
+        write_bank_register(&mut mmc3, 7, 5);
+        assert_eq!(3, mmc3.cpu_read(0x8000));
+        assert_eq!(5, mmc3.cpu_read(0xA000));
+        assert_eq!(14, mmc3.cpu_read(0xC000));
+        assert_eq!(15, mmc3.cpu_read(0xE000));
+    }
+
+    #[test]
+    fn prg_mode_1_switches_0xc000_and_fixes_second_to_last_bank_at_0x8000() {
+        let mut mmc3 = create_test_mmc3(16, 8);
+        mmc3.cpu_write(0x8000, 0x40);
+        write_bank_register(&mut mmc3, 6, 3);
+        write_bank_register(&mut mmc3, 7, 5);
+        assert_eq!(14, mmc3.cpu_read(0x8000));
+        assert_eq!(5, mmc3.cpu_read(0xA000));
+        assert_eq!(3, mmc3.cpu_read(0xC000));
+        assert_eq!(15, mmc3.cpu_read(0xE000));
+    }
+
+    #[test]
+    fn chr_2kb_banks_ignore_low_bit() {
+        let mut mmc3 = create_test_mmc3(4, 16);
+        write_bank_register(&mut mmc3, 0, 5);
+        write_bank_register(&mut mmc3, 1, 8);
+        assert_eq!(4, mmc3.ppu_read(0x0000));
+        assert_eq!(5, mmc3.ppu_read(0x0400));
+        assert_eq!(8, mmc3.ppu_read(0x0800));
+        assert_eq!(9, mmc3.ppu_read(0x0C00));
+    }
+
+    #[test]
+    fn chr_1kb_banks_are_at_0x1000() {
+        let mut mmc3 = create_test_mmc3(4, 16);
+        write_bank_register(&mut mmc3, 2, 10);
+        write_bank_register(&mut mmc3, 5, 13);
+        assert_eq!(10, mmc3.ppu_read(0x1000));
+        assert_eq!(13, mmc3.ppu_read(0x1C00));
+    }
+
+    #[test]
+    fn chr_inversion_swaps_pattern_table_halves() {
+        let mut mmc3 = create_test_mmc3(4, 16);
+        mmc3.cpu_write(0x8000, 0x80);
+        write_bank_register(&mut mmc3, 0, 6);
+        write_bank_register(&mut mmc3, 2, 10);
+        assert_eq!(10, mmc3.ppu_read(0x0000));
+        assert_eq!(6, mmc3.ppu_read(0x1000));
+        assert_eq!(7, mmc3.ppu_read(0x1400));
+    }
+
+    #[test]
+    fn write_to_0xa000_selects_mirroring() {
+        let mut mmc3 = create_test_mmc3(4, 8);
+        mmc3.cpu_write(0xA000, 0x01);
+        assert_eq!(Mirroring::HorizontalMirroring, mmc3.mirroring());
+        mmc3.cpu_write(0xA000, 0x00);
+        assert_eq!(Mirroring::VerticalMirroring, mmc3.mirroring());
+    }
+
+    #[test]
+    fn write_protected_prg_ram_ignores_writes() {
+        let mut mmc3 = create_test_mmc3(4, 8);
+        mmc3.cpu_write(0x6000, 0x12);
+        mmc3.cpu_write(0xA001, 0xC0);
+        mmc3.cpu_write(0x6000, 0x34);
+        assert_eq!(0x12, mmc3.cpu_read(0x6000));
+    }
+
+    #[test]
+    fn disabled_prg_ram_reads_zero() {
+        let mut mmc3 = create_test_mmc3(4, 8);
+        mmc3.cpu_write(0x6000, 0x12);
+        mmc3.cpu_write(0xA001, 0x00);
+        assert_eq!(0, mmc3.cpu_read(0x6000));
+    }
+
+    #[test]
+    fn disabled_prg_ram_does_not_drive_the_bus() {
+        let mut mmc3 = create_test_mmc3(4, 8);
+        mmc3.cpu_write(0xA001, 0x00);
+        assert_eq!(false, mmc3.cpu_read_is_driven(0x6000));
+        assert_eq!(true, mmc3.cpu_read_is_driven(0x8000));
+    }
+
+    #[test]
+    fn irq_is_raised_when_counter_reaches_zero() {
+        let mut mmc3 = create_test_mmc3(4, 8);
+        mmc3.cpu_write(0xC000, 2);
+        mmc3.cpu_write(0xC001, 0);
+        mmc3.cpu_write(0xE001, 0);
+
+        mmc3.ppu_a12_rising_edge(); // reload to 2
+        assert_eq!(false, mmc3.irq_pending());
+        mmc3.ppu_a12_rising_edge();
+        assert_eq!(false, mmc3.irq_pending());
+        mmc3.ppu_a12_rising_edge();
+        assert_eq!(true, mmc3.irq_pending());
+    }
+
+    #[test]
+    fn irq_is_not_raised_when_disabled() {
+        let mut mmc3 = create_test_mmc3(4, 8);
+        mmc3.cpu_write(0xC000, 1);
+        mmc3.cpu_write(0xC001, 0);
+
+        mmc3.ppu_a12_rising_edge();
+        mmc3.ppu_a12_rising_edge();
+        assert_eq!(false, mmc3.irq_pending());
+    }
+
+    #[test]
+    fn counter_is_reloaded_after_reaching_zero() {
+        let mut mmc3 = create_test_mmc3(4, 8);
+        mmc3.cpu_write(0xC000, 3);
+        mmc3.cpu_write(0xC001, 0);
+
+        mmc3.ppu_a12_rising_edge();
+        mmc3.ppu_a12_rising_edge();
+        mmc3.ppu_a12_rising_edge();
+        mmc3.ppu_a12_rising_edge();
+        assert_eq!(0, mmc3.irq_counter);
+        mmc3.ppu_a12_rising_edge();
+        assert_eq!(3, mmc3.irq_counter);
+    }
+
+    #[test]
+    fn write_to_0xe000_acknowledges_irq() {
+        let mut mmc3 = create_test_mmc3(4, 8);
+        mmc3.cpu_write(0xC000, 0);
+        mmc3.cpu_write(0xE001, 0);
+        mmc3.ppu_a12_rising_edge();
+        assert_eq!(true, mmc3.irq_pending());
+        mmc3.cpu_write(0xE000, 0);
+        assert_eq!(false, mmc3.irq_pending());
+    }
+}
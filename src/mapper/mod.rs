@@ -0,0 +1,86 @@
+// see http://wiki.nesdev.com/w/index.php/Mapper for more information
+mod nrom;
+mod mmc1;
+mod uxrom;
+mod cnrom;
+mod mmc3;
+mod axrom;
+mod gxrom;
+
+use rom::{Rom, RomError, Mirroring};
+use self::nrom::Nrom;
+use self::mmc1::Mmc1;
+use self::uxrom::Uxrom;
+use self::cnrom::Cnrom;
+use self::mmc3::Mmc3;
+use self::axrom::Axrom;
+use self::gxrom::Gxrom;
+
+// Cartridge hardware. The memory bus forwards cpu accesses in range 0x4020 - 0xFFFF
+// and vram forwards ppu accesses in range 0x0000 - 0x1FFF (pattern tables) to the mapper,
+// which then translates them into prg\chr offsets depending on the current bank configuration.
+pub trait Mapper {
+    fn cpu_read(&mut self, address: u16) -> u8;
+    fn cpu_write(&mut self, address: u16, value: u8);
+    fn ppu_read(&mut self, address: u16) -> u8;
+    fn ppu_write(&mut self, address: u16, value: u8);
+
+    // nametable arrangement; some mappers can change this at runtime
+    fn mirroring(&self) -> Mirroring;
+
+    // the cartridge itself; used for accessing battery backed save ram
+    fn rom_mut(&mut self) -> &mut Rom;
+
+    // Whether the cartridge drives the data bus when the cpu reads the address. Reads nothing
+    // responds to return the last value on the bus (open bus). By default only the prg rom is
+    // driven; boards with prg ram override this
+    fn cpu_read_is_driven(&self, address: u16) -> bool {
+        address >= 0x8000
+    }
+
+    // state of the cartridge irq line
+    fn irq_pending(&self) -> bool {
+        false
+    }
+
+    // Called once for each cpu cycle. Mappers that care about write timing use this
+    fn cpu_cycle(&mut self) {
+
+    }
+
+    // Called by the ppu when address line 12 rises during pattern table fetches. This happens
+    // (usually) once per scanline, and is used by mappers with scanline counters
+    fn ppu_a12_rising_edge(&mut self) {
+
+    }
+}
+
+pub fn create_mapper(rom: Rom) -> Result<Box<dyn Mapper>, RomError> {
+    let mapper: Box<dyn Mapper> = match rom.header.mapper {
+        0 => Box::new(Nrom::new(rom)),
+        1 => Box::new(Mmc1::new(rom)),
+        2 => Box::new(Uxrom::new(rom)),
+        3 => Box::new(Cnrom::new(rom)),
+        4 => Box::new(Mmc3::new(rom)),
+        7 => Box::new(Axrom::new(rom)),
+        66 => Box::new(Gxrom::new(rom)),
+        mapper => return Err(RomError::UnsupportedMapper(mapper)),
+    };
+    Ok(mapper)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_mapper_returns_error_on_unsupported_mapper() {
+        let mut rom = Rom::with_data(vec![0; 0x4000], vec![0; 0x2000]);
+        rom.header.mapper = 5;
+        match create_mapper(rom) {
+            Err(RomError::UnsupportedMapper(5)) => {},
+            Err(e) => panic!("Unexpected error {:?}", e),
+            Ok(_) => panic!("Unsupported mapper was created"),
+        }
+    }
+}
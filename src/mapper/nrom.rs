@@ -0,0 +1,147 @@
+// http://wiki.nesdev.com/w/index.php/NROM
+use mapper::Mapper;
+use rom::{Rom, Mirroring};
+
+pub struct Nrom {
+    rom: Rom,
+}
+
+impl Nrom {
+    pub fn new(rom: Rom) -> Nrom {
+        Nrom {
+            rom: rom,
+        }
+    }
+}
+
+impl Mapper for Nrom {
+    fn cpu_read(&mut self, address: u16) -> u8 {
+        if address >= 0x8000 {
+            // program rom is mapped to memory addresses 0x8000 - 0xBFFF and 0xC000 - 0xFFFF
+            // if rom size is 16kb, 0x8000 - 0xBFFF and 0xC000 - 0xFFFF are mirrored
+            self.rom.prg_rom_read((address - 0x8000) as usize)
+        } else if address >= 0x6000 {
+            self.rom.work_ram_read((address - 0x6000) as usize)
+        } else {
+            0
+        }
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) {
+        // nrom has no registers, so writes to rom are ignored
+        if address >= 0x6000 && address <= 0x7FFF {
+            self.rom.work_ram_write((address - 0x6000) as usize, value);
+        }
+    }
+
+    fn ppu_read(&mut self, address: u16) -> u8 {
+        self.rom.chr_read(address as usize)
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        self.rom.chr_write(address as usize, value);
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.rom.header.mirroring
+    }
+
+    fn rom_mut(&mut self) -> &mut Rom {
+        &mut self.rom
+    }
+
+    // prg ram is optional on nrom boards
+    fn cpu_read_is_driven(&self, address: u16) -> bool {
+        address >= 0x8000 || (address >= 0x6000 && self.rom.has_prg_ram())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mapper::Mapper;
+    use rom::Rom;
+
+    fn create_test_nrom(prg_banks: usize) -> Nrom {
+        let mut prg = vec![0; 0x4000*prg_banks];
+        for bank in 0..prg_banks {
+            prg[0x4000*bank] = bank as u8 + 1;
+        }
+        let mut chr = vec![0; 0x2000];
+        chr[0x1FFF] = 0xC4;
+        Nrom::new(Rom::with_data(prg, chr))
+    }
+
+    #[test]
+    fn read_from_0x8000_reads_first_prg_bank() {
+        let mut nrom = create_test_nrom(2);
+        assert_eq!(1, nrom.cpu_read(0x8000));
+    }
+
+    #[test]
+    fn read_from_0xc000_reads_second_prg_bank_with_32kb_prg_rom() {
+        let mut nrom = create_test_nrom(2);
+        assert_eq!(2, nrom.cpu_read(0xC000));
+    }
+
+    #[test]
+    fn read_from_0xc000_is_mirrored_to_0x8000_with_16kb_prg_rom() {
+        let mut nrom = create_test_nrom(1);
+        assert_eq!(1, nrom.cpu_read(0xC000));
+    }
+
+    #[test]
+    fn write_to_work_ram_can_be_read_back() {
+        let mut nrom = create_test_nrom(1);
+        nrom.cpu_write(0x6123, 0x5A);
+        assert_eq!(0x5A, nrom.cpu_read(0x6123));
+    }
+
+    #[test]
+    fn write_to_prg_rom_is_ignored() {
+        let mut nrom = create_test_nrom(1);
+        nrom.cpu_write(0x8000, 0x5A);
+        assert_eq!(1, nrom.cpu_read(0x8000));
+    }
+
+    #[test]
+    fn write_below_work_ram_is_ignored() {
+        let mut nrom = create_test_nrom(1);
+        nrom.cpu_write(0x4020, 0x5A);
+        nrom.cpu_write(0x5FFF, 0x5A);
+        assert_eq!(0, nrom.cpu_read(0x6000));
+    }
+
+    #[test]
+    fn work_ram_does_not_drive_the_bus_without_prg_ram() {
+        let nrom = create_test_nrom(1);
+        assert_eq!(false, nrom.cpu_read_is_driven(0x6000));
+        assert_eq!(true, nrom.cpu_read_is_driven(0x8000));
+    }
+
+    #[test]
+    fn ppu_read_reads_from_chr_rom() {
+        let mut nrom = create_test_nrom(1);
+        assert_eq!(0xC4, nrom.ppu_read(0x1FFF));
+    }
+
+    #[test]
+    fn mirroring_is_taken_from_header() {
+        let nrom = create_test_nrom(1);
+        assert_eq!(Mirroring::VerticalMirroring, nrom.mirroring());
+    }
+
+    #[test]
+    fn ppu_write_to_chr_rom_is_ignored() {
+        let mut nrom = create_test_nrom(1);
+        nrom.ppu_write(0x1FFF, 0x12);
+        assert_eq!(0xC4, nrom.ppu_read(0x1FFF));
+    }
+
+    #[test]
+    fn ppu_write_to_chr_ram_can_be_read_back() {
+        let mut nrom = Nrom::new(Rom::with_data(vec![0; 0x4000], vec![]));
+        nrom.ppu_write(0x1FFF, 0x34);
+        assert_eq!(0x34, nrom.ppu_read(0x1FFF));
+    }
+}
@@ -0,0 +1,142 @@
+// http://wiki.nesdev.com/w/index.php/UxROM
+use mapper::Mapper;
+use rom::{Rom, Mirroring};
+
+const PRG_BANK_SIZE: usize = 0x4000;
+
+pub struct Uxrom {
+    rom: Rom,
+    prg_bank: u8,
+}
+
+impl Uxrom {
+    pub fn new(rom: Rom) -> Uxrom {
+        Uxrom {
+            rom: rom,
+            prg_bank: 0,
+        }
+    }
+}
+
+impl Mapper for Uxrom {
+    fn cpu_read(&mut self, address: u16) -> u8 {
+        if address >= 0xC000 {
+            // last bank is fixed at 0xC000; images smaller than a bank are mirrored
+            let last_bank = (self.rom.prg_rom_size() / PRG_BANK_SIZE).saturating_sub(1);
+            self.rom.prg_rom_read(last_bank * PRG_BANK_SIZE + (address - 0xC000) as usize)
+        } else if address >= 0x8000 {
+            let bank = self.prg_bank as usize;
+            self.rom.prg_rom_read(bank * PRG_BANK_SIZE + (address - 0x8000) as usize)
+        } else if address >= 0x6000 {
+            self.rom.work_ram_read((address - 0x6000) as usize)
+        } else {
+            0
+        }
+    }
+
+    fn cpu_write(&mut self, address: u16, value: u8) {
+        if address >= 0x8000 {
+            // rom is not disabled during the write, so the value is ANDed with the rom byte (bus conflict)
+            self.prg_bank = value & self.cpu_read(address);
+        } else if address >= 0x6000 {
+            self.rom.work_ram_write((address - 0x6000) as usize, value);
+        }
+    }
+
+    fn ppu_read(&mut self, address: u16) -> u8 {
+        self.rom.chr_read(address as usize)
+    }
+
+    fn ppu_write(&mut self, address: u16, value: u8) {
+        self.rom.chr_write(address as usize, value);
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.rom.header.mirroring
+    }
+
+    fn rom_mut(&mut self) -> &mut Rom {
+        &mut self.rom
+    }
+
+    // work ram at $6000 - $7FFF only drives the bus if the header says the board has prg ram
+    fn cpu_read_is_driven(&self, address: u16) -> bool {
+        address >= 0x8000 || (address >= 0x6000 && self.rom.has_prg_ram())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mapper::Mapper;
+    use rom::Rom;
+
+    // each prg bank begins with its own bank number, rest of the rom is 0xFF so that
+    // bus conflicts do not interfere with bank switching
+    fn create_test_uxrom(prg_banks: usize) -> Uxrom {
+        let mut prg = vec![0xFF; PRG_BANK_SIZE*prg_banks];
+        for bank in 0..prg_banks {
+            prg[PRG_BANK_SIZE*bank] = bank as u8;
+        }
+        // UxROM boards use chr ram
+        Uxrom::new(Rom::with_data(prg, vec![]))
+    }
+
+    #[test]
+    fn work_ram_does_not_drive_the_bus_without_prg_ram() {
+        let uxrom = create_test_uxrom(8);
+        assert_eq!(false, uxrom.cpu_read_is_driven(0x6000));
+        assert_eq!(false, uxrom.cpu_read_is_driven(0x7FFF));
+        assert_eq!(true, uxrom.cpu_read_is_driven(0x8000));
+    }
+
+    #[test]
+    fn work_ram_drives_the_bus_with_prg_ram() {
+        let mut uxrom = create_test_uxrom(8);
+        uxrom.rom.header.prg_ram_size = 0x2000;
+        uxrom.cpu_write(0x6000, 0x5A);
+        assert_eq!(true, uxrom.cpu_read_is_driven(0x6000));
+        assert_eq!(true, uxrom.cpu_read_is_driven(0x7FFF));
+        assert_eq!(0x5A, uxrom.cpu_read(0x6000));
+    }
+
+    #[test]
+    fn prg_rom_smaller_than_16kb_is_mirrored() {
+        let mut prg = vec![0xFF; 0x2000];
+        prg[0] = 0x12;
+        let mut uxrom = Uxrom::new(Rom::with_data(prg, vec![]));
+        assert_eq!(0x12, uxrom.cpu_read(0x8000));
+        assert_eq!(0x12, uxrom.cpu_read(0xC000));
+        assert_eq!(0x12, uxrom.cpu_read(0xE000));
+    }
+
+    #[test]
+    fn last_prg_bank_is_fixed_at_0xc000() {
+        let mut uxrom = create_test_uxrom(8);
+        uxrom.cpu_write(0x8001, 3);
+        assert_eq!(7, uxrom.cpu_read(0xC000));
+    }
+
+    #[test]
+    fn write_switches_prg_bank_at_0x8000() {
+        let mut uxrom = create_test_uxrom(8);
+        uxrom.cpu_write(0x8001, 3);
+        assert_eq!(3, uxrom.cpu_read(0x8000));
+    }
+
+    #[test]
+    fn written_value_is_anded_with_rom_value_on_bus_conflict() {
+        let mut uxrom = create_test_uxrom(8);
+        uxrom.cpu_write(0xC000, 0x05); // rom contains 0x07 here
+        assert_eq!(0x05, uxrom.prg_bank);
+        uxrom.cpu_write(0xC000, 0x0E);
+        assert_eq!(0x06, uxrom.prg_bank);
+    }
+
+    #[test]
+    fn ppu_write_writes_to_chr_ram() {
+        let mut uxrom = create_test_uxrom(8);
+        uxrom.ppu_write(0x1234, 0xAB);
+        assert_eq!(0xAB, uxrom.ppu_read(0x1234));
+    }
+}
@@ -5,6 +5,18 @@ pub trait Memory {
     fn write(&mut self, address: u16, value: u8);
 }
 
+// The cpu side memory bus, which also carries the interrupt lines. Cpu is generic over this, so
+// memory accesses are statically dispatched
+pub trait Bus: Memory {
+    fn nmi_occured(&mut self) -> bool;
+    fn irq_line(&mut self) -> bool;
+
+    // for the trace logger: reads without side effects and without clocking the system, and
+    // the ppu (scanline, dot) position
+    fn peek(&mut self, address: u16) -> u8;
+    fn ppu_position(&self) -> (u16, u16);
+}
+
 impl fmt::Debug for Memory {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "(Memory content not shown)")
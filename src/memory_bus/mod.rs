@@ -1,77 +1,162 @@
 use memory::*;
+use mapper::Mapper;
 use ram::*;
 use ppu::*;
 use apu::*;
 use controller::*;
-use std::rc::Rc;
-use std::cell::RefCell;
 
+// Every cpu read and write goes through the bus and takes one cpu cycle, so the bus
+// clocks the rest of the system once per access. This keeps the ppu, apu and mapper in step
+// with the cpu in the middle of instructions as well.
+//
+// The bus owns the ppu, apu and controllers. The cartridge is owned by the ppu, and is reached
+// through it.
 pub struct MemoryBus<'a> {
-    rom: Rc<RefCell<Box<Memory>>>,
     ram: Box<Memory>,
-    ppu: Rc<RefCell<Ppu<'a>>>,
-    apu: Rc<RefCell<Apu<'a>>>,
-    controllers: Vec<Rc<RefCell<Controller>>>,
+    ppu: Ppu<'a>,
+    apu: Apu,
+    controllers: Vec<Controller>,
+    cycles: u64,
+    open_bus: u8, // last value on the data bus; returned for bits and addresses nothing drives
 }
 
 
 impl<'a> Memory for MemoryBus<'a> {
     fn read(&mut self, address: u16) -> u8 {
-        if address < 0x2000 {
-            self.ram.read(address)
-        } else if (address >= 0x2000 && address <= 0x3FFF) || address == 0x4014 {
-            self.ppu.borrow_mut().read(address)
-        } else if address == 0x4016 {
-            self.controllers[0].borrow_mut().read(address)
-        } else if address == 0x04017 {
-            self.controllers[1].borrow_mut().read(address)
-        } else if (address >= 0x4000 && address <= 0x4015) || address == 0x4017 {
-            self.apu.borrow_mut().read(address)
-        } else if address >= 0x4020 {
-            self.rom.borrow_mut().read(address)
-        } else {
-            0
+        let dma_request = self.apu.dmc_dma_request();
+        if let Some(sample_address) = dma_request {
+            self.do_dmc_dma(address, sample_address);
         }
+
+        self.tick();
+        self.read_address(address)
     }
 
     fn write(&mut self, address: u16, value: u8) {
+        self.tick();
+        self.open_bus = value;
         if address < 0x2000 {
             self.ram.write(address, value);
         } else if address >= 0x2000 && address <= 0x3FFF {
-            self.ppu.borrow_mut().write(address, value);
-        } else if address == 0x4014 {
-            let start = (value as u16) << 8;
-            let mut data = vec![];
-
-            for i in start..(start + 0x100) {
-                data.push(self.read(i));
-            }
-
-            self.ppu.borrow_mut().oam_dma_write(data);
+            self.ppu.write(address, value);
         } else if address == 0x4016 {
-            self.controllers[0].borrow_mut().write(address, value);
-            self.controllers[1].borrow_mut().write(address, value);
+            self.controllers[0].write(address, value);
+            self.controllers[1].write(address, value);
         } else if (address >= 0x4000 && address <= 0x4015) || address == 0x4017 {
-            self.apu.borrow_mut().write(address, value);
+            self.apu.write(address, value);
         } else if address >= 0x4020 {
-            self.rom.borrow_mut().write(address, value);
+            self.mapper_mut().cpu_write(address, value);
+        }
+        // 0x4014 (oam dma) is handled by the cpu, as the dma unit is part of the cpu chip
+    }
+}
+
+impl<'a> Bus for MemoryBus<'a> {
+    fn nmi_occured(&mut self) -> bool {
+        self.ppu.nmi_occured()
+    }
+
+    fn irq_line(&mut self) -> bool {
+        self.apu.pending_interrupt() || self.mapper_mut().irq_pending()
+    }
+
+    // registers are not read, as reads from them have side effects
+    fn peek(&mut self, address: u16) -> u8 {
+        if address < 0x2000 {
+            self.ram.read(address)
+        } else if address >= 0x4020 && self.mapper_mut().cpu_read_is_driven(address) {
+            self.mapper_mut().cpu_read(address)
+        } else {
+            self.open_bus
         }
     }
 
+    fn ppu_position(&self) -> (u16, u16) {
+        (self.ppu.scanline(), self.ppu.dot())
+    }
 }
 
 impl<'a> MemoryBus<'a> {
-    pub fn new(rom: Rc<RefCell<Box<Memory>>>,
-               ppu: Rc<RefCell<Ppu<'a>>>,
-               apu: Rc<RefCell<Apu<'a>>>,
-               controllers: Vec<Rc<RefCell<Controller>>>) -> MemoryBus<'a>  {
+    pub fn new(ppu: Ppu<'a>,
+               apu: Apu,
+               controllers: Vec<Controller>) -> MemoryBus<'a>  {
         MemoryBus {
-            rom: rom,
             ram: Box::new(Ram::new()) as Box<Memory>,
             ppu: ppu,
             apu: apu,
             controllers: controllers,
+            cycles: 0,
+            open_bus: 0,
+        }
+    }
+
+    pub fn mapper_mut(&mut self) -> &mut dyn Mapper {
+        self.ppu.mapper_mut()
+    }
+
+    pub fn apu_mut(&mut self) -> &mut Apu {
+        &mut self.apu
+    }
+
+    pub fn controllers_mut(&mut self) -> &mut [Controller] {
+        &mut self.controllers
+    }
+
+    // advances everything else on the bus by one cpu cycle
+    fn tick(&mut self) {
+        self.cycles += 1;
+        self.mapper_mut().cpu_cycle();
+        // emulate PPU cycles. Executes 3 cycles (NTSC) or average 3.2 cycles (PAL) per cpu cycle.
+        // PAL executes 3 cycles with an additional cycle every few cpu cycles to remain in sync
+        self.ppu.execute_cycles();
+        // apu strictly speaking cycles once for each two cpu cycle, but for
+        // timing reasons we cycle it once per cpu cycle. apu handles
+        // this difference internally
+        self.apu.execute_cycle();
+    }
+
+    fn read_address(&mut self, address: u16) -> u8 {
+        let value = if address < 0x2000 {
+            self.ram.read(address)
+        } else if address >= 0x2000 && address <= 0x3FFF {
+            self.ppu.read(address)
+        } else if address == 0x4015 {
+            // bit 5 is not driven
+            (self.apu.read(address) & 0xDF) | (self.open_bus & 0x20)
+        } else if address == 0x4016 {
+            (self.controllers[0].read(address) & 0x1F) | (self.open_bus & 0xE0)
+        } else if address == 0x4017 {
+            (self.controllers[1].read(address) & 0x1F) | (self.open_bus & 0xE0)
+        } else if address >= 0x4020 && self.mapper_mut().cpu_read_is_driven(address) {
+            self.mapper_mut().cpu_read(address)
+        } else {
+            // write only apu and dma registers, test mode registers, and anything the cartridge
+            // does not respond to
+            self.open_bus
+        };
+        self.open_bus = value;
+        value
+    }
+
+    // The dmc halts the cpu on its next read cycle. While halted, the cpu keeps repeating the
+    // read, which is why dmc fetches can cause double reads of $2007 and the controller ports.
+    // The sample byte itself is fetched on a get (even) cycle, so the stall takes 3 or 4 cycles
+    fn do_dmc_dma(&mut self, cpu_address: u16, sample_address: u16) {
+        // halt and dummy cycles
+        for _ in 0..2 {
+            self.tick();
+            self.read_address(cpu_address);
         }
+
+        // alignment cycle
+        if self.cycles % 2 == 0 {
+            self.tick();
+            self.read_address(cpu_address);
+        }
+
+        self.tick();
+        let sample = self.read_address(sample_address);
+        self.apu.dmc_dma_complete(sample);
     }
 }
 
@@ -83,12 +168,12 @@ mod tests {
 
     use super::*;
     use memory::*;
+    use mapper::Mapper;
     use ppu::*;
     use rom::*;
     use ppu::renderer::*;
     use apu::{Apu, Audio};
-    use std::cell::RefCell;
-    use std::rc::Rc;
+    use controller::Controller;
     use self::sdl2::audio::{AudioQueue};
 
 
@@ -107,13 +192,15 @@ mod tests {
         }
     }
     struct MockMemory {
-        memory: Vec<u8>
+        memory: Vec<u8>,
+        driven: bool,
     }
 
     impl MockMemory {
         fn new() -> MockMemory {
             MockMemory {
                 memory: vec![0;0xFFFF + 1],
+                driven: true,
             }
         }
     }
@@ -128,28 +215,61 @@ mod tests {
         }
     }
 
+    impl Mapper for MockMemory {
+        fn cpu_read(&mut self, address: u16) -> u8 {
+            self.memory[address as usize]
+        }
+
+        fn cpu_write(&mut self, address: u16, value: u8) {
+            self.memory[address as usize] = value;
+        }
+
+        fn ppu_read(&mut self, address: u16) -> u8 {
+            self.memory[address as usize]
+        }
+
+        fn ppu_write(&mut self, address: u16, value: u8) {
+            self.memory[address as usize] = value;
+        }
+
+        fn mirroring(&self) -> Mirroring {
+            Mirroring::VerticalMirroring
+        }
+
+        fn rom_mut(&mut self) -> &mut Rom {
+            unimplemented!()
+        }
+
+        fn cpu_read_is_driven(&self, _address: u16) -> bool {
+            self.driven
+        }
+    }
+
     // few helpers
     impl<'a> MemoryBus<'a> {
         fn assert_value_present_in_ram_only(&mut self, address: u16, value: u8) {
             assert_eq!(value, self.ram.read(address));
-            assert!(self.rom.borrow_mut().read(address) != value);
+            assert!(self.mapper_mut().cpu_read(address) != value);
         }
 
         fn assert_value_present_in_rom_only(&mut self, address: u16, value: u8) {
-            assert_eq!(value, self.rom.borrow_mut().read(address));
+            assert_eq!(value, self.mapper_mut().cpu_read(address));
             assert!(self.ram.read(address) != value);
         }
     }
 
     fn create_test_memory_bus<'a>() -> MemoryBus<'a> {
+        create_test_memory_bus_with_mapper(MockMemory::new())
+    }
 
-        let rom = Rc::new(RefCell::new(Box::new(MockMemory::new()) as Box<Memory>));
+    fn create_test_memory_bus_with_mapper<'a>(mapper: MockMemory) -> MemoryBus<'a> {
         MemoryBus {
-            rom: rom.clone(),
             ram: Box::new(MockMemory::new()),
-            ppu: Rc::new(RefCell::new(Ppu::new(Box::new(MockRenderer::new()), TvSystem::NTSC, Mirroring::VerticalMirroring, rom.clone()))),
-            controllers: vec![],
-            apu: Rc::new(RefCell::new(Apu::new(Box::new(MockAudio::new())))),
+            ppu: Ppu::new(Box::new(MockRenderer::new()), TvSystem::NTSC, Box::new(mapper)),
+            controllers: vec![Controller::new(None), Controller::new(None)],
+            apu: Apu::new(Box::new(MockAudio::new())),
+            cycles: 0,
+            open_bus: 0,
         }
     }
 
@@ -236,21 +356,116 @@ mod tests {
     #[test]
     fn read_above_0x4020_is_read_from_rom() {
         let mut mem_bus = create_test_memory_bus();
-        mem_bus.rom.borrow_mut().write(0xEFFF, 0x4B);
+        mem_bus.mapper_mut().cpu_write(0xEFFF, 0x4B);
         assert_eq!(0x4B, mem_bus.read(0xEFFF));
     }
 
     #[test]
     fn read_at_0x4020_is_read_from_rom() {
         let mut mem_bus = create_test_memory_bus();
-        mem_bus.rom.borrow_mut().write(0x4020, 0x4B);
+        mem_bus.mapper_mut().cpu_write(0x4020, 0x4B);
         assert_eq!(0x4B, mem_bus.read(0x4020));
     }
 
     #[test]
     fn read_at_0xFFFF_is_read_from_rom() {
         let mut mem_bus = create_test_memory_bus();
-        mem_bus.rom.borrow_mut().write(0xFFFF, 0x4B);
+        mem_bus.mapper_mut().cpu_write(0xFFFF, 0x4B);
         assert_eq!(0x4B, mem_bus.read(0xFFFF));
     }
+
+    #[test]
+    fn every_access_takes_one_cycle() {
+        let mut mem_bus = create_test_memory_bus();
+        mem_bus.read(0x0123);
+        mem_bus.write(0x0123, 0x4B);
+        assert_eq!(2, mem_bus.cycles);
+    }
+
+    fn start_dmc_sample(mem_bus: &mut MemoryBus) {
+        mem_bus.write(0x4010, 0x0F);
+        mem_bus.write(0x4012, 0x00);
+        mem_bus.write(0x4013, 0x00);
+        mem_bus.write(0x4015, 0x10);
+    }
+
+    #[test]
+    fn dmc_sample_fetch_stalls_read_for_3_or_4_cycles() {
+        let mut mem_bus = create_test_memory_bus();
+        start_dmc_sample(&mut mem_bus);
+        let cycles = mem_bus.cycles;
+        mem_bus.read(0x0123);
+        let stall = mem_bus.cycles - cycles - 1;
+        assert!(stall == 3 || stall == 4);
+    }
+
+    #[test]
+    fn dmc_sample_fetch_waits_for_a_read_cycle() {
+        let mut mem_bus = create_test_memory_bus();
+        start_dmc_sample(&mut mem_bus);
+        let cycles = mem_bus.cycles;
+        mem_bus.write(0x0123, 0x4B);
+        assert_eq!(cycles + 1, mem_bus.cycles);
+    }
+
+    #[test]
+    fn read_from_unmapped_address_returns_last_value_on_bus() {
+        let mut mem_bus = create_test_memory_bus();
+        mem_bus.write(0x0000, 0x5A);
+        assert_eq!(0x5A, mem_bus.read(0x4018));
+    }
+
+    #[test]
+    fn read_from_write_only_apu_register_returns_last_value_on_bus() {
+        let mut mem_bus = create_test_memory_bus();
+        mem_bus.ram.write(0x0010, 0x5A);
+        mem_bus.read(0x0010);
+        assert_eq!(0x5A, mem_bus.read(0x4000));
+        assert_eq!(0x5A, mem_bus.read(0x4014));
+    }
+
+    #[test]
+    fn controller_read_keeps_upper_3_bits_of_last_value_on_bus() {
+        let mut mem_bus = create_test_memory_bus();
+        mem_bus.open_bus = 0x40;
+        assert_eq!(0x40, mem_bus.read(0x4016) & 0xE0);
+        mem_bus.open_bus = 0xFF;
+        assert_eq!(0xE0, mem_bus.read(0x4017) & 0xE0);
+    }
+
+    #[test]
+    fn apu_status_read_keeps_bit_5_of_last_value_on_bus() {
+        let mut mem_bus = create_test_memory_bus();
+        mem_bus.open_bus = 0xFF;
+        assert_eq!(0x20, mem_bus.read(0x4015) & 0x20);
+        mem_bus.open_bus = 0x00;
+        assert_eq!(0x00, mem_bus.read(0x4015) & 0x20);
+    }
+
+    #[test]
+    fn read_from_address_cartridge_does_not_drive_returns_last_value_on_bus() {
+        let mapper = MockMemory {
+            memory: vec![0;0xFFFF + 1],
+            driven: false,
+        };
+        let mut mem_bus = create_test_memory_bus_with_mapper(mapper);
+        mem_bus.open_bus = 0x5A;
+        assert_eq!(0x5A, mem_bus.read(0x6000));
+    }
+
+    #[test]
+    fn peek_returns_ram_value_without_clocking_the_bus() {
+        let mut mem_bus = create_test_memory_bus();
+        mem_bus.ram.write(0x0456, 0x4B);
+        assert_eq!(0x4B, mem_bus.peek(0x0456));
+        assert_eq!(0, mem_bus.cycles);
+    }
+
+    #[test]
+    fn peek_does_not_read_ppu_registers() {
+        let mut mem_bus = create_test_memory_bus();
+        mem_bus.ppu.write(0x2000, 0x80);
+        mem_bus.open_bus = 0x5A;
+        assert_eq!(0x5A, mem_bus.peek(0x2002));
+    }
 }
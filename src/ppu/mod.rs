@@ -1,38 +1,49 @@
 mod vram;
 mod tv_system_values;
 pub mod renderer;
+pub mod palette;
 
 
 use memory::Memory;
+use mapper::Mapper;
 use rom::*;
 use self::vram::Vram;
 use self::tv_system_values::TvSystemValues;
 use self::renderer::Renderer;
 use self::renderer::Pixel;
+use self::palette::Palette;
 
 use std::fmt;
-use std::rc::Rc;
-use std::cell::RefCell;
-
-static PALETTE: [u8; 192] = [
-    124,124,124,    0,0,252,        0,0,188,        68,40,188,
-    148,0,132,      168,0,32,       168,16,0,       136,20,0,
-    80,48,0,        0,120,0,        0,104,0,        0,88,0,
-    0,64,88,        0,0,0,          0,0,0,          0,0,0,
-    188,188,188,    0,120,248,      0,88,248,       104,68,252,
-    216,0,204,      228,0,88,       248,56,0,       228,92,16,
-    172,124,0,      0,184,0,        0,168,0,        0,168,68,
-    0,136,136,      0,0,0,          0,0,0,          0,0,0,
-    248,248,248,    60,188,252,     104,136,252,    152,120,248,
-    248,120,248,    248,88,152,     248,120,88,     252,160,68,
-    248,184,0,      184,248,24,     88,216,84,      88,248,152,
-    0,232,216,      120,120,120,    0,0,0,          0,0,0,
-    252,252,252,    164,228,252,    184,184,248,    216,184,248,
-    248,184,248,    248,164,192,    240,208,176,    252,224,168,
-    248,216,120,    216,248,120,    184,248,184,    184,248,216,
-    0,252,252,      248,216,248,    0,0,0,          0,0,0
-];
+use std::cmp;
+
+// MMC3 filters out A12 rises that happen within a few cpu cycles of A12 being high; this
+// way only the first sprite fetch on a scanline clocks the counter. The filter counts cpu
+// cycles, so the number of ppu cycles it spans depends on the tv system
+const A12_LOW_CPU_CYCLES_BEFORE_RISING_EDGE: u16 = 3;
+
+// Bits in the io latch decay to 0 if they are not refreshed; roughly 600 ms on real hardware
+const IO_LATCH_DECAY_FRAMES: u32 = 36;
+
+// sprite data fetched during cycles 257 - 320 for rendering on the next scanline. Patterns are
+// already flipped horizontally and unused slots are transparent
+#[derive(Clone, Copy)]
+struct SpriteOutputUnit {
+    pattern_low_byte: u8,
+    pattern_high_byte: u8,
+    attribute: u8,
+    x: u8,
+}
 
+impl SpriteOutputUnit {
+    fn new() -> SpriteOutputUnit {
+        SpriteOutputUnit {
+            pattern_low_byte: 0,
+            pattern_high_byte: 0,
+            attribute: 0,
+            x: 0,
+        }
+    }
+}
 
 struct SpriteRenderData {
     is_sprite_0: bool,
@@ -53,9 +64,23 @@ impl SpriteRenderData {
 pub struct Ppu<'a> {
     object_attribute_memory: Vec<u8>,
     secondary_oam: Vec<u8>,
+    // write position in secondary oam during evaluation; afterwards tells how many sprites were found
+    secondary_oam_address: u8,
     secondary_contains_sprite_0: bool,
+    // value on the oam data bus, returned by 0x2004 reads during rendering
+    oam_latch: u8,
+    // bytes of an in-range sprite (or overflow sprite) still to be copied during evaluation
+    sprite_evaluation_bytes_left: u8,
+    sprite_evaluation_done: bool,
+    sprite_units: [SpriteOutputUnit; 8],
+    sprite_units_contain_sprite_0: bool,
     is_even_frame: bool,
-    vram: Box<Memory>,
+    frame_count: u32,
+    // the ppu data bus; reads from write-only registers return its value (open bus)
+    io_latch: u8,
+    io_latch_refresh_frames: [u32; 8],
+    vram: Vram,
+    cpu_cycles_since_a12_high: u16,
     registers: Registers,
     address_latch: bool,
     vram_address: u16,
@@ -71,6 +96,7 @@ pub struct Ppu<'a> {
     pattern_table_high_byte: u8,
     background_data: u64,
     pixels: Vec<Pixel>,
+    palette: Palette,
     renderer: Box<Renderer + 'a>,
 }
 
@@ -84,25 +110,31 @@ impl<'a> fmt::Debug for Ppu<'a> {
 }
 
 impl<'a> Memory for Ppu<'a> {
+    // Reads from write-only registers return the io latch. Registers which do not drive all
+    // 8 bits (status, palette) fill the remaining bits from the latch
     fn read(&mut self, cpu_address: u16) -> u8 {
         match cpu_address & 0x0007 {
-            0 => panic!("Attempting to read from write-only ppu control register (address 0x{:04X})", cpu_address),
-            1 => panic!("Attempting to read from write-only ppu mask register (address 0x{:04X})", cpu_address),
-            2 => self.status_register_read(),
-            3 => panic!("Attempting to read from write-only ppu oam address register (address 0x{:04X})", cpu_address),
-            4 => self.oam_data_register_read(),
-            5 => panic!("Attempting to read from write-only ppu scroll register (address 0x{:04X})", cpu_address),
-            6 => panic!("Attempting to read from write-only ppu address register (address 0x{:04X})", cpu_address),
+            0 | 1 | 3 | 5 | 6 => self.decay_io_latch(),
+            2 => {
+                let value = self.status_register_read();
+                self.refresh_io_latch(value, 0xE0)
+            },
+            4 => {
+                let value = self.oam_data_register_read();
+                self.refresh_io_latch(value, 0xFF)
+            },
             7 => self.ppu_data_register_read(),
             _ => panic!("Something went horribly wrong in modulus calculation in ppu.read (address: {})", cpu_address),
         }
     }
 
     fn write(&mut self, cpu_address: u16, value: u8) {
+        // any write fills the io latch, including writes to read-only status register
+        self.refresh_io_latch(value, 0xFF);
         match cpu_address & 0x0007 {
             0 => self.control_register_write(value),
             1 => self.registers.mask = value,
-            2 => panic!("Attempting to write to read-only ppu status register (address 0x{:04X})", cpu_address),
+            2 => { },
             3 => self.registers.oam_address = value,
             4 => self.oam_data_register_write(value),
             5 => self.scroll_register_write(value),
@@ -117,15 +149,24 @@ impl<'a> Ppu<'a> {
     pub fn new(
         renderer: Box<Renderer + 'a>,
         tv_system: TvSystem,
-        mirroring: Mirroring,
-        rom: Rc<RefCell<Box<Memory>>>) -> Ppu<'a> {
+        mapper: Box<dyn Mapper>) -> Ppu<'a> {
 
         Ppu {
             object_attribute_memory: vec![0;256],
             secondary_oam: vec![0;32],
+            secondary_oam_address: 0,
             secondary_contains_sprite_0: false,
+            oam_latch: 0,
+            sprite_evaluation_bytes_left: 0,
+            sprite_evaluation_done: false,
+            sprite_units: [SpriteOutputUnit::new(); 8],
+            sprite_units_contain_sprite_0: false,
             is_even_frame: true,
-            vram: Box::new(Vram::new(mirroring, rom)),
+            frame_count: 0,
+            io_latch: 0,
+            io_latch_refresh_frames: [0; 8],
+            vram: Vram::new(mapper),
+            cpu_cycles_since_a12_high: A12_LOW_CPU_CYCLES_BEFORE_RISING_EDGE + 1,
             registers: Registers::new(),
             address_latch: false,
             vram_address: 0,
@@ -141,10 +182,15 @@ impl<'a> Ppu<'a> {
             pattern_table_high_byte: 0,
             background_data: 0,
             pixels: vec![Pixel::new(0,0,0);240*256],
+            palette: Palette::new(),
             renderer: renderer,
         }
     }
 
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
     fn generate_nmi_if_flags_set(&mut self) {
         if self.registers.control & 0x80 == 0x80 && self.registers.status & 0x80 == 0x80 {
             self.nmi_occured = true;
@@ -157,6 +203,22 @@ impl<'a> Ppu<'a> {
         occured
     }
 
+    pub fn mapper_mut(&mut self) -> &mut dyn Mapper {
+        self.vram.mapper_mut()
+    }
+
+    // Scanlines are counted from the start of vblank internally; this returns the usual
+    // numbering where the first rendered line is 0 and the pre-render line is the last one
+    pub fn scanline(&self) -> u16 {
+        let scanlines = self.tv_system.scanlines_per_frame();
+        let first_rendered_line = self.tv_system.vblank_frames + 1;
+        (self.current_scanline + scanlines - first_rendered_line) % scanlines
+    }
+
+    pub fn dot(&self) -> u16 {
+        self.pos_at_scanline
+    }
+
     fn increment_vram(&mut self) {
         if self.registers.control & 0x04 == 0 {
             self.vram_address += 1;
@@ -178,14 +240,26 @@ impl<'a> Ppu<'a> {
         val
     }
 
+    // during rendering oam is busy with sprite evaluation and reads return whatever is on the oam bus
     fn oam_data_register_read(&mut self) -> u8 {
-        let address = self.registers.oam_address as usize;
-        self.object_attribute_memory[address]
+        if self.is_rendering() {
+            self.oam_latch
+        } else {
+            let address = self.registers.oam_address as usize;
+            self.object_attribute_memory[address]
+        }
     }
 
     fn oam_data_register_write(&mut self, value: u8) {
+        if self.is_rendering() {
+            // writes are ignored during rendering, but oam address gets a glitchy increment
+            // which only bumps the sprite index (high 6 bits)
+            self.registers.oam_address = self.registers.oam_address.wrapping_add(4);
+            return;
+        }
+
         let address = self.registers.oam_address as usize;
-        self.registers.oam_address += 1;
+        self.registers.oam_address = self.registers.oam_address.wrapping_add(1);
         self.object_attribute_memory[address] = value;
     }
 
@@ -255,30 +329,47 @@ impl<'a> Ppu<'a> {
         if address <= 0x3EFF {
             // read is buffered; return value in buffer and update buffer to value at address
             let buffer = self.vram_read_buffer;
+            self.watch_a12(address);
             self.vram_read_buffer = self.vram.read(address);
-            buffer
+            self.refresh_io_latch(buffer, 0xFF)
         } else {
             // read is not buffered; update buffer to value at address - 0x1000
+            // palette entries are 6 bits, the upper 2 bits come from the io latch
             let value = self.vram.read(address);
             self.vram_read_buffer = self.vram.read(address - 0x1000);
-            value
+            self.refresh_io_latch(value, 0x3F)
+        }
+    }
+
+    // sets the bits selected by the mask and returns the new latch value
+    fn refresh_io_latch(&mut self, value: u8, mask: u8) -> u8 {
+        self.decay_io_latch();
+        self.io_latch = (self.io_latch & !mask) | (value & mask);
+        for bit in 0..8 {
+            if mask & (1 << bit) != 0 {
+                self.io_latch_refresh_frames[bit] = self.frame_count;
+            }
+        }
+        self.io_latch
+    }
+
+    fn decay_io_latch(&mut self) -> u8 {
+        for bit in 0..8 {
+            if self.frame_count.wrapping_sub(self.io_latch_refresh_frames[bit]) > IO_LATCH_DECAY_FRAMES {
+                self.io_latch &= !(1 << bit);
+            }
         }
+        self.io_latch
     }
 
     fn ppu_data_register_write(&mut self, value: u8) {
         let address = self.vram_address;
 
         self.increment_vram();
+        self.watch_a12(address & 0x3FFF);
         self.vram.write(address, value);
     }
 
-    pub fn oam_dma_write(&mut self, data: Vec<u8>) {
-        if data.len() != 256 {
-            panic!("Invalid oam data write length: 256 expected but was {}", data.len());
-        }
-        self.object_attribute_memory = data;
-    }
-
     // how many cycles will be executed this cpu cycle
     // also updates counters
     fn get_cycle_count(&mut self) -> u8 {
@@ -300,6 +391,7 @@ impl<'a> Ppu<'a> {
         for _ in 0..cycles {
             self.execute_cycle();
         }
+        self.cpu_cycles_since_a12_high = self.cpu_cycles_since_a12_high.saturating_add(1);
     }
 
     fn update_scanline_pos(&mut self) {
@@ -308,9 +400,10 @@ impl<'a> Ppu<'a> {
         if self.pos_at_scanline == 341 {
             self.pos_at_scanline = 0;
             self.current_scanline += 1;
-            if self.current_scanline > 261 {
+            if self.current_scanline >= self.tv_system.scanlines_per_frame() {
                 self.current_scanline = 0;
                 self.is_even_frame = true;
+                self.frame_count = self.frame_count.wrapping_add(1);
             }
         }
     }
@@ -338,29 +431,45 @@ impl<'a> Ppu<'a> {
     }
 
 
-    fn rendering_enabled(&mut self) -> bool {
+    fn rendering_enabled(&self) -> bool {
         (self.registers.mask & 0x18) != 0
     }
 
+    // true on pre-render and render lines when rendering is enabled
+    fn is_rendering(&self) -> bool {
+        let pre_render_line = self.tv_system.vblank_frames;
+        self.rendering_enabled()
+            && self.current_scanline >= pre_render_line
+            && self.current_scanline <= pre_render_line + 240
+    }
+
 
     fn do_vblank(&mut self) {
         // VBLANK - do not access memory or render.
         // However, set vblank flag on second tick of first scanline and raise NMI if nmi flag is set
-        // also clear the overflow flag and sprite 0 hit
         if self.current_scanline == 0 && self.pos_at_scanline == 1 {
-            self.registers.status = (self.registers.status & 0x9F) | 0x80;
+            self.registers.status = self.registers.status | 0x80;
             self.generate_nmi_if_flags_set();
         }
     }
 
     fn do_pre_render_line(&mut self) {
-        if self.pos_at_scanline == 1 { // unset vblank flag on second tick
-            self.registers.status = self.registers.status & 0x7F;
+        if self.pos_at_scanline == 1 { // unset vblank, sprite 0 hit and overflow flags on second tick
+            self.registers.status = self.registers.status & 0x1F;
+            // sprites are not evaluated on pre-render line, so nothing is rendered on the first line
+            self.secondary_oam_address = 0;
+            self.secondary_contains_sprite_0 = false;
+
+            if self.rendering_enabled() {
+                self.corrupt_oam();
+            }
         }
 
         if self.rendering_enabled() {
             if (self.pos_at_scanline >=1 && self.pos_at_scanline <= 256) || (self.pos_at_scanline >= 321 && self.pos_at_scanline <= 336) {
                 self.do_memory_access();
+            } else if self.pos_at_scanline >= 257 && self.pos_at_scanline <= 320 {
+                self.do_sprite_memory_access();
             }
 
             if self.pos_at_scanline == 256 {
@@ -373,14 +482,6 @@ impl<'a> Ppu<'a> {
             if self.pos_at_scanline >= 280 && self.pos_at_scanline <= 304 && self.rendering_enabled() {
                 self.update_y_scroll();
             }
-            // in real NES, sprite evaluation happens at the same time than background evaluation
-            // however, whereas cycle accuracy with background is required for split screen emulation
-            // I am unaware as of writing this of any downsides of doing sprite evaluation in single pass.
-            // This may backfire horribly later on
-            if self.pos_at_scanline == 256 {
-                self.evaluate_sprites();
-            }
-
             if self.pos_at_scanline == 339 && !self.is_even_frame {
                 match self.tv_system.tv_type {
                     TvSystem::NTSC => self.pos_at_scanline += 1,
@@ -403,21 +504,18 @@ impl<'a> Ppu<'a> {
         } else if self.pos_at_scanline <= 256 {
             self.render_pixel();
             self.do_memory_access();
+            // sprites for the next line are evaluated at the same time
+            self.do_sprite_evaluation();
             if self.pos_at_scanline == 256 {
                 self.increment_vram_y();
             }
         } else if self.pos_at_scanline <= 320 {
-            // background wise do nothing - sprite part is handled in evaluate_sprites
-            // Actual nes recycles circuitry and sprites use same tile
+            // background wise do nothing - actual nes recycles circuitry and sprites use same tile
+            self.do_sprite_memory_access();
         } else if self.pos_at_scanline <= 336 {
             self.do_memory_access();
         }
 
-        // as with pre-render line
-        if self.pos_at_scanline == 256 {
-            self.evaluate_sprites();
-        }
-
         if self.pos_at_scanline == 257  {
             self.update_x_scroll();
         }
@@ -509,18 +607,107 @@ impl<'a> Ppu<'a> {
 
     fn read_pattern_table_low_byte(&mut self) {
         let address = self.calculate_pattern_table_address();
-        self.pattern_table_low_byte = self.vram.read(address);
+        self.pattern_table_low_byte = self.read_pattern_table(address);
     }
 
     fn read_pattern_table_high_byte(&mut self) {
         // offset for high byte
         let address = self.calculate_pattern_table_address() + 8;
-        self.pattern_table_high_byte = self.vram.read(address);
+        self.pattern_table_high_byte = self.read_pattern_table(address);
+    }
+
+    // Sprite patterns are fetched during cycles 257 - 320, 8 cycles per sprite, from the sprites in
+    // secondary oam. The first 4 cycles read the sprite bytes from secondary oam (and garbage nametable
+    // bytes from vram, which are not emulated), the low and high pattern bytes are read after that.
+    // Unused slots fetch tile 0xFF and are transparent. Oam address is held at 0 during fetching.
+    fn do_sprite_memory_access(&mut self) {
+        let sprite = ((self.pos_at_scanline - 257) / 8) as usize;
+        let cycle = (self.pos_at_scanline - 257) & 0x07;
+
+        self.registers.oam_address = 0;
+        self.oam_latch = self.secondary_oam[sprite*4 + cmp::min(cycle, 3) as usize];
+        if self.pos_at_scanline == 257 {
+            self.sprite_units_contain_sprite_0 = self.secondary_contains_sprite_0;
+        }
+
+        if cycle != 4 && cycle != 6 {
+            return;
+        }
+
+        let sprite_y = self.secondary_oam[sprite*4];
+        let tile = self.secondary_oam[sprite*4 + 1] as u16;
+        let attribute = self.secondary_oam[sprite*4 + 2];
+
+        let height = self.sprite_height();
+        let row = self.sprite_evaluation_line().wrapping_sub(sprite_y as u16);
+        let is_used = sprite*4 < self.secondary_oam_address as usize && row < height;
+
+        // vertical flip flips the whole sprite; with 8x16 sprites the tiles swap places as well
+        let row = if !is_used {
+            0
+        } else if attribute & 0x80 != 0 {
+            height - 1 - row
+        } else {
+            row
+        };
+
+        let address = self.sprite_pattern_address(tile, row);
+        let offset = if cycle == 4 { 0 } else { 8 };
+        let data = self.read_pattern_table(address + offset);
+
+        // horizontal flip is handled here by reversing the pattern
+        let data = if !is_used {
+            0
+        } else if attribute & 0x40 != 0 {
+            data.reverse_bits()
+        } else {
+            data
+        };
+
+        let unit = &mut self.sprite_units[sprite];
+        if cycle == 4 {
+            unit.pattern_low_byte = data;
+        } else {
+            unit.pattern_high_byte = data;
+        }
+        unit.attribute = attribute;
+        unit.x = self.secondary_oam[sprite*4 + 3];
+    }
+
+    // address of the low pattern byte for given sprite tile and row (0 - 7, or 0 - 15 for 8x16 sprites)
+    fn sprite_pattern_address(&self, tile: u16, row: u16) -> u16 {
+        if self.registers.control & 0x20 == 0 {
+            // select correct pattern table (if bit 3 at control is 0 -> table at 0x000, 1 -> table at 0x1000)
+            let table = 0x1000 * (((self.registers.control as u16) & 0x08) >> 3);
+            table + tile*16 + row
+        } else {
+            // 8x16 sprites take the pattern table from bit 0 of the tile index and ignore control bit 3.
+            // Top half is the even tile and bottom half the following tile
+            let table = 0x1000 * (tile & 0x01);
+            let tile = (tile & 0xFE) + row / 8;
+            table + tile*16 + (row & 0x07)
+        }
+    }
+
+    // Mappers such as MMC3 count scanlines by watching ppu address line 12; report the rising
+    // edge if the line has been low long enough
+    fn read_pattern_table(&mut self, address: u16) -> u8 {
+        self.watch_a12(address);
+        self.vram.read(address)
+    }
+
+    // cpu accesses through 0x2007 drive the same address lines as rendering fetches
+    fn watch_a12(&mut self, address: u16) {
+        if address < 0x2000 && address & 0x1000 != 0 {
+            if self.cpu_cycles_since_a12_high > A12_LOW_CPU_CYCLES_BEFORE_RISING_EDGE {
+                self.vram.mapper_mut().ppu_a12_rising_edge();
+            }
+            self.cpu_cycles_since_a12_high = 0;
+        }
     }
 
 
 
-    // TODO - implement color emphasis
     fn render_pixel(&mut self) {
         // for now, only background rendering.
 
@@ -528,7 +715,7 @@ impl<'a> Ppu<'a> {
         let x = self.pos_at_scanline - 1; // - 1 for the skipped cycle
 
         let background = self.get_background_for_rendering(x);
-        let sprite = self.get_sprite_for_rendering(x, y);
+        let sprite = self.get_sprite_for_rendering(x);
 
         let sprite_multiplex = sprite.palette_index % 4;
         let background_multiplex = background % 4;
@@ -540,7 +727,8 @@ impl<'a> Ppu<'a> {
         } else if sprite_multiplex != 0 && background_multiplex == 0 {
             sprite.palette_index
         } else {
-            if sprite.is_sprite_0 {
+            // sprite 0 hit never happens at the rightmost pixel
+            if sprite.is_sprite_0 && x != 255 {
                 self.registers.status = self.registers.status | 0x40;
             }
 
@@ -551,10 +739,40 @@ impl<'a> Ppu<'a> {
             }
         };
 
-        let color_index = (self.vram.read(0x3F00 + palette_index as u16) % 64) as usize;
+        let color_index = self.vram.read(0x3F00 + palette_index as u16) % 64;
 
         let index = y as usize*256 + x as usize;
-        self.pixels[index] = Pixel::new(PALETTE[color_index*3], PALETTE[color_index*3 + 1], PALETTE[color_index*3 + 2]);
+        self.pixels[index] = self.color_for_palette_entry(color_index);
+    }
+
+    /*
+        Mask register bits 0 and 5 - 7 modify the final color
+
+        76543210
+        |||    |
+        |||    +- Greyscale (0: normal color, 1: produce a greyscale display)
+        ||+------ Emphasize red (green on PAL\Dendy)
+        |+------- Emphasize green (red on PAL\Dendy)
+        +-------- Emphasize blue
+    */
+    fn color_for_palette_entry(&self, color_index: u8) -> Pixel {
+        // greyscale selects the grey column of the palette
+        let color_index = if self.registers.mask & 0x01 != 0 {
+            color_index & 0x30
+        } else {
+            color_index
+        };
+
+        self.palette.color(color_index, self.emphasis())
+    }
+
+    // emphasis bits in red, green, blue order (bit 0 is red)
+    fn emphasis(&self) -> u8 {
+        let emphasis = self.registers.mask >> 5;
+        match self.tv_system.tv_type {
+            TvSystem::PAL | TvSystem::Dendy => (emphasis & 0x04) | (emphasis & 0x01) << 1 | (emphasis & 0x02) >> 1,
+            _ => emphasis,
+        }
     }
 
     fn get_background_for_rendering(&mut self, x: u16) -> u8{
@@ -573,61 +791,31 @@ impl<'a> Ppu<'a> {
        }
     }
 
-    fn get_sprite_for_rendering(&mut self, x: u16, y: u16) -> SpriteRenderData {
+    fn get_sprite_for_rendering(&mut self, x: u16) -> SpriteRenderData {
         if self.registers.mask & 0x04 == 0 && x < 8 { // sprite rendering disabled for first 8 pixels
             SpriteRenderData::new(false, false, 0)
         } else {
             if self.registers.mask & 0x10 == 0 {
                 SpriteRenderData::new(false, false, 0) // sprite rendering is disabled
             } else {
-                // 16 8x16 sprites not implemented yet
-                if self.registers.control & 0x20 != 0 {
-                    panic!("8x16 sprites are not implemented yet");
-                }
                 // first non-transparent pixel is selected for rendering
                 for i in 0..8 {
-                    let sprite_y = self.secondary_oam[i*4 + 0];
-                    let sprite_patten_index = self.secondary_oam[i*4 + 1] as u16;
-                    let sprite_attribute = self.secondary_oam[i*4 + 2];
-                    let sprite_begin_x = self.secondary_oam[i*4 + 3] as u16;
+                    let unit = self.sprite_units[i];
+                    let x_diff = x.wrapping_sub(unit.x as u16);
 
                     // in range && not transparent
-                    if x >= sprite_begin_x && x < sprite_begin_x + 8 {
-                        let x_diff = x - sprite_begin_x;
-
-                        // horizonal offset
-                        let x_shift = if sprite_attribute & 0x40 == 0 {
-                            x_diff
-                        } else {
-                            7 - x_diff
-                        };
-
-                        let y_diff = y - (sprite_y) as u16;
-                        let sprite_y_offset = if sprite_attribute & 0x80 == 0{
-                            y_diff
-                        } else {
-                            7 - y_diff
-                        };
-
-                        // TODO - consider reusing the pattern table fetching code from background logic
-                        // pattern table
-                        //let table = 0x1000 * (((self.registers.control as u16) & 0x10) >> 4);
-                        //println!("table: {}", table);
-                        let table = 0;
-                        let low_byte = self.vram.read(table + sprite_patten_index*16 + sprite_y_offset);
-                        let high_byte = self.vram.read(table + sprite_patten_index*16 + 8 + sprite_y_offset);
-
-                		let mut color = ((low_byte << x_shift) & 0x80) >> 7;
-                		color = color  | ((high_byte << x_shift) & 0x80) >> 6;
+                    if x_diff < 8 {
+                		let mut color = ((unit.pattern_low_byte << x_diff) & 0x80) >> 7;
+                		color = color  | ((unit.pattern_high_byte << x_diff) & 0x80) >> 6;
                         if color == 0 {
                             continue;
                         }
                         let sprite_palette_offset = 4*4;
                         let palette_index = sprite_palette_offset
-                            + ((sprite_attribute & 0x03) << 2 | color);
+                            + ((unit.attribute & 0x03) << 2 | color);
 
-                        return SpriteRenderData::new(i == 0 && self.secondary_contains_sprite_0,
-                            sprite_attribute & 0x20 == 0,
+                        return SpriteRenderData::new(i == 0 && self.sprite_units_contain_sprite_0,
+                            unit.attribute & 0x20 == 0,
                             palette_index);
                     }
                 }
@@ -681,80 +869,122 @@ impl<'a> Ppu<'a> {
     }
 
     // http://wiki.nesdev.com/w/index.php/PPU_sprite_evaluation
-    // For ease of implementation, I'm not going for cycle accuracy here.
-    // This will be changed in case this actually causes issues.
-    fn evaluate_sprites(&mut self) {
-        self.initialize_secondary_oam();
-        self.copy_data_to_secondary_oam();
-    }
+    // Secondary oam is cleared during cycles 1 - 64 and sprites for the next line are evaluated
+    // during cycles 65 - 256. Oam is read on odd cycles and secondary oam is written on even cycles.
+    // Evaluation uses oam address as its pointer, so writes to 0x2003 during rendering affect it.
+    fn do_sprite_evaluation(&mut self) {
+        if self.pos_at_scanline <= 64 {
+            // reads return 0xFF while secondary oam is being cleared
+            self.oam_latch = 0xFF;
+            if self.pos_at_scanline & 0x01 == 0 {
+                self.secondary_oam[((self.pos_at_scanline - 1) / 2) as usize] = 0xFF;
+            }
+            return;
+        }
 
-    fn initialize_secondary_oam(&mut self) {
-        // initialize secondary oam
-        for i in 0..32 {
-            self.secondary_oam[i] = 0xFF;
+        if self.pos_at_scanline == 65 {
+            self.secondary_oam_address = 0;
+            self.secondary_contains_sprite_0 = false;
+            self.sprite_evaluation_bytes_left = 0;
+            self.sprite_evaluation_done = false;
+        }
+
+        if self.pos_at_scanline & 0x01 == 1 {
+            self.oam_latch = self.object_attribute_memory[self.registers.oam_address as usize];
+        } else if self.sprite_evaluation_done {
+            // all sprites have been evaluated; hardware keeps reading y coordinates and failing to
+            // write them into secondary oam
+            self.registers.oam_address = self.registers.oam_address.wrapping_add(4);
+        } else if self.secondary_oam_address < 32 {
+            self.evaluate_sprite_byte();
+        } else {
+            self.evaluate_sprite_overflow_byte();
         }
-        self.secondary_contains_sprite_0 = false;
     }
 
-    fn copy_data_to_secondary_oam(&mut self) {
-        let mut secondary_oam_sprites = 0;
-        // used to emulate the PPU overflow flag bug where offset is incorrectly incremented
-        let mut overflow_offset = 0;
-        for oam_sprite in 0..64 {
-            if secondary_oam_sprites < 8 {
-                self.evaluate_sprite_for_addition(oam_sprite, &mut secondary_oam_sprites);
-            } else {
-                // NES PPU has a hardware bug when handling overflow flag; it is supposed to scan
-                // the remaining sprite y coordinates and set the overflow flag if additional sprites
-                // are on the scanline. However the cirucitry incorrectly increments the offset
-                // and thus the result is more or less random
-
-                // for what it's worth, every 4th sprite is evaluated correctly
-                let incorrect_index = (oam_sprite * 4 + overflow_offset) as usize;
-                let incorrect_y_coordinate = self.object_attribute_memory[incorrect_index];
-
-                if self.sprite_is_on_scanline(incorrect_y_coordinate) {
-                    self.registers.status = self.registers.status | 0x20;
-                    break;
-                }
-                // incorrect PPU offset increment
-                overflow_offset = (overflow_offset + 1) & 0x03; // wraps around
+    fn evaluate_sprite_byte(&mut self) {
+        let value = self.oam_latch;
+        // y is written to secondary oam in any case, even if sprite is not visible. In case there
+        // are fewer than 8 sprites on scanline, this will be the y value of the last evaluated sprite
+        self.secondary_oam[self.secondary_oam_address as usize] = value;
+
+        if self.sprite_evaluation_bytes_left > 0 {
+            // copy remaining bytes into secondary oam
+            self.sprite_evaluation_bytes_left -= 1;
+            self.secondary_oam_address += 1;
+            self.increment_evaluation_address(1);
+        } else if self.sprite_is_on_scanline(value) {
+            // first evaluated sprite is sprite 0 (unless oam address was modified)
+            if self.pos_at_scanline == 66 {
+                self.secondary_contains_sprite_0 = true;
             }
+            self.sprite_evaluation_bytes_left = 3;
+            self.secondary_oam_address += 1;
+            self.increment_evaluation_address(1);
+        } else {
+            self.increment_evaluation_address(4);
         }
     }
 
-    fn evaluate_sprite_for_addition(&mut self, oam_sprite: u8, secondary_oam_sprites: &mut u8) {
-        let index = (oam_sprite * 4) as usize;
-        let y = self.object_attribute_memory[index];
-        let secondary_index = (*secondary_oam_sprites*4) as usize;
-
-        // y is written to secondary oam in any case (if there is space), even if sprite is not visible
-        // in case there are fewer than 8 sprites on scanline, this will be the y value of sprite 63
-        if secondary_index < self.secondary_oam.len() {
-            self.secondary_oam[secondary_index] = self.object_attribute_memory[index];
+    // NES PPU has a hardware bug when handling overflow flag; it is supposed to scan the remaining
+    // sprite y coordinates and set the overflow flag if additional sprites are on the scanline.
+    // However the circuitry incorrectly increments the byte offset along with the sprite index when
+    // a sprite is not on the scanline, and thus the result is more or less random
+    fn evaluate_sprite_overflow_byte(&mut self) {
+        if self.sprite_evaluation_bytes_left > 0 {
+            // remaining bytes of the overflowing sprite are read, but not used
+            self.sprite_evaluation_bytes_left -= 1;
+            self.increment_evaluation_address(1);
+            if self.sprite_evaluation_bytes_left == 0 {
+                self.sprite_evaluation_done = true;
+            }
+        } else if self.sprite_is_on_scanline(self.oam_latch) {
+            self.registers.status = self.registers.status | 0x20;
+            self.sprite_evaluation_bytes_left = 3;
+            self.increment_evaluation_address(1);
+        } else {
+            // incorrect increment; both sprite index and byte offset are incremented, without carry
+            let address = self.registers.oam_address;
+            self.registers.oam_address = (address.wrapping_add(4) & 0xFC) | (address.wrapping_add(1) & 0x03);
+            if address >= 0xFC {
+                self.sprite_evaluation_done = true;
+            }
         }
+    }
 
+    // evaluation is done once the sprite index wraps around
+    fn increment_evaluation_address(&mut self, amount: u8) {
+        let (address, overflow) = self.registers.oam_address.overflowing_add(amount);
+        self.registers.oam_address = address;
+        if overflow {
+            self.sprite_evaluation_done = true;
+        }
+    }
 
-        if self.sprite_is_on_scanline(y) {
-            if oam_sprite == 0 {
-                self.secondary_contains_sprite_0 = true;
-            }
-            // copy remaining bytes into secondary oam
-            for i in 1..4 {
-                self.secondary_oam[secondary_index + i] = self.object_attribute_memory[index + i];
+    // If oam address is 8 or above when rendering starts, the 8 bytes starting at the address & 0xF8
+    // are copied over the first 8 bytes of oam
+    fn corrupt_oam(&mut self) {
+        let address = (self.registers.oam_address & 0xF8) as usize;
+        if address >= 8 {
+            for i in 0..8 {
+                self.object_attribute_memory[i] = self.object_attribute_memory[address + i];
             }
-            *secondary_oam_sprites += 1;
         }
     }
 
-    fn sprite_is_on_scanline(&mut self, y: u8) -> bool {
-        let diff = (self.current_scanline - self.tv_system.vblank_frames) as i16 - y as i16;
-        // sprite height; either 8 pixels or 16 pixels, depending on whether bit 5 is set
-        // in the control register
-        let height = 8 + 8*((0x20 & self.registers.control) >> 5) as i16;
+    // line for which sprites are evaluated and fetched; the sprites are rendered on the following line
+    fn sprite_evaluation_line(&self) -> u16 {
+        self.current_scanline.wrapping_sub(self.tv_system.vblank_frames + 1)
+    }
+
+    // sprite height; either 8 pixels or 16 pixels, depending on whether bit 5 is set
+    // in the control register
+    fn sprite_height(&self) -> u16 {
+        8 + 8*((0x20 & self.registers.control) >> 5) as u16
+    }
 
-        // sprite is visible if diff is between 0 and height
-        diff >= 0 && diff < height
+    fn sprite_is_on_scanline(&self, y: u8) -> bool {
+        self.sprite_evaluation_line().wrapping_sub(y as u16) < self.sprite_height()
     }
 }
 
@@ -785,29 +1015,45 @@ impl Registers {
 mod tests {
     use super::*;
     use memory::Memory;
+    use mapper::Mapper;
     use rom::*;
     use std::rc::Rc;
-    use std::cell::RefCell;
+    use std::cell::Cell;
     use super::renderer::*;
-    struct MockMemory {
-        memory: Vec<u8>
+
+    // 8 kilobytes of chr ram
+    struct MockMapper {
+        chr: Vec<u8>,
+        a12_rising_edges: Rc<Cell<u32>>,
     }
 
-    impl MockMemory {
-        fn new() -> MockMemory {
-            MockMemory {
-                memory: vec![0;0xFFFF + 1],
-            }
+    impl Mapper for MockMapper {
+        fn cpu_read(&mut self, address: u16) -> u8 {
+            0
+        }
+
+        fn cpu_write(&mut self, address: u16, value: u8) {
+
+        }
+
+        fn ppu_read(&mut self, address: u16) -> u8 {
+            self.chr[address as usize]
         }
-    }
 
-    impl Memory for MockMemory {
-        fn read(&mut self, address: u16) -> u8 {
-            self.memory[address as usize]
+        fn ppu_write(&mut self, address: u16, value: u8) {
+            self.chr[address as usize] = value;
         }
 
-        fn write(&mut self, address: u16, value: u8) {
-            self.memory[address as usize] = value;
+        fn mirroring(&self) -> Mirroring {
+            Mirroring::VerticalMirroring
+        }
+
+        fn rom_mut(&mut self) -> &mut Rom {
+            unimplemented!()
+        }
+
+        fn ppu_a12_rising_edge(&mut self) {
+            self.a12_rising_edges.set(self.a12_rising_edges.get() + 1);
         }
     }
 
@@ -826,11 +1072,17 @@ mod tests {
     }
 
     fn create_test_ppu<'a>() -> Ppu<'a> {
-        let rom = Rc::new(RefCell::new(Box::new(MockMemory::new()) as Box<Memory>));
-        let mut ppu = Ppu::new(Box::new(MockRenderer::new()), TvSystem::NTSC, Mirroring::VerticalMirroring, rom);
-        // replace vram with mock
-        ppu.vram = Box::new(MockMemory::new());
-        ppu
+        create_test_ppu_with_a12_edge_counter().0
+    }
+
+    fn create_test_ppu_with_a12_edge_counter<'a>() -> (Ppu<'a>, Rc<Cell<u32>>) {
+        let a12_rising_edges = Rc::new(Cell::new(0));
+        let mapper = MockMapper {
+            chr: vec![0; 0x2000],
+            a12_rising_edges: a12_rising_edges.clone(),
+        };
+        let ppu = Ppu::new(Box::new(MockRenderer::new()), TvSystem::NTSC, Box::new(mapper));
+        (ppu, a12_rising_edges)
     }
 
     #[test]
@@ -858,10 +1110,10 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
-    fn read_from_0x2000_panics() {
+    fn read_from_0x2000_returns_io_latch() {
         let mut ppu = create_test_ppu();
-        ppu.read(0x2000);
+        ppu.io_latch = 0xA5;
+        assert_eq!(0xA5, ppu.read(0x2000));
     }
 
     #[test]
@@ -872,24 +1124,43 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
-    fn read_from_0x2001_panics() {
+    fn read_from_0x2001_returns_io_latch() {
         let mut ppu = create_test_ppu();
-        ppu.read(0x2001);
+        ppu.io_latch = 0xA5;
+        assert_eq!(0xA5, ppu.read(0x2001));
     }
 
     #[test]
-    #[should_panic]
-    fn write_to_0x2002_panics() {
+    fn write_to_0x2002_only_updates_io_latch() {
         let mut ppu = create_test_ppu();
+        ppu.registers.status = 0x00;
         ppu.write(0x2002, 0xD4);
+        assert_eq!(0x00, ppu.registers.status);
+        assert_eq!(0xD4, ppu.io_latch);
     }
 
     #[test]
     fn read_from_0x2002_returns_status_register_register() {
+        let mut ppu = create_test_ppu();
+        ppu.registers.status = 0xC0;
+        assert_eq!(0xC0, ppu.read(0x2002));
+    }
+
+    #[test]
+    fn read_from_0x2002_fills_low_5_bits_from_io_latch() {
         let mut ppu = create_test_ppu();
         ppu.registers.status = 0xD5;
-        assert_eq!(0xD5, ppu.read(0x2002));
+        ppu.io_latch = 0x2A;
+        assert_eq!(0xCA, ppu.read(0x2002));
+    }
+
+    #[test]
+    fn read_from_0x2002_refreshes_top_3_bits_of_io_latch() {
+        let mut ppu = create_test_ppu();
+        ppu.registers.status = 0x80;
+        ppu.io_latch = 0x6A;
+        ppu.read(0x2002);
+        assert_eq!(0x8A, ppu.io_latch);
     }
 
     #[test]
@@ -924,10 +1195,10 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
-    fn read_from_0x2003_panics() {
+    fn read_from_0x2003_returns_io_latch() {
         let mut ppu = create_test_ppu();
-        ppu.read(0x2003);
+        ppu.io_latch = 0xA5;
+        assert_eq!(0xA5, ppu.read(0x2003));
     }
 
     #[test]
@@ -992,10 +1263,10 @@ mod tests {
 
 
     #[test]
-    #[should_panic]
-    fn read_from_0x2005_panics() {
+    fn read_from_0x2005_returns_io_latch() {
         let mut ppu = create_test_ppu();
-        ppu.read(0x2005);
+        ppu.io_latch = 0xA5;
+        assert_eq!(0xA5, ppu.read(0x2005));
     }
 
     #[test]
@@ -1015,10 +1286,60 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
-    fn read_from_0x2006_panics() {
+    fn read_from_0x2006_returns_io_latch() {
+        let mut ppu = create_test_ppu();
+        ppu.io_latch = 0xA5;
+        assert_eq!(0xA5, ppu.read(0x2006));
+    }
+
+    #[test]
+    fn write_to_ppu_register_sets_io_latch() {
+        let mut ppu = create_test_ppu();
+        ppu.write(0x2005, 0x3C);
+        assert_eq!(0x3C, ppu.read(0x2000));
+    }
+
+    #[test]
+    fn io_latch_decays_if_not_refreshed() {
+        let mut ppu = create_test_ppu();
+        ppu.write(0x2000, 0xFF);
+        ppu.frame_count += IO_LATCH_DECAY_FRAMES + 1;
+        assert_eq!(0x00, ppu.read(0x2000));
+    }
+
+    #[test]
+    fn io_latch_does_not_decay_before_decay_time() {
+        let mut ppu = create_test_ppu();
+        ppu.write(0x2000, 0xFF);
+        ppu.frame_count += IO_LATCH_DECAY_FRAMES;
+        assert_eq!(0xFF, ppu.read(0x2000));
+    }
+
+    #[test]
+    fn io_latch_decays_only_bits_which_were_not_refreshed() {
+        let mut ppu = create_test_ppu();
+        ppu.write(0x2000, 0xFF);
+        ppu.frame_count += IO_LATCH_DECAY_FRAMES;
+        ppu.registers.status = 0x00;
+        ppu.read(0x2002);
+        ppu.frame_count += 1;
+        assert_eq!(0x00, ppu.read(0x2000));
+
+        ppu.write(0x2000, 0xFF);
+        ppu.frame_count += IO_LATCH_DECAY_FRAMES;
+        ppu.registers.status = 0xE0;
+        ppu.read(0x2002);
+        ppu.frame_count += 1;
+        assert_eq!(0xE0, ppu.read(0x2000));
+    }
+
+    #[test]
+    fn palette_read_from_0x2007_fills_top_2_bits_from_io_latch() {
         let mut ppu = create_test_ppu();
-        ppu.read(0x2006);
+        ppu.vram.write(0x3F01, 0x16);
+        ppu.vram_address = 0x3F01;
+        ppu.io_latch = 0xC0;
+        assert_eq!(0xD6, ppu.read(0x2007));
     }
 
     #[test]
@@ -1097,6 +1418,7 @@ mod tests {
         ppu.vram_address = 0x3F00;
         ppu.vram_read_buffer = 0xE1;
         ppu.vram.write(0x3F00, 0xBE);
+        ppu.io_latch = 0x80;
         assert_eq!(0xBE, ppu.read(0x2007));
     }
 
@@ -1158,6 +1480,7 @@ mod tests {
     fn read_from_address_0x200A_is_mirrored_to_status_register() {
         let mut ppu = create_test_ppu();
         ppu.registers.status = 0xD5;
+        ppu.io_latch = 0x15;
         assert_eq!(0xD5, ppu.read(0x200A));
     }
 
@@ -1193,22 +1516,16 @@ mod tests {
         assert_eq!(0xF2, ppu.read(0x3FFF));
     }
 
+    // oam dma writes the page through the oam data register
     #[test]
-    #[should_panic]
-    fn oam_dma_write_panics_if_data_length_is_not_256_bytes() {
-        let data:Vec<u8> = vec![1, 2, 3, 4, 5];
-        let mut ppu = create_test_ppu();
-        ppu.oam_dma_write(data);
-    }
-
-    #[test]
-    fn oam_dma_write_replaces_oam() {
+    fn writing_256_bytes_to_oam_data_register_replaces_oam() {
         let mut ppu = create_test_ppu();
         let mut data = vec![];
+        ppu.write(0x2003, 0x00);
         for i in 0..256 {
-            data.push(((i as u16) % 256) as u8);
+            data.push(i as u8);
+            ppu.write(0x2004, i as u8);
         }
-        ppu.oam_dma_write(data.clone());
         assert_eq!(data, ppu.object_attribute_memory);
     }
 
@@ -1316,6 +1633,90 @@ mod tests {
         assert_eq!(0x00, ppu.registers.status);
     }
 
+    #[test]
+    fn ntsc_frame_wraps_after_262_scanlines() {
+        let mut ppu = create_test_ppu();
+
+        ppu.current_scanline = 261;
+        ppu.pos_at_scanline = 340;
+        ppu.execute_cycle();
+
+        assert_eq!(0, ppu.current_scanline);
+    }
+
+    #[test]
+    fn pal_frame_wraps_after_312_scanlines() {
+        let mut ppu = create_test_ppu();
+        ppu.tv_system = TvSystemValues::new(&TvSystem::PAL);
+
+        ppu.current_scanline = 261;
+        ppu.pos_at_scanline = 340;
+        ppu.execute_cycle();
+        assert_eq!(262, ppu.current_scanline);
+
+        ppu.current_scanline = 311;
+        ppu.pos_at_scanline = 340;
+        ppu.execute_cycle();
+        assert_eq!(0, ppu.current_scanline);
+    }
+
+    #[test]
+    fn dendy_frame_has_312_scanlines_with_ntsc_length_vblank() {
+        let tv_system = TvSystemValues::new(&TvSystem::Dendy);
+        assert_eq!(312, tv_system.scanlines_per_frame());
+        assert_eq!(20, tv_system.vblank_frames);
+        assert_eq!(3, tv_system.ppu_cycles_per_cpu_cycle);
+        assert_eq!(0, tv_system.ppu_extra_cycle_every_cpu_cycle);
+    }
+
+    #[test]
+    fn dendy_sets_vblank_bit_after_51_post_render_scanlines() {
+        let mut ppu = create_test_ppu();
+        ppu.tv_system = TvSystemValues::new(&TvSystem::Dendy);
+
+        // last post render scanline
+        ppu.current_scanline = 311;
+        for _ in 0..343 {
+            ppu.execute_cycle();
+        }
+        assert_eq!(0x80, ppu.registers.status & 0x80);
+    }
+
+    #[test]
+    fn pal_pre_render_line_does_not_skip_cycle_on_odd_frames() {
+        let mut ppu = create_test_ppu();
+        ppu.tv_system = TvSystemValues::new(&TvSystem::PAL);
+
+        ppu.registers.mask = 0x18;
+        ppu.current_scanline = ppu.tv_system.vblank_frames;
+        ppu.pos_at_scanline = 339;
+        ppu.is_even_frame = false;
+        ppu.execute_cycle();
+
+        assert_eq!(340, ppu.pos_at_scanline);
+    }
+
+    #[test]
+    fn scanline_is_0_on_first_rendered_line() {
+        let mut ppu = create_test_ppu();
+        ppu.current_scanline = 21;
+        assert_eq!(0, ppu.scanline());
+    }
+
+    #[test]
+    fn scanline_is_241_on_first_vblank_line() {
+        let mut ppu = create_test_ppu();
+        ppu.current_scanline = 0;
+        assert_eq!(241, ppu.scanline());
+    }
+
+    #[test]
+    fn scanline_is_261_on_pre_render_line() {
+        let mut ppu = create_test_ppu();
+        ppu.current_scanline = 20;
+        assert_eq!(261, ppu.scanline());
+    }
+
     #[test]
     fn nmi_occured_returns_true_if_nmi_has_occured() {
         let mut ppu = create_test_ppu();
@@ -1349,4 +1750,465 @@ mod tests {
         assert_eq!(false, ppu.nmi_occured);
     }
 
+    #[test]
+    fn pattern_table_read_from_0x1000_after_a12_has_been_low_clocks_mapper() {
+        let (mut ppu, a12_rising_edges) = create_test_ppu_with_a12_edge_counter();
+        ppu.cpu_cycles_since_a12_high = A12_LOW_CPU_CYCLES_BEFORE_RISING_EDGE + 1;
+        ppu.read_pattern_table(0x1000);
+        assert_eq!(1, a12_rising_edges.get());
+    }
+
+    #[test]
+    fn pattern_table_read_from_0x1000_shortly_after_a12_was_high_does_not_clock_mapper() {
+        let (mut ppu, a12_rising_edges) = create_test_ppu_with_a12_edge_counter();
+        ppu.cpu_cycles_since_a12_high = A12_LOW_CPU_CYCLES_BEFORE_RISING_EDGE;
+        ppu.read_pattern_table(0x1000);
+        assert_eq!(0, a12_rising_edges.get());
+    }
+
+    #[test]
+    fn pattern_table_read_from_0x0000_does_not_clock_mapper() {
+        let (mut ppu, a12_rising_edges) = create_test_ppu_with_a12_edge_counter();
+        ppu.cpu_cycles_since_a12_high = A12_LOW_CPU_CYCLES_BEFORE_RISING_EDGE + 1;
+        ppu.read_pattern_table(0x0000);
+        assert_eq!(0, a12_rising_edges.get());
+    }
+
+    #[test]
+    fn sprite_fetches_from_0x1000_clock_mapper_once_per_scanline() {
+        let (mut ppu, a12_rising_edges) = create_test_ppu_with_a12_edge_counter();
+        ppu.registers.mask = 0x18;
+        ppu.registers.control = 0x08; // background at 0x0000, sprites at 0x1000
+        ppu.current_scanline = ppu.tv_system.vblank_frames + 1;
+        ppu.pos_at_scanline = 0;
+
+        for _ in 0..341*2/3 {
+            ppu.execute_cycles();
+        }
+        assert_eq!(2, a12_rising_edges.get());
+    }
+
+    #[test]
+    fn sprite_fetches_do_not_clock_mapper_when_rendering_is_disabled() {
+        let (mut ppu, a12_rising_edges) = create_test_ppu_with_a12_edge_counter();
+        ppu.registers.mask = 0x00;
+        ppu.registers.control = 0x08;
+        ppu.current_scanline = ppu.tv_system.vblank_frames + 1;
+        ppu.pos_at_scanline = 0;
+
+        for _ in 0..341/3 {
+            ppu.execute_cycles();
+        }
+        assert_eq!(0, a12_rising_edges.get());
+    }
+
+    #[test]
+    fn sprite_fetches_from_0x1000_clock_mapper_once_per_scanline_on_pal() {
+        let (mut ppu, a12_rising_edges) = create_test_ppu_with_a12_edge_counter();
+        ppu.tv_system = TvSystemValues::new(&TvSystem::PAL);
+        ppu.registers.mask = 0x18;
+        ppu.registers.control = 0x08;
+        ppu.current_scanline = ppu.tv_system.vblank_frames + 1;
+        ppu.pos_at_scanline = 0;
+
+        for _ in 0..341*2*5/16 {
+            ppu.execute_cycles();
+        }
+        assert_eq!(2, a12_rising_edges.get());
+    }
+
+    #[test]
+    fn read_from_0x2007_in_upper_pattern_table_clocks_mapper() {
+        let (mut ppu, a12_rising_edges) = create_test_ppu_with_a12_edge_counter();
+        ppu.vram_address = 0x1000;
+        ppu.read(0x2007);
+        assert_eq!(1, a12_rising_edges.get());
+    }
+
+    #[test]
+    fn write_to_0x2007_in_upper_pattern_table_clocks_mapper() {
+        let (mut ppu, a12_rising_edges) = create_test_ppu_with_a12_edge_counter();
+        ppu.vram_address = 0x1FFF;
+        ppu.write(0x2007, 0x12);
+        assert_eq!(1, a12_rising_edges.get());
+    }
+
+    #[test]
+    fn consecutive_reads_from_0x2007_in_upper_pattern_table_clock_mapper_once() {
+        let (mut ppu, a12_rising_edges) = create_test_ppu_with_a12_edge_counter();
+        ppu.vram_address = 0x1000;
+        ppu.read(0x2007);
+        ppu.read(0x2007);
+        assert_eq!(1, a12_rising_edges.get());
+    }
+
+    #[test]
+    fn read_from_0x2007_in_name_tables_does_not_clock_mapper() {
+        let (mut ppu, a12_rising_edges) = create_test_ppu_with_a12_edge_counter();
+        ppu.vram_address = 0x3000;
+        ppu.read(0x2007);
+        assert_eq!(0, a12_rising_edges.get());
+    }
+
+    fn create_test_ppu_with_sprite<'a>(y: u8, tile: u8, attribute: u8, x: u8) -> Ppu<'a> {
+        let mut ppu = create_test_ppu();
+        ppu.registers.mask = 0x14; // sprites enabled, including the leftmost 8 pixels
+        for i in 0..32 {
+            ppu.secondary_oam[i] = 0xFF;
+        }
+        ppu.secondary_oam[0] = y;
+        ppu.secondary_oam[1] = tile;
+        ppu.secondary_oam[2] = attribute;
+        ppu.secondary_oam[3] = x;
+        ppu.secondary_oam_address = 4;
+        ppu
+    }
+
+    // fetches the sprites for given line and returns the sprite at x on the following line
+    fn render_sprite_pixel(ppu: &mut Ppu, x: u16, line: u16) -> SpriteRenderData {
+        ppu.current_scanline = ppu.tv_system.vblank_frames + 1 + line;
+        for pos in 257..321 {
+            ppu.pos_at_scanline = pos;
+            ppu.do_sprite_memory_access();
+        }
+        ppu.get_sprite_for_rendering(x)
+    }
+
+    #[test]
+    fn sprite_8x16_top_half_uses_even_tile_from_table_selected_by_tile_bit_0() {
+        let mut ppu = create_test_ppu_with_sprite(20, 0x03, 0x00, 10);
+        ppu.registers.control = 0x20;
+        ppu.vram.write(0x1000 + 2*16 + 1, 0x80);
+
+        assert_eq!(17, render_sprite_pixel(&mut ppu, 10, 21).palette_index);
+    }
+
+    #[test]
+    fn sprite_8x16_bottom_half_uses_following_tile() {
+        let mut ppu = create_test_ppu_with_sprite(20, 0x02, 0x00, 10);
+        ppu.registers.control = 0x20;
+        ppu.vram.write(3*16 + 1, 0x80);
+
+        assert_eq!(17, render_sprite_pixel(&mut ppu, 10, 29).palette_index);
+    }
+
+    #[test]
+    fn sprite_8x16_vertical_flip_swaps_tiles() {
+        let mut ppu = create_test_ppu_with_sprite(20, 0x02, 0x80, 10);
+        ppu.registers.control = 0x20;
+        ppu.vram.write(3*16 + 7, 0x80);
+
+        assert_eq!(17, render_sprite_pixel(&mut ppu, 10, 20).palette_index);
+        assert_eq!(0, render_sprite_pixel(&mut ppu, 10, 35).palette_index);
+    }
+
+    #[test]
+    fn sprite_8x8_is_not_rendered_below_eighth_row() {
+        let mut ppu = create_test_ppu_with_sprite(20, 0x00, 0x00, 10);
+        ppu.vram.write(1*16 + 0, 0x80);
+
+        assert_eq!(0, render_sprite_pixel(&mut ppu, 10, 28).palette_index);
+    }
+
+    #[test]
+    fn unused_secondary_oam_slots_are_not_rendered() {
+        let mut ppu = create_test_ppu_with_sprite(0xFF, 0xFF, 0xFF, 0xFF);
+        ppu.vram.write(0x1000 + 0xFF*16, 0xFF);
+
+        assert_eq!(0, render_sprite_pixel(&mut ppu, 255, 10).palette_index);
+    }
+
+    #[test]
+    fn sprite_8x8_uses_table_at_0x0000_when_control_bit_3_is_clear() {
+        let mut ppu = create_test_ppu_with_sprite(20, 0x05, 0x00, 10);
+        ppu.registers.control = 0x00;
+        ppu.vram.write(5*16 + 1, 0x80);
+        ppu.vram.write(0x1000 + 5*16 + 1, 0x00);
+
+        assert_eq!(17, render_sprite_pixel(&mut ppu, 10, 21).palette_index);
+    }
+
+    #[test]
+    fn sprite_8x8_uses_table_at_0x1000_when_control_bit_3_is_set() {
+        let mut ppu = create_test_ppu_with_sprite(20, 0x05, 0x00, 10);
+        ppu.registers.control = 0x08;
+        ppu.vram.write(5*16 + 1, 0x00);
+        ppu.vram.write(0x1000 + 5*16 + 1, 0x80);
+
+        assert_eq!(17, render_sprite_pixel(&mut ppu, 10, 21).palette_index);
+    }
+
+    #[test]
+    fn sprite_8x8_table_is_not_selected_by_background_table_bit() {
+        let mut ppu = create_test_ppu_with_sprite(20, 0x05, 0x00, 10);
+        ppu.registers.control = 0x10;
+        ppu.vram.write(5*16 + 1, 0x80);
+        ppu.vram.write(0x1000 + 5*16 + 1, 0x00);
+
+        assert_eq!(17, render_sprite_pixel(&mut ppu, 10, 21).palette_index);
+    }
+
+    #[test]
+    fn sprite_8x16_ignores_control_bit_3() {
+        let mut ppu = create_test_ppu_with_sprite(20, 0x04, 0x00, 10);
+        ppu.registers.control = 0x28;
+        ppu.vram.write(4*16 + 1, 0x80);
+        ppu.vram.write(0x1000 + 4*16 + 1, 0x00);
+
+        assert_eq!(17, render_sprite_pixel(&mut ppu, 10, 21).palette_index);
+    }
+
+    fn evaluate_sprites_for_line(ppu: &mut Ppu, line: u16) {
+        ppu.registers.mask = 0x18;
+        ppu.current_scanline = ppu.tv_system.vblank_frames + 1 + line;
+        for pos in 1..257 {
+            ppu.pos_at_scanline = pos;
+            ppu.do_sprite_evaluation();
+        }
+    }
+
+    fn set_sprite_y_coordinates(ppu: &mut Ppu, y: u8, count: usize) {
+        for i in 0..64 {
+            ppu.object_attribute_memory[i*4] = if i < count { y } else { 0xF0 };
+        }
+    }
+
+    #[test]
+    fn sprite_evaluation_copies_sprites_on_line_to_secondary_oam() {
+        let mut ppu = create_test_ppu();
+        set_sprite_y_coordinates(&mut ppu, 0xF0, 0);
+        ppu.object_attribute_memory[8] = 20;
+        ppu.object_attribute_memory[9] = 0x12;
+        ppu.object_attribute_memory[10] = 0x34;
+        ppu.object_attribute_memory[11] = 0x56;
+
+        evaluate_sprites_for_line(&mut ppu, 25);
+
+        assert_eq!(vec![20, 0x12, 0x34, 0x56], ppu.secondary_oam[0..4].to_vec());
+        assert_eq!(4, ppu.secondary_oam_address);
+        assert_eq!(false, ppu.secondary_contains_sprite_0);
+    }
+
+    #[test]
+    fn sprite_evaluation_clears_unused_secondary_oam_slots() {
+        let mut ppu = create_test_ppu();
+        set_sprite_y_coordinates(&mut ppu, 0xF0, 0);
+        ppu.object_attribute_memory[1] = 0x12;
+
+        evaluate_sprites_for_line(&mut ppu, 25);
+
+        // y of the last evaluated sprite is written into the first free slot
+        assert_eq!(0xF0, ppu.secondary_oam[0]);
+        assert_eq!(vec![0xFF; 31], ppu.secondary_oam[1..32].to_vec());
+        assert_eq!(0, ppu.secondary_oam_address);
+    }
+
+    #[test]
+    fn sprite_evaluation_detects_sprite_0() {
+        let mut ppu = create_test_ppu();
+        set_sprite_y_coordinates(&mut ppu, 20, 1);
+
+        evaluate_sprites_for_line(&mut ppu, 20);
+
+        assert_eq!(true, ppu.secondary_contains_sprite_0);
+    }
+
+    #[test]
+    fn sprite_evaluation_sets_overflow_flag_if_more_than_8_sprites_are_on_line() {
+        let mut ppu = create_test_ppu();
+        set_sprite_y_coordinates(&mut ppu, 20, 9);
+
+        evaluate_sprites_for_line(&mut ppu, 20);
+
+        assert_eq!(32, ppu.secondary_oam_address);
+        assert_eq!(0x20, ppu.registers.status & 0x20);
+    }
+
+    #[test]
+    fn sprite_evaluation_does_not_set_overflow_flag_with_8_sprites_on_line() {
+        let mut ppu = create_test_ppu();
+        set_sprite_y_coordinates(&mut ppu, 20, 8);
+
+        evaluate_sprites_for_line(&mut ppu, 20);
+
+        assert_eq!(0, ppu.registers.status & 0x20);
+    }
+
+    #[test]
+    fn sprite_evaluation_overflow_check_uses_incorrect_byte_offset() {
+        let mut ppu = create_test_ppu();
+        set_sprite_y_coordinates(&mut ppu, 20, 8);
+        for i in 8..64 {
+            for j in 1..4 {
+                ppu.object_attribute_memory[i*4 + j] = 0xF0;
+            }
+        }
+        // ninth sprite is not on line, so byte 1 of the tenth sprite is checked instead of its y coordinate
+        ppu.object_attribute_memory[9*4] = 20;
+
+        evaluate_sprites_for_line(&mut ppu, 20);
+
+        assert_eq!(0, ppu.registers.status & 0x20);
+    }
+
+    #[test]
+    fn sprite_evaluation_starts_from_oam_address() {
+        let mut ppu = create_test_ppu();
+        set_sprite_y_coordinates(&mut ppu, 20, 1);
+        ppu.registers.oam_address = 4;
+
+        evaluate_sprites_for_line(&mut ppu, 20);
+
+        assert_eq!(0, ppu.secondary_oam_address);
+    }
+
+    #[test]
+    fn read_from_0x2004_returns_0xff_while_secondary_oam_is_cleared() {
+        let mut ppu = create_test_ppu();
+        ppu.registers.mask = 0x18;
+        ppu.object_attribute_memory[0] = 0x12;
+        ppu.current_scanline = ppu.tv_system.vblank_frames + 1;
+        ppu.pos_at_scanline = 10;
+        ppu.do_sprite_evaluation();
+
+        assert_eq!(0xFF, ppu.read(0x2004));
+    }
+
+    #[test]
+    fn read_from_0x2004_returns_value_read_by_sprite_evaluation() {
+        let mut ppu = create_test_ppu();
+        ppu.registers.mask = 0x18;
+        ppu.registers.oam_address = 0x10;
+        ppu.object_attribute_memory[0x10] = 0x12;
+        ppu.current_scanline = ppu.tv_system.vblank_frames + 1;
+        ppu.pos_at_scanline = 65;
+        ppu.do_sprite_evaluation();
+
+        assert_eq!(0x12, ppu.read(0x2004));
+    }
+
+    #[test]
+    fn write_to_0x2004_during_rendering_does_not_write_but_increments_sprite_index() {
+        let mut ppu = create_test_ppu();
+        ppu.registers.mask = 0x18;
+        ppu.registers.oam_address = 0x21;
+        ppu.current_scanline = ppu.tv_system.vblank_frames + 1;
+        ppu.write(0x2004, 0x12);
+
+        assert_eq!(0, ppu.object_attribute_memory[0x21]);
+        assert_eq!(0x25, ppu.registers.oam_address);
+    }
+
+    #[test]
+    fn sprite_fetch_resets_oam_address() {
+        let mut ppu = create_test_ppu_with_sprite(20, 0, 0, 10);
+        ppu.registers.oam_address = 0x21;
+        ppu.current_scanline = ppu.tv_system.vblank_frames + 1;
+        ppu.pos_at_scanline = 257;
+        ppu.do_sprite_memory_access();
+
+        assert_eq!(0, ppu.registers.oam_address);
+    }
+
+    #[test]
+    fn sprite_8x8_horizontal_flip_is_applied_when_fetching() {
+        let mut ppu = create_test_ppu_with_sprite(20, 0x00, 0x40, 10);
+        ppu.vram.write(1, 0x80);
+
+        assert_eq!(17, render_sprite_pixel(&mut ppu, 17, 21).palette_index);
+        assert_eq!(0, render_sprite_pixel(&mut ppu, 10, 21).palette_index);
+    }
+
+    #[test]
+    fn oam_is_corrupted_if_oam_address_is_8_or_above_when_rendering_starts() {
+        let mut ppu = create_test_ppu();
+        ppu.registers.mask = 0x18;
+        ppu.registers.oam_address = 0x13;
+        for i in 0..8 {
+            ppu.object_attribute_memory[0x10 + i] = i as u8 + 1;
+        }
+        ppu.current_scanline = ppu.tv_system.vblank_frames;
+        ppu.pos_at_scanline = 1;
+        ppu.execute_cycle();
+
+        assert_eq!(vec![1, 2, 3, 4, 5, 6, 7, 8], ppu.object_attribute_memory[0..8].to_vec());
+    }
+
+    #[test]
+    fn sprite_flags_are_not_cleared_at_vblank_start() {
+        let mut ppu = create_test_ppu();
+        ppu.current_scanline = 0;
+        ppu.pos_at_scanline = 1;
+        ppu.registers.status = 0x60;
+        ppu.execute_cycle();
+
+        assert_eq!(0xE0, ppu.registers.status);
+    }
+
+    #[test]
+    fn sprite_flags_are_cleared_on_pre_render_scanline_second_pixel() {
+        let mut ppu = create_test_ppu();
+        ppu.current_scanline = ppu.tv_system.vblank_frames;
+        ppu.pos_at_scanline = 1;
+        ppu.registers.status = 0x60;
+        ppu.execute_cycle();
+
+        assert_eq!(0x00, ppu.registers.status);
+    }
+
+    #[test]
+    fn sprite_is_rendered_on_line_after_its_y_coordinate() {
+        let mut ppu = create_test_ppu();
+        ppu.registers.mask = 0x14;
+        ppu.vram.write(0x3F11, 0x16);
+        ppu.vram.write(0, 0xFF);
+        set_sprite_y_coordinates(&mut ppu, 0xF0, 0);
+        ppu.object_attribute_memory[0] = 20;
+        ppu.object_attribute_memory[3] = 0;
+
+        // run line 20, which evaluates and fetches sprites for line 21
+        ppu.current_scanline = ppu.tv_system.vblank_frames + 1 + 20;
+        ppu.pos_at_scanline = 0;
+        for _ in 0..341*2 {
+            ppu.execute_cycle();
+        }
+
+        assert_eq!(ppu.palette.color(0x00, 0), ppu.pixels[20*256]);
+        assert_eq!(ppu.palette.color(0x16, 0), ppu.pixels[21*256]);
+    }
+
+    #[test]
+    fn greyscale_selects_grey_column_of_the_palette() {
+        let mut ppu = create_test_ppu();
+        ppu.registers.mask = 0x01;
+        assert_eq!(ppu.color_for_palette_entry(0x20), ppu.color_for_palette_entry(0x2A));
+    }
+
+    #[test]
+    fn color_is_not_modified_without_emphasis() {
+        let ppu = create_test_ppu();
+        assert_eq!(Pixel::new(248, 248, 248), ppu.color_for_palette_entry(0x20));
+    }
+
+    #[test]
+    fn red_emphasis_attenuates_green_and_blue() {
+        let mut ppu = create_test_ppu();
+        ppu.registers.mask = 0x20;
+        assert_eq!(Pixel::new(248, 202, 202), ppu.color_for_palette_entry(0x20));
+    }
+
+    #[test]
+    fn all_emphasis_bits_attenuate_all_channels() {
+        let mut ppu = create_test_ppu();
+        ppu.registers.mask = 0xE0;
+        assert_eq!(Pixel::new(202, 202, 202), ppu.color_for_palette_entry(0x20));
+    }
+
+    #[test]
+    fn pal_emphasis_swaps_red_and_green() {
+        let mut ppu = create_test_ppu();
+        ppu.tv_system = TvSystemValues::new(&TvSystem::PAL);
+        ppu.registers.mask = 0x20;
+        assert_eq!(Pixel::new(202, 248, 202), ppu.color_for_palette_entry(0x20));
+    }
 }
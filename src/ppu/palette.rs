@@ -0,0 +1,314 @@
+// see http://wiki.nesdev.com/w/index.php/PPU_palettes and http://wiki.nesdev.com/w/index.php/NTSC_video
+use ppu::renderer::Pixel;
+
+use std::f64::consts::PI;
+use std::fs;
+use std::io;
+
+// Emphasis darkens the color channels that are not emphasized
+const EMPHASIS_ATTENUATION: f64 = 0.816328;
+
+static DEFAULT_PALETTE: [u8; 192] = [
+    124,124,124,    0,0,252,        0,0,188,        68,40,188,
+    148,0,132,      168,0,32,       168,16,0,       136,20,0,
+    80,48,0,        0,120,0,        0,104,0,        0,88,0,
+    0,64,88,        0,0,0,          0,0,0,          0,0,0,
+    188,188,188,    0,120,248,      0,88,248,       104,68,252,
+    216,0,204,      228,0,88,       248,56,0,       228,92,16,
+    172,124,0,      0,184,0,        0,168,0,        0,168,68,
+    0,136,136,      0,0,0,          0,0,0,          0,0,0,
+    248,248,248,    60,188,252,     104,136,252,    152,120,248,
+    248,120,248,    248,88,152,     248,120,88,     252,160,68,
+    248,184,0,      184,248,24,     88,216,84,      88,248,152,
+    0,232,216,      120,120,120,    0,0,0,          0,0,0,
+    252,252,252,    164,228,252,    184,184,248,    216,184,248,
+    248,184,248,    248,164,192,    240,208,176,    252,224,168,
+    248,216,120,    216,248,120,    184,248,184,    184,248,216,
+    0,252,252,      248,216,248,    0,0,0,          0,0,0
+];
+
+// 64 colors, 3 bytes each
+const PALETTE_SIZE: usize = 192;
+// 8 emphasis combinations of the above
+const EMPHASIS_PALETTE_SIZE: usize = PALETTE_SIZE * 8;
+
+// Parameters for the generated NTSC palette. Hue is in degrees, the rest are multipliers
+// (or, in case of brightness, an offset) applied to the decoded signal
+#[derive(Debug, Clone)]
+pub struct NtscPaletteParameters {
+    pub hue: f64,
+    pub saturation: f64,
+    pub contrast: f64,
+    pub brightness: f64,
+}
+
+impl NtscPaletteParameters {
+    pub fn new() -> NtscPaletteParameters {
+        NtscPaletteParameters {
+            hue: 0.0,
+            saturation: 1.0,
+            contrast: 1.0,
+            brightness: 0.0,
+        }
+    }
+}
+
+pub struct Palette {
+    // rgb values for 64 colors, or 512 colors if the palette contains colors for each
+    // emphasis combination
+    data: Vec<u8>,
+}
+
+impl Palette {
+    pub fn new() -> Palette {
+        Palette {
+            data: DEFAULT_PALETTE.to_vec(),
+        }
+    }
+
+    // .pal files are either 192 bytes (64 colors) or 1536 bytes (64 colors for each emphasis combination)
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Palette> {
+        if bytes.len() != PALETTE_SIZE && bytes.len() != EMPHASIS_PALETTE_SIZE {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                format!("Palette size is {} bytes but {} or {} was expected",
+                    bytes.len(),
+                    PALETTE_SIZE,
+                    EMPHASIS_PALETTE_SIZE)));
+        }
+
+        Ok(Palette {
+            data: bytes.to_vec(),
+        })
+    }
+
+    pub fn from_file(file_path: &str) -> io::Result<Palette> {
+        let bytes = fs::read(file_path)?;
+        Palette::from_bytes(&bytes)
+    }
+
+    // generates all 512 colors by decoding the composite signal the ppu would produce
+    pub fn generate_ntsc(parameters: &NtscPaletteParameters) -> Palette {
+        let mut data = Vec::with_capacity(EMPHASIS_PALETTE_SIZE);
+        for emphasis in 0..8 {
+            for color in 0..64 {
+                let (r, g, b) = generate_ntsc_color(color, emphasis, parameters);
+                data.push(r);
+                data.push(g);
+                data.push(b);
+            }
+        }
+
+        Palette {
+            data: data,
+        }
+    }
+
+    // emphasis bits are in red, green, blue order (bit 0 is red)
+    pub fn color(&self, color_index: u8, emphasis: u8) -> Pixel {
+        let color_index = (color_index & 0x3F) as usize;
+        let emphasis = (emphasis & 0x07) as usize;
+
+        if self.data.len() == EMPHASIS_PALETTE_SIZE {
+            let index = (emphasis*64 + color_index)*3;
+            Pixel::new(self.data[index], self.data[index + 1], self.data[index + 2])
+        } else {
+            let index = color_index*3;
+            let pixel = (self.data[index], self.data[index + 1], self.data[index + 2]);
+            let (r, g, b) = apply_emphasis(pixel, emphasis as u8);
+            Pixel::new(r, g, b)
+        }
+    }
+}
+
+// palettes without per-emphasis colors approximate the emphasis by darkening the other channels
+fn apply_emphasis(pixel: (u8, u8, u8), emphasis: u8) -> (u8, u8, u8) {
+    if emphasis == 0 {
+        return pixel;
+    }
+
+    // with all bits set, every channel is darkened
+    let attenuate = |value: u8, bit: u8| {
+        if emphasis & bit == 0 || emphasis == 0x07 {
+            (value as f64 * EMPHASIS_ATTENUATION) as u8
+        } else {
+            value
+        }
+    };
+
+    let (r, g, b) = pixel;
+    (attenuate(r, 0x01), attenuate(g, 0x02), attenuate(b, 0x04))
+}
+
+/*
+    The ppu generates a square wave which alternates between two voltage levels. Luminance (bits 4-5
+    of the color) selects the levels and hue (bits 0-3) selects the phase of the wave among 12 phases.
+    Hue 0 is not modulated (high level only), hues 13 - 15 are not modulated (low level only), and
+    hues 14 and 15 are forced black.
+
+    Emphasis attenuates the signal during the phases that correspond to the emphasized color.
+
+    The palette is generated by sampling each of the 12 phases and decoding the signal into YIQ, which
+    is then converted to RGB.
+*/
+// signal levels relative to sync; low levels for luminances 0 - 3, then high levels
+static SIGNAL_LEVELS: [f64; 8] = [0.350, 0.518, 0.962, 1.550, 1.094, 1.506, 1.962, 1.962];
+const BLACK_LEVEL: f64 = 0.518;
+const WHITE_LEVEL: f64 = 1.962;
+const SIGNAL_ATTENUATION: f64 = 0.746;
+
+fn in_color_phase(hue: u8, phase: u8) -> bool {
+    (hue + phase) % 12 < 6
+}
+
+fn generate_ntsc_color(color: u8, emphasis: u8, parameters: &NtscPaletteParameters) -> (u8, u8, u8) {
+    let hue = color & 0x0F;
+    // hues 14 and 15 are black regardless of luminance
+    let luminance = if hue > 13 { 1 } else { (color >> 4) as usize };
+
+    let mut low = SIGNAL_LEVELS[luminance];
+    let mut high = SIGNAL_LEVELS[4 + luminance];
+    if hue == 0 {
+        low = high;
+    } else if hue > 12 {
+        high = low;
+    }
+
+    let hue_offset = parameters.hue * PI / 180.0;
+    let (mut y, mut i, mut q) = (0.0, 0.0, 0.0);
+    for phase in 0..12 {
+        let mut signal = if in_color_phase(hue, phase) { high } else { low };
+
+        // emphasis bits attenuate red (phase 0), green (phase 4) and blue (phase 8)
+        let attenuated = (emphasis & 0x01 != 0 && in_color_phase(0, phase))
+            || (emphasis & 0x02 != 0 && in_color_phase(4, phase))
+            || (emphasis & 0x04 != 0 && in_color_phase(8, phase));
+        if attenuated && hue < 14 {
+            signal *= SIGNAL_ATTENUATION;
+        }
+
+        let level = (signal - BLACK_LEVEL) / (WHITE_LEVEL - BLACK_LEVEL);
+        let angle = PI * (phase as f64 + 3.0) / 6.0 + hue_offset;
+        y += level;
+        i += level * angle.cos();
+        q += level * angle.sin();
+    }
+
+    let y = (y / 12.0) * parameters.contrast + parameters.brightness;
+    let i = (i / 12.0) * parameters.saturation;
+    let q = (q / 12.0) * parameters.saturation;
+
+    // FCC YIQ to RGB conversion
+    let r = y + 0.946882*i + 0.623557*q;
+    let g = y - 0.274788*i - 0.635691*q;
+    let b = y - 1.108545*i + 1.709007*q;
+
+    (to_color_channel(r), to_color_channel(g), to_color_channel(b))
+}
+
+fn to_color_channel(value: f64) -> u8 {
+    (value * 255.0).max(0.0).min(255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ppu::renderer::Pixel;
+
+    #[test]
+    fn default_palette_returns_color_without_emphasis() {
+        let palette = Palette::new();
+        assert_eq!(Pixel::new(0, 120, 248), palette.color(0x11, 0));
+    }
+
+    #[test]
+    fn default_palette_applies_emphasis_by_attenuating_other_channels() {
+        let palette = Palette::new();
+        assert_eq!(Pixel::new(248, 202, 202), palette.color(0x20, 0x01));
+    }
+
+    #[test]
+    fn palette_from_192_bytes_is_accepted() {
+        let mut bytes = vec![0; 192];
+        bytes[3] = 1;
+        bytes[4] = 2;
+        bytes[5] = 3;
+        let palette = Palette::from_bytes(&bytes).unwrap();
+        assert_eq!(Pixel::new(1, 2, 3), palette.color(0x01, 0));
+    }
+
+    #[test]
+    fn palette_from_1536_bytes_contains_colors_for_each_emphasis() {
+        let mut bytes = vec![0; 1536];
+        // emphasis 5, color 2
+        bytes[(5*64 + 2)*3] = 10;
+        bytes[(5*64 + 2)*3 + 1] = 20;
+        bytes[(5*64 + 2)*3 + 2] = 30;
+        let palette = Palette::from_bytes(&bytes).unwrap();
+        assert_eq!(Pixel::new(10, 20, 30), palette.color(0x02, 5));
+        assert_eq!(Pixel::new(0, 0, 0), palette.color(0x02, 0));
+    }
+
+    #[test]
+    fn palette_of_invalid_size_is_rejected() {
+        assert!(Palette::from_bytes(&vec![0; 100]).is_err());
+    }
+
+    #[test]
+    fn generated_palette_has_black_in_column_0x0f() {
+        let palette = Palette::generate_ntsc(&NtscPaletteParameters::new());
+        assert_eq!(Pixel::new(0, 0, 0), palette.color(0x0F, 0));
+        assert_eq!(Pixel::new(0, 0, 0), palette.color(0x3E, 0));
+    }
+
+    #[test]
+    fn generated_palette_greys_are_neutral() {
+        let (r, g, b) = generate_ntsc_color(0x10, 0, &NtscPaletteParameters::new());
+        assert_eq!(r, g);
+        assert_eq!(g, b);
+    }
+
+    #[test]
+    fn generated_palette_white_is_brighter_than_grey() {
+        let parameters = NtscPaletteParameters::new();
+        let (white, _, _) = generate_ntsc_color(0x30, 0, &parameters);
+        let (grey, _, _) = generate_ntsc_color(0x10, 0, &parameters);
+        assert!(white > grey);
+    }
+
+    #[test]
+    fn generated_palette_hue_0x06_is_red() {
+        let (r, g, b) = generate_ntsc_color(0x16, 0, &NtscPaletteParameters::new());
+        assert!(r > g && r > b);
+    }
+
+    #[test]
+    fn generated_palette_hue_0x02_is_blue() {
+        let (r, g, b) = generate_ntsc_color(0x12, 0, &NtscPaletteParameters::new());
+        assert!(b > r && b > g);
+    }
+
+    #[test]
+    fn generated_palette_hue_0x0a_is_green() {
+        let (r, g, b) = generate_ntsc_color(0x1A, 0, &NtscPaletteParameters::new());
+        assert!(g > r && g > b);
+    }
+
+    #[test]
+    fn generated_palette_red_emphasis_darkens_green_and_blue() {
+        let parameters = NtscPaletteParameters::new();
+        let (r, g, b) = generate_ntsc_color(0x20, 0, &parameters);
+        let (emphasized_r, emphasized_g, emphasized_b) = generate_ntsc_color(0x20, 0x01, &parameters);
+        assert!(emphasized_g < g);
+        assert!(emphasized_b < b);
+        assert!(r - emphasized_r < g - emphasized_g);
+    }
+
+    #[test]
+    fn generated_palette_brightness_parameter_brightens_colors() {
+        let mut parameters = NtscPaletteParameters::new();
+        let (normal, _, _) = generate_ntsc_color(0x00, 0, &parameters);
+        parameters.brightness = 0.1;
+        let (brighter, _, _) = generate_ntsc_color(0x00, 0, &parameters);
+        assert!(brighter > normal);
+    }
+}
@@ -8,7 +8,7 @@ use std::cell::RefCell;
 use self::sdl2::pixels::PixelFormatEnum;
 use self::sdl2::rect::Rect;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Pixel {
     r: u8,
     g: u8,
@@ -12,10 +12,19 @@ pub struct TvSystemValues {
     pub post_render_scanlines: u16,
 }
 
+const RENDERED_SCANLINES: u16 = 240;
+
 impl TvSystemValues {
     pub fn new(tv_type: &TvSystem) -> TvSystemValues {
         match *tv_type {
-            TvSystem::PAL => panic!("PAL support is not implemented"),
+            TvSystem::PAL => TvSystemValues {
+                tv_type: tv_type.clone(),
+                ppu_cycles_per_cpu_cycle: 3,
+                ppu_extra_cycle_every_cpu_cycle: 5,
+                extra_cycle_counter: 0,
+                vblank_frames: 70,
+                post_render_scanlines: 1,
+            },
             TvSystem::NTSC => TvSystemValues {
                 tv_type: tv_type.clone(),
                 ppu_cycles_per_cpu_cycle: 3,
@@ -24,8 +33,23 @@ impl TvSystemValues {
                 vblank_frames: 20,
                 post_render_scanlines: 1,
             },
+            // Dendy has the PAL frame length, but the extra scanlines are after the post render line
+            // so that the vblank (and nmi) starts at scanline 291 and lasts as long as on NTSC
+            TvSystem::Dendy => TvSystemValues {
+                tv_type: tv_type.clone(),
+                ppu_cycles_per_cpu_cycle: 3,
+                ppu_extra_cycle_every_cpu_cycle: 0,
+                extra_cycle_counter: 0,
+                vblank_frames: 20,
+                post_render_scanlines: 51,
+            },
             _ => panic!("Invalid TV system type given for ppu: {:?}", tv_type),
         }
 
     }
+
+    // vblank lines + pre-render line + rendered lines + post render lines
+    pub fn scanlines_per_frame(&self) -> u16 {
+        self.vblank_frames + 1 + RENDERED_SCANLINES + self.post_render_scanlines
+    }
 }
@@ -1,51 +1,61 @@
 use memory::Memory;
+use mapper::Mapper;
 use rom::Mirroring;
 
-use std::rc::Rc;
-use std::cell::RefCell;
-
+// The cartridge is owned here, as pattern tables are the ppu's window into it. The cpu memory bus
+// reaches the cartridge through the ppu
 pub struct Vram {
-    rom: Rc<RefCell<Box<Memory>>>,
-    memory: Vec<u8>, // regular 2kb ram
+    mapper: Box<dyn Mapper>,
+    memory: Vec<u8>, // regular 2kb ram, or 4kb with four screen vram
     palette_memory: Vec<u8>, // memory for palettes, 32 bytes
-    mirroring: Mirroring,
 }
 
 impl Vram {
-    pub fn new(mirroring: Mirroring, rom: Rc<RefCell<Box<Memory>>>) -> Vram {
+    pub fn new(mapper: Box<dyn Mapper>) -> Vram {
+        // four screen boards have 2kb of extra ram on the cartridge, so that every nametable
+        // has its own page
+        let memory_size = if mapper.mirroring() == Mirroring::FourScreenVRAM {
+            0x1000
+        } else {
+            0x0800
+        };
+
         Vram {
-            rom: rom,
-            memory: vec![0;0x0800],
+            mapper: mapper,
+            memory: vec![0;memory_size],
             palette_memory: vec![0;0x20],
-            mirroring: mirroring,
         }
     }
 
+    pub fn mapper_mut(&mut self) -> &mut dyn Mapper {
+        &mut *self.mapper
+    }
+
     // calculates address to ppu ram from ppu memory map address
     fn get_nametable_address(&mut self, address: u16) -> usize {
-        if address >= 0x2000 && address < 0x2400 { // nametable 0 does not need mirroring
-            (address - 0x2000) as usize
-        } else if address >= 0x2400 && address < 0x2800 {
-            match self.mirroring {
-                Mirroring::HorizontalMirroring => (address - 0x2400) as usize,
-                Mirroring::VerticalMirroring => (address - 0x2400 + 0x400) as usize,
-                _ => panic!("Invalid mirroring option when looking up nametable 1 address: {:?}", self.mirroring),
-            }
-        } else if address >= 0x2800 && address < 0x2C00 {
-            match self.mirroring {
-                Mirroring::HorizontalMirroring => (address - 0x2800 + 0x400) as usize,
-                Mirroring::VerticalMirroring => (address - 0x2800) as usize,
-                _ => panic!("Invalid mirroring option when looking up nametable 2 address: {:?}", self.mirroring),
-            }
-        }
-        else if address >= 0x2C00 && address < 0x3000 { // nametable 3 does not need mirroring
-            (address - 0x2C00 + 0x400) as usize
-        } else if address >= 0x3000 && address < 0x3F00 { // 0x3000 - 0x3EFFF is mirror of 0x2000 - 0x2EFF
-            self.get_nametable_address(address - 0x1000)
-        }
-        else {
+        if address >= 0x3000 && address < 0x3F00 { // 0x3000 - 0x3EFFF is mirror of 0x2000 - 0x2EFF
+            return self.get_nametable_address(address - 0x1000);
+        } else if address < 0x2000 || address >= 0x3000 {
             panic!("Invalid nametable address: 0x{:04X}", address);
         }
+
+        // nametables 0 - 3 are mapped into the two physical 1kb pages. Mapping depends on
+        // cartridge and may change at runtime
+        let nametable = (address - 0x2000) >> 10;
+        let offset = (address & 0x03FF) as usize;
+
+        // mirroring is requested from mapper on each access, as some mappers change it at runtime
+        let mirroring = self.mapper.mirroring();
+        let page = match mirroring {
+            Mirroring::HorizontalMirroring => nametable >> 1,
+            Mirroring::VerticalMirroring => nametable & 0x01,
+            Mirroring::SingleScreenLower => 0,
+            Mirroring::SingleScreenUpper => 1,
+            Mirroring::FourScreenVRAM => nametable,
+            _ => panic!("Invalid mirroring option when looking up nametable {} address: {:?}", nametable, mirroring),
+        };
+
+        page as usize * 0x400 + offset
     }
 
     // calculates address to ppu palette memory from ppu memory map address
@@ -65,7 +75,7 @@ impl Vram {
 impl Memory for Vram {
     fn read(&mut self, address: u16) -> u8 {
         if address < 0x2000 {
-            self.rom.borrow_mut().read(address)
+            self.mapper.ppu_read(address)
         } else if address >= 0x2000 && address < 0x3F00 { // read from nametable
             let mem_address = self.get_nametable_address(address);
             self.memory[mem_address]
@@ -79,7 +89,7 @@ impl Memory for Vram {
 
     fn write(&mut self, address: u16, value: u8) {
         if address < 0x2000 {
-            self.rom.borrow_mut().write(address, value);
+            self.mapper.ppu_write(address, value);
         } else if address >= 0x2000 && address < 0x3F00 { // write to nametable
             let mem_address = self.get_nametable_address(address);
             self.memory[mem_address] = value;
@@ -100,49 +110,68 @@ impl Memory for Vram {
 mod tests {
     use super::*;
     use memory::Memory;
+    use mapper::Mapper;
     use rom::*;
 
-    use std::rc::Rc;
-    use std::cell::RefCell;
-
     struct MockMemory {
-        memory: Vec<u8>
+        memory: Vec<u8>,
+        mirroring: Mirroring,
     }
 
     impl MockMemory {
-        fn new() -> MockMemory {
+        fn new(mirroring: Mirroring) -> MockMemory {
             MockMemory {
                 memory: vec![0;0xFFFF + 1],
+                mirroring: mirroring,
             }
         }
     }
 
-    impl Memory for MockMemory {
-        fn read(&mut self, address: u16) -> u8 {
+    impl Mapper for MockMemory {
+        fn cpu_read(&mut self, address: u16) -> u8 {
             self.memory[address as usize]
         }
 
-        fn write(&mut self, address: u16, value: u8) {
+        fn cpu_write(&mut self, address: u16, value: u8) {
             self.memory[address as usize] = value;
         }
+
+        fn ppu_read(&mut self, address: u16) -> u8 {
+            self.memory[address as usize]
+        }
+
+        fn ppu_write(&mut self, address: u16, value: u8) {
+            self.memory[address as usize] = value;
+        }
+
+        fn mirroring(&self) -> Mirroring {
+            self.mirroring
+        }
+
+        fn rom_mut(&mut self) -> &mut Rom {
+            unimplemented!()
+        }
     }
 
     fn create_test_vram() -> Vram {
-        let rom = Rc::new(RefCell::new(Box::new(MockMemory::new()) as Box<Memory>));
-        Vram::new(Mirroring::HorizontalMirroring, rom)
+        create_test_vram_with_mirroring(Mirroring::HorizontalMirroring)
+    }
+
+    fn create_test_vram_with_mirroring(mirroring: Mirroring) -> Vram {
+        Vram::new(Box::new(MockMemory::new(mirroring)))
     }
 
     #[test]
     fn write_to_0x0000_is_redirected_to_rom() {
         let mut vram = create_test_vram();
         vram.write(0x0000, 0x7B);
-        assert_eq!(0x7B, vram.rom.borrow_mut().read(0x0000));
+        assert_eq!(0x7B, vram.mapper.ppu_read(0x0000));
     }
 
     #[test]
     fn read_from_0x0000_is_redirected_to_rom() {
         let mut vram = create_test_vram();
-        vram.rom.borrow_mut().write(0x0000, 0x7B);
+        vram.mapper.ppu_write(0x0000, 0x7B);
         assert_eq!(0x7B, vram.read(0x0000));
     }
 
@@ -150,13 +179,13 @@ mod tests {
     fn write_to_0x1FFF_is_redirected_to_rom() {
         let mut vram = create_test_vram();
         vram.write(0x1FFF, 0x7B);
-        assert_eq!(0x7B, vram.rom.borrow_mut().read(0x1FFF));
+        assert_eq!(0x7B, vram.mapper.ppu_read(0x1FFF));
     }
 
     #[test]
     fn read_from_0x1FFF_is_redirected_to_rom() {
         let mut vram = create_test_vram();
-        vram.rom.borrow_mut().write(0x1FFF, 0x7B);
+        vram.mapper.ppu_write(0x1FFF, 0x7B);
         assert_eq!(0x7B, vram.read(0x1FFF));
     }
 
@@ -164,13 +193,13 @@ mod tests {
     fn write_to_0x2000_is_not_redirected_to_rom() {
         let mut vram = create_test_vram();
         vram.write(0x2000, 0x7B);
-        assert_eq!(0x00, vram.rom.borrow_mut().read(0x2000));
+        assert_eq!(0x00, vram.mapper.ppu_read(0x2000));
     }
 
     #[test]
     fn read_from_0x2000_is_not_redirected_to_rom() {
         let mut vram = create_test_vram();
-        vram.rom.borrow_mut().write(0x2000, 0x7B);
+        vram.mapper.ppu_write(0x2000, 0x7B);
         assert_eq!(0x00, vram.read(0x2000));
     }
 
@@ -230,34 +259,50 @@ mod tests {
         assert_eq!(0xFE, vram.read(0x27FF));
     }
 
+    #[test]
+    fn four_screen_vram_has_separate_page_for_each_nametable() {
+        let mut vram = create_test_vram_with_mirroring(Mirroring::FourScreenVRAM);
+        vram.write(0x2000, 0x12);
+        vram.write(0x2400, 0x34);
+        vram.write(0x2800, 0x56);
+        vram.write(0x2C00, 0x78);
+        assert_eq!(0x12, vram.read(0x2000));
+        assert_eq!(0x34, vram.read(0x2400));
+        assert_eq!(0x56, vram.read(0x2800));
+        assert_eq!(0x78, vram.read(0x2C00));
+    }
+
+    #[test]
+    fn four_screen_vram_writes_to_end_of_nametable_3_correctly() {
+        let mut vram = create_test_vram_with_mirroring(Mirroring::FourScreenVRAM);
+        vram.write(0x2FFF, 0x12);
+        assert_eq!(0x12, vram.memory[0xFFF]);
+    }
+
     #[test]
     fn vram_writes_to_beginning_of_nametable_1_correctly_with_vertical_mirroring() {
-        let mut vram = create_test_vram();
-        vram.mirroring = Mirroring::VerticalMirroring;
+        let mut vram = create_test_vram_with_mirroring(Mirroring::VerticalMirroring);
         vram.write(0x2400, 0x12);
         assert_eq!(0x12, vram.memory[0x400]);
     }
 
     #[test]
     fn vram_writes_to_end_of_nametable_1_correctly_with_vertical_mirroring() {
-        let mut vram = create_test_vram();
-        vram.mirroring = Mirroring::VerticalMirroring;
+        let mut vram = create_test_vram_with_mirroring(Mirroring::VerticalMirroring);
         vram.write(0x27FF, 0x12);
         assert_eq!(0x12, vram.memory[0x07FF]);
     }
 
     #[test]
     fn vram_reads_from_beginning_of_nametable_1_correctly_with_vertical_mirroring() {
-        let mut vram = create_test_vram();
-        vram.mirroring = Mirroring::VerticalMirroring;
+        let mut vram = create_test_vram_with_mirroring(Mirroring::VerticalMirroring);
         vram.memory[0x400] = 0xFE;
         assert_eq!(0xFE, vram.read(0x2400));
     }
 
     #[test]
     fn vram_reads_from_end_of_nametable_1_correctly_with_vertical_mirroring() {
-        let mut vram = create_test_vram();
-        vram.mirroring = Mirroring::VerticalMirroring;
+        let mut vram = create_test_vram_with_mirroring(Mirroring::VerticalMirroring);
         vram.memory[0x07FF] = 0xFE;
         assert_eq!(0xFE, vram.read(0x27FF));
     }
@@ -293,32 +338,28 @@ mod tests {
 
     #[test]
     fn vram_writes_to_beginning_of_nametable_2_correctly_with_vertical_mirroring() {
-        let mut vram = create_test_vram();
-        vram.mirroring = Mirroring::VerticalMirroring;
+        let mut vram = create_test_vram_with_mirroring(Mirroring::VerticalMirroring);
         vram.write(0x2800, 0x12);
         assert_eq!(0x12, vram.memory[0x000]);
     }
 
     #[test]
     fn vram_writes_to_end_of_nametable_2_correctly_with_vertical_mirroring() {
-        let mut vram = create_test_vram();
-        vram.mirroring = Mirroring::VerticalMirroring;
+        let mut vram = create_test_vram_with_mirroring(Mirroring::VerticalMirroring);
         vram.write(0x2BFF, 0x12);
         assert_eq!(0x12, vram.memory[0x03FF]);
     }
 
     #[test]
     fn vram_reads_from_beginning_of_nametable_2_correctly_with_vertical_mirroring() {
-        let mut vram = create_test_vram();
-        vram.mirroring = Mirroring::VerticalMirroring;
+        let mut vram = create_test_vram_with_mirroring(Mirroring::VerticalMirroring);
         vram.memory[0x000] = 0xFE;
         assert_eq!(0xFE, vram.read(0x2800));
     }
 
     #[test]
     fn vram_reads_from_end_of_nametable_2_correctly_with_vertical_mirroring() {
-        let mut vram = create_test_vram();
-        vram.mirroring = Mirroring::VerticalMirroring;
+        let mut vram = create_test_vram_with_mirroring(Mirroring::VerticalMirroring);
         vram.memory[0x03FF] = 0xFE;
         assert_eq!(0xFE, vram.read(0x2BFF));
     }
@@ -332,16 +373,14 @@ mod tests {
 
     #[test]
     fn vram_writes_to_end_of_nametable_3_correctly() {
-        let mut vram = create_test_vram();
-        vram.mirroring = Mirroring::VerticalMirroring;
+        let mut vram = create_test_vram_with_mirroring(Mirroring::VerticalMirroring);
         vram.write(0x2FFF, 0x12);
         assert_eq!(0x12, vram.memory[0x07FF]);
     }
 
     #[test]
     fn vram_reads_from_beginning_of_nametable_3_correctly() {
-        let mut vram = create_test_vram();
-        vram.mirroring = Mirroring::VerticalMirroring;
+        let mut vram = create_test_vram_with_mirroring(Mirroring::VerticalMirroring);
         vram.memory[0x400] = 0xFE;
         assert_eq!(0xFE, vram.read(0x2C00));
     }
@@ -353,6 +392,26 @@ mod tests {
         assert_eq!(0xFE, vram.read(0x2FFF));
     }
 
+    #[test]
+    fn all_nametables_map_to_first_page_with_single_screen_lower_mirroring() {
+        let mut vram = create_test_vram_with_mirroring(Mirroring::SingleScreenLower);
+        vram.memory[0x0123] = 0xFE;
+        assert_eq!(0xFE, vram.read(0x2123));
+        assert_eq!(0xFE, vram.read(0x2523));
+        assert_eq!(0xFE, vram.read(0x2923));
+        assert_eq!(0xFE, vram.read(0x2D23));
+    }
+
+    #[test]
+    fn all_nametables_map_to_second_page_with_single_screen_upper_mirroring() {
+        let mut vram = create_test_vram_with_mirroring(Mirroring::SingleScreenUpper);
+        vram.memory[0x0523] = 0xFE;
+        assert_eq!(0xFE, vram.read(0x2123));
+        assert_eq!(0xFE, vram.read(0x2523));
+        assert_eq!(0xFE, vram.read(0x2923));
+        assert_eq!(0xFE, vram.read(0x2D23));
+    }
+
     #[test]
     fn vram_address_0x3000_mirrors_to_0x2000() {
         let mut vram = create_test_vram();
@@ -1,46 +1,96 @@
 // see http://wiki.nesdev.com/w/index.php/INES for more information
-use std::fs::File;
+use std::fs;
+use std::io;
 use std::io::Read;
 use std::fmt;
-use memory::Memory;
 
-pub fn read_rom(file_path: &str) -> Rom {
+const PRG_ROM_UNIT_SIZE: usize = 16384;
+const CHR_ROM_UNIT_SIZE: usize = 8192;
+const PRG_RAM_UNIT_SIZE: usize = 8192;
 
-    let mut rom = Rom::new();
-
-    let mut rom_file = File::open(file_path).unwrap_or_else(|e| {
-        panic!("Could not open the rom file {}: {}", file_path, e);
-        });
+#[derive(Debug)]
+pub enum RomError {
+    Io(io::Error),
+    BadMagic,
+    Truncated { field: &'static str, expected: u64, actual: u64 },
+    UnsupportedMapper(u16),
+    BadHeader(String),
+}
 
+impl fmt::Display for RomError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RomError::Io(ref e) => write!(formatter, "Could not read the rom file: {}", e),
+            RomError::BadMagic => write!(formatter, "Invalid magic number; file is not an iNES rom"),
+            RomError::Truncated { field, expected, actual } =>
+                write!(formatter, "Could not read {}: {} bytes read but {} was expected", field, actual, expected),
+            RomError::UnsupportedMapper(mapper) => write!(formatter, "Mapper {} is not supported", mapper),
+            RomError::BadHeader(ref msg) => write!(formatter, "Invalid header: {}", msg),
+        }
+    }
+}
 
-    rom.read_header(&mut rom_file);
-    rom.read_trainer_field(&mut rom_file);
-    rom.read_prg_rom(&mut rom_file);
-    rom.read_chr_rom(&mut rom_file);
-    // mappers are currently unimplemented; mapper 0 is hardcoded
-    // thus, panic if other mappers are used
-    if rom.header.mapper != 0 {
-        panic!("Currently only mapper 0 is implemented (rom uses mapper {})", rom.header.mapper);
+impl From<io::Error> for RomError {
+    fn from(error: io::Error) -> RomError {
+        RomError::Io(error)
     }
-    rom
+}
+
+pub fn read_rom(file_path: &str) -> Result<Rom, RomError> {
+    // roms are small enough to be read into memory in one go
+    let bytes = fs::read(file_path)?;
+    Rom::from_bytes(&bytes)
 }
 
 
-fn read_bytes_from_file_or_panic(length:u64, file: &mut File, err_msg: &str) -> Vec<u8>{
+fn read_bytes<R: Read>(length: u64, reader: &mut R, field: &'static str) -> Result<Vec<u8>, RomError> {
     let mut buf = vec![];
-    let read_bytes =
-        file
-            .take(length)
-            .read_to_end(&mut buf)
-            .unwrap_or_else(
-                |e| {
-                    panic!("{}: {}", err_msg, e);
-                });
-
-    if read_bytes != buf.len() {
-        panic!("{}: {} bytes read but {} was expected", err_msg, read_bytes, length);
-    }
-    buf
+    let read_bytes = reader.by_ref().take(length).read_to_end(&mut buf)? as u64;
+
+    if read_bytes != length {
+        return Err(RomError::Truncated { field: field, expected: length, actual: read_bytes });
+    }
+    Ok(buf)
+}
+
+/*
+    NES 2.0 rom size is either a 12 bit multiple of the unit size, or, if the msb nybble is 0xF,
+    in exponent-multiplier notation:
+
+    EEEEEEMM
+    ||||||++- multiplier, actual value is MM*2+1 (1, 3, 5, 7)
+    ++++++--- exponent (2^E), 0-63
+
+    where the size is 2^E * (MM*2+1) bytes
+*/
+fn nes_2_0_rom_size(lsb: u8, msb: u8, unit_size: usize) -> Result<usize, RomError> {
+    if msb == 0x0F {
+        let exponent = (lsb >> 2) as u32;
+        let multiplier = (lsb & 0x03) as usize * 2 + 1;
+        2usize.checked_pow(exponent)
+            .and_then(|size| size.checked_mul(multiplier))
+            .ok_or_else(|| RomError::BadHeader(
+                format!("Rom size 2^{} * {} is too large", exponent, multiplier)))
+    } else {
+        Ok((((msb as usize) << 8) | lsb as usize) * unit_size)
+    }
+}
+
+fn nes_2_0_ram_size(shift_count: u8) -> usize {
+    if shift_count == 0 {
+        0
+    } else {
+        64 << shift_count
+    }
+}
+
+// multi-region roms are run as NTSC
+fn tv_system_from_timing(timing: CpuPpuTiming) -> TvSystem {
+    match timing {
+        CpuPpuTiming::NTSC | CpuPpuTiming::MultiRegion => TvSystem::NTSC,
+        CpuPpuTiming::PAL => TvSystem::PAL,
+        CpuPpuTiming::Dendy => TvSystem::Dendy,
+    }
 }
 
 struct RamArray {
@@ -52,8 +102,10 @@ pub struct Rom {
     pub header: RomHeader,
     trainer: Vec<u8>, // length is 0 if no trainer is present
     prg_rom_data: Vec<u8>,
-    chr_rom_data: Vec<u8>,
+    chr_rom_data: Vec<u8>, // contains chr ram instead if the cartridge has no chr rom
+    chr_is_ram: bool,
     work_ram: RamArray,
+    work_ram_dirty: bool, // set when work ram is written; used for flushing battery backed saves
 }
 
 impl fmt::Debug for RamArray {
@@ -62,100 +114,166 @@ impl fmt::Debug for RamArray {
     }
 }
 
+impl Rom {
+    pub fn new() -> Rom {
+        Rom {
+            header: RomHeader::new(),
+            trainer: vec![],
+            prg_rom_data: vec![],
+            chr_rom_data: vec![],
+            chr_is_ram: false,
+            work_ram: RamArray { data: [0; 0x2000] },
+            work_ram_dirty: false,
+
+        }
+    }
 
+    pub fn from_bytes(bytes: &[u8]) -> Result<Rom, RomError> {
+        Rom::from_reader(bytes)
+    }
 
-// this is an inefficient implementation, requiring address calculations on each read\write
-fn get_offset_temp_hardcoded_impl(length: u8, address: u16) -> usize {
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Rom, RomError> {
+        let mut rom = Rom::new();
+        rom.read_header(&mut reader)?;
+        rom.read_trainer_field(&mut reader)?;
+        rom.read_prg_rom(&mut reader)?;
+        rom.read_chr_rom(&mut reader)?;
+        Ok(rom)
+    }
 
-    // program rom is mapped to memory addresses 0x8000 - 0xBFFF and 0xC000 - 0xFFFF
-    // if rom size is 16kb, 0x8000 - 0xBFFF and 0xC000 - 0xFFFF are mirrored
-    // otherwise first 16kb of rom is mapped to 0x8000 -> and second 16kb is mapped to C0000 ->
+    #[cfg(test)]
+    pub fn with_data(prg_rom_data: Vec<u8>, chr_rom_data: Vec<u8>) -> Rom {
+        let mut rom = Rom::new();
+        rom.header.prg_rom_size = prg_rom_data.len();
+        rom.header.chr_rom_size = chr_rom_data.len();
+        rom.header.mirroring = Mirroring::VerticalMirroring;
+        rom.prg_rom_data = prg_rom_data;
+        rom.chr_rom_data = chr_rom_data;
+        rom.allocate_chr_ram_if_needed();
+        rom
+    }
 
-    let base = address & 0x3FFF; // mask first two bits away to get offset
-    if length > 1 && (address & 0xC000 == 0xC000) {
-        (base + 0x4000) as usize // if rom size is not 16kb and address is 0xC000 ->, map to second 16kb
-    } else {
-        base as usize
+    // Raw cartridge memory accessors for mappers. Mappers are responsible for translating
+    // cpu\ppu addresses into offsets; offsets wrap around the size of the memory so that
+    // smaller roms are mirrored into larger bank windows.
+    pub fn prg_rom_size(&self) -> usize {
+        self.prg_rom_data.len()
     }
-}
 
-impl Memory for Rom {
+    pub fn prg_rom_read(&self, offset: usize) -> u8 {
+        self.prg_rom_data[offset % self.prg_rom_data.len()]
+    }
 
-    fn read(&mut self, address: u16) -> u8 {
-        // TODO: Implement mappers & let them handle this
-        // for now, mapper 0 is hardcoded (poorly)
+    pub fn chr_read(&self, offset: usize) -> u8 {
+        self.chr_rom_data[offset % self.chr_rom_data.len()]
+    }
 
-        // basically the hardcoded assumption right now is that any read form 0x2000 or below is to chr rom,
-        // otherwise it's to prg rom
-        if address < 0x2000 {
-            self.chr_rom_data[address as usize]
-        } else if address >= 0x6000 && address <= 0x7FFF {
-            // work ram on the rom
-            self.work_ram.data[(address - 0x6000) as usize]
-        } else {
-            let offset = get_offset_temp_hardcoded_impl(self.header.prg_rom_size, address);
-            self.prg_rom_data[offset]
+    // games may write to pattern tables through $2007 even when they are rom; such writes are
+    // ignored
+    pub fn chr_write(&mut self, offset: usize, value: u8) {
+        if self.chr_is_ram {
+            let len = self.chr_rom_data.len();
+            self.chr_rom_data[offset % len] = value;
         }
     }
 
-    fn write(&mut self, address: u16, value: u8) {
-        if address >= 0x6000 && address <= 0x7FFF {
-            // work ram on the rom
-            self.work_ram.data[(address - 0x6000) as usize] = value;
-        } else {
-            panic!("Invalid write into rom memory address space (address 0x{:04X}, value: 0x{:04X})",
-                address,
-                value);
-        }
+    pub fn work_ram_read(&self, offset: usize) -> u8 {
+        self.work_ram.data[offset % self.work_ram.data.len()]
     }
-}
 
+    pub fn work_ram_write(&mut self, offset: usize, value: u8) {
+        let len = self.work_ram.data.len();
+        self.work_ram.data[offset % len] = value;
+        self.work_ram_dirty = true;
+    }
 
+    pub fn has_battery_backing(&self) -> bool {
+        self.header.has_battery_backing
+    }
 
-impl Rom {
-    pub fn new() -> Rom {
-        Rom {
-            header: RomHeader::new(),
-            trainer: vec![],
-            prg_rom_data: vec![],
-            chr_rom_data: vec![],
-            work_ram: RamArray { data: [0; 0x2000] },
+    // iNES 1.0 headers infer 8kb of prg ram even if the board has none; a trainer or battery
+    // also implies the ram exists
+    pub fn has_prg_ram(&self) -> bool {
+        self.header.prg_ram_size > 0
+            || self.header.prg_nvram_size > 0
+            || self.header.has_battery_backing
+            || self.header.has_trainer
+    }
 
+    // Battery backed work ram is saved between sessions. The dirty flag is cleared when the
+    // contents are taken for saving
+    pub fn save_ram(&mut self) -> Option<&[u8]> {
+        if self.work_ram_dirty {
+            self.work_ram_dirty = false;
+            Some(&self.work_ram.data[..])
+        } else {
+            None
         }
     }
 
-    fn read_header(&mut self, rom_file: &mut File) {
-        RomHeader::verify_magic_number_or_panic(rom_file);
-        self.header.read_prg_rom_size(rom_file);
-        self.header.read_chr_rom_size(rom_file);
-        self.header.read_flags_6(rom_file);
-        self.header.read_flags_7(rom_file);
-        self.header.read_prg_ram_size(rom_file);
-        self.header.read_flags_9(rom_file);
-        RomHeader::read_padding(rom_file);
+    // the trainer is loaded into work ram at power on, so it is kept over the saved contents
+    pub fn load_save_ram(&mut self, data: &[u8]) {
+        let len = data.len().min(self.work_ram.data.len());
+        self.work_ram.data[..len].copy_from_slice(&data[..len]);
+        self.copy_trainer_to_work_ram();
+    }
+
+    // trainer is mapped to 0x7000 - 0x71FF, which is offset 0x1000 in work ram
+    fn copy_trainer_to_work_ram(&mut self) {
+        if !self.trainer.is_empty() {
+            self.work_ram.data[0x1000..0x1200].copy_from_slice(&self.trainer);
+        }
     }
 
-    fn read_trainer_field(&mut self, rom_file: &mut File) {
+    fn read_header<R: Read>(&mut self, rom_file: &mut R) -> Result<(), RomError> {
+        RomHeader::verify_magic_number(rom_file)?;
+        self.header.read_prg_rom_size(rom_file)?;
+        self.header.read_chr_rom_size(rom_file)?;
+        self.header.read_flags_6(rom_file)?;
+        self.header.read_flags_7(rom_file)?;
+        if self.header.is_nes_2_0 {
+            self.header.read_nes_2_0_fields(rom_file)?;
+        } else {
+            self.header.read_ines_fields(rom_file)?;
+        }
+
+        // the cpu starts executing from prg rom, so a rom without it cannot run
+        if self.header.prg_rom_size == 0 {
+            return Err(RomError::BadHeader("Prg rom size is 0".to_string()));
+        }
+        Ok(())
+    }
+
+    fn read_trainer_field<R: Read>(&mut self, rom_file: &mut R) -> Result<(), RomError> {
         // check if the trainer bit is set - if not, there is no trainer and do nothing
         if self.header.has_trainer {
-
-            self.trainer = read_bytes_from_file_or_panic(512, rom_file,
-                "Could not read the trainer field from the rom");
+            self.trainer = read_bytes(512, rom_file, "the trainer field from the rom")?;
+            self.copy_trainer_to_work_ram();
         }
+        Ok(())
     }
 
-    fn read_prg_rom(&mut self, rom_file: &mut File) {
-        let prg_rom_unit_size = 16384;
-        let size = prg_rom_unit_size * self.header.prg_rom_size as u64;
-        self.prg_rom_data = read_bytes_from_file_or_panic(size, rom_file,
-            "Could not read prg rom data from rom");
+    fn read_prg_rom<R: Read>(&mut self, rom_file: &mut R) -> Result<(), RomError> {
+        let size = self.header.prg_rom_size as u64;
+        self.prg_rom_data = read_bytes(size, rom_file, "prg rom data from rom")?;
+        Ok(())
     }
 
-    fn read_chr_rom(&mut self, rom_file: &mut File) {
-        let chr_rom_unit_size = 8192;
-        let size = chr_rom_unit_size * self.header.chr_rom_size as u64;
-        self.chr_rom_data = read_bytes_from_file_or_panic(size, rom_file,
-            "Could not read chr rom data from rom");
+    fn read_chr_rom<R: Read>(&mut self, rom_file: &mut R) -> Result<(), RomError> {
+        let size = self.header.chr_rom_size as u64;
+        self.chr_rom_data = read_bytes(size, rom_file, "chr rom data from rom")?;
+        self.allocate_chr_ram_if_needed();
+        Ok(())
+    }
+
+    // cartridges without chr rom have chr ram instead, which the game fills at runtime. iNES headers
+    // do not specify the size, in which case 8kb is assumed
+    fn allocate_chr_ram_if_needed(&mut self) {
+        if self.chr_rom_data.is_empty() {
+            let size = self.header.chr_ram_size + self.header.chr_nvram_size;
+            self.chr_rom_data = vec![0; if size == 0 { 8192 } else { size }];
+            self.chr_is_ram = true;
+        }
     }
 }
 
@@ -163,26 +281,54 @@ impl Rom {
 pub enum TvSystem {
     Uninitialized,
     PAL,
-    NTSC
+    NTSC,
+    Dendy, // famiclone timing; PAL frame rate with NTSC cpu\ppu clock ratio
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CpuPpuTiming {
+    NTSC,
+    PAL,
+    MultiRegion,
+    Dendy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConsoleType {
+    NES,
+    VsSystem { ppu_type: u8, hardware_type: u8 },
+    Playchoice10,
+    Extended(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Mirroring {
     Uninitialized,
     HorizontalMirroring,
     VerticalMirroring,
+    SingleScreenLower,
+    SingleScreenUpper,
     FourScreenVRAM
 }
 
 
 #[derive(Debug)]
 pub struct RomHeader {
-    pub prg_rom_size:u8, // size in 16kb units
-    pub chr_rom_size:u8, // size in 8kb units - if 0, chr ram is used
-    pub prg_ram_size:u8, // size in 8kb units - if 0, 8kb of ram is assumed
-    pub mapper: u8,
+    pub is_nes_2_0: bool,
+    pub prg_rom_size: usize, // size in bytes
+    pub chr_rom_size: usize, // size in bytes - if 0, chr ram is used
+    pub prg_ram_size: usize, // size in bytes
+    pub prg_nvram_size: usize, // size in bytes, nes 2.0 only
+    pub chr_ram_size: usize, // size in bytes, nes 2.0 only
+    pub chr_nvram_size: usize, // size in bytes, nes 2.0 only
+    pub mapper: u16,
+    pub submapper: u8, // nes 2.0 only
     pub mirroring: Mirroring,
+    pub timing: CpuPpuTiming,
     pub tv_system: TvSystem,
+    pub console_type: ConsoleType,
+    pub misc_rom_count: u8, // nes 2.0 only
+    pub default_expansion_device: u8, // nes 2.0 only
     has_trainer: bool,
     has_battery_backing: bool,
 }
@@ -191,36 +337,47 @@ pub struct RomHeader {
 impl RomHeader {
     fn new() -> RomHeader {
         RomHeader {
+            is_nes_2_0: false,
             prg_rom_size: 0,
-            chr_rom_size:0,
-            prg_ram_size:0,
+            chr_rom_size: 0,
+            prg_ram_size: 0,
+            prg_nvram_size: 0,
+            chr_ram_size: 0,
+            chr_nvram_size: 0,
             mapper: 0,
+            submapper: 0,
             mirroring: Mirroring::Uninitialized,
+            timing: CpuPpuTiming::NTSC,
             tv_system: TvSystem::Uninitialized,
+            console_type: ConsoleType::NES,
+            misc_rom_count: 0,
+            default_expansion_device: 0,
             has_trainer: false,
             has_battery_backing: false,
         }
     }
 
-    fn verify_magic_number_or_panic(rom_file: &mut File) {
-        let buf = read_bytes_from_file_or_panic(4, rom_file,
-            "Could not read the magic number from the header");
+    fn verify_magic_number<R: Read>(rom_file: &mut R) -> Result<(), RomError> {
+        let buf = read_bytes(4, rom_file, "the magic number from the header")?;
 
         if !(buf[0] == 0x4E && buf[1] == 0x45 && buf[2] == 0x53 && buf[3] == 0x1A) {
-            panic!("Invalid magic number");
+            return Err(RomError::BadMagic);
         }
+        Ok(())
     }
 
-    fn read_prg_rom_size(&mut self, rom_file: &mut File) {
-        let buf = read_bytes_from_file_or_panic(1, rom_file,
-            "Could not read the prg rom size from the header");
-        self.prg_rom_size = buf[0];
+    fn read_prg_rom_size<R: Read>(&mut self, rom_file: &mut R) -> Result<(), RomError> {
+        let buf = read_bytes(1, rom_file, "the prg rom size from the header")?;
+        // size in 16kb units; nes 2.0 headers may later replace this with the extended size
+        self.prg_rom_size = buf[0] as usize * PRG_ROM_UNIT_SIZE;
+        Ok(())
     }
 
-    fn read_chr_rom_size(&mut self, rom_file: &mut File) {
-        let buf = read_bytes_from_file_or_panic(1, rom_file,
-            "Could not read the chr rom size from the header");
-        self.chr_rom_size = buf[0];
+    fn read_chr_rom_size<R: Read>(&mut self, rom_file: &mut R) -> Result<(), RomError> {
+        let buf = read_bytes(1, rom_file, "the chr rom size from the header")?;
+        // size in 8kb units; nes 2.0 headers may later replace this with the extended size
+        self.chr_rom_size = buf[0] as usize * CHR_ROM_UNIT_SIZE;
+        Ok(())
     }
 
 
@@ -237,9 +394,8 @@ impl RomHeader {
     |||| +--- 1: 512-byte trainer at $7000-$71FF (stored before PRG data)
     ++++----- Lower nybble of mapper number
 */
-    fn read_flags_6(&mut self, rom_file: &mut File) {
-        let buf = read_bytes_from_file_or_panic(1, rom_file,
-            "Could not read the flags_6 field from header");
+    fn read_flags_6<R: Read>(&mut self, rom_file: &mut R) -> Result<(), RomError> {
+        let buf = read_bytes(1, rom_file, "the flags_6 field from header")?;
 
         // if bit 2 is set, trainer is present
         self.has_trainer = (buf[0] & (1 << 2)) != 0;
@@ -259,9 +415,10 @@ impl RomHeader {
         }
 
         // set lower 4 bits of mapper number
-        let lower_nybble =  buf[0] >> 4;
-        self.mapper = self.mapper & 0xf0; // set lower 4 bits to 0, in case they were not
+        let lower_nybble =  (buf[0] >> 4) as u16;
+        self.mapper = self.mapper & 0xfff0; // set lower 4 bits to 0, in case they were not
         self.mapper = self.mapper | lower_nybble;
+        Ok(())
     }
 
     /*
@@ -274,34 +431,60 @@ impl RomHeader {
     ||||++--- If equal to 2, flags 8-15 are in NES 2.0 format
     ++++----- Upper nybble of mapper number
 
+    In NES 2.0 format, bits 0 and 1 together are the console type (0: NES, 1: Vs. System,
+    2: Playchoice 10, 3: extended console type in byte 13)
     */
-    fn read_flags_7(&mut self, rom_file: &mut File) {
-        let buf = read_bytes_from_file_or_panic(1, rom_file,
-            "Could not read the flags_7 field from header");
+    fn read_flags_7<R: Read>(&mut self, rom_file: &mut R) -> Result<(), RomError> {
+        let buf = read_bytes(1, rom_file, "the flags_7 field from header")?;
+
+        self.is_nes_2_0 = (buf[0] & 0x0C) >> 2 == 0x02;
 
-        // check if nes 2.0 format; if so, panic as this is currently not supported
-        if (buf[0] & 0x0C) >> 2 == 0x02 {
-            panic!("Rom is in nes 2.0 format which is currently unsupported");
-        }
         // extract the upper nybble of the mapper number
-        let upper_nybble = 0xf0 & buf[0];
+        let upper_nybble = (0xf0 & buf[0]) as u16;
         // set upper nybble to zero, in case it wasn't
-        self.mapper = self.mapper & 0x0f;
+        self.mapper = self.mapper & 0xff0f;
         self.mapper = self.mapper | upper_nybble;
 
-        // unisystem - playchoice are currently ignored
+        // vs. system type is only known with nes 2.0 headers and gets filled in later
+        self.console_type = match buf[0] & 0x03 {
+            0 => ConsoleType::NES,
+            1 => ConsoleType::VsSystem { ppu_type: 0, hardware_type: 0 },
+            2 => ConsoleType::Playchoice10,
+            _ if self.is_nes_2_0 => ConsoleType::Extended(0),
+            // iNES allows both bits to be set; treat it as a vs. system
+            _ => ConsoleType::VsSystem { ppu_type: 0, hardware_type: 0 },
+        };
+        Ok(())
     }
 
-    fn read_prg_ram_size(&mut self, rom_file: &mut File) {
-        let buf = read_bytes_from_file_or_panic(1, rom_file,
-            "Could not read the prg ram size from header");
+    /*
+        iNES bytes 8 - 15: prg ram size, flags 9 and padding.
+
+        Some old tools wrote their name (such as "DiskDude!") into bytes 7 - 15. If bytes 12 - 15
+        are not zero, these bytes are assumed to contain garbage and are ignored along with the
+        upper nybble of the mapper number, as recommended by nesdev. Bytes 10 and 11 are not
+        checked, as they are used by some legitimate iNES dumps.
+    */
+    fn read_ines_fields<R: Read>(&mut self, rom_file: &mut R) -> Result<(), RomError> {
+        let buf = read_bytes(8, rom_file, "the flags 8 - 15 from header")?;
+
+        if buf[4..].iter().any(|&byte| byte != 0) {
+            println!("Warning: Header bytes 12 - 15 are not zero initialized; ignoring header bytes 7 - 15");
+            self.mapper = self.mapper & 0x000f;
+            self.console_type = ConsoleType::NES;
+            self.read_prg_ram_size(0);
+            return self.read_flags_9(0);
+        }
+
+        self.read_prg_ram_size(buf[0]);
+        self.read_flags_9(buf[1])
+    }
 
-        self.prg_ram_size = buf[0];
+    fn read_prg_ram_size(&mut self, value: u8) {
         // to quoth the documentation:
         // "Size of PRG RAM in 8 KB units (Value 0 infers 8 KB for compatibility; see PRG RAM circuit)"
-        if self.prg_ram_size == 0 {
-            self.prg_ram_size = 1;
-        }
+        let units = if value == 0 { 1 } else { value as usize };
+        self.prg_ram_size = units * PRG_RAM_UNIT_SIZE;
     }
 
 
@@ -315,30 +498,361 @@ impl RomHeader {
         +++++++-- Reserved, set to zero
 
     */
-    fn read_flags_9(&mut self, rom_file: &mut File) {
-        let buf = read_bytes_from_file_or_panic(1, rom_file,
-            "Could not read the flags_9 field from header");
-
+    fn read_flags_9(&mut self, value: u8) -> Result<(), RomError> {
         // Bits 1 - 7 should be zero. Thus, if the value is greater than 1, one or more of these
         // bits are set and something is wrong (possibly unsupported ROM version)
-        if buf[0] > 1 {
-            panic!("flags_9 field has invalid value {}: Other bits than the first one are set",
-            buf[0])
+        if value > 1 {
+            return Err(RomError::BadHeader(
+                format!("flags_9 field has invalid value {}: Other bits than the first one are set", value)));
         }
 
-        if buf[0] == 0 {
-            self.tv_system = TvSystem::NTSC;
+        if value == 0 {
+            self.timing = CpuPpuTiming::NTSC;
         } else {
-            self.tv_system = TvSystem::PAL;
+            self.timing = CpuPpuTiming::PAL;
+        }
+        self.tv_system = tv_system_from_timing(self.timing);
+        Ok(())
+    }
+
+    /*
+        Documentation on NES 2.0 bytes 8 - 15
+        (see http://wiki.nesdev.com/w/index.php/NES_2.0)
+
+        8:  SSSSMMMM - submapper, mapper number bits 8 - 11
+        9:  CCCCPPPP - chr rom size msb, prg rom size msb
+        10: ppppPPPP - prg nvram shift count, prg ram shift count
+        11: ccccCCCC - chr nvram shift count, chr ram shift count
+        12: ......VV - cpu\ppu timing (0: NTSC, 1: PAL, 2: multi-region, 3: Dendy)
+        13: hhhhpppp - vs. system hardware type and ppu type, or extended console type in the low nybble
+        14: ......RR - number of miscellaneous roms
+        15: ..DDDDDD - default expansion device
+
+        RAM sizes are 64 << shift count bytes, or 0 if the shift count is 0
+    */
+    fn read_nes_2_0_fields<R: Read>(&mut self, rom_file: &mut R) -> Result<(), RomError> {
+        let buf = read_bytes(8, rom_file, "the nes 2.0 fields from header")?;
+
+        self.mapper = self.mapper | ((buf[0] & 0x0F) as u16) << 8;
+        self.submapper = buf[0] >> 4;
+
+        // rom size lsb was stored by read_prg_rom_size\read_chr_rom_size in iNES units
+        let prg_lsb = (self.prg_rom_size / PRG_ROM_UNIT_SIZE) as u8;
+        let chr_lsb = (self.chr_rom_size / CHR_ROM_UNIT_SIZE) as u8;
+        self.prg_rom_size = nes_2_0_rom_size(prg_lsb, buf[1] & 0x0F, PRG_ROM_UNIT_SIZE)?;
+        self.chr_rom_size = nes_2_0_rom_size(chr_lsb, buf[1] >> 4, CHR_ROM_UNIT_SIZE)?;
+
+        self.prg_ram_size = nes_2_0_ram_size(buf[2] & 0x0F);
+        self.prg_nvram_size = nes_2_0_ram_size(buf[2] >> 4);
+        self.chr_ram_size = nes_2_0_ram_size(buf[3] & 0x0F);
+        self.chr_nvram_size = nes_2_0_ram_size(buf[3] >> 4);
+
+        self.timing = match buf[4] & 0x03 {
+            0 => CpuPpuTiming::NTSC,
+            1 => CpuPpuTiming::PAL,
+            2 => CpuPpuTiming::MultiRegion,
+            _ => CpuPpuTiming::Dendy,
+        };
+        self.tv_system = tv_system_from_timing(self.timing);
+
+        self.console_type = match self.console_type {
+            ConsoleType::VsSystem { .. } => ConsoleType::VsSystem {
+                ppu_type: buf[5] & 0x0F,
+                hardware_type: buf[5] >> 4,
+            },
+            ConsoleType::Extended(_) => ConsoleType::Extended(buf[5] & 0x0F),
+            console_type => console_type,
+        };
+
+        self.misc_rom_count = buf[6] & 0x03;
+        self.default_expansion_device = buf[7] & 0x3F;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_ines_header(prg_rom_size: u8, chr_rom_size: u8, flags_6: u8, flags_7: u8) -> Vec<u8> {
+        vec![0x4E, 0x45, 0x53, 0x1A, prg_rom_size, chr_rom_size, flags_6, flags_7, 0, 0, 0, 0, 0, 0, 0, 0]
+    }
+
+    fn create_test_rom_bytes(header: Vec<u8>, prg_rom_size: usize, chr_rom_size: usize) -> Vec<u8> {
+        let mut bytes = header;
+        bytes.extend(vec![0xAA; prg_rom_size]);
+        bytes.extend(vec![0xBB; chr_rom_size]);
+        bytes
+    }
+
+    #[test]
+    fn from_bytes_reads_ines_rom() {
+        let header = create_ines_header(2, 1, 0x41, 0x40);
+        let rom = Rom::from_bytes(&create_test_rom_bytes(header, 0x8000, 0x2000)).unwrap();
+
+        assert_eq!(0x44, rom.header.mapper);
+        assert_eq!(0x8000, rom.header.prg_rom_size);
+        assert_eq!(0x2000, rom.header.chr_rom_size);
+        assert_eq!(Mirroring::VerticalMirroring, rom.header.mirroring);
+        assert_eq!(0xAA, rom.prg_rom_read(0x7FFF));
+        assert_eq!(0xBB, rom.chr_read(0x1FFF));
+    }
+
+    #[test]
+    fn from_reader_reads_rom_from_reader() {
+        let header = create_ines_header(1, 1, 0x00, 0x00);
+        let bytes = create_test_rom_bytes(header, 0x4000, 0x2000);
+        let rom = Rom::from_reader(io::Cursor::new(bytes)).unwrap();
+
+        assert_eq!(0x4000, rom.prg_rom_size());
+    }
+
+    #[test]
+    fn from_bytes_returns_error_on_invalid_magic_number() {
+        let mut header = create_ines_header(1, 1, 0x00, 0x00);
+        header[3] = 0x00;
+        match Rom::from_bytes(&create_test_rom_bytes(header, 0x4000, 0x2000)) {
+            Err(RomError::BadMagic) => {},
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn from_bytes_returns_error_if_prg_rom_is_truncated() {
+        let header = create_ines_header(2, 1, 0x00, 0x00);
+        match Rom::from_bytes(&create_test_rom_bytes(header, 0x4000, 0)) {
+            Err(RomError::Truncated { expected: 0x8000, actual: 0x4000, .. }) => {},
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn from_bytes_returns_error_if_prg_rom_size_is_zero() {
+        let header = create_ines_header(0, 1, 0x00, 0x00);
+        match Rom::from_bytes(&create_test_rom_bytes(header, 0, 0x2000)) {
+            Err(RomError::BadHeader(_)) => {},
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn from_bytes_returns_error_on_invalid_flags_9() {
+        let mut header = create_ines_header(1, 1, 0x00, 0x00);
+        header[9] = 0x02;
+        match Rom::from_bytes(&create_test_rom_bytes(header, 0x4000, 0x2000)) {
+            Err(RomError::BadHeader(_)) => {},
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn dirty_header_padding_is_ignored_with_upper_mapper_nybble() {
+        let mut header = create_ines_header(1, 1, 0x10, 0x00);
+        header[7..].copy_from_slice(b"DiskDude!");
+        let rom = Rom::from_bytes(&create_test_rom_bytes(header, 0x4000, 0x2000)).unwrap();
+
+        assert_eq!(1, rom.header.mapper);
+        assert_eq!(8192, rom.header.prg_ram_size);
+        assert_eq!(CpuPpuTiming::NTSC, rom.header.timing);
+    }
+
+    #[test]
+    fn nonzero_header_bytes_10_and_11_do_not_discard_upper_mapper_nybble() {
+        let mut header = create_ines_header(1, 1, 0x10, 0x40);
+        header[10] = 0x10;
+        header[11] = 0x01;
+        let rom = Rom::from_bytes(&create_test_rom_bytes(header, 0x4000, 0x2000)).unwrap();
+        assert_eq!(0x41, rom.header.mapper);
+    }
+
+    #[test]
+    fn from_bytes_returns_error_if_header_is_truncated() {
+        let header = create_ines_header(1, 1, 0x00, 0x00);
+        match Rom::from_bytes(&header[..10]) {
+            Err(RomError::Truncated { .. }) => {},
+            result => panic!("Unexpected result {:?}", result),
         }
     }
 
-    fn read_padding(rom_file: &mut File) {
-        let buf = read_bytes_from_file_or_panic(6, rom_file,
-            "Could not read the padding from the header");
+    #[test]
+    fn from_bytes_returns_error_if_chr_rom_is_truncated() {
+        let header = create_ines_header(1, 2, 0x00, 0x00);
+        match Rom::from_bytes(&create_test_rom_bytes(header, 0x4000, 0x2000)) {
+            Err(RomError::Truncated { expected: 0x4000, actual: 0x2000, .. }) => {},
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn bad_header_error_message_contains_reason() {
+        let mut header = create_ines_header(1, 1, 0x00, 0x00);
+        header[9] = 0x02;
+        let error = Rom::from_bytes(&create_test_rom_bytes(header, 0x4000, 0x2000)).unwrap_err();
+        assert!(format!("{}", error).starts_with("Invalid header: flags_9"));
+    }
+
+    #[test]
+    fn chr_ram_is_allocated_when_rom_has_no_chr_rom() {
+        let header = create_ines_header(1, 0, 0x00, 0x00);
+        let mut rom = Rom::from_bytes(&create_test_rom_bytes(header, 0x4000, 0)).unwrap();
+        rom.chr_write(0x1FFF, 0x12);
+        assert_eq!(0x12, rom.chr_read(0x1FFF));
+    }
+
+    #[test]
+    fn ines_rom_has_prg_ram() {
+        let header = create_ines_header(1, 1, 0x00, 0x00);
+        let rom = Rom::from_bytes(&create_test_rom_bytes(header, 0x4000, 0x2000)).unwrap();
+        assert_eq!(true, rom.has_prg_ram());
+    }
+
+    #[test]
+    fn nes_2_0_rom_without_prg_ram_size_has_no_prg_ram() {
+        let header = create_ines_header(1, 1, 0x00, 0x08);
+        let rom = Rom::from_bytes(&create_test_rom_bytes(header, 0x4000, 0x2000)).unwrap();
+        assert_eq!(false, rom.has_prg_ram());
+    }
+
+    #[test]
+    fn chr_write_to_chr_rom_is_ignored() {
+        let header = create_ines_header(1, 1, 0x00, 0x00);
+        let mut rom = Rom::from_bytes(&create_test_rom_bytes(header, 0x4000, 0x2000)).unwrap();
+        let original = rom.chr_read(0x1FFF);
+        rom.chr_write(0x1FFF, original.wrapping_add(1));
+        assert_eq!(original, rom.chr_read(0x1FFF));
+    }
+
+    #[test]
+    fn from_bytes_reads_nes_2_0_header() {
+        let mut header = create_ines_header(2, 0, 0x00, 0x48);
+        header[8] = 0x31; // submapper 3, mapper bits 8 - 11 = 1
+        header[10] = 0x97; // 32kb prg nvram, 8kb prg ram
+        header[11] = 0x08; // 16kb chr ram
+        header[12] = 0x03; // Dendy
+        header[15] = 0x01;
+        let rom = Rom::from_bytes(&create_test_rom_bytes(header, 0x8000, 0)).unwrap();
+
+        assert_eq!(true, rom.header.is_nes_2_0);
+        assert_eq!(0x140, rom.header.mapper);
+        assert_eq!(3, rom.header.submapper);
+        assert_eq!(0x8000, rom.header.prg_rom_size);
+        assert_eq!(8192, rom.header.prg_ram_size);
+        assert_eq!(32768, rom.header.prg_nvram_size);
+        assert_eq!(16384, rom.header.chr_ram_size);
+        assert_eq!(CpuPpuTiming::Dendy, rom.header.timing);
+        assert_eq!(ConsoleType::NES, rom.header.console_type);
+        assert_eq!(1, rom.header.default_expansion_device);
+        assert_eq!(16384, rom.chr_rom_data.len());
+    }
+
+    #[test]
+    fn nes_2_0_header_reads_vs_system_type() {
+        let mut header = create_ines_header(1, 1, 0x00, 0x09);
+        header[13] = 0x21;
+        let rom = Rom::from_bytes(&create_test_rom_bytes(header, 0x4000, 0x2000)).unwrap();
+
+        assert_eq!(ConsoleType::VsSystem { ppu_type: 1, hardware_type: 2 }, rom.header.console_type);
+    }
+
+    #[test]
+    fn nes_2_0_rom_size_with_msb_uses_unit_size() {
+        assert_eq!(0x102 * 16384, nes_2_0_rom_size(0x02, 0x01, 16384).unwrap());
+    }
+
+    #[test]
+    fn nes_2_0_rom_size_uses_exponent_notation_when_msb_is_0xf() {
+        // 2^10 * (1*2 + 1)
+        assert_eq!(3072, nes_2_0_rom_size(0x29, 0x0F, 16384).unwrap());
+    }
 
-        if !(buf[0] == 0 && buf[1] == 0 && buf[2] == 0 && buf[3] == 0 && buf[4] == 0 && buf[5] == 0) {
-            panic!("Invalid padding: Padding is expected to be zero initialized");
+    #[test]
+    fn nes_2_0_rom_size_returns_error_when_size_overflows() {
+        // 2^63 * (1*2 + 1)
+        match nes_2_0_rom_size(0xFD, 0x0F, 16384) {
+            Err(RomError::BadHeader(_)) => {},
+            result => panic!("Unexpected result {:?}", result),
         }
     }
+
+    #[test]
+    fn from_bytes_returns_error_when_nes_2_0_rom_size_overflows() {
+        let mut header = create_ines_header(1, 1, 0x00, 0x08);
+        header[4] = 0xFD;
+        header[9] = 0x0F;
+        match Rom::from_bytes(&create_test_rom_bytes(header, 0x4000, 0x2000)) {
+            Err(RomError::BadHeader(_)) => {},
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn nes_2_0_ram_size_is_zero_when_shift_count_is_zero() {
+        assert_eq!(0, nes_2_0_ram_size(0));
+    }
+
+    #[test]
+    fn nes_2_0_ram_size_is_64_shifted_left_by_shift_count() {
+        assert_eq!(8192, nes_2_0_ram_size(7));
+    }
+
+    #[test]
+    fn dendy_timing_uses_dendy_tv_system() {
+        match tv_system_from_timing(CpuPpuTiming::Dendy) {
+            TvSystem::Dendy => {},
+            tv_system => panic!("Unexpected tv system {:?}", tv_system),
+        }
+    }
+
+    #[test]
+    fn save_ram_is_none_if_work_ram_has_not_been_written() {
+        let mut rom = Rom::with_data(vec![0; 0x4000], vec![0; 0x2000]);
+        assert_eq!(None, rom.save_ram());
+    }
+
+    #[test]
+    fn save_ram_returns_work_ram_once_after_write() {
+        let mut rom = Rom::with_data(vec![0; 0x4000], vec![0; 0x2000]);
+        rom.work_ram_write(0x0010, 0x42);
+        assert_eq!(Some(0x42), rom.save_ram().map(|ram| ram[0x0010]));
+        assert_eq!(None, rom.save_ram());
+    }
+
+    #[test]
+    fn load_save_ram_replaces_work_ram_without_marking_it_dirty() {
+        let mut rom = Rom::with_data(vec![0; 0x4000], vec![0; 0x2000]);
+        rom.load_save_ram(&[0x01, 0x02, 0x03]);
+        assert_eq!(0x03, rom.work_ram_read(0x0002));
+        assert_eq!(None, rom.save_ram());
+    }
+
+    #[test]
+    fn trainer_is_copied_to_work_ram_at_0x7000() {
+        let mut bytes = create_ines_header(1, 1, 0x04, 0x00);
+        let mut trainer = vec![0; 512];
+        trainer[0] = 0x12;
+        trainer[511] = 0x34;
+        bytes.extend(trainer);
+        let rom = Rom::from_bytes(&create_test_rom_bytes(bytes, 0x4000, 0x2000)).unwrap();
+
+        assert_eq!(0x12, rom.work_ram_read(0x1000));
+        assert_eq!(0x34, rom.work_ram_read(0x11FF));
+        assert_eq!(0xAA, rom.prg_rom_read(0));
+    }
+
+    #[test]
+    fn load_save_ram_keeps_trainer_in_work_ram() {
+        let mut bytes = create_ines_header(1, 1, 0x06, 0x00); // trainer and battery
+        let mut trainer = vec![0; 512];
+        trainer[0] = 0x12;
+        trainer[511] = 0x34;
+        bytes.extend(trainer);
+        let mut rom = Rom::from_bytes(&create_test_rom_bytes(bytes, 0x4000, 0x2000)).unwrap();
+
+        rom.load_save_ram(&vec![0x56; 0x2000]);
+
+        assert_eq!(0x56, rom.work_ram_read(0x0000));
+        assert_eq!(0x12, rom.work_ram_read(0x1000));
+        assert_eq!(0x34, rom.work_ram_read(0x11FF));
+        assert_eq!(0x56, rom.work_ram_read(0x1200));
+    }
 }